@@ -146,6 +146,10 @@ defmodule RustyCSV do
       `:basic`, `:indexed`, `:parallel`, `:zero_copy`)
     * `:strategy` on `dump_to_iodata/2` - Select the encoding approach
       (default or `:parallel`)
+    * `:invalid_utf8` on `dump_to_iodata/2` - Copy, replace or reject fields
+      that are not valid UTF-8
+    * `:excel` on `dump_to_iodata/2` - Write a file Excel opens with every
+      cell intact
     * `:headers` - Return rows as maps instead of lists
 
   ## Headers-to-Maps
@@ -191,7 +195,7 @@ defmodule RustyCSV do
 
     * Fewer columns than keys — missing values are `nil`
     * More columns than keys — extra columns are ignored
-    * Duplicate headers — last column wins
+    * Duplicate headers — last column wins (see `:duplicate_headers`)
     * Empty header field — key is `""`
 
   ## Multi-Separator Support
@@ -268,6 +272,10 @@ defmodule RustyCSV do
   Supported encodings:
     * `:utf8` - UTF-8 (default, no conversion overhead)
     * `:latin1` - ISO-8859-1 / Latin-1
+    * `:windows_1252` - Windows-1252, as Excel on Windows writes "ANSI" files
+    * `:iso_8859_15` - ISO-8859-15 / Latin-9
+    * `{:codepage, number}` - A single-byte codepage: 437, 850, 1250, 1251, 1252,
+      20866 (KOI8-R) or 28605 (ISO-8859-15)
     * `{:utf16, :little}` - UTF-16 Little Endian
     * `{:utf16, :big}` - UTF-16 Big Endian
     * `{:utf32, :little}` - UTF-32 Little Endian
@@ -351,6 +359,40 @@ defmodule RustyCSV do
       * list of atoms or strings - Use as explicit keys, return list of maps.
         The first row is skipped by default (`:skip_headers` applies). Pass
         `skip_headers: false` if the file has no header row.
+    * `:duplicate_headers` - How maps handle a key that appears in more than
+      one column. Only applies with `:headers`. Defaults to `:last`.
+      * `:last` - The last column with the key wins
+      * `:first` - The first column with the key wins
+      * `:error` - Raise `RustyCSV.ParseError` naming the duplicate key
+      * `:suffix` - Rename repeats to `key_2`, `key_3`, ... (strings and atoms)
+      * `:list` - Collect the values of every column with the key into a list
+    * `:extra_columns` - What to do with fields past the last header when a
+      row is longer than the header. Only applies with `:headers`.
+      * `:ignore` - Drop them (default)
+      * `:error` - Raise `RustyCSV.ParseError` naming the row number
+      * `{:collect, key}` - Store them as a list under `key` (`[]` when the
+        row has no extra fields). Raises `RustyCSV.ParseError` if `key` is also
+        a header, and `ArgumentError` with `:into`
+    * `:missing_columns` - What to do with keys a short row has no field for.
+      Only applies with `:headers`.
+      * `nil` - Map them to `nil` (default)
+      * `:omit` - Leave them out of the map (structs keep the field default)
+      * `:error` - Raise `RustyCSV.ParseError` naming the row number
+    * `:row_format` - Shape of each parsed row (`parse_string/2` only).
+      * `:list` - List of fields (default without `:headers`)
+      * `:tuple` - Tuple of fields, for O(1) `elem/2` access on wide rows
+      * `:map` - Map of key to field (default with `:headers`)
+      * `:keyword` - Keyword list in column order, duplicate keys preserved
+        (`:duplicate_headers` is rejected). Requires `:headers`; with
+        `headers: true` each header name must already exist as an atom, so
+        header text can't create atoms. Raises `RustyCSV.ParseError` otherwise.
+    * `:into` - A struct module (`parse_string/2` with `:headers` only). Rows
+      are built as structs holding only the struct's fields: headers match
+      fields by name, other columns are dropped, and fields without a column
+      keep their default. No intermediate map is allocated.
+    * `:required` - List of struct fields that must appear in the header when
+      using `:into`. Raises `RustyCSV.ParseError` if one is missing.
+      Defaults to `[]`; `@enforce_keys` are not checked automatically.
 
   ## Streaming Options
 
@@ -361,15 +403,41 @@ defmodule RustyCSV do
       `streaming_feed/2`, a `:buffer_overflow` exception is raised. Increase
       this if your data contains rows longer than 256 MB. Decrease it to fail
       faster on malformed input that lacks newlines.
+    * `:max_pending_rows` - Maximum complete rows the streaming parser queues.
+      Once reached, a fed chunk is scanned only as rows are taken, so a large
+      chunk of short rows doesn't build a large row queue. Defaults to no limit.
 
   """
+  @typedoc """
+  Policy for header keys that appear in more than one column.
+  """
+  @type duplicate_headers :: :last | :first | :error | :suffix | :list
+
+  @typedoc """
+  Policy for rows longer than the header.
+  """
+  @type extra_columns :: :ignore | :error | {:collect, atom() | String.t()}
+
+  @typedoc """
+  Policy for rows shorter than the header.
+  """
+  @type missing_columns :: nil | :omit | :error
+
   @type parse_options :: [
           skip_headers: boolean(),
           strategy: strategy(),
           headers: boolean() | [atom() | String.t()],
+          duplicate_headers: duplicate_headers(),
+          extra_columns: extra_columns(),
+          missing_columns: missing_columns(),
+          row_format: :list | :tuple | :map | :keyword,
+          into: module(),
+          required: [atom()],
           chunk_size: pos_integer(),
           batch_size: pos_integer(),
-          max_buffer_size: pos_integer()
+          max_buffer_size: pos_integer(),
+          max_pending_rows: pos_integer(),
+          width_policy: :error | :pad | :truncate | [:pad | :truncate]
         ]
 
   @typedoc """
@@ -380,9 +448,20 @@ defmodule RustyCSV do
     * `:strategy` - Encoding strategy to use. Defaults to the single-threaded
       SIMD-accelerated encoder (no option needed). Pass `:parallel` for
       multi-threaded encoding via rayon, which is faster for quoting-heavy data.
+    * `:invalid_utf8` - What to do with fields that are not valid UTF-8:
+      `:passthrough` (default) copies them as-is, `:replace` replaces each
+      invalid sequence with U+FFFD, and `:error` raises `RustyCSV.EncodeError`
+      naming the row and field.
+    * `:excel` - `true` (same as `:utf16`), `:utf16` or `:utf8` to write a
+      file for Excel: BOM, tab separators or a `sep=` line, formula escaping,
+      and `="00123"` for digit strings Excel would turn into numbers.
 
   """
-  @type dump_options :: [strategy: :parallel]
+  @type dump_options :: [
+          strategy: :parallel,
+          invalid_utf8: :passthrough | :replace | :error,
+          excel: boolean() | :utf16 | :utf8
+        ]
 
   @typedoc """
   Encoding for CSV data.
@@ -390,12 +469,29 @@ defmodule RustyCSV do
   Supported encodings:
     * `:utf8` - UTF-8 (default, no conversion)
     * `:latin1` - ISO-8859-1 / Latin-1
+    * `:windows_1252` - Windows-1252, as Excel on Windows writes "ANSI" files
+    * `:iso_8859_15` - ISO-8859-15 / Latin-9
+    * `{:codepage, number}` - A single-byte codepage: 437, 850, 1250, 1251, 1252,
+      20866 (KOI8-R) or 28605 (ISO-8859-15)
     * `{:utf16, :little}` - UTF-16 Little Endian
     * `{:utf16, :big}` - UTF-16 Big Endian
     * `{:utf32, :little}` - UTF-32 Little Endian
     * `{:utf32, :big}` - UTF-32 Big Endian
   """
-  @type encoding :: :utf8 | :latin1 | {:utf16, :little | :big} | {:utf32, :little | :big}
+  @type encoding ::
+          :utf8
+          | :latin1
+          | :windows_1252
+          | :iso_8859_15
+          | {:codepage, pos_integer()}
+          | {:utf16, :little | :big}
+          | {:utf32, :little | :big}
+
+  @typedoc """
+  What to dump for characters a single-byte encoding has no byte for.
+  See the `:on_unmappable` option of `define/2`.
+  """
+  @type on_unmappable :: :replace | {:replace, String.t()} | :error | :transliterate
 
   @typedoc """
   Options for `define/2`.
@@ -409,8 +505,18 @@ defmodule RustyCSV do
       Defaults to `","`.
     * `:escape` - Escape/quote character. Defaults to `"\""`.
     * `:newlines` - List of recognized line endings. Defaults to `["\r\n", "\n"]`.
+      A single one-byte ending (e.g. `["\r"]` or `["|"]`) and `["\r\n", "\n", "\r"]`
+      (bare `\r` as well) use the SIMD scanner; other lists use the general parser.
     * `:trim_bom` - Remove BOM when parsing strings. Defaults to `false`.
+    * `:sep_directive` - When `true`, a first line like `sep=;` (as Excel writes it)
+      sets the separator for the rest of the input and is dropped, with a UTF-8
+      BOM before it. Input without one is parsed with `:separator`. Defaults to
+      `false`.
     * `:encoding` - Character encoding. Defaults to `:utf8`. See `t:encoding/0`.
+      `:auto` takes the encoding of parsed input from its BOM (UTF-8, UTF-16 or
+      UTF-32) and drops the BOM.
+    * `:default_encoding` - With `encoding: :auto`, the encoding of input without
+      a BOM, and of dumped output. Defaults to `:utf8`.
 
   ## Dumping Options
 
@@ -420,6 +526,9 @@ defmodule RustyCSV do
     * `:escape_formula` - Map for formula injection prevention. Defaults to `nil`.
       When set, fields starting with trigger characters are prefixed with a
       replacement string inside quotes. Handled natively in the Rust NIF.
+    * `:on_unmappable` - What to dump for characters a single-byte encoding cannot
+      represent: `:replace` (with `"?"`, the default), `{:replace, string}`, `:error`
+      (raise `RustyCSV.EncodeError`) or `:transliterate` (ASCII look-alikes).
 
   ## Other Options
 
@@ -433,10 +542,13 @@ defmodule RustyCSV do
           newlines: [String.t()],
           line_separator: String.t(),
           trim_bom: boolean(),
+          sep_directive: boolean(),
           dump_bom: boolean(),
           reserved: [String.t()],
           escape_formula: map() | nil,
-          encoding: encoding(),
+          on_unmappable: on_unmappable(),
+          encoding: encoding() | :auto,
+          default_encoding: encoding(),
           strategy: strategy(),
           moduledoc: String.t() | false | nil
         ]
@@ -460,6 +572,119 @@ defmodule RustyCSV do
     def message(%{message: message}), do: message
   end
 
+  defmodule EncodeError do
+    @moduledoc """
+    Exception raised when dumping rows fails, such as for a character the
+    output encoding cannot represent with `on_unmappable: :error`.
+
+    ## Fields
+
+      * `:message` - Human-readable error description
+
+    """
+    defexception [:message]
+
+    @impl true
+    def message(%{message: message}), do: message
+  end
+
+  # ==========================================================================
+  # Struct Rows
+  # ==========================================================================
+
+  @doc false
+  # Builds the `{:struct, module, fields, required}` row format passed to the
+  # headers NIFs for `into: module`.
+  def struct_row_format!(module, required) do
+    unless is_atom(module) and Code.ensure_loaded?(module) and
+             function_exported?(module, :__struct__, 0) do
+      raise ArgumentError,
+            "invalid :into option, expected a struct module, got: #{inspect(module)}"
+    end
+
+    fields = module.__struct__() |> Map.from_struct() |> Map.to_list()
+
+    unless is_list(required) and Enum.all?(required, &List.keymember?(fields, &1, 0)) do
+      raise ArgumentError,
+            "invalid :required option, expected a list of #{inspect(module)} fields, " <>
+              "got: #{inspect(required)}"
+    end
+
+    {:struct, module, fields, required}
+  end
+
+  @doc false
+  # Builds the `{extra_columns, missing_columns}` policy tuple passed to the
+  # headers NIFs.
+  def column_policy!(opts) do
+    extra =
+      case Keyword.get(opts, :extra_columns, :ignore) do
+        policy when policy in [:ignore, :error] ->
+          policy
+
+        {:collect, key} = policy when is_atom(key) or is_binary(key) ->
+          if Keyword.get(opts, :into) do
+            raise ArgumentError,
+                  "extra_columns: {:collect, key} can't be used with :into, " <>
+                    "since structs only hold their own fields"
+          end
+
+          policy
+
+        other ->
+          raise ArgumentError,
+                "invalid :extra_columns option, expected :ignore, :error, or " <>
+                  "{:collect, key}, got: #{inspect(other)}"
+      end
+
+    missing =
+      case Keyword.get(opts, :missing_columns, nil) do
+        policy when policy in [nil, :omit, :error] ->
+          policy
+
+        other ->
+          raise ArgumentError,
+                "invalid :missing_columns option, expected nil, :omit, or :error, " <>
+                  "got: #{inspect(other)}"
+      end
+
+    {extra, missing}
+  end
+
+  @doc false
+  # Message for a `{:invalid_encoding | :incomplete_encoding, encoding, offset}`
+  # error raised by the decoding NIFs.
+  def encoding_error_message({:invalid_encoding, encoding, offset}) do
+    "Invalid #{inspect(encoding)} sequence at byte #{offset}"
+  end
+
+  def encoding_error_message({:incomplete_encoding, encoding, offset}) do
+    "Incomplete #{inspect(encoding)} sequence at byte #{offset}"
+  end
+
+  @doc false
+  # Message for an `{:unmappable, encoding, row, column, character}` or
+  # `{:invalid_utf8, row, column, offset}` error raised by the encoding NIFs,
+  # with `rows_before` added to the row.
+  def encode_error_message({:unmappable, encoding, row, column, char}, rows_before) do
+    <<codepoint::utf8>> = char
+    hex = codepoint |> Integer.to_string(16) |> String.pad_leading(4, "0")
+
+    "Cannot encode #{inspect(char)} (U+#{hex}) in #{inspect(encoding)} " <>
+      "at row #{row + rows_before}, column #{column}"
+  end
+
+  def encode_error_message({:invalid_utf8, row, column, offset}, rows_before) do
+    "Invalid UTF-8 in row #{row + rows_before}, field #{column} at byte #{offset}"
+  end
+
+  @doc false
+  # Message for a `{:extra_columns | :missing_columns, row, expected, actual}`
+  # error raised by the headers NIFs.
+  def column_error_message({_kind, row, expected, actual}) do
+    "Row #{row} has #{actual} fields, expected #{expected}"
+  end
+
   # ==========================================================================
   # Callbacks (Behaviour)
   # ==========================================================================
@@ -559,17 +784,35 @@ defmodule RustyCSV do
     * `:trim_bom` - When `true`, removes the BOM (byte order marker)
       from the beginning of strings before parsing. Defaults to `false`.
 
+    * `:sep_directive` - When `true`, a first line like `sep=;` sets the separator
+      for the rest of the input and is dropped, as Excel does when it opens a file.
+      A UTF-8 BOM before the line (as in `excel: :utf8` dumps) is dropped with it,
+      without `:trim_bom`. Input without one is parsed with `:separator`. Works for
+      strings and streams. Defaults to `false`.
+
     * `:encoding` - Character encoding for input/output. Defaults to `:utf8`.
       Supported encodings:
       * `:utf8` - UTF-8 (default, no conversion overhead)
       * `:latin1` - ISO-8859-1 / Latin-1
+      * `:windows_1252` - Windows-1252, as Excel on Windows writes "ANSI" files
+      * `:iso_8859_15` - ISO-8859-15 / Latin-9
+      * `{:codepage, number}` - A single-byte codepage: 437, 850, 1250, 1251, 1252,
+        20866 (KOI8-R) or 28605 (ISO-8859-15)
       * `{:utf16, :little}` - UTF-16 Little Endian
       * `{:utf16, :big}` - UTF-16 Big Endian
       * `{:utf32, :little}` - UTF-32 Little Endian
       * `{:utf32, :big}` - UTF-32 Big Endian
 
-      When encoding is not `:utf8`, input data is converted to UTF-8 for
-      parsing, and output is converted back to the target encoding.
+      When encoding is not `:utf8`, each parsed field is converted to UTF-8
+      as it is built, and output is converted back to the target encoding.
+
+      With `:auto`, parsing reads the encoding from a UTF-8, UTF-16 or UTF-32
+      BOM at the start of the input and drops the BOM; input without one is
+      read as `:default_encoding`. Use it for one module that accepts whatever
+      a spreadsheet application exported.
+
+    * `:default_encoding` - The encoding `encoding: :auto` falls back to, and
+      the encoding dumped output is written in. Defaults to `:utf8`.
 
   ### Dumping Options
 
@@ -589,6 +832,17 @@ defmodule RustyCSV do
 
       Example: `%{"=" => true, "+" => true, "-" => true, "@" => true}`
 
+    * `:on_unmappable` - What to write for characters that a single-byte
+      `:encoding` has no byte for. Defaults to `:replace`.
+      * `:replace` - Write `"?"`
+      * `{:replace, string}` - Write `string`, quoted and encoded like the rest
+        of the field (its own characters the encoding lacks become `"?"`)
+      * `:error` - Raise `RustyCSV.EncodeError` naming the row and column of
+        the first such character
+      * `:transliterate` - Write an ASCII look-alike: curly quotes become
+        straight ones, `"–"` becomes `"-"`, `"…"` becomes `"..."`, `"€"`
+        becomes `"EUR"`, `"ł"` becomes `"l"`, and `"?"` where there is none
+
   ### Strategy Options
 
     * `:strategy` - The default parsing strategy. One of:
@@ -669,17 +923,24 @@ defmodule RustyCSV do
     line_separator = Keyword.get(options, :line_separator, "\n")
     newlines = Keyword.get(options, :newlines, ["\r\n", "\n"])
     trim_bom = Keyword.get(options, :trim_bom, false)
+    sep_directive = Keyword.get(options, :sep_directive, false)
     dump_bom = Keyword.get(options, :dump_bom, false)
     reserved = Keyword.get(options, :reserved, [])
     reserved_binaries = Enum.map(reserved, &normalize_codepoint/1)
     escape_formula = Keyword.get(options, :escape_formula, nil)
+    on_unmappable = Keyword.get(options, :on_unmappable, :replace)
+    validate_on_unmappable!(on_unmappable)
     default_strategy = Keyword.get(options, :strategy, :simd)
     moduledoc = Keyword.get(options, :moduledoc)
 
-    # Encoding support
+    # Encoding support. With `:auto` the input encoding comes from its BOM,
+    # and output is dumped in the default encoding.
     encoding = Keyword.get(options, :encoding, :utf8)
-    validate_encoding!(encoding)
-    bom = :unicode.encoding_to_bom(encoding)
+    default_encoding = Keyword.get(options, :default_encoding, :utf8)
+    validate_encoding!(default_encoding)
+    unless encoding == :auto, do: validate_encoding!(encoding)
+    dump_encoding = if encoding == :auto, do: default_encoding, else: encoding
+    bom = encoding_bom(dump_encoding)
 
     stored_options = [
       separator: separator_list,
@@ -687,10 +948,13 @@ defmodule RustyCSV do
       line_separator: line_separator,
       newlines: newlines,
       trim_bom: trim_bom,
+      sep_directive: sep_directive,
       dump_bom: dump_bom,
       reserved: reserved,
       escape_formula: escape_formula,
+      on_unmappable: on_unmappable,
       encoding: encoding,
+      default_encoding: default_encoding,
       strategy: default_strategy
     ]
 
@@ -702,12 +966,16 @@ defmodule RustyCSV do
       line_separator: line_separator,
       newlines: newlines,
       trim_bom: trim_bom,
+      sep_directive: sep_directive,
       dump_bom: dump_bom,
       escape_formula: escape_formula,
+      on_unmappable: on_unmappable,
       default_strategy: default_strategy,
       stored_options: stored_options,
       moduledoc: moduledoc,
       encoding: encoding,
+      default_encoding: default_encoding,
+      dump_encoding: dump_encoding,
       bom: bom,
       reserved_binaries: reserved_binaries
     }
@@ -756,16 +1024,51 @@ defmodule RustyCSV do
           "RustyCSV separator must be a string or list of strings, got: #{inspect(other)}"
   end
 
-  defp validate_encoding!(encoding) when encoding in [:utf8, :latin1], do: :ok
+  @single_byte_encodings [:latin1, :windows_1252, :iso_8859_15]
+
+  defp validate_encoding!(encoding) when encoding in [:utf8 | @single_byte_encodings], do: :ok
   defp validate_encoding!({:utf16, endian}) when endian in [:little, :big], do: :ok
   defp validate_encoding!({:utf32, endian}) when endian in [:little, :big], do: :ok
 
+  # The codepage tables live in Rust, so the supported numbers come from there
+  defp validate_encoding!({:codepage, number} = encoding) do
+    codepages = RustyCSV.Native.codepages()
+    unless number in codepages, do: raise_invalid_encoding!(encoding, codepages)
+    :ok
+  end
+
   defp validate_encoding!(encoding) do
+    raise_invalid_encoding!(encoding, RustyCSV.Native.codepages())
+  end
+
+  defp raise_invalid_encoding!(encoding, codepages) do
     raise ArgumentError,
           "Invalid encoding: #{inspect(encoding)}. " <>
-            "Supported: :utf8, :latin1, {:utf16, :little}, {:utf16, :big}, {:utf32, :little}, {:utf32, :big}"
+            "Supported: :utf8, :latin1, :windows_1252, :iso_8859_15, " <>
+            "{:codepage, n} (n in #{inspect(codepages)}), {:utf16, :little}, " <>
+            "{:utf16, :big}, {:utf32, :little}, {:utf32, :big} (and :auto for :encoding)"
   end
 
+  defp validate_on_unmappable!(policy) when policy in [:replace, :error, :transliterate], do: :ok
+
+  defp validate_on_unmappable!({:replace, string} = policy) when is_binary(string) do
+    unless String.valid?(string), do: invalid_on_unmappable!(policy)
+    :ok
+  end
+
+  defp validate_on_unmappable!(policy), do: invalid_on_unmappable!(policy)
+
+  defp invalid_on_unmappable!(policy) do
+    raise ArgumentError,
+          "Invalid on_unmappable: #{inspect(policy)}. " <>
+            "Supported: :replace, {:replace, string}, :error, :transliterate"
+  end
+
+  # Single-byte encodings have no BOM (`:unicode` only knows `:latin1`)
+  defp encoding_bom(encoding) when encoding in @single_byte_encodings, do: ""
+  defp encoding_bom({:codepage, _}), do: ""
+  defp encoding_bom(encoding), do: :unicode.encoding_to_bom(encoding)
+
   # ==========================================================================
   # Private: Module Compilation
   # ==========================================================================
@@ -812,6 +1115,16 @@ defmodule RustyCSV do
 
       @separator unquote(Macro.escape(config.separator))
       @separator_binaries unquote(Macro.escape(config.separator_binaries))
+      @sep_directive unquote(Macro.escape(config.sep_directive))
+      @separator_nif unquote(
+                       Macro.escape(
+                         if config.sep_directive do
+                           {:sep_directive, config.separator_binaries}
+                         else
+                           config.separator_binaries
+                         end
+                       )
+                     )
       @escape unquote(Macro.escape(config.escape))
       @escape_binary unquote(Macro.escape(config.escape_binary))
       @line_separator unquote(Macro.escape(config.line_separator))
@@ -831,8 +1144,11 @@ defmodule RustyCSV do
       @default_strategy unquote(Macro.escape(config.default_strategy))
       @stored_options unquote(Macro.escape(config.stored_options))
       @encoding unquote(Macro.escape(config.encoding))
+      @default_encoding unquote(Macro.escape(config.default_encoding))
+      @dump_encoding unquote(Macro.escape(config.dump_encoding))
       @bom unquote(Macro.escape(config.bom))
       @reserved_binaries unquote(Macro.escape(config.reserved_binaries))
+      @on_unmappable unquote(Macro.escape(config.on_unmappable))
     end
   end
 
@@ -858,21 +1174,50 @@ defmodule RustyCSV do
 
   defp quoted_parse_string_function(config) do
     [
-      quoted_parse_string_main(config.encoding),
+      quoted_parse_string_main(config.encoding, config.default_encoding),
       quoted_parse_string_headers_clauses(),
       quoted_parse_to_maps_clauses(),
       quoted_maybe_trim_bom(config.trim_bom),
-      quoted_maybe_to_utf8(config.encoding),
+      quoted_parse_utf8_functions(config),
+      quoted_parse_encoded_clauses(config)
+    ]
+  end
+
+  # Other encodings scan their input encoded and never parse a UTF-8 copy;
+  # `:auto` parses UTF-8 input as UTF-8 modules do
+  defp quoted_parse_utf8_functions(%{encoding: :utf8}) do
+    [quoted_parse_utf8_clauses(), quoted_do_parse_string_clauses()]
+  end
+
+  defp quoted_parse_utf8_functions(%{encoding: :auto} = config) do
+    [
+      quoted_parse_utf8_clauses(),
+      quoted_auto_utf8_input(config.default_encoding),
       quoted_do_parse_string_clauses()
     ]
   end
 
-  defp quoted_parse_string_main(encoding) do
+  defp quoted_parse_utf8_functions(_config), do: []
+
+  defp quoted_parse_string_main(encoding, default_encoding) do
     encoding_doc =
-      if encoding == :utf8,
-        do: "",
-        else:
-          "\n\n  Input is expected in #{inspect(encoding)} encoding and will be converted to UTF-8 for parsing."
+      case encoding do
+        :utf8 ->
+          ""
+
+        :auto ->
+          "\n\n  The input encoding is taken from its BOM (UTF-8, UTF-16 or UTF-32), or is " <>
+            "#{inspect(default_encoding)} without one. Input in any other encoding than " <>
+            "UTF-8 is scanned as it is and each field is converted to UTF-8 as it is " <>
+            "built (`:strategy` is checked but does not apply)."
+
+        _ ->
+          "\n\n  Input is expected in #{inspect(encoding)} encoding. It is scanned as it " <>
+            "is and each field is converted to UTF-8 as it is built, for every row " <>
+            "format (`:strategy` is checked but does not apply)."
+      end
+
+    parse_body = quoted_parse_string_body(encoding)
 
     quote do
       @doc """
@@ -888,72 +1233,357 @@ defmodule RustyCSV do
             `:skip_headers` is ignored.
           * `[atom | string, ...]` - Use explicit keys, return maps.
             First row skipped by default; pass `skip_headers: false` if no header row.
+        * `:duplicate_headers` - Policy for repeated header keys: `:last` (default),
+          `:first`, `:error`, `:suffix`, or `:list`. See `RustyCSV` for details.
+        * `:extra_columns` - Rows longer than the header: `:ignore` (default),
+          `:error`, or `{:collect, key}`.
+        * `:missing_columns` - Rows shorter than the header: `nil` (default),
+          `:omit`, or `:error`.
+        * `:row_format` - Shape of each row. `:list` (default) or `:tuple` without
+          `:headers`; `:map` (default) or `:keyword` with `:headers`.
+        * `:into` - Struct module to build rows as, with `:headers`. See `RustyCSV`.
+        * `:required` - Struct fields that must appear in the header (with `:into`).
       #{unquote(encoding_doc)}
       """
       @impl RustyCSV
-      @spec parse_string(binary(), RustyCSV.parse_options()) :: RustyCSV.rows() | [map()]
+      @spec parse_string(binary(), RustyCSV.parse_options()) ::
+              RustyCSV.rows() | [tuple()] | [map()] | [keyword()]
       def parse_string(string, opts \\ [])
 
       def parse_string(string, opts) when is_binary(string) and is_list(opts) do
         headers = Keyword.get(opts, :headers, false)
         strategy = Keyword.get(opts, :strategy, @default_strategy)
-        string = string |> maybe_trim_bom() |> maybe_to_utf8()
-        do_parse_string_with_headers(string, strategy, headers, opts)
+        unquote(parse_body)
+      end
+    end
+  end
+
+  # Input in any other encoding than UTF-8 is never decoded whole: the NIFs scan
+  # it encoded and decode each field as they build it
+  defp quoted_parse_string_body(:utf8) do
+    quote do
+      do_parse_string_with_headers(maybe_trim_bom(string), strategy, headers, opts)
+    end
+  end
+
+  defp quoted_parse_string_body(:auto) do
+    quote do
+      string = maybe_trim_bom(string)
+
+      case auto_utf8_input(string) do
+        {:ok, utf8} ->
+          do_parse_string_with_headers(utf8, strategy, headers, opts)
+
+        :error ->
+          validate_decoding_strategy!(strategy)
+          do_parse_encoded(string, headers, opts)
       end
     end
   end
 
+  defp quoted_parse_string_body(_encoding) do
+    quote do
+      validate_decoding_strategy!(strategy)
+      do_parse_encoded(maybe_trim_bom(string), headers, opts)
+    end
+  end
+
+  # `:unicode` finds the same BOMs as the NIFs; input without one is in the
+  # default encoding
+  defp quoted_auto_utf8_input(default_encoding) do
+    no_bom = if default_encoding == :utf8, do: quote(do: {:ok, string}), else: :error
+
+    quote do
+      defp auto_utf8_input(string) do
+        case :unicode.bom_to_encoding(string) do
+          {:utf8, bom_size} -> {:ok, binary_part(string, bom_size, byte_size(string) - bom_size)}
+          {:latin1, 0} -> unquote(no_bom)
+          _ -> :error
+        end
+      end
+    end
+  end
+
+  # Clauses behind the field-decoding bodies above; `:strategy` doesn't apply
+  # to them, but is still checked as it would be for UTF-8 input
+  defp quoted_parse_encoded_clauses(%{encoding: :utf8}), do: []
+
+  defp quoted_parse_encoded_clauses(config) do
+    [
+      quoted_validate_decoding_strategy(),
+      quoted_do_parse_encoded_clauses(nif_encoding(config.encoding, config.default_encoding))
+    ]
+  end
+
+  defp quoted_validate_decoding_strategy do
+    quote do
+      defp validate_decoding_strategy!(strategy)
+           when strategy in [:basic, :simd, :indexed, :parallel, :zero_copy],
+           do: :ok
+
+      defp validate_decoding_strategy!(other) do
+        raise ArgumentError,
+              "invalid :strategy option, expected :basic, :simd, :indexed, :parallel, " <>
+                "or :zero_copy, got: #{inspect(other)}"
+      end
+    end
+  end
+
+  defp quoted_do_parse_encoded_clauses(encoding) do
+    quote do
+      defp do_parse_encoded(string, false, opts) do
+        rows =
+          case row_format_opt(opts, false) do
+            :list -> do_parse_encoded_rows(string)
+            :tuple -> do_parse_encoded_to_tuples(string)
+          end
+
+        skip_header_row(rows, Keyword.get(opts, :skip_headers, true))
+      end
+
+      defp do_parse_encoded(string, true, opts) do
+        do_parse_encoded_to_maps(string, true, true, opts)
+      end
+
+      defp do_parse_encoded(string, header_list, opts) when is_list(header_list) do
+        skip_headers = Keyword.get(opts, :skip_headers, true)
+        do_parse_encoded_to_maps(string, header_list, skip_headers, opts)
+      end
+
+      defp do_parse_encoded(_string, other, _opts) do
+        raise ArgumentError,
+              "invalid :headers option, expected false, true, or a list of keys, " <>
+                "got: #{inspect(other)}"
+      end
+
+      defp do_parse_encoded_rows(string) do
+        RustyCSV.Native.parse_string_encoded_with_config(
+          string,
+          unquote(encoding),
+          @separator_nif,
+          @escape_binary,
+          @newlines_nif
+        )
+      rescue
+        e in ErlangError -> reraise_encoded_error(e, __STACKTRACE__)
+      end
+
+      defp do_parse_encoded_to_tuples(string) do
+        RustyCSV.Native.parse_encoded_to_tuples(
+          string,
+          unquote(encoding),
+          @separator_nif,
+          @escape_binary,
+          @newlines_nif
+        )
+      rescue
+        e in ErlangError -> reraise_encoded_error(e, __STACKTRACE__)
+      end
+
+      defp do_parse_encoded_to_maps(string, header_mode, skip_first, opts) do
+        RustyCSV.Native.parse_encoded_to_maps(
+          string,
+          unquote(encoding),
+          @separator_nif,
+          @escape_binary,
+          @newlines_nif,
+          header_mode,
+          skip_first,
+          duplicate_headers_opt(opts),
+          map_row_format_opt(opts, header_mode),
+          RustyCSV.column_policy!(opts)
+        )
+      rescue
+        e in ErlangError -> reraise_encoded_error(e, __STACKTRACE__)
+      end
+
+      defp reraise_encoded_error(%ErlangError{original: {kind, _, _} = error}, _)
+           when kind in [:invalid_encoding, :incomplete_encoding] do
+        raise RustyCSV.ParseError, message: RustyCSV.encoding_error_message(error)
+      end
+
+      defp reraise_encoded_error(error, stacktrace), do: reraise_header_error(error, stacktrace)
+    end
+  end
+
   defp quoted_parse_string_headers_clauses do
+    quote do
+      defp skip_header_row([_ | tail], true), do: tail
+      defp skip_header_row(rows, _skip_headers), do: rows
+
+      defp duplicate_headers_opt(opts) do
+        case Keyword.get(opts, :duplicate_headers, :last) do
+          policy when policy in [:last, :first, :error, :suffix, :list] ->
+            policy
+
+          other ->
+            raise ArgumentError,
+                  "invalid :duplicate_headers option, expected :last, :first, :error, " <>
+                    ":suffix, or :list, got: #{inspect(other)}"
+        end
+      end
+
+      defp row_format_opt(opts, false) do
+        if Keyword.has_key?(opts, :into) do
+          raise ArgumentError, ":into requires the :headers option"
+        end
+
+        case Keyword.get(opts, :row_format, :list) do
+          format when format in [:list, :tuple] ->
+            format
+
+          other ->
+            raise ArgumentError,
+                  "invalid :row_format option without :headers, expected :list or :tuple, " <>
+                    "got: #{inspect(other)}"
+        end
+      end
+
+      defp row_format_opt(opts, _headers) do
+        case Keyword.get(opts, :row_format, :map) do
+          format when format in [:map, :keyword] ->
+            format
+
+          other ->
+            raise ArgumentError,
+                  "invalid :row_format option with :headers, expected :map or :keyword, " <>
+                    "got: #{inspect(other)}"
+        end
+      end
+    end
+  end
+
+  defp quoted_parse_to_maps_clauses do
+    quote do
+      defp map_row_format_opt(opts, header_mode) do
+        case {Keyword.get(opts, :into), row_format_opt(opts, header_mode)} do
+          {nil, :keyword} ->
+            if Keyword.has_key?(opts, :duplicate_headers) do
+              raise ArgumentError,
+                    ":duplicate_headers does not apply to row_format: :keyword, " <>
+                      "which keeps every column"
+            end
+
+            :keyword
+
+          {nil, format} ->
+            format
+
+          {module, :map} ->
+            RustyCSV.struct_row_format!(module, Keyword.get(opts, :required, []))
+
+          {_module, format} ->
+            raise ArgumentError, ":into requires row_format: :map, got: #{inspect(format)}"
+        end
+      end
+
+      defp reraise_header_error(%ErlangError{original: {:duplicate_header, name}}, _) do
+        raise RustyCSV.ParseError, message: "Duplicate header: #{inspect(name)}"
+      end
+
+      defp reraise_header_error(%ErlangError{original: {:missing_required_header, name}}, _) do
+        raise RustyCSV.ParseError, message: "Missing required header: #{inspect(name)}"
+      end
+
+      defp reraise_header_error(%ErlangError{original: {:unknown_header_atom, name}}, _) do
+        raise RustyCSV.ParseError,
+          message:
+            "Header #{inspect(name)} is not an existing atom; " <>
+              "pass the keys with headers: [...] for row_format: :keyword"
+      end
+
+      defp reraise_header_error(%ErlangError{original: {:collect_key_conflict, name}}, _) do
+        raise RustyCSV.ParseError,
+          message: "extra_columns: {:collect, key} uses #{inspect(name)}, which is also a header"
+      end
+
+      defp reraise_header_error(%ErlangError{original: {kind, _, _, _} = error}, _)
+           when kind in [:extra_columns, :missing_columns] do
+        raise RustyCSV.ParseError, message: RustyCSV.column_error_message(error)
+      end
+
+      defp reraise_header_error(error, stacktrace), do: reraise(error, stacktrace)
+    end
+  end
+
+  defp quoted_parse_utf8_clauses do
     quote do
       defp do_parse_string_with_headers(string, strategy, false, opts) do
         skip_headers = Keyword.get(opts, :skip_headers, true)
-        rows = do_parse_string(string, strategy)
 
-        case {skip_headers, rows} do
-          {true, [_ | tail]} -> tail
-          _ -> rows
-        end
+        rows =
+          case row_format_opt(opts, false) do
+            :list -> do_parse_string(string, strategy)
+            :tuple -> do_parse_to_tuples(string, strategy)
+          end
+
+        skip_header_row(rows, skip_headers)
       end
 
-      defp do_parse_string_with_headers(string, strategy, true, _opts) do
-        do_parse_to_maps(string, strategy, true, true)
+      defp do_parse_string_with_headers(string, strategy, true, opts) do
+        do_parse_to_maps(string, strategy, true, true, opts)
       end
 
       defp do_parse_string_with_headers(string, strategy, header_list, opts)
            when is_list(header_list) do
         skip_headers = Keyword.get(opts, :skip_headers, true)
-        do_parse_to_maps(string, strategy, header_list, skip_headers)
+        do_parse_to_maps(string, strategy, header_list, skip_headers, opts)
       end
 
       defp do_parse_string_with_headers(_string, _strategy, other, _opts) do
         raise ArgumentError,
               "invalid :headers option, expected false, true, or a list of keys, got: #{inspect(other)}"
       end
-    end
-  end
 
-  defp quoted_parse_to_maps_clauses do
-    quote do
-      defp do_parse_to_maps(string, :parallel, header_mode, skip_first) do
+      defp do_parse_to_maps(string, :parallel, header_mode, skip_first, opts) do
         RustyCSV.Native.parse_to_maps_parallel(
           string,
-          @separator_binaries,
+          @separator_nif,
           @escape_binary,
           @newlines_nif,
           header_mode,
-          skip_first
+          skip_first,
+          duplicate_headers_opt(opts),
+          map_row_format_opt(opts, header_mode),
+          RustyCSV.column_policy!(opts)
         )
+      rescue
+        e in ErlangError -> reraise_header_error(e, __STACKTRACE__)
       end
 
-      defp do_parse_to_maps(string, strategy, header_mode, skip_first) do
+      defp do_parse_to_maps(string, strategy, header_mode, skip_first, opts) do
         RustyCSV.Native.parse_to_maps(
           string,
-          @separator_binaries,
+          @separator_nif,
           @escape_binary,
           @newlines_nif,
           strategy,
           header_mode,
-          skip_first
+          skip_first,
+          duplicate_headers_opt(opts),
+          map_row_format_opt(opts, header_mode),
+          RustyCSV.column_policy!(opts)
+        )
+      rescue
+        e in ErlangError -> reraise_header_error(e, __STACKTRACE__)
+      end
+
+      defp do_parse_to_tuples(string, :parallel) do
+        RustyCSV.Native.parse_to_tuples_parallel(
+          string,
+          @separator_nif,
+          @escape_binary,
+          @newlines_nif
+        )
+      end
+
+      defp do_parse_to_tuples(string, strategy) do
+        RustyCSV.Native.parse_to_tuples(
+          string,
+          @separator_nif,
+          @escape_binary,
+          @newlines_nif,
+          strategy
         )
       end
     end
@@ -972,43 +1602,16 @@ defmodule RustyCSV do
     end
   end
 
-  # For UTF-8, encoding conversion is a no-op
-  defp quoted_maybe_to_utf8(:utf8) do
-    quote do
-      defp maybe_to_utf8(data), do: data
-    end
-  end
-
-  # For other encodings, convert to UTF-8 using :unicode module
-  defp quoted_maybe_to_utf8(encoding) do
-    quote do
-      defp maybe_to_utf8(data) do
-        case :unicode.characters_to_binary(data, unquote(Macro.escape(encoding)), :utf8) do
-          binary when is_binary(binary) ->
-            binary
-
-          {:incomplete, converted, rest} ->
-            raise RustyCSV.ParseError,
-              message:
-                "Incomplete #{inspect(unquote(Macro.escape(encoding)))} sequence: " <>
-                  "converted #{byte_size(converted)} bytes, #{byte_size(rest)} bytes remaining"
-
-          {:error, converted, rest} ->
-            raise RustyCSV.ParseError,
-              message:
-                "Invalid #{inspect(unquote(Macro.escape(encoding)))} sequence at byte #{byte_size(converted)}: " <>
-                  "#{inspect(binary_part(rest, 0, min(byte_size(rest), 10)))}"
-        end
-      end
-    end
-  end
+  # `:auto` is `{:auto, default}` for the decoding NIFs
+  defp nif_encoding(:auto, default_encoding), do: {:auto, default_encoding}
+  defp nif_encoding(encoding, _default_encoding), do: encoding
 
   defp quoted_do_parse_string_clauses do
     quote do
       defp do_parse_string(string, :basic) do
         RustyCSV.Native.parse_string_with_config(
           string,
-          @separator_binaries,
+          @separator_nif,
           @escape_binary,
           @newlines_nif
         )
@@ -1017,7 +1620,7 @@ defmodule RustyCSV do
       defp do_parse_string(string, :simd) do
         RustyCSV.Native.parse_string_fast_with_config(
           string,
-          @separator_binaries,
+          @separator_nif,
           @escape_binary,
           @newlines_nif
         )
@@ -1026,7 +1629,7 @@ defmodule RustyCSV do
       defp do_parse_string(string, :indexed) do
         RustyCSV.Native.parse_string_indexed_with_config(
           string,
-          @separator_binaries,
+          @separator_nif,
           @escape_binary,
           @newlines_nif
         )
@@ -1035,7 +1638,7 @@ defmodule RustyCSV do
       defp do_parse_string(string, :parallel) do
         RustyCSV.Native.parse_string_parallel_with_config(
           string,
-          @separator_binaries,
+          @separator_nif,
           @escape_binary,
           @newlines_nif
         )
@@ -1044,7 +1647,7 @@ defmodule RustyCSV do
       defp do_parse_string(string, :zero_copy) do
         RustyCSV.Native.parse_string_zero_copy_with_config(
           string,
-          @separator_binaries,
+          @separator_nif,
           @escape_binary,
           @newlines_nif
         )
@@ -1055,8 +1658,7 @@ defmodule RustyCSV do
   defp quoted_parse_stream_function do
     [
       quoted_parse_stream_main(),
-      quoted_stream_headers_clauses(),
-      quoted_zip_to_map()
+      quoted_stream_headers_clauses()
     ]
   end
 
@@ -1074,10 +1676,21 @@ defmodule RustyCSV do
             `:skip_headers` is ignored.
           * `[atom | string, ...]` - Use explicit keys, return maps.
             First row skipped by default; pass `skip_headers: false` if no header row.
+        * `:duplicate_headers` - Policy for repeated header keys: `:last` (default),
+          `:first`, `:error`, `:suffix`, or `:list`. See `RustyCSV` for details.
+        * `:extra_columns` - Rows longer than the header: `:ignore` (default),
+          `:error`, or `{:collect, key}`.
+        * `:missing_columns` - Rows shorter than the header: `nil` (default),
+          `:omit`, or `:error`.
         * `:chunk_size` - Bytes per IO read. Defaults to `65536`.
         * `:batch_size` - Rows per batch. Defaults to `1000`.
         * `:max_buffer_size` - Maximum streaming buffer size in bytes.
           Defaults to `268_435_456` (256 MB). Raises if exceeded during parsing.
+        * `:max_pending_rows` - Maximum complete rows queued in the streaming
+          parser. Defaults to no limit.
+        * `:width_policy` - Check each row's field count against the header
+          row: `:error`, `:pad`, `:truncate`, or `[:pad, :truncate]`. See
+          `RustyCSV.Streaming`.
 
       """
       @impl RustyCSV
@@ -1096,15 +1709,24 @@ defmodule RustyCSV do
           escape: @escape_binary,
           newlines: @newlines_nif,
           encoding: @encoding,
+          default_encoding: @default_encoding,
           bom: @bom,
-          trim_bom: @trim_bom
+          trim_bom: @trim_bom,
+          sep_directive: @sep_directive
         ]
 
         stream_opts =
-          case Keyword.fetch(opts, :max_buffer_size) do
-            {:ok, max} -> Keyword.put(stream_opts, :max_buffer_size, max)
-            :error -> stream_opts
-          end
+          stream_opts ++ Keyword.take(opts, [:max_buffer_size, :max_pending_rows, :width_policy])
+
+        stream_opts = stream_opts ++ stream_header_opts(headers, opts)
+
+        if row_format_opt(opts, headers) in [:tuple, :keyword] do
+          raise ArgumentError, ":row_format #{inspect(opts[:row_format])} requires parse_string/2"
+        end
+
+        if Keyword.has_key?(opts, :into) do
+          raise ArgumentError, ":into requires parse_string/2"
+        end
 
         result_stream = RustyCSV.Streaming.stream_enumerable(stream, stream_opts)
 
@@ -1115,6 +1737,24 @@ defmodule RustyCSV do
 
   defp quoted_stream_headers_clauses do
     quote do
+      # Maps are built by the streaming resource itself (see RustyCSV.Streaming)
+      defp stream_header_opts(false, _opts), do: []
+
+      defp stream_header_opts(headers, opts) when headers == true or is_list(headers) do
+        [
+          headers: headers,
+          skip_headers: Keyword.get(opts, :skip_headers, true),
+          duplicate_headers: duplicate_headers_opt(opts),
+          extra_columns: Keyword.get(opts, :extra_columns, :ignore),
+          missing_columns: Keyword.get(opts, :missing_columns, nil)
+        ]
+      end
+
+      defp stream_header_opts(other, _opts) do
+        raise ArgumentError,
+              "invalid :headers option, expected false, true, or a list of keys, got: #{inspect(other)}"
+      end
+
       defp do_stream_with_headers(stream, false, opts) do
         if Keyword.get(opts, :skip_headers, true) do
           Stream.drop(stream, 1)
@@ -1123,44 +1763,7 @@ defmodule RustyCSV do
         end
       end
 
-      defp do_stream_with_headers(stream, true, _opts) do
-        Stream.transform(stream, :no_header, fn
-          row, :no_header ->
-            {[], {:header, row, length(row)}}
-
-          row, {:header, _keys, _num_keys} = state ->
-            {[zip_to_map(state, row)], state}
-        end)
-      end
-
-      defp do_stream_with_headers(stream, header_list, opts) when is_list(header_list) do
-        num_keys = length(header_list)
-        state = {:header, header_list, num_keys}
-        base = if Keyword.get(opts, :skip_headers, true), do: Stream.drop(stream, 1), else: stream
-        Stream.map(base, &zip_to_map(state, &1))
-      end
-
-      defp do_stream_with_headers(_stream, other, _opts) do
-        raise ArgumentError,
-              "invalid :headers option, expected false, true, or a list of keys, got: #{inspect(other)}"
-      end
-    end
-  end
-
-  defp quoted_zip_to_map do
-    quote do
-      defp zip_to_map({:header, keys, num_keys}, row) do
-        row_len = length(row)
-
-        padded =
-          cond do
-            row_len == num_keys -> row
-            row_len < num_keys -> row ++ List.duplicate(nil, num_keys - row_len)
-            true -> Enum.take(row, num_keys)
-          end
-
-        keys |> Enum.zip(padded) |> Map.new()
-      end
+      defp do_stream_with_headers(stream, _headers, _opts), do: stream
     end
   end
 
@@ -1246,6 +1849,25 @@ defmodule RustyCSV do
           SIMD-accelerated encoder. Pass `strategy: :parallel` for multi-threaded
           encoding via rayon, which is faster for quoting-heavy data.
 
+        * `:invalid_utf8` - What to do with fields that are not valid UTF-8.
+          `:passthrough` (default) copies the bytes as they are, `:replace`
+          replaces each invalid sequence with U+FFFD (then encoded like any
+          other character), and `:error` raises `RustyCSV.EncodeError` naming
+          the row and field.
+
+        * `:excel` - Write a file Excel opens with every cell intact. `true`
+          (or `:utf16`) writes UTF-16LE with a BOM and tab separators, which
+          every Excel version reads; `:utf8` writes UTF-8 with a BOM and a
+          `sep=` first line naming the separator (some Excel versions ignore
+          the BOM after reading that line, so prefer `:utf16` for non-ASCII
+          data; `:utf8` raises for a multi-character separator, which `sep=`
+          can't name). Either way fields Excel would run as formulas get a `'`
+          prefix (plain numbers like `-12.50` are left alone), and digit
+          strings with a leading zero or more than 15 digits are written as
+          `="00123"` so Excel keeps them as text.
+          Replaces the module's `:encoding`, `:dump_bom` and `:escape_formula`
+          settings, and ignores `:strategy`.
+
       ## Examples
 
           # Default encoder (best for most data)
@@ -1254,30 +1876,104 @@ defmodule RustyCSV do
           # Parallel encoder (best for quoting-heavy data)
           #{inspect(__MODULE__)}.dump_to_iodata(rows, strategy: :parallel)
 
+          # For Excel
+          #{inspect(__MODULE__)}.dump_to_iodata(rows, excel: true)
+
       """
       @impl RustyCSV
       @spec dump_to_iodata(Enumerable.t(), RustyCSV.dump_options()) :: iodata()
       def dump_to_iodata(enumerable, opts \\ []) do
         rows = if is_list(enumerable), do: enumerable, else: Enum.to_list(enumerable)
         strategy = Keyword.get(opts, :strategy)
-        result = encode_rows_nif(rows, strategy)
+        invalid_utf8 = Keyword.get(opts, :invalid_utf8, :passthrough)
 
-        if @dump_bom do
-          [@bom, result]
-        else
-          result
+        unless invalid_utf8 in [:passthrough, :replace, :error] do
+          raise ArgumentError,
+                "invalid_utf8 must be :passthrough, :replace or :error, " <>
+                  "got: #{inspect(invalid_utf8)}"
+        end
+
+        case excel_variant(Keyword.get(opts, :excel, false)) do
+          nil ->
+            result =
+              try do
+                encode_rows_nif(rows, strategy, invalid_utf8)
+              rescue
+                e in ErlangError -> reraise_encode_error(e, 0, __STACKTRACE__)
+              end
+
+            if @dump_bom do
+              [@bom, result]
+            else
+              result
+            end
+
+          variant ->
+            try do
+              encode_excel_nif(rows, variant, invalid_utf8)
+            rescue
+              e in ErlangError -> reraise_encode_error(e, 0, __STACKTRACE__)
+            end
         end
       end
 
-      defp encode_rows_nif(rows, :parallel) do
+      defp excel_variant(off) when off in [false, nil], do: nil
+      defp excel_variant(true), do: :utf16
+      defp excel_variant(:utf16), do: :utf16
+
+      defp excel_variant(:utf8) do
+        [separator | _] = @separator_binaries
+
+        unless String.length(separator) == 1 do
+          raise ArgumentError,
+                "excel: :utf8 writes a sep= line, which needs a single-character " <>
+                  "separator, got: #{inspect(separator)}"
+        end
+
+        :utf8
+      end
+
+      defp excel_variant(other) do
+        raise ArgumentError,
+              "excel must be a boolean, :utf16 or :utf8, got: #{inspect(other)}"
+      end
+
+      defp encode_excel_nif(rows, variant, invalid_utf8) do
+        RustyCSV.Native.encode_string_excel(
+          rows,
+          variant,
+          @separator_binaries,
+          @escape_binary,
+          @line_separator,
+          @reserved_binaries,
+          invalid_utf8
+        )
+      rescue
+        ArgumentError ->
+          rows = coerce_fields_to_binary(rows)
+
+          RustyCSV.Native.encode_string_excel(
+            rows,
+            variant,
+            @separator_binaries,
+            @escape_binary,
+            @line_separator,
+            @reserved_binaries,
+            invalid_utf8
+          )
+      end
+
+      defp encode_rows_nif(rows, :parallel, invalid_utf8) do
         RustyCSV.Native.encode_string_parallel(
           rows,
           @separator_binaries,
           @escape_binary,
           @line_separator,
           @formula_nif_config,
-          @encoding,
-          @reserved_binaries
+          @dump_encoding,
+          @reserved_binaries,
+          @on_unmappable,
+          invalid_utf8
         )
       rescue
         ArgumentError ->
@@ -1289,20 +1985,24 @@ defmodule RustyCSV do
             @escape_binary,
             @line_separator,
             @formula_nif_config,
-            @encoding,
-            @reserved_binaries
+            @dump_encoding,
+            @reserved_binaries,
+            @on_unmappable,
+            invalid_utf8
           )
       end
 
-      defp encode_rows_nif(rows, _strategy) do
+      defp encode_rows_nif(rows, _strategy, invalid_utf8) do
         RustyCSV.Native.encode_string(
           rows,
           @separator_binaries,
           @escape_binary,
           @line_separator,
           @formula_nif_config,
-          @encoding,
-          @reserved_binaries
+          @dump_encoding,
+          @reserved_binaries,
+          @on_unmappable,
+          invalid_utf8
         )
       rescue
         ArgumentError ->
@@ -1314,11 +2014,21 @@ defmodule RustyCSV do
             @escape_binary,
             @line_separator,
             @formula_nif_config,
-            @encoding,
-            @reserved_binaries
+            @dump_encoding,
+            @reserved_binaries,
+            @on_unmappable,
+            invalid_utf8
           )
       end
 
+      # `rows_before` counts the rows dumped before those the NIF was given
+      defp reraise_encode_error(%ErlangError{original: error}, rows_before, _)
+           when elem(error, 0) in [:unmappable, :invalid_utf8] do
+        raise RustyCSV.EncodeError, message: RustyCSV.encode_error_message(error, rows_before)
+      end
+
+      defp reraise_encode_error(error, _, stacktrace), do: reraise(error, stacktrace)
+
       defp coerce_fields_to_binary(rows) do
         Enum.map(rows, fn row ->
           Enum.map(row, fn
@@ -1334,7 +2044,15 @@ defmodule RustyCSV do
       @impl RustyCSV
       @spec dump_to_stream(Enumerable.t()) :: Enumerable.t()
       def dump_to_stream(enumerable) do
-        Stream.map(enumerable, &encode_single_row_nif/1)
+        enumerable
+        |> Stream.with_index()
+        |> Stream.map(fn {row, index} ->
+          try do
+            encode_single_row_nif(row)
+          rescue
+            e in ErlangError -> reraise_encode_error(e, index, __STACKTRACE__)
+          end
+        end)
       end
 
       defp encode_single_row_nif(row) do
@@ -1344,8 +2062,10 @@ defmodule RustyCSV do
           @escape_binary,
           @line_separator,
           @formula_nif_config,
-          @encoding,
-          @reserved_binaries
+          @dump_encoding,
+          @reserved_binaries,
+          @on_unmappable,
+          :passthrough
         )
       rescue
         ArgumentError ->
@@ -1361,8 +2081,10 @@ defmodule RustyCSV do
             @escape_binary,
             @line_separator,
             @formula_nif_config,
-            @encoding,
-            @reserved_binaries
+            @dump_encoding,
+            @reserved_binaries,
+            @on_unmappable,
+            :passthrough
           )
       end
     end
@@ -14,6 +14,9 @@ defmodule RustyCSV.Native do
     * **Binary** — a single separator, possibly multi-byte: `<<44>>` (comma), `"::"` (double colon)
     * **List of binaries** — multiple separators: `[<<44>>, <<59>>]` (comma or semicolon),
       `[",", "::"]` (comma or double colon)
+    * **`{:sep_directive, separator}`** — any of the above, replaced by the separator
+      named in a first line like `sep=;`. The directive line (and a UTF-8 BOM before
+      it) is dropped; input without one is parsed with `separator`
 
   ## Escape Format
 
@@ -49,6 +52,8 @@ defmodule RustyCSV.Native do
   All parsing NIFs run on BEAM dirty CPU schedulers to avoid blocking
   normal schedulers. This includes all `parse_string*` functions,
   `streaming_feed/2`, `streaming_next_rows/2`, and `streaming_finalize/1`.
+  `streaming_open_file/2` and `streaming_read_rows/2` do file IO and run on
+  dirty IO schedulers.
 
   Parallel parsing runs on a dedicated named `rustycsv-*` rayon thread pool
   (capped at 8 threads) rather than the global rayon pool.
@@ -128,6 +133,15 @@ defmodule RustyCSV.Native do
   @typedoc "Opaque reference to a streaming parser"
   @opaque parser_ref :: reference()
 
+  @typedoc """
+  Status after feeding: `{available_rows, buffer_size}`, or
+  `{:drain, available_rows, buffer_size}` when bytes are left unscanned until
+  rows are taken (see `streaming_set_max_pending_rows/2`)
+  """
+  @type feed_status ::
+          {non_neg_integer(), non_neg_integer()}
+          | {:drain, non_neg_integer(), non_neg_integer()}
+
   @typedoc "A parsed row (list of field binaries)"
   @type row :: [binary()]
 
@@ -139,8 +153,14 @@ defmodule RustyCSV.Native do
     * Integer byte (e.g., `44` for comma) — single separator
     * Binary (e.g., `<<44>>` or `<<58, 58>>`) — single separator (possibly multi-byte)
     * List of binaries (e.g., `[<<44>>, <<59>>]`) — multiple separators
+    * `{:sep_directive, separator}` — `separator` unless the input starts with a
+      `sep=` line, which picks the separator and is dropped
   """
-  @type separator :: binary() | non_neg_integer() | [binary()]
+  @type separator ::
+          binary()
+          | non_neg_integer()
+          | [binary()]
+          | {:sep_directive, binary() | non_neg_integer() | [binary()]}
 
   @typedoc """
   Quote/escape sequence. Accepts:
@@ -325,6 +345,9 @@ defmodule RustyCSV.Native do
       # Multi-byte separator streaming parser
       parser = RustyCSV.Native.streaming_new_with_config("::", 34)
 
+      # Comma unless the first line is a `sep=` directive
+      parser = RustyCSV.Native.streaming_new_with_config({:sep_directive, ","}, 34, :default)
+
   """
   @spec streaming_new_with_config(separator(), escape(), term()) :: parser_ref()
   def streaming_new_with_config(_separator, _escape, _newlines),
@@ -334,7 +357,9 @@ defmodule RustyCSV.Native do
   Feed a chunk of CSV data to the streaming parser. Runs on a dirty CPU scheduler.
 
   Returns `{available_rows, buffer_size}` indicating the number of complete
-  rows ready to be taken and the current buffer size.
+  rows ready to be taken and the current buffer size. When the limit set by
+  `streaming_set_max_pending_rows/2` stopped the scan with bytes left over,
+  returns `{:drain, available_rows, buffer_size}`: take rows to continue.
 
   ## Raises
 
@@ -349,13 +374,37 @@ defmodule RustyCSV.Native do
       {available, buffer_size} = RustyCSV.Native.streaming_feed(parser, chunk)
 
   """
-  @spec streaming_feed(parser_ref(), binary()) :: {non_neg_integer(), non_neg_integer()}
+  @spec streaming_feed(parser_ref(), binary()) :: feed_status()
   def streaming_feed(_parser, _chunk), do: :erlang.nif_error(:nif_not_loaded)
 
+  @doc """
+  Feed chunks and take up to `max` complete rows in a single call. Runs on a
+  dirty CPU scheduler.
+
+  `chunks` is a binary or a list of binaries fed in order. Returns
+  `{rows, status}`, where the status (as returned by `streaming_feed/2`) is
+  what remains after taking; rows are returned as by `streaming_next_rows/2`. Saves a
+  scheduler switch per chunk compared to calling `streaming_feed/2` and
+  `streaming_next_rows/2` separately.
+
+  Raises like `streaming_feed/2`. When a chunk in a list overflows the buffer,
+  the chunks before it have already been fed.
+
+  ## Examples
+
+      {rows, {available, buffer_size}} =
+        RustyCSV.Native.streaming_feed_take(parser, [chunk1, chunk2], 1000)
+
+  """
+  @spec streaming_feed_take(parser_ref(), binary() | [binary()], non_neg_integer()) ::
+          {rows() | [map()], feed_status()}
+  def streaming_feed_take(_parser, _chunks, _max), do: :erlang.nif_error(:nif_not_loaded)
+
   @doc """
   Take up to `max` complete rows from the streaming parser. Runs on a dirty CPU scheduler.
 
-  Returns the rows as a list of lists of binaries.
+  Returns the rows as a list of lists of binaries, or as maps after
+  `streaming_set_headers/5`. The header row does not count towards `max`.
 
   ## Examples
 
@@ -379,6 +428,158 @@ defmodule RustyCSV.Native do
   @spec streaming_finalize(parser_ref()) :: rows()
   def streaming_finalize(_parser), do: :erlang.nif_error(:nif_not_loaded)
 
+  @doc """
+  Open a file for a streaming parser that reads it itself. Runs on a dirty IO
+  scheduler.
+
+  The parser owns the file handle and reads straight into its buffer, so no
+  chunk binaries are allocated on the BEAM side. Take rows with
+  `streaming_read_rows/2`; every other `streaming_*` function works on the
+  returned parser as usual.
+
+  ## Options
+
+    * `:separator`, `:escape`, `:newlines` - As for
+      `streaming_new_with_config/3`. Default to `","`, `?"` and `:default`.
+    * `:chunk_size` - Bytes read per read call. Defaults to `65536`.
+    * `:resume` - An `{offset, rows}` checkpoint (see `streaming_checkpoint/1`)
+      to seek to and continue from. With a `{:sep_directive, separator}`
+      separator, the directive is read from the start of the file first.
+
+  Returns `{:ok, parser}`, or `{:error, reason}` with a POSIX reason such as
+  `:enoent` if the file can't be opened.
+
+  ## Examples
+
+      {:ok, parser} = RustyCSV.Native.streaming_open_file("data.csv", chunk_size: 1_048_576)
+
+  """
+  @spec streaming_open_file(String.t(), keyword()) :: {:ok, parser_ref()} | {:error, atom()}
+  def streaming_open_file(_path, _opts), do: :erlang.nif_error(:nif_not_loaded)
+
+  @doc """
+  Read up to `max` rows from a parser opened by `streaming_open_file/2`. Runs
+  on a dirty IO scheduler.
+
+  Reads the file into the parser buffer until `max` rows are complete (or the
+  `streaming_set_max_pending_rows/2` limit is reached) and returns them like
+  `streaming_next_rows/2`. At the end of the file the last row without a
+  trailing newline is included and the file is closed. Returns `[]` once every
+  row has been read.
+
+  ## Raises
+
+    * `{:read_error, reason}` — reading the file failed.
+    * `:buffer_overflow` — a single row does not fit in the maximum buffer
+      size.
+    * `:badarg` — the parser was not opened by `streaming_open_file/2`.
+
+  ## Examples
+
+      {:ok, parser} = RustyCSV.Native.streaming_open_file("data.csv", [])
+      rows = RustyCSV.Native.streaming_read_rows(parser, 1000)
+
+  """
+  @spec streaming_read_rows(parser_ref(), non_neg_integer()) :: rows() | [map()]
+  def streaming_read_rows(_parser, _max), do: :erlang.nif_error(:nif_not_loaded)
+
+  @doc """
+  Close the file of a parser opened by `streaming_open_file/2` before its end.
+
+  Rows already read are still returned by `streaming_read_rows/2`, which then
+  behaves as at the end of the file. The file is also closed when the parser
+  is garbage collected.
+
+  ## Examples
+
+      :ok = RustyCSV.Native.streaming_close_file(parser)
+
+  """
+  @spec streaming_close_file(parser_ref()) :: :ok
+  def streaming_close_file(_parser), do: :erlang.nif_error(:nif_not_loaded)
+
+  @doc """
+  Put the streaming parser in header-aware mode.
+
+  The first row parsed is kept as the header (see `streaming_headers/1`) and
+  every later row's field count is checked against it as rows are cut.
+  `policy` says what happens to a row of a different width:
+
+    * `:error` - the parse stops before the row. Rows before it are still
+      returned; once they have all been taken, the next feed, take or finalize
+      raises `{:row_width, row, offset, expected, actual}` with the record
+      number (header = 1) and the absolute byte offset of the row
+    * `:pad` - short rows are padded with empty fields; long rows are an error
+    * `:truncate` - extra fields of long rows are dropped; short rows are an error
+    * `[:pad, :truncate]` - both fixes, every row has the header's width
+    * `nil` - turn header-aware mode off
+
+  The header row itself is still returned as the first row. The mode and
+  policy survive `streaming_reset/1` and `streaming_reconfigure/2`; the header
+  is read again from the next input.
+
+  ## Examples
+
+      :ok = RustyCSV.Native.streaming_set_width_policy(parser, :pad)
+      RustyCSV.Native.streaming_feed(parser, "a,b,c\n1,2\n")
+      RustyCSV.Native.streaming_next_rows(parser, 10)
+      #=> [["a", "b", "c"], ["1", "2", ""]]
+
+  """
+  @spec streaming_set_width_policy(
+          parser_ref(),
+          :error | :pad | :truncate | [:pad | :truncate] | nil
+        ) :: :ok
+  def streaming_set_width_policy(_parser, _policy), do: :erlang.nif_error(:nif_not_loaded)
+
+  @doc """
+  Get the header row of a parser in header-aware mode, or `nil` until it
+  has been parsed. See `streaming_set_width_policy/2`.
+  """
+  @spec streaming_headers(parser_ref()) :: row() | nil
+  def streaming_headers(_parser), do: :erlang.nif_error(:nif_not_loaded)
+
+  @doc """
+  Switch the streaming parser to map output.
+
+  After this call `streaming_next_rows/2` and `streaming_finalize/1` return
+  maps instead of lists. Keys are interned once per call and shared by every
+  map in the batch.
+
+  ## Parameters
+
+    * `header_mode` - `true` (first row = string keys) or a list of atom or
+      string keys
+    * `skip_first` - Whether to drop the first row when using explicit keys
+    * `duplicate_headers` - Policy for repeated keys (see `parse_to_maps/10`)
+    * `column_policy` - `{extra_columns, missing_columns}` (see `parse_to_maps/10`).
+      Row numbers count every row taken from the parser, header included.
+
+  ## Examples
+
+      parser = RustyCSV.Native.streaming_new()
+      :ok = RustyCSV.Native.streaming_set_headers(parser, true, true, :last, {:ignore, nil})
+      RustyCSV.Native.streaming_feed(parser, "a,b\\n1,2\\n")
+      RustyCSV.Native.streaming_next_rows(parser, 100)
+      #=> [%{"a" => "1", "b" => "2"}]
+
+  """
+  @spec streaming_set_headers(
+          parser_ref(),
+          true | [atom() | binary()],
+          boolean(),
+          atom(),
+          {atom() | {:collect, term()}, atom()}
+        ) :: :ok
+  def streaming_set_headers(
+        _parser,
+        _header_mode,
+        _skip_first,
+        _duplicate_headers,
+        _column_policy
+      ),
+      do: :erlang.nif_error(:nif_not_loaded)
+
   @doc """
   Get the current status of the streaming parser.
 
@@ -395,6 +596,43 @@ defmodule RustyCSV.Native do
   @spec streaming_status(parser_ref()) :: {non_neg_integer(), non_neg_integer(), boolean()}
   def streaming_status(_parser), do: :erlang.nif_error(:nif_not_loaded)
 
+  @doc """
+  Get cumulative counters of a streaming parser, for telemetry.
+
+  Returns a map with:
+    * `:bytes_fed` - Bytes passed to `streaming_feed/2`
+    * `:bytes_consumed` - Bytes parsed into complete rows (terminators and
+      blank lines included); the rest is the buffered partial row
+    * `:rows_emitted` - Rows returned by `streaming_next_rows/2` and
+      `streaming_finalize/1` (a consumed header row included)
+    * `:physical_lines` - Line breaks fed, including those inside quoted fields
+      (`\r\n` counts once)
+    * `:largest_row` - Longest complete row in bytes, without its terminator
+    * `:peak_buffer` - Largest internal buffer size reached, in bytes
+    * `:compactions` - Times processed data was dropped from the buffer
+
+  Counters start at zero again after `streaming_reset/1` and
+  `streaming_reconfigure/2`.
+
+  ## Examples
+
+      RustyCSV.Native.streaming_feed(parser, "a,b\n1,2\n3,")
+      RustyCSV.Native.streaming_stats(parser)
+      #=> %{bytes_fed: 10, bytes_consumed: 8, rows_emitted: 0, physical_lines: 2,
+      #     largest_row: 3, peak_buffer: 10, compactions: 1}
+
+  """
+  @spec streaming_stats(parser_ref()) :: %{
+          bytes_fed: non_neg_integer(),
+          bytes_consumed: non_neg_integer(),
+          rows_emitted: non_neg_integer(),
+          physical_lines: non_neg_integer(),
+          largest_row: non_neg_integer(),
+          peak_buffer: non_neg_integer(),
+          compactions: non_neg_integer()
+        }
+  def streaming_stats(_parser), do: :erlang.nif_error(:nif_not_loaded)
+
   @doc """
   Set the maximum buffer size (in bytes) for the streaming parser.
   Default is 256 MB. Raises on overflow during `streaming_feed/2`.
@@ -402,6 +640,142 @@ defmodule RustyCSV.Native do
   @spec streaming_set_max_buffer(parser_ref(), non_neg_integer()) :: :ok
   def streaming_set_max_buffer(_parser, _max), do: :erlang.nif_error(:nif_not_loaded)
 
+  @doc """
+  Limit the complete rows a streaming parser queues.
+
+  Once `max` rows are waiting, feeding stops scanning and keeps the rest of
+  the buffer; `streaming_feed/2` then returns `{:drain, available_rows,
+  buffer_size}`. Taking rows resumes the scan, and `streaming_finalize/1`
+  parses everything left. Bounds memory when large chunks hold many short
+  rows. Defaults to no limit.
+
+  ## Examples
+
+      RustyCSV.Native.streaming_set_max_pending_rows(parser, 2)
+      RustyCSV.Native.streaming_feed(parser, "a\nb\nc\nd\n")
+      #=> {:drain, 2, 4}
+
+  """
+  @spec streaming_set_max_pending_rows(parser_ref(), pos_integer()) :: :ok
+  def streaming_set_max_pending_rows(_parser, _max), do: :erlang.nif_error(:nif_not_loaded)
+
+  @doc """
+  Get the checkpoint of a streaming parser.
+
+  Returns `{offset, rows}`: the absolute byte offset just past the last row
+  returned by `streaming_next_rows/2` or `streaming_finalize/1`, and how many
+  rows have been returned (a header row consumed by `streaming_set_headers/5`
+  included). Offsets count from the start of the input, or from the offset the
+  parser was created at with `streaming_new_at/2`.
+
+  Offsets count bytes of UTF-8 input, which don't seek back to the same place
+  in input of another encoding, so a parser with `streaming_set_encoding/2`
+  raises `{:encoded_checkpoint, encoding}`.
+
+  ## Examples
+
+      RustyCSV.Native.streaming_feed(parser, "a,b\\n1,2\\n3,")
+      RustyCSV.Native.streaming_next_rows(parser, 1)
+      RustyCSV.Native.streaming_checkpoint(parser)
+      #=> {4, 1}
+
+  """
+  @spec streaming_checkpoint(parser_ref()) :: {non_neg_integer(), non_neg_integer()}
+  def streaming_checkpoint(_parser), do: :erlang.nif_error(:nif_not_loaded)
+
+  @doc """
+  Create a streaming parser that resumes a parse at a checkpoint.
+
+  `config` is a `{separator, escape, newlines}` tuple in the same formats as
+  `streaming_new_with_config/3`, and `checkpoint` is an `{offset, rows}`
+  tuple from `streaming_checkpoint/1`. Feed the parser the input starting at
+  byte `offset`; its checkpoints continue from the given values. A
+  `{:sep_directive, separator}` is only looked for when `offset` is `0`; past
+  it, pass the separator the directive named.
+
+  ## Examples
+
+      {offset, _rows} = checkpoint
+      {:ok, ^offset} = :file.position(device, offset)
+      parser = RustyCSV.Native.streaming_new_at({<<?,>>, <<?">>, :default}, checkpoint)
+
+  """
+  @spec streaming_new_at(
+          {separator(), escape(), term()},
+          {non_neg_integer(), non_neg_integer()}
+        ) :: parser_ref()
+  def streaming_new_at(_config, _checkpoint), do: :erlang.nif_error(:nif_not_loaded)
+
+  @doc """
+  Reset the streaming parser so it can be reused for another input.
+
+  Buffered data and pending rows are discarded. The separator, escape,
+  newlines, maximum buffer size and buffer capacity are kept, so a pool of
+  workers can reuse one parser per worker instead of allocating a new one per
+  file. After `streaming_set_headers/5` the next row is read as a header row
+  again, and with a `{:sep_directive, separator}` separator the next input is
+  checked for a `sep=` line again.
+
+  ## Examples
+
+      :ok = RustyCSV.Native.streaming_reset(parser)
+      RustyCSV.Native.streaming_feed(parser, next_file_contents)
+
+  """
+  @spec streaming_reset(parser_ref()) :: :ok
+  def streaming_reset(_parser), do: :erlang.nif_error(:nif_not_loaded)
+
+  @doc """
+  Reset the streaming parser and switch it to a new configuration.
+
+  `config` is a `{separator, escape, newlines}` tuple in the same formats as
+  `streaming_new_with_config/3`. Like `streaming_reset/1`, buffered data is
+  discarded while the buffer capacity, maximum buffer size and map mode are
+  kept. The parser may switch between the single-byte and multi-byte
+  implementations.
+
+  ## Examples
+
+      :ok = RustyCSV.Native.streaming_reconfigure(parser, {<<9>>, <<34>>, :default})
+
+  """
+  @spec streaming_reconfigure(parser_ref(), {separator(), escape(), term()}) :: :ok
+  def streaming_reconfigure(_parser, _config), do: :erlang.nif_error(:nif_not_loaded)
+
+  @doc """
+  Set the encoding of the input fed to the streaming parser.
+
+  `encoding` is one of `:utf8`, `:latin1`, `:windows_1252`, `:iso_8859_15`,
+  `{:codepage, number}`, `{:utf16, endian}` or `{:utf32, endian}`, or
+  `{:auto, default}` to take it from a BOM at the start of the input (the BOM
+  is dropped) and use `default` without one. Chunks passed to
+  `streaming_feed/2` (or read by `streaming_read_rows/2`) are then decoded to
+  UTF-8 straight into the parser's buffer; a sequence split across chunks is
+  carried over to the next one. Rows are returned as UTF-8.
+  `streaming_reset/1` keeps the encoding.
+
+  An invalid sequence raises `{:invalid_encoding, encoding, offset}`, and
+  input ending part way through a sequence raises
+  `{:incomplete_encoding, encoding, offset}` at `streaming_finalize/1`.
+  `offset` counts bytes of the undecoded input.
+
+  Checkpoints are not kept for decoded input (see `streaming_checkpoint/1`):
+  setting an encoding other than `:utf8` on a parser resumed from a checkpoint
+  (with `streaming_new_at/2` or `streaming_open_file/2`'s `:resume`), or on
+  one that has already returned rows, raises `{:encoded_checkpoint, encoding}`.
+
+  ## Examples
+
+      :ok = RustyCSV.Native.streaming_set_encoding(parser, {:utf16, :little})
+      RustyCSV.Native.streaming_feed(parser, <<?a, 0, ?\n>>)
+      RustyCSV.Native.streaming_feed(parser, <<0>>)
+      RustyCSV.Native.streaming_next_rows(parser, 100)
+      #=> [["a"]]
+
+  """
+  @spec streaming_set_encoding(parser_ref(), term()) :: :ok
+  def streaming_set_encoding(_parser, _encoding), do: :erlang.nif_error(:nif_not_loaded)
+
   # ==========================================================================
   # Strategy E: Parallel Parsing
   # ==========================================================================
@@ -501,12 +875,47 @@ defmodule RustyCSV.Native do
     * `strategy` - Atom: `:basic`, `:simd`, `:indexed`, or `:zero_copy`
     * `header_mode` - Atom `:true` (first row = keys) or list of key terms
     * `skip_first` - Whether to skip the first row when using explicit keys
+    * `duplicate_headers` - Policy for repeated keys: `:last`, `:first`,
+      `:error`, `:suffix`, or `:list`. `:error` raises
+      `{:duplicate_header, name}`.
+    * `row_format` - `:map`, `:keyword` for `[{key, value}]` lists in
+      column order (binary keys are converted to atoms), or
+      `{:struct, module, [{field, default}], required}` to build structs
+      holding only the given fields. A required field without a matching
+      header raises `{:missing_required_header, name}`.
+    * `column_policy` - `{extra_columns, missing_columns}` for rows whose
+      width differs from the header. `extra_columns` is `:ignore`, `:error`,
+      or `{:collect, key}`; `missing_columns` is `nil`, `:omit`, or `:error`.
+      Errors raise `{:extra_columns | :missing_columns, row, expected, actual}`
+      with a 1-based row number counting the header row.
 
   """
-  @spec parse_to_maps(binary(), separator(), escape(), term(), atom(), atom() | list(), boolean()) ::
-          [map()]
-  def parse_to_maps(_input, _separator, _escape, _newlines, _strategy, _header_mode, _skip_first),
-    do: :erlang.nif_error(:nif_not_loaded)
+  @spec parse_to_maps(
+          binary(),
+          separator(),
+          escape(),
+          term(),
+          atom(),
+          atom() | list(),
+          boolean(),
+          atom(),
+          :map | :keyword | {:struct, module(), [{atom(), term()}], [atom()]},
+          {atom() | {:collect, term()}, atom()}
+        ) ::
+          [map()] | [keyword()] | [struct()]
+  def parse_to_maps(
+        _input,
+        _separator,
+        _escape,
+        _newlines,
+        _strategy,
+        _header_mode,
+        _skip_first,
+        _duplicate_headers,
+        _row_format,
+        _column_policy
+      ),
+      do: :erlang.nif_error(:nif_not_loaded)
 
   @doc """
   Parse CSV in parallel and return list of maps.
@@ -520,6 +929,9 @@ defmodule RustyCSV.Native do
     * `escape` - Escape sequence (see "Escape Format" above)
     * `header_mode` - Atom `:true` (first row = keys) or list of key terms
     * `skip_first` - Whether to skip the first row when using explicit keys
+    * `duplicate_headers` - Policy for repeated keys (see `parse_to_maps/10`)
+    * `row_format` - `:map`, `:keyword`, or a struct spec (see `parse_to_maps/10`)
+    * `column_policy` - `{extra_columns, missing_columns}` (see `parse_to_maps/10`)
 
   """
   @spec parse_to_maps_parallel(
@@ -528,10 +940,51 @@ defmodule RustyCSV.Native do
           escape(),
           term(),
           atom() | list(),
-          boolean()
+          boolean(),
+          atom(),
+          :map | :keyword | {:struct, module(), [{atom(), term()}], [atom()]},
+          {atom() | {:collect, term()}, atom()}
         ) ::
-          [map()]
-  def parse_to_maps_parallel(_input, _separator, _escape, _newlines, _header_mode, _skip_first),
+          [map()] | [keyword()] | [struct()]
+  def parse_to_maps_parallel(
+        _input,
+        _separator,
+        _escape,
+        _newlines,
+        _header_mode,
+        _skip_first,
+        _duplicate_headers,
+        _row_format,
+        _column_policy
+      ),
+      do: :erlang.nif_error(:nif_not_loaded)
+
+  # ==========================================================================
+  # Tuple Rows
+  # ==========================================================================
+
+  @doc """
+  Parse CSV and return list of tuples, dispatching to the specified strategy.
+
+  ## Parameters
+
+    * `input` - The CSV binary to parse
+    * `separator` - Separator(s) (see "Separator Format" above)
+    * `escape` - Escape sequence (see "Escape Format" above)
+    * `strategy` - Atom: `:basic`, `:simd`, `:indexed`, or `:zero_copy`
+
+  """
+  @spec parse_to_tuples(binary(), separator(), escape(), term(), atom()) :: [tuple()]
+  def parse_to_tuples(_input, _separator, _escape, _newlines, _strategy),
+    do: :erlang.nif_error(:nif_not_loaded)
+
+  @doc """
+  Parse CSV in parallel and return list of tuples.
+
+  Uses the rayon thread pool on a dirty CPU scheduler.
+  """
+  @spec parse_to_tuples_parallel(binary(), separator(), escape(), term()) :: [tuple()]
+  def parse_to_tuples_parallel(_input, _separator, _escape, _newlines),
     do: :erlang.nif_error(:nif_not_loaded)
 
   # ==========================================================================
@@ -550,6 +1003,13 @@ defmodule RustyCSV.Native do
   Returns iodata (nested lists) — clean fields are passed through as
   zero-copy references, only dirty fields requiring quoting are allocated.
 
+  Field characters that a single-byte `encoding` cannot represent are handled
+  by `on_unmappable`: `:replace`, `{:replace, string}`, `:transliterate`, or
+  `:error` to raise `{:unmappable, encoding, row, column, character}` for the
+  first one. Fields that are not valid UTF-8 are handled by `invalid_utf8`:
+  `:passthrough` copies them as-is, `:replace` replaces each invalid sequence
+  with U+FFFD, and `:error` raises `{:invalid_utf8, row, column, offset}`.
+
   ## Parameters
 
     * `rows` - List of rows (list of lists of binaries)
@@ -570,11 +1030,23 @@ defmodule RustyCSV.Native do
           binary() | atom(),
           term(),
           term(),
-          [binary()]
+          [binary()],
+          term(),
+          :passthrough | :replace | :error
         ) ::
           iodata()
-  def encode_string(_rows, _separator, _escape, _line_separator, _formula, _encoding, _reserved),
-    do: :erlang.nif_error(:nif_not_loaded)
+  def encode_string(
+        _rows,
+        _separator,
+        _escape,
+        _line_separator,
+        _formula,
+        _encoding,
+        _reserved,
+        _on_unmappable,
+        _invalid_utf8
+      ),
+      do: :erlang.nif_error(:nif_not_loaded)
 
   @doc """
   Encode rows to CSV in parallel using rayon, returning iodata (list of binaries).
@@ -590,7 +1062,8 @@ defmodule RustyCSV.Native do
   `encode_string/4` which avoids the copy via zero-copy term references.
 
   Only supports single-byte separator/escape. Raises `ArgumentError` for
-  multi-byte configurations — use `encode_string/4` instead.
+  multi-byte configurations — use `encode_string/4` instead. Handles
+  `on_unmappable` and `invalid_utf8` as `encode_string/9` does.
 
   ## Parameters
 
@@ -613,7 +1086,9 @@ defmodule RustyCSV.Native do
           binary() | atom(),
           term(),
           term(),
-          [binary()]
+          [binary()],
+          term(),
+          :passthrough | :replace | :error
         ) :: iodata()
   def encode_string_parallel(
         _rows,
@@ -622,10 +1097,192 @@ defmodule RustyCSV.Native do
         _line_separator,
         _formula,
         _encoding,
-        _reserved
+        _reserved,
+        _on_unmappable,
+        _invalid_utf8
       ),
       do: :erlang.nif_error(:nif_not_loaded)
 
+  @doc """
+  Encode rows as a file Excel opens with every cell intact.
+
+  `variant` is `:utf16` for UTF-16LE with a BOM and tab separators, or `:utf8`
+  for UTF-8 with a BOM and a `sep=` first line naming `separator`. Fields
+  starting with `=`, `+`, `-`, `@`, tab or CR get a `'` prefix unless they are
+  a plain number, and digit strings with a leading zero or more than 15
+  digits are written as `="00123"` so Excel keeps them as text. Handles
+  `invalid_utf8` as `encode_string/9` does.
+
+  ## Examples
+
+      iex> RustyCSV.Native.encode_string_excel([["00123", "=1+1"]], :utf8, [","], "\\"", :default, [], :passthrough)
+      "\\uFEFFsep=,\\n\\"=\\"\\"00123\\"\\"\\",'=1+1\\n"
+
+  """
+  @spec encode_string_excel(
+          [[binary()]],
+          :utf16 | :utf8,
+          separator(),
+          escape(),
+          binary() | atom(),
+          [binary()],
+          :passthrough | :replace | :error
+        ) :: binary()
+  def encode_string_excel(
+        _rows,
+        _variant,
+        _separator,
+        _escape,
+        _line_separator,
+        _reserved,
+        _invalid_utf8
+      ),
+      do: :erlang.nif_error(:nif_not_loaded)
+
+  @doc """
+  Decode a binary in another encoding to UTF-8. Runs on a dirty CPU scheduler.
+
+  `encoding` is one of `:utf8`, `:latin1`, `:windows_1252`, `:iso_8859_15`,
+  `{:codepage, number}`, `{:utf16, endian}` or `{:utf32, endian}`, or
+  `{:auto, default}` to take it from a BOM at the start of the input (the BOM
+  is dropped) and use `default` without one. UTF-8 input is returned
+  unchanged. The result is allocated at its exact size, so the parse NIFs can
+  reference it directly.
+
+  Raises `{:invalid_encoding, encoding, offset}` on an invalid sequence, or
+  `{:incomplete_encoding, encoding, offset}` when the input ends part way
+  through one.
+
+  ## Examples
+
+      iex> RustyCSV.Native.decode_to_utf8(<<"caf", 0xE9>>, :latin1)
+      "café"
+
+  """
+  @spec decode_to_utf8(binary(), term()) :: binary()
+  def decode_to_utf8(_input, _encoding), do: :erlang.nif_error(:nif_not_loaded)
+
+  @doc """
+  Parse non-UTF-8 CSV into rows of UTF-8 binaries without decoding the whole
+  input first. Runs on a dirty CPU scheduler.
+
+  `encoding` is named as for `decode_to_utf8/2`, including `{:auto, default}`,
+  which skips the BOM it finds; other encodings keep a BOM as part of the
+  first field. The separators, quotes and newlines are found in the encoded
+  input, and each field is decoded into its own binary as the rows are built,
+  so the input is never held twice. UTF-16 and UTF-32 input is scanned as
+  code units; Latin-1 and the codepages keep ASCII as it is, so their input is
+  parsed as bytes and plain ASCII fields are sub-binaries of it. Separators,
+  escape and newlines must be single ASCII characters for this (Latin-1 and
+  the codepages also take longer ASCII separators and newlines); other
+  configurations, and `{:sep_directive, separator}`, decode the input and
+  parse it as `parse_string_fast_with_config/4` would.
+
+  Raises `{:invalid_encoding, encoding, offset}` or
+  `{:incomplete_encoding, encoding, offset}` like `decode_to_utf8/2`.
+
+  ## Examples
+
+      iex> encoding = {:utf16, :little}
+      iex> input = :unicode.characters_to_binary("a,b\\nc,d\\n", :utf8, encoding)
+      iex> RustyCSV.Native.parse_string_encoded_with_config(input, encoding, ",", "\\"", :default)
+      [["a", "b"], ["c", "d"]]
+
+      iex> RustyCSV.Native.parse_string_encoded_with_config(
+      ...>   <<"caf", 0xE9, ",b\\n">>, :latin1, ",", "\\"", :default)
+      [["café", "b"]]
+
+  """
+  @spec parse_string_encoded_with_config(binary(), term(), separator(), escape(), term()) ::
+          rows()
+  def parse_string_encoded_with_config(_input, _encoding, _separators, _escape, _newlines),
+    do: :erlang.nif_error(:nif_not_loaded)
+
+  @doc """
+  Parse non-UTF-8 CSV into a list of tuples, decoding fields as
+  `parse_string_encoded_with_config/5` does. Runs on a dirty CPU scheduler.
+  """
+  @spec parse_encoded_to_tuples(binary(), term(), separator(), escape(), term()) :: [tuple()]
+  def parse_encoded_to_tuples(_input, _encoding, _separator, _escape, _newlines),
+    do: :erlang.nif_error(:nif_not_loaded)
+
+  @doc """
+  Parse non-UTF-8 CSV into a list of maps, keyword lists or structs, decoding
+  fields as `parse_string_encoded_with_config/5` does. Runs on a dirty CPU
+  scheduler.
+
+  `encoding` is named as for `decode_to_utf8/2`; the other parameters are as
+  for `parse_to_maps/10`, without `strategy`. Raises encoding errors like
+  `decode_to_utf8/2` and header errors like `parse_to_maps/10`.
+  """
+  @spec parse_encoded_to_maps(
+          binary(),
+          term(),
+          separator(),
+          escape(),
+          term(),
+          atom() | list(),
+          boolean(),
+          atom(),
+          :map | :keyword | {:struct, module(), [{atom(), term()}], [atom()]},
+          {atom() | {:collect, term()}, atom()}
+        ) ::
+          [map()] | [keyword()] | [struct()]
+  def parse_encoded_to_maps(
+        _input,
+        _encoding,
+        _separator,
+        _escape,
+        _newlines,
+        _header_mode,
+        _skip_first,
+        _duplicate_headers,
+        _row_format,
+        _column_policy
+      ),
+      do: :erlang.nif_error(:nif_not_loaded)
+
+  @doc """
+  List the Windows numbers of the codepages `{:codepage, number}` accepts,
+  one per mapping table in the NIF.
+
+  ## Examples
+
+      iex> 1252 in RustyCSV.Native.codepages()
+      true
+
+  """
+  @spec codepages() :: [pos_integer()]
+  def codepages, do: :erlang.nif_error(:nif_not_loaded)
+
+  @doc """
+  Guess the encoding of a sample from the start of some input, for input
+  without a BOM. Runs on a dirty CPU scheduler.
+
+  Returns `{encoding, confidence}` tuples, most likely first, with confidence
+  from `0.0` to `1.0`. Encodings are named as `decode_to_utf8/2` takes them.
+  Candidates are UTF-8, UTF-16 and UTF-32 without a BOM, `:windows_1252`,
+  `:latin1`, `:iso_8859_15` and the codepages 1250, 1251, 20866 (KOI8-R), 850
+  and 437; ones that cannot decode the sample are left out. The scores come
+  from UTF-8 validity, where NUL bytes fall, and how plausible the letters
+  each single-byte encoding decodes the high bytes to are as words.
+
+  A BOM gives `[{encoding, 1.0}]`. ASCII-only samples give `[{:utf8, 1.0}]`,
+  since all the ASCII-compatible encodings read them the same. The sample may
+  end part way through a character. A few kilobytes is usually enough.
+
+  ## Examples
+
+      iex> RustyCSV.Native.detect_encoding("name\ncafé\n") |> hd()
+      {:utf8, 1.0}
+
+      iex> RustyCSV.Native.detect_encoding(<<"caf", 0xE9, " cr", 0xE8, "me">>) |> hd()
+      {:windows_1252, 0.9}
+
+  """
+  @spec detect_encoding(binary()) :: [{term(), float()}]
+  def detect_encoding(_sample), do: :erlang.nif_error(:nif_not_loaded)
+
   # ==========================================================================
   # Memory Tracking (requires `memory_tracking` feature flag)
   # ==========================================================================
@@ -49,7 +49,7 @@ defmodule RustyCSV.Streaming do
 
   The streaming functions support character encoding conversion via the
   `:encoding` option. When a non-UTF8 encoding is specified, the stream
-  is automatically converted to UTF-8 before parsing, with proper handling
+  is decoded to UTF-8 by the parser as each chunk is fed, with proper handling
   of multi-byte character boundaries across chunks.
 
   ## Buffer Limit
@@ -68,6 +68,67 @@ defmodule RustyCSV.Streaming do
 
       CSV.parse_stream(stream, max_buffer_size: 512 * 1024 * 1024)
 
+  The buffer limit does not bound the rows waiting to be taken: a single
+  64 MB chunk of short rows can hold millions of them. `:max_pending_rows`
+  caps that queue. Once it is full, the rest of the chunk stays in the buffer
+  unscanned and is parsed as rows are taken:
+
+      RustyCSV.Streaming.stream_file("huge.csv", chunk_size: 64 * 1024 * 1024,
+        max_pending_rows: 10_000)
+
+  ## Checkpoint and Resume
+
+  `stream_file/2` can report how far a parse has got and pick it up again
+  later. `:on_checkpoint` is called with `{offset, rows}` before each read,
+  covering every row the stream has handed to its consumer so far: `offset`
+  is the byte offset just past the last of those rows and `rows` is how many
+  there were (the header row included). Store it, and after a crash pass it
+  back as `:resume` to seek to `offset` and continue:
+
+      RustyCSV.Streaming.stream_file("huge.csv",
+        headers: true,
+        on_checkpoint: &save_checkpoint/1
+      )
+      |> Stream.each(&import_row/1)
+      |> Stream.run()
+
+      # Later, after a crash
+      RustyCSV.Streaming.stream_file("huge.csv", headers: true, resume: load_checkpoint())
+
+  With `headers: true` the header row is read from the start of the file
+  before seeking; with explicit keys the first row is not skipped again.
+
+  Offsets count bytes of UTF-8 input. A parser decoding another encoding has
+  no checkpoint that seeks back into the encoded file, so
+  `RustyCSV.Native.streaming_checkpoint/1` raises for one, and an encoding
+  can't be set on a resumed parser.
+
+  ## Maps Output
+
+  Pass `:headers` to get maps instead of lists. The streaming resource
+  captures the header row and builds the maps in Rust, interning the keys
+  once per batch:
+
+      RustyCSV.Streaming.stream_file("data.csv", headers: true)
+      |> Enum.each(fn %{"name" => name} -> IO.puts(name) end)
+
+  With explicit keys (`headers: [:name, :age]`), the first row is dropped
+  unless `skip_headers: false` is given. `:duplicate_headers`,
+  `:extra_columns` and `:missing_columns` accept the same policies as
+  `c:RustyCSV.parse_string/2`; row numbers in errors count from the start of
+  the stream.
+
+  ## Row Width
+
+  `:width_policy` checks every row's field count against the header row
+  inside the parser, as rows are cut. `:error` raises `RustyCSV.ParseError`
+  naming the row number and byte offset of the first misaligned row (after
+  the rows before it have been emitted); `:pad` pads short rows, `:truncate`
+  drops extra fields, and `[:pad, :truncate]` does both. See
+  `RustyCSV.Native.streaming_set_width_policy/2`.
+
+      RustyCSV.Streaming.stream_file("feed.csv", width_policy: :error)
+
   ## Concurrency
 
   Streaming parser references are safe to share across BEAM processes — the
@@ -80,7 +141,12 @@ defmodule RustyCSV.Streaming do
   ## Scheduling
 
   `streaming_feed/2`, `streaming_next_rows/2`, and `streaming_finalize/1` run
-  on dirty CPU schedulers to avoid blocking normal BEAM schedulers.
+  on dirty CPU schedulers to avoid blocking normal BEAM schedulers. Each chunk
+  read is fed and its rows taken with a single `streaming_feed_take/3` call.
+
+  `stream_file/2` does its IO in Rust: the parser owns the file handle and
+  `streaming_read_rows/2` reads straight into the parser buffer on a dirty IO
+  scheduler, so no chunk binaries are allocated on the BEAM side.
 
   ## Implementation Notes
 
@@ -92,6 +158,7 @@ defmodule RustyCSV.Streaming do
     * Compacts internal buffer to prevent unbounded growth
     * Enforces a configurable maximum buffer size (default 256 MB)
     * Returns owned data (copies bytes) since input chunks are temporary
+    * Builds maps in Rust when `:headers` is set (keys interned per batch)
 
   """
 
@@ -102,6 +169,12 @@ defmodule RustyCSV.Streaming do
   @typedoc "A parsed row (list of field binaries)"
   @type row :: [binary()]
 
+  @typedoc """
+  Resume point of a streaming parse: the byte offset just past the last row
+  handed out, and the number of rows handed out (header row included).
+  """
+  @type checkpoint :: {offset :: non_neg_integer(), rows :: non_neg_integer()}
+
   @typedoc """
   Options for streaming functions.
 
@@ -109,6 +182,11 @@ defmodule RustyCSV.Streaming do
   integer byte (e.g., `?,` or `9`). When called from a module defined via
   `RustyCSV.define/2`, the separator is already normalized to a binary.
 
+  With `sep_directive: true`, a first line like `sep=;` replaces the separator
+  for the rest of the input and is not returned as a row, nor is a UTF-8 BOM
+  before it. The parser waits for the whole first line before it decides, so
+  the directive may span chunks.
+
   The `:max_buffer_size` option sets the maximum internal buffer size in bytes.
   Defaults to `268_435_456` (256 MB). If a `streaming_feed/2` call would push
   the buffer past this limit, a `:buffer_overflow` exception is raised. Increase
@@ -121,10 +199,21 @@ defmodule RustyCSV.Streaming do
           separator: binary() | non_neg_integer() | [binary()],
           escape: binary() | non_neg_integer(),
           newlines: :default | [binary()],
-          encoding: RustyCSV.encoding(),
+          encoding: RustyCSV.encoding() | :auto,
+          default_encoding: RustyCSV.encoding(),
           bom: binary(),
           trim_bom: boolean(),
-          max_buffer_size: pos_integer()
+          sep_directive: boolean(),
+          max_buffer_size: pos_integer(),
+          max_pending_rows: pos_integer(),
+          width_policy: :error | :pad | :truncate | [:pad | :truncate],
+          headers: boolean() | [atom() | String.t()],
+          skip_headers: boolean(),
+          duplicate_headers: RustyCSV.duplicate_headers(),
+          resume: checkpoint(),
+          on_checkpoint: (checkpoint() -> any()),
+          extra_columns: RustyCSV.extra_columns(),
+          missing_columns: RustyCSV.missing_columns()
         ]
 
   # ==========================================================================
@@ -143,8 +232,9 @@ defmodule RustyCSV.Streaming do
   Create a stream that reads a CSV file in chunks.
 
   Opens the file, creates a streaming parser, and returns a `Stream` that
-  yields rows as they are parsed. The file is automatically closed when
-  the stream is consumed or halted.
+  yields rows as they are parsed. The parser reads the file itself (see
+  `RustyCSV.Native.streaming_open_file/2`). The file is automatically closed
+  when the stream is consumed or halted.
 
   ## Options
 
@@ -157,9 +247,22 @@ defmodule RustyCSV.Streaming do
     * `:max_buffer_size` - Maximum internal buffer in bytes. Defaults to
       `268_435_456` (256 MB). Raises `:buffer_overflow` if exceeded.
 
+    * `:max_pending_rows` - Maximum complete rows queued in the parser before
+      scanning pauses. Defaults to no limit. See "Buffer Limit" above.
+
+    * `:headers` - `true` (first row = keys) or a list of keys to yield maps
+      instead of lists. Defaults to `false`. See "Maps Output" above.
+
+    * `:resume` - A `{offset, rows}` checkpoint to continue a previous parse
+      from. See "Checkpoint and Resume" above.
+
+    * `:on_checkpoint` - Function called with the current `{offset, rows}`
+      checkpoint before each read.
+
   ## Returns
 
-  A `Stream` that yields rows. Each row is a list of field binaries.
+  A `Stream` that yields rows. Each row is a list of field binaries, or a
+  map when `:headers` is given.
 
   ## Examples
 
@@ -186,13 +289,13 @@ defmodule RustyCSV.Streaming do
   def stream_file(path, opts \\ []) do
     chunk_size = Keyword.get(opts, :chunk_size, @default_chunk_size)
     batch_size = Keyword.get(opts, :batch_size, @default_batch_size)
-    separator = Keyword.get(opts, :separator, <<?,>>)
+    separator = separator_opt(opts)
     escape = Keyword.get(opts, :escape, ?")
     newlines = Keyword.get(opts, :newlines, :default)
 
     Stream.resource(
       fn -> init_file_stream(path, chunk_size, batch_size, separator, escape, newlines, opts) end,
-      &next_rows_file/1,
+      &next_file_batch/1,
       &cleanup_file_stream/1
     )
   end
@@ -209,7 +312,12 @@ defmodule RustyCSV.Streaming do
 
     * `:batch_size` - Maximum rows to yield per iteration. Defaults to `1000`.
 
-    * `:encoding` - Character encoding of input. Defaults to `:utf8`.
+    * `:encoding` - Character encoding of input. Defaults to `:utf8`. With
+      `:auto`, the encoding is taken from a BOM at the start of the input
+      (which is dropped), or is `:default_encoding` without one.
+
+    * `:default_encoding` - Encoding of input without a BOM when `:encoding`
+      is `:auto`. Defaults to `:utf8`.
 
     * `:bom` - BOM to strip if `:trim_bom` is true. Defaults to `""`.
 
@@ -218,6 +326,12 @@ defmodule RustyCSV.Streaming do
     * `:max_buffer_size` - Maximum internal buffer in bytes. Defaults to
       `268_435_456` (256 MB). Raises `:buffer_overflow` if exceeded.
 
+    * `:max_pending_rows` - Maximum complete rows queued in the parser before
+      scanning pauses. Defaults to no limit. See "Buffer Limit" above.
+
+    * `:headers` - `true` (first row = keys) or a list of keys to yield maps
+      instead of lists. Defaults to `false`. See "Maps Output" above.
+
   ## Examples
 
       # Parse from a list of chunks
@@ -235,7 +349,7 @@ defmodule RustyCSV.Streaming do
   @spec stream_enumerable(Enumerable.t(), stream_options()) :: Enumerable.t()
   def stream_enumerable(enumerable, opts \\ []) do
     batch_size = Keyword.get(opts, :batch_size, @default_batch_size)
-    separator = Keyword.get(opts, :separator, <<?,>>)
+    separator = separator_opt(opts)
     escape = Keyword.get(opts, :escape, ?")
     newlines = Keyword.get(opts, :newlines, :default)
     encoding = Keyword.get(opts, :encoding, :utf8)
@@ -247,24 +361,26 @@ defmodule RustyCSV.Streaming do
     # Binary chunks (~64KB each) reduce iterations from ~100K to ~100.
     enumerable = optimize_file_stream(enumerable)
 
-    # If encoding is not UTF-8, convert stream to UTF-8 first
-    converted_enumerable =
-      if encoding == :utf8 do
-        if trim_bom and bom != "" do
-          strip_bom_stream(enumerable, bom)
-        else
-          enumerable
-        end
-      else
-        enumerable
-        |> maybe_strip_bom_stream(trim_bom, bom)
-        |> convert_stream_to_utf8(encoding)
-      end
+    enumerable = maybe_strip_bom_stream(enumerable, trim_bom, bom)
 
     parser = new_parser(separator, escape, newlines, opts)
 
+    # Non-UTF-8 chunks are decoded by the parser as they are fed, carrying
+    # split multi-byte sequences over to the next chunk
+    case encoding do
+      :utf8 ->
+        :ok
+
+      :auto ->
+        default_encoding = Keyword.get(opts, :default_encoding, :utf8)
+        RustyCSV.Native.streaming_set_encoding(parser, {:auto, default_encoding})
+
+      encoding ->
+        RustyCSV.Native.streaming_set_encoding(parser, encoding)
+    end
+
     Stream.transform(
-      converted_enumerable,
+      enumerable,
       fn -> {[], 0} end,
       fn chunk, {buf_chunks, buf_size} ->
         chunk_binary = if is_binary(chunk), do: chunk, else: to_string(chunk)
@@ -273,9 +389,8 @@ defmodule RustyCSV.Streaming do
 
         if new_buf_size >= @min_buffer_size do
           combined = new_buf_chunks |> Enum.reverse() |> IO.iodata_to_binary()
-          RustyCSV.Native.streaming_feed(parser, combined)
-          rows = RustyCSV.Native.streaming_next_rows(parser, batch_size)
-          {rows, {[], 0}}
+          {rows, status} = feed_take(parser, combined, batch_size)
+          {with_drained_rows(rows, parser, status, batch_size), {[], 0}}
         else
           {[], {new_buf_chunks, new_buf_size}}
         end
@@ -283,12 +398,10 @@ defmodule RustyCSV.Streaming do
       fn {buf_chunks, _buf_size} ->
         unless buf_chunks == [] do
           combined = buf_chunks |> Enum.reverse() |> IO.iodata_to_binary()
-          RustyCSV.Native.streaming_feed(parser, combined)
+          feed(parser, combined)
         end
 
-        rows_from_buffer = RustyCSV.Native.streaming_next_rows(parser, 100_000)
-        final_rows = RustyCSV.Native.streaming_finalize(parser)
-        {rows_from_buffer ++ final_rows, {[], 0}}
+        {remaining_rows(parser, batch_size), {[], 0}}
       end,
       fn _acc -> :ok end
     )
@@ -317,29 +430,6 @@ defmodule RustyCSV.Streaming do
 
   defp maybe_strip_bom_stream(enumerable, _, _), do: enumerable
 
-  # Convert stream from source encoding to UTF-8, handling multi-byte boundaries
-  defp convert_stream_to_utf8(stream, encoding) do
-    Stream.transform(stream, <<>>, fn chunk, acc ->
-      input = acc <> chunk
-
-      case :unicode.characters_to_binary(input, encoding, :utf8) do
-        binary when is_binary(binary) ->
-          # Full conversion succeeded
-          {[binary], <<>>}
-
-        {:incomplete, converted, rest} ->
-          # Partial conversion - rest contains incomplete multi-byte sequence
-          {[converted], rest}
-
-        {:error, converted, rest} ->
-          raise RustyCSV.ParseError,
-            message:
-              "Invalid #{inspect(encoding)} sequence at byte #{byte_size(converted)}: " <>
-                "#{inspect(binary_part(rest, 0, min(byte_size(rest), 10)))}"
-      end
-    end)
-  end
-
   @doc """
   Stream from an already-open IO device.
 
@@ -358,6 +448,12 @@ defmodule RustyCSV.Streaming do
     * `:max_buffer_size` - Maximum internal buffer in bytes. Defaults to
       `268_435_456` (256 MB). Raises `:buffer_overflow` if exceeded.
 
+    * `:max_pending_rows` - Maximum complete rows queued in the parser before
+      scanning pauses. Defaults to no limit. See "Buffer Limit" above.
+
+    * `:headers` - `true` (first row = keys) or a list of keys to yield maps
+      instead of lists. Defaults to `false`. See "Maps Output" above.
+
   ## Examples
 
       File.open!("data.csv", [:read, :binary], fn device ->
@@ -370,7 +466,7 @@ defmodule RustyCSV.Streaming do
   def stream_device(device, opts \\ []) do
     chunk_size = Keyword.get(opts, :chunk_size, @default_chunk_size)
     batch_size = Keyword.get(opts, :batch_size, @default_batch_size)
-    separator = Keyword.get(opts, :separator, <<?,>>)
+    separator = separator_opt(opts)
     escape = Keyword.get(opts, :escape, ?")
     newlines = Keyword.get(opts, :newlines, :default)
 
@@ -394,11 +490,19 @@ defmodule RustyCSV.Streaming do
     * `:separator` - Field separator. Accepts an integer byte (e.g., `9` for tab),
       a binary (e.g., `"\\t"`, `"::"`), or a list of binaries (e.g., `[",", ";"]`).
       Defaults to `","`.
+    * `:sep_directive` - When `true`, a `sep=` first line sets the separator and
+      is dropped. Defaults to `false`.
     * `:escape` - Escape/quote sequence. Accepts an integer byte (e.g., `34`) or
       a binary (e.g., `"\""`, `"$$"`). Defaults to `"` (34).
     * `:max_buffer_size` - Maximum internal buffer in bytes. Defaults to
       `268_435_456` (256 MB). Raises `:buffer_overflow` if exceeded.
 
+    * `:max_pending_rows` - Maximum complete rows queued in the parser before
+      scanning pauses. Defaults to no limit. See "Buffer Limit" above.
+
+    * `:headers` - `true` (first row = keys) or a list of keys to yield maps
+      instead of lists. Defaults to `false`. See "Maps Output" above.
+
   ## Examples
 
       RustyCSV.Streaming.parse_chunks(["a,b\\n1,", "2\\n3,4\\n"])
@@ -410,91 +514,273 @@ defmodule RustyCSV.Streaming do
       #=> [["a", "b"], ["1", "2"]]
 
   """
-  @spec parse_chunks([binary()], keyword()) :: [row()]
+  @spec parse_chunks([binary()], keyword()) :: [row() | map()]
   def parse_chunks(chunks, opts \\ []) when is_list(chunks) do
-    separator = Keyword.get(opts, :separator, <<?,>>)
+    separator = separator_opt(opts)
     escape = Keyword.get(opts, :escape, ?")
     newlines = Keyword.get(opts, :newlines, :default)
     parser = new_parser(separator, escape, newlines, opts)
 
     # Feed all chunks
     Enum.each(chunks, fn chunk ->
-      RustyCSV.Native.streaming_feed(parser, chunk)
+      feed(parser, chunk)
     end)
 
-    # Take all available rows
-    {available, _buffer_size, _has_partial} = RustyCSV.Native.streaming_status(parser)
-    rows = RustyCSV.Native.streaming_next_rows(parser, available + 1)
-
-    # Finalize to get any remaining partial row
-    final_rows = RustyCSV.Native.streaming_finalize(parser)
-
-    rows ++ final_rows
+    parser |> remaining_rows(@default_batch_size) |> Enum.to_list()
   end
 
   # ==========================================================================
   # Parser Creation (Private)
   # ==========================================================================
 
+  # With `sep_directive: true`, a `sep=` first line picks the separator
+  defp separator_opt(opts) do
+    separator = Keyword.get(opts, :separator, <<?,>>)
+
+    if Keyword.get(opts, :sep_directive, false) do
+      {:sep_directive, separator}
+    else
+      separator
+    end
+  end
+
   defp new_parser(separator, escape, newlines, opts) do
-    parser = RustyCSV.Native.streaming_new_with_config(separator, escape, newlines)
+    separator
+    |> RustyCSV.Native.streaming_new_with_config(escape, newlines)
+    |> configure_parser(opts)
+  end
 
+  defp configure_parser(parser, opts) do
     if max = Keyword.get(opts, :max_buffer_size) do
       RustyCSV.Native.streaming_set_max_buffer(parser, max)
     end
 
+    if max = Keyword.get(opts, :max_pending_rows) do
+      RustyCSV.Native.streaming_set_max_pending_rows(parser, max)
+    end
+
+    if policy = Keyword.get(opts, :width_policy) do
+      RustyCSV.Native.streaming_set_width_policy(parser, policy)
+    end
+
+    maybe_set_headers(parser, Keyword.get(opts, :headers, false), opts)
     parser
   end
 
+  defp maybe_set_headers(_parser, false, _opts), do: :ok
+
+  defp maybe_set_headers(parser, headers, opts) when headers == true or is_list(headers) do
+    skip_first = Keyword.get(opts, :skip_headers, true)
+    duplicate_headers = Keyword.get(opts, :duplicate_headers, :last)
+
+    unless duplicate_headers in [:last, :first, :error, :suffix, :list] do
+      raise ArgumentError,
+            "invalid :duplicate_headers option, expected :last, :first, :error, " <>
+              ":suffix, or :list, got: #{inspect(duplicate_headers)}"
+    end
+
+    RustyCSV.Native.streaming_set_headers(
+      parser,
+      headers,
+      skip_first,
+      duplicate_headers,
+      RustyCSV.column_policy!(opts)
+    )
+  end
+
+  defp maybe_set_headers(_parser, other, _opts) do
+    raise ArgumentError,
+          "invalid :headers option, expected false, true, or a list of keys, got: #{inspect(other)}"
+  end
+
+  # ==========================================================================
+  # Row Retrieval (Private)
+  # ==========================================================================
+
+  # Header policy errors (`duplicate_headers: :error`, `extra_columns: :error`,
+  # `missing_columns: :error`, `width_policy`) surface from the NIF as tagged
+  # tuples; report them like `parse_string/2` does.
+  defp feed(parser, chunk) do
+    RustyCSV.Native.streaming_feed(parser, chunk)
+  rescue
+    e in ErlangError -> reraise_header_error(e, __STACKTRACE__)
+  end
+
+  defp take_rows(parser, max) do
+    RustyCSV.Native.streaming_next_rows(parser, max)
+  rescue
+    e in ErlangError -> reraise_header_error(e, __STACKTRACE__)
+  end
+
+  defp feed_take(parser, chunk, max) do
+    RustyCSV.Native.streaming_feed_take(parser, chunk, max)
+  rescue
+    e in ErlangError -> reraise_header_error(e, __STACKTRACE__)
+  end
+
+  # Rows held back by `:max_pending_rows` are taken before the next chunk is
+  # fed, so unscanned bytes don't pile up in the buffer. They are taken
+  # `max` at a time as the stream is consumed, so they don't pile up in one
+  # list either.
+  defp with_drained_rows(rows, parser, {:drain, _available, _buffer_size}, max) do
+    drained =
+      Stream.unfold(:drain, fn
+        :drain ->
+          case feed_take(parser, [], max) do
+            {rows, {:drain, _available, _buffer_size}} -> {rows, :drain}
+            {rows, _status} -> {rows, :done}
+          end
+
+        :done ->
+          nil
+      end)
+
+    Stream.concat(rows, Stream.concat(drained))
+  end
+
+  defp with_drained_rows(rows, _parser, _status, _max), do: rows
+
+  defp finalize(parser) do
+    RustyCSV.Native.streaming_finalize(parser)
+  rescue
+    e in ErlangError -> reraise_header_error(e, __STACKTRACE__)
+  end
+
+  # Rows left once all input is fed, `max` at a time: takes until a call
+  # returns no rows, then finalizes until one does. A pending `width_policy`
+  # error is only raised by a call that returns no rows, so it surfaces
+  # after every row before it.
+  defp remaining_rows(parser, max) do
+    Stream.unfold(:take, fn
+      :take ->
+        case take_rows(parser, max) do
+          [] -> {finalize(parser), :finalize}
+          rows -> {rows, :take}
+        end
+
+      :finalize ->
+        case finalize(parser) do
+          [] -> nil
+          rows -> {rows, :finalize}
+        end
+    end)
+    |> Stream.concat()
+  end
+
+  defp read_rows(parser, max) do
+    RustyCSV.Native.streaming_read_rows(parser, max)
+  rescue
+    e in ErlangError -> reraise_header_error(e, __STACKTRACE__)
+  end
+
+  defp reraise_header_error(%ErlangError{original: {:duplicate_header, name}}, _) do
+    raise RustyCSV.ParseError, message: "Duplicate header: #{inspect(name)}"
+  end
+
+  defp reraise_header_error(%ErlangError{original: {:collect_key_conflict, name}}, _) do
+    raise RustyCSV.ParseError,
+      message: "extra_columns: {:collect, key} uses #{inspect(name)}, which is also a header"
+  end
+
+  defp reraise_header_error(%ErlangError{original: {kind, _, _, _} = error}, _)
+       when kind in [:extra_columns, :missing_columns] do
+    raise RustyCSV.ParseError, message: RustyCSV.column_error_message(error)
+  end
+
+  defp reraise_header_error(
+         %ErlangError{original: {:row_width, row, offset, expected, actual}},
+         _
+       ) do
+    raise RustyCSV.ParseError,
+      message: "Row #{row} at byte #{offset} has #{actual} fields, expected #{expected}"
+  end
+
+  defp reraise_header_error(%ErlangError{original: {kind, _, _} = error}, _)
+       when kind in [:invalid_encoding, :incomplete_encoding] do
+    raise RustyCSV.ParseError, message: RustyCSV.encoding_error_message(error)
+  end
+
+  defp reraise_header_error(%ErlangError{original: {:read_error, reason}}, _) do
+    raise "Error reading CSV file: #{inspect(reason)}"
+  end
+
+  defp reraise_header_error(error, stacktrace), do: reraise(error, stacktrace)
+
   # ==========================================================================
   # File Streaming (Private)
   # ==========================================================================
 
   defp init_file_stream(path, chunk_size, batch_size, separator, escape, newlines, opts) do
-    device = File.open!(path, [:read, :binary, :raw])
-    parser = new_parser(separator, escape, newlines, opts)
-    {:file, device, parser, chunk_size, batch_size}
+    checkpoint = checkpoint!(Keyword.get(opts, :resume))
+    file_opts = [separator: separator, escape: escape, newlines: newlines, chunk_size: chunk_size]
+    opts = resume_header_opts(path, checkpoint, file_opts, opts)
+
+    parser =
+      path
+      |> open_file!(Keyword.put(file_opts, :resume, checkpoint))
+      |> configure_parser(opts)
+
+    {:file, parser, batch_size, Keyword.get(opts, :on_checkpoint)}
   end
 
-  defp next_rows_file({:file, device, parser, chunk_size, batch_size} = state) do
-    {available, _buffer_size, _has_partial} = RustyCSV.Native.streaming_status(parser)
+  defp checkpoint!(nil), do: nil
 
-    if available > 0 do
-      rows = RustyCSV.Native.streaming_next_rows(parser, batch_size)
-      emit_rows(rows, state)
-    else
-      read_and_process_file(device, parser, chunk_size, batch_size, state)
-    end
+  defp checkpoint!({offset, rows} = checkpoint)
+       when is_integer(offset) and offset >= 0 and is_integer(rows) and rows >= 0,
+       do: checkpoint
+
+  defp checkpoint!(other) do
+    raise ArgumentError,
+          "invalid :resume option, expected an {offset, rows} checkpoint, got: #{inspect(other)}"
   end
 
-  defp next_rows_file({:done, _device, _parser, _chunk_size, _batch_size} = state) do
-    {:halt, state}
+  # The parser reads the file itself on a dirty IO scheduler, straight into
+  # its buffer, so chunks never become BEAM binaries.
+  defp open_file!(path, file_opts) do
+    path = IO.chardata_to_string(path)
+
+    case RustyCSV.Native.streaming_open_file(path, file_opts) do
+      {:ok, parser} -> parser
+      {:error, reason} -> raise File.Error, reason: reason, action: "open", path: path
+    end
   end
 
-  defp read_and_process_file(device, parser, chunk_size, batch_size, state) do
-    case IO.binread(device, chunk_size) do
-      :eof ->
-        finalize_file_stream(parser, device, chunk_size, batch_size, state)
+  # When resuming past the header row, take the keys from the start of the
+  # file (`headers: true`) and don't skip the first row read from `offset`.
+  defp resume_header_opts(_path, nil, _file_opts, opts), do: opts
+  defp resume_header_opts(_path, {0, _rows}, _file_opts, opts), do: opts
 
-      {:error, reason} ->
-        raise "Error reading CSV file: #{inspect(reason)}"
+  defp resume_header_opts(path, _checkpoint, file_opts, opts) do
+    case Keyword.get(opts, :headers, false) do
+      false ->
+        opts
 
-      chunk when is_binary(chunk) ->
-        {_available, _buffer_size} = RustyCSV.Native.streaming_feed(parser, chunk)
-        rows = RustyCSV.Native.streaming_next_rows(parser, batch_size)
-        emit_rows(rows, state)
+      true ->
+        parser = open_file!(path, file_opts)
+        keys = parser |> read_rows(1) |> List.first([])
+        RustyCSV.Native.streaming_close_file(parser)
+        Keyword.merge(opts, headers: keys, skip_headers: false)
+
+      _keys ->
+        Keyword.put(opts, :skip_headers, false)
     end
   end
 
-  defp finalize_file_stream(parser, device, chunk_size, batch_size, state) do
-    case RustyCSV.Native.streaming_finalize(parser) do
+  # Called once per batch request: every row emitted before this point has
+  # been consumed, so the parser's checkpoint is safe to report.
+  defp next_file_batch({:file, parser, batch_size, on_checkpoint} = state) do
+    if on_checkpoint do
+      on_checkpoint.(RustyCSV.Native.streaming_checkpoint(parser))
+    end
+
+    case read_rows(parser, batch_size) do
       [] -> {:halt, state}
-      rows -> {rows, {:done, device, parser, chunk_size, batch_size}}
+      rows -> {rows, state}
     end
   end
 
-  defp cleanup_file_stream({_, device, _parser, _chunk_size, _batch_size}) do
-    File.close(device)
+  defp cleanup_file_stream({:file, parser, _batch_size, _on_checkpoint}) do
+    RustyCSV.Native.streaming_close_file(parser)
   end
 
   # ==========================================================================
@@ -510,15 +796,19 @@ defmodule RustyCSV.Streaming do
     {available, _buffer_size, _has_partial} = RustyCSV.Native.streaming_status(parser)
 
     if available > 0 do
-      rows = RustyCSV.Native.streaming_next_rows(parser, batch_size)
+      rows = take_rows(parser, batch_size)
       emit_rows(rows, state)
     else
       read_and_process_device(device, parser, chunk_size, batch_size, state)
     end
   end
 
-  defp next_rows_device({:device_done, _device, _parser, _chunk_size, _batch_size} = state) do
-    {:halt, state}
+  # Finalize again until no rows come back, so a pending width error raises
+  defp next_rows_device({:device_done, _device, parser, _chunk_size, _batch_size} = state) do
+    case finalize(parser) do
+      [] -> {:halt, state}
+      rows -> {rows, state}
+    end
   end
 
   defp read_and_process_device(device, parser, chunk_size, batch_size, state) do
@@ -530,14 +820,13 @@ defmodule RustyCSV.Streaming do
         raise "Error reading from device: #{inspect(reason)}"
 
       chunk when is_binary(chunk) ->
-        {_available, _buffer_size} = RustyCSV.Native.streaming_feed(parser, chunk)
-        rows = RustyCSV.Native.streaming_next_rows(parser, batch_size)
+        {rows, _status} = feed_take(parser, chunk, batch_size)
         emit_rows(rows, state)
     end
   end
 
   defp finalize_device_stream(parser, device, chunk_size, batch_size, state) do
-    case RustyCSV.Native.streaming_finalize(parser) do
+    case finalize(parser) do
       [] -> {:halt, state}
       rows -> {rows, {:device_done, device, parser, chunk_size, batch_size}}
     end
@@ -547,7 +836,7 @@ defmodule RustyCSV.Streaming do
   # Helpers (Private)
   # ==========================================================================
 
-  defp emit_rows([], state), do: next_rows_file(state)
+  defp emit_rows([], state), do: next_rows_device(state)
   defp emit_rows(rows, state), do: {rows, state}
 
   # Switch File.Stream from line mode to binary chunk mode.
@@ -9,12 +9,14 @@ pub mod simd_scanner;
 pub use field::*;
 pub use newlines::*;
 pub use scanner::*;
-#[allow(unused_imports)]
 pub use simd_index::RowEnd;
 pub use simd_index::StructuralIndex;
 pub use simd_scanner::scan_structural;
-#[allow(unused_imports)]
 pub use simd_scanner::scan_structural_incremental;
+pub use simd_scanner::scan_structural_utf16;
+pub use simd_scanner::scan_structural_utf32;
+pub use simd_scanner::scan_structural_with_terminator;
+pub use simd_scanner::Terminator;
 pub use simd_scanner::CHUNK;
 #[cfg(target_feature = "avx2")]
 pub use simd_scanner::WIDE;
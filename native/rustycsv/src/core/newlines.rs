@@ -1,8 +1,11 @@
+use super::simd_scanner::Terminator;
+
 /// Custom newline support for CSV parsing.
 ///
 /// When `is_default` is true, the standard \r\n and \n handling is used
-/// (SIMD-optimized paths). When false, the general byte-by-byte parser
-/// checks against the custom patterns.
+/// (SIMD-optimized paths). When false, patterns the SIMD scanner supports
+/// (see `terminator`) still take the SIMD paths; others use the general
+/// byte-by-byte parser, which checks against the custom patterns.
 
 #[derive(Debug, Clone)]
 pub struct Newlines {
@@ -31,6 +34,23 @@ impl Newlines {
         }
     }
 
+    /// The SIMD scanner terminator equivalent to these patterns, if any:
+    /// the default pair, `\r\n`/`\n`/`\r`, or a single one-byte pattern.
+    pub fn terminator(&self) -> Option<Terminator> {
+        if self.is_default {
+            return Some(Terminator::Newline);
+        }
+        let mut patterns: Vec<&[u8]> = self.patterns.iter().map(Vec::as_slice).collect();
+        patterns.sort_unstable();
+        patterns.dedup();
+        match patterns.as_slice() {
+            [[byte]] => Some(Terminator::Byte(*byte)),
+            [b"\n", b"\r", b"\r\n"] => Some(Terminator::AnyNewline),
+            [b"\n", b"\r\n"] => Some(Terminator::Newline),
+            _ => None,
+        }
+    }
+
     /// Maximum pattern length (used for chunk-boundary safety in streaming).
     pub fn max_pattern_len(&self) -> usize {
         self.patterns.iter().map(|p| p.len()).max().unwrap_or(1)
@@ -121,4 +141,26 @@ mod tests {
         let input = b"a||b";
         assert_eq!(match_newline(input, 1, &nl), 2); // "||" matches (longest first)
     }
+
+    #[test]
+    fn test_terminator_for_simd_scanner() {
+        let custom = |patterns: &[&[u8]]| {
+            Newlines::custom(patterns.iter().map(|p| p.to_vec()).collect()).terminator()
+        };
+        assert_eq!(
+            Newlines::default_newlines().terminator(),
+            Some(Terminator::Newline)
+        );
+        assert_eq!(custom(&[b"\n", b"\r\n"]), Some(Terminator::Newline));
+        assert_eq!(custom(&[b"\r"]), Some(Terminator::Byte(b'\r')));
+        assert_eq!(custom(&[b"|"]), Some(Terminator::Byte(b'|')));
+        assert_eq!(
+            custom(&[b"\r", b"\r\n", b"\n"]),
+            Some(Terminator::AnyNewline)
+        );
+        // Multi-byte and other combinations stay on the general parser
+        assert_eq!(custom(&[b"||"]), None);
+        assert_eq!(custom(&[b"\r", b"\n"]), None);
+        assert_eq!(custom(&[b"|", b"\n"]), None);
+    }
 }
@@ -23,6 +23,8 @@
 //   for all targets, with CLMUL/PMULL fast paths on x86_64/aarch64.
 // - AVX2 wide path (32 bytes) processes first, then 16-byte remainder,
 //   then scalar tail. Same pattern as RustyJSON's skip_plain_string_bytes.
+// - Row terminators are one compared byte per chunk (`\n` or a custom byte),
+//   plus `\r` when bare CR is a newline too; see `Terminator`.
 
 use std::simd::prelude::*;
 
@@ -35,6 +37,29 @@ pub const CHUNK: usize = 16;
 #[cfg(target_feature = "avx2")]
 pub const WIDE: usize = 32;
 
+/// Row terminator recognised by the scanner.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum Terminator {
+    /// `\n` and `\r\n` (RFC 4180; a bare `\r` is data)
+    #[default]
+    Newline,
+    /// `\n`, `\r\n` and bare `\r` (classic Mac line endings)
+    AnyNewline,
+    /// A single custom byte, e.g. `|` or `\r`; `\n` is data
+    Byte(u8),
+}
+
+impl Terminator {
+    /// Byte whose unquoted occurrences end rows (besides a bare `\r`)
+    #[inline]
+    fn byte(self) -> u8 {
+        match self {
+            Terminator::Newline | Terminator::AnyNewline => b'\n',
+            Terminator::Byte(byte) => byte,
+        }
+    }
+}
+
 // ---------------------------------------------------------------------------
 // Prefix-XOR: compute cumulative XOR to determine quoted regions
 // ---------------------------------------------------------------------------
@@ -84,13 +109,62 @@ fn extract_positions(mut mask: u64, base_pos: u32, out: &mut Vec<u32>) {
 /// `separators` are the field delimiter bytes (e.g., &[b',']).
 /// `escape` is the quote/escape byte (e.g., b'"').
 pub fn scan_structural(input: &[u8], separators: &[u8], escape: u8) -> StructuralIndex {
+    scan_structural_with_terminator(input, separators, escape, Terminator::Newline)
+}
+
+/// Scan the input with a configurable row terminator.
+pub fn scan_structural_with_terminator(
+    input: &[u8],
+    separators: &[u8],
+    escape: u8,
+    terminator: Terminator,
+) -> StructuralIndex {
     let est_seps = input.len() / 10 + 16;
     let est_rows = input.len() / 50 + 4;
     let mut field_seps: Vec<u32> = Vec::with_capacity(est_seps);
     let mut row_ends: Vec<RowEnd> = Vec::with_capacity(est_rows);
 
-    let mut pos: usize = 0;
-    let mut quote_carry: u64 = 0; // 0 or 1: parity of quotes seen so far
+    scan_structural_incremental(
+        input,
+        0,
+        separators,
+        escape,
+        terminator,
+        false,
+        &mut field_seps,
+        &mut row_ends,
+    );
+
+    StructuralIndex {
+        field_seps,
+        row_ends,
+        input_len: input.len() as u32,
+    }
+}
+
+/// Incremental scan for the streaming parser.
+///
+/// Scans `input[start..]` with the given carry state, appending positions
+/// (relative to `input`) to `field_seps` and `row_ends`. `\r\n` detection
+/// looks back at `input[pos - 1]`, so `input` must include the bytes before
+/// `start`. Returns the updated carry state (true = currently in quotes).
+///
+/// With `Terminator::AnyNewline` a `\r` at the end of `input` is a row end;
+/// if the next scan starts with `\n` that emits `\r\n` again, overlapping
+/// the previous row end by one byte (an empty row the caller skips).
+#[allow(clippy::too_many_arguments)]
+pub fn scan_structural_incremental(
+    input: &[u8],
+    start: usize,
+    separators: &[u8],
+    escape: u8,
+    terminator: Terminator,
+    in_quotes: bool,
+    field_seps: &mut Vec<u32>,
+    row_ends: &mut Vec<RowEnd>,
+) -> bool {
+    let mut pos = start;
+    let mut quote_carry: u64 = u64::from(in_quotes); // 0 or 1: parity of quotes seen so far
 
     // -----------------------------------------------------------------------
     // AVX2 wide path: 32-byte chunks
@@ -98,7 +172,7 @@ pub fn scan_structural(input: &[u8], separators: &[u8], escape: u8) -> Structura
     #[cfg(target_feature = "avx2")]
     {
         let esc_splat = Simd::<u8, WIDE>::splat(escape);
-        let lf_splat = Simd::<u8, WIDE>::splat(b'\n');
+        let lf_splat = Simd::<u8, WIDE>::splat(terminator.byte());
         let cr_splat = Simd::<u8, WIDE>::splat(b'\r');
 
         let sep_splats: Vec<Simd<u8, WIDE>> = separators
@@ -125,12 +199,12 @@ pub fn scan_structural(input: &[u8], separators: &[u8], escape: u8) -> Structura
             for splat in &sep_splats {
                 sep_bits |= chunk.simd_eq(*splat).to_bitmask() & MASK_32;
             }
-            extract_positions(sep_bits & not_quoted, base, &mut field_seps);
+            extract_positions(sep_bits & not_quoted, base, field_seps);
 
             let lf_bits = chunk.simd_eq(lf_splat).to_bitmask() & not_quoted;
             let cr_bits = chunk.simd_eq(cr_splat).to_bitmask() & not_quoted;
 
-            emit_row_ends(input, pos, lf_bits, cr_bits, &mut row_ends);
+            emit_row_ends(input, pos, lf_bits, cr_bits, terminator, row_ends);
 
             pos += WIDE;
         }
@@ -141,7 +215,7 @@ pub fn scan_structural(input: &[u8], separators: &[u8], escape: u8) -> Structura
     // -----------------------------------------------------------------------
     {
         let esc_splat = Simd::<u8, CHUNK>::splat(escape);
-        let lf_splat = Simd::<u8, CHUNK>::splat(b'\n');
+        let lf_splat = Simd::<u8, CHUNK>::splat(terminator.byte());
         let cr_splat = Simd::<u8, CHUNK>::splat(b'\r');
 
         let sep_splats: Vec<Simd<u8, CHUNK>> = separators
@@ -168,12 +242,12 @@ pub fn scan_structural(input: &[u8], separators: &[u8], escape: u8) -> Structura
             for splat in &sep_splats {
                 sep_bits |= chunk.simd_eq(*splat).to_bitmask() & MASK_16;
             }
-            extract_positions(sep_bits & not_quoted, base, &mut field_seps);
+            extract_positions(sep_bits & not_quoted, base, field_seps);
 
             let lf_bits = chunk.simd_eq(lf_splat).to_bitmask() & not_quoted;
             let cr_bits = chunk.simd_eq(cr_splat).to_bitmask() & not_quoted;
 
-            emit_row_ends(input, pos, lf_bits, cr_bits, &mut row_ends);
+            emit_row_ends(input, pos, lf_bits, cr_bits, terminator, row_ends);
 
             pos += CHUNK;
         }
@@ -187,76 +261,7 @@ pub fn scan_structural(input: &[u8], separators: &[u8], escape: u8) -> Structura
         pos,
         separators,
         escape,
-        quote_carry != 0,
-        &mut field_seps,
-        &mut row_ends,
-    );
-
-    StructuralIndex {
-        field_seps,
-        row_ends,
-        input_len: input.len() as u32,
-    }
-}
-
-/// Incremental scan for the streaming parser.
-///
-/// Scans `input[start..]` with the given carry state.
-/// Returns the updated carry state (true = currently in quotes).
-#[allow(dead_code)]
-pub fn scan_structural_incremental(
-    input: &[u8],
-    start: usize,
-    separators: &[u8],
-    escape: u8,
-    in_quotes: bool,
-    field_seps: &mut Vec<u32>,
-    row_ends: &mut Vec<RowEnd>,
-) -> bool {
-    let mut pos = start;
-    let mut quote_carry: u64 = if in_quotes { 1 } else { 0 };
-
-    {
-        let esc_splat = Simd::<u8, CHUNK>::splat(escape);
-        let lf_splat = Simd::<u8, CHUNK>::splat(b'\n');
-        let cr_splat = Simd::<u8, CHUNK>::splat(b'\r');
-
-        let sep_splats: Vec<Simd<u8, CHUNK>> = separators
-            .iter()
-            .map(|&s| Simd::<u8, CHUNK>::splat(s))
-            .collect();
-
-        const MASK_16: u64 = (1u64 << 16) - 1;
-
-        while pos + CHUNK <= input.len() {
-            let chunk = Simd::<u8, CHUNK>::from_slice(&input[pos..pos + CHUNK]);
-            let base = pos as u32;
-
-            let esc_mask = chunk.simd_eq(esc_splat).to_bitmask() & MASK_16;
-            let raw_quoted = prefix_xor(esc_mask) & MASK_16;
-            let quoted = raw_quoted ^ (quote_carry.wrapping_neg() & MASK_16);
-            quote_carry ^= (esc_mask.count_ones() as u64) & 1;
-            let not_quoted = !quoted & MASK_16;
-
-            let mut sep_bits: u64 = 0;
-            for splat in &sep_splats {
-                sep_bits |= chunk.simd_eq(*splat).to_bitmask() & MASK_16;
-            }
-            extract_positions(sep_bits & not_quoted, base, field_seps);
-
-            let lf_bits = chunk.simd_eq(lf_splat).to_bitmask() & not_quoted;
-            let cr_bits = chunk.simd_eq(cr_splat).to_bitmask() & not_quoted;
-            emit_row_ends(input, pos, lf_bits, cr_bits, row_ends);
-
-            pos += CHUNK;
-        }
-    }
-
-    scan_scalar_tail(
-        input,
-        pos,
-        separators,
-        escape,
+        terminator,
         quote_carry != 0,
         field_seps,
         row_ends,
@@ -272,19 +277,49 @@ pub fn scan_structural_incremental(
 /// For each \n bit: check if preceded by \r → emit RowEnd { pos: \r_pos, len: 2 }
 /// else emit RowEnd { pos: \n_pos, len: 1 }.
 ///
-/// \r bits that are NOT followed by \n are bare \r = data (per RFC 4180), ignored.
+/// \r bits that are NOT followed by \n are bare \r = data (per RFC 4180), ignored,
+/// unless the terminator is `AnyNewline`. For `Byte`, `lf_bits` holds the
+/// custom byte and each one ends a row on its own.
 #[inline]
 fn emit_row_ends(
     input: &[u8],
     chunk_start: usize,
     mut lf_bits: u64,
-    _cr_bits: u64,
+    cr_bits: u64,
+    terminator: Terminator,
     out: &mut Vec<RowEnd>,
 ) {
+    match terminator {
+        Terminator::Newline => {}
+        Terminator::AnyNewline => {
+            lf_bits |= cr_bits;
+        }
+        Terminator::Byte(_) => {
+            while lf_bits != 0 {
+                let bit = lf_bits.trailing_zeros();
+                out.push(RowEnd {
+                    pos: chunk_start as u32 + bit,
+                    len: 1,
+                });
+                lf_bits &= lf_bits - 1;
+            }
+            return;
+        }
+    }
+
     while lf_bits != 0 {
         let bit = lf_bits.trailing_zeros() as usize;
         let abs_pos = chunk_start + bit;
-        if abs_pos > 0 && input[abs_pos - 1] == b'\r' {
+        lf_bits &= lf_bits - 1;
+        if input[abs_pos] == b'\r' {
+            // Bare \r (AnyNewline only); \r\n is emitted at the \n
+            if input.get(abs_pos + 1) != Some(&b'\n') {
+                out.push(RowEnd {
+                    pos: abs_pos as u32,
+                    len: 1,
+                });
+            }
+        } else if abs_pos > 0 && input[abs_pos - 1] == b'\r' {
             out.push(RowEnd {
                 pos: (abs_pos - 1) as u32,
                 len: 2,
@@ -295,7 +330,6 @@ fn emit_row_ends(
                 len: 1,
             });
         }
-        lf_bits &= lf_bits - 1;
     }
 }
 
@@ -305,11 +339,13 @@ fn emit_row_ends(
 
 /// Scalar scan for remaining bytes after SIMD processing.
 /// Returns the final `in_quotes` state.
+#[allow(clippy::too_many_arguments)]
 fn scan_scalar_tail(
     input: &[u8],
     start: usize,
     separators: &[u8],
     escape: u8,
+    terminator: Terminator,
     mut in_quotes: bool,
     field_seps: &mut Vec<u32>,
     row_ends: &mut Vec<RowEnd>,
@@ -331,6 +367,25 @@ fn scan_scalar_tail(
         } else if byte == escape {
             in_quotes = true;
             pos += 1;
+        } else if let Terminator::Byte(terminator) = terminator {
+            if byte == terminator {
+                row_ends.push(RowEnd {
+                    pos: pos as u32,
+                    len: 1,
+                });
+            } else if is_sep_scalar(byte, separators) {
+                field_seps.push(pos as u32);
+            }
+            pos += 1;
+        } else if byte == b'\r'
+            && terminator == Terminator::AnyNewline
+            && input.get(pos + 1) != Some(&b'\n')
+        {
+            row_ends.push(RowEnd {
+                pos: pos as u32,
+                len: 1,
+            });
+            pos += 1;
         } else if byte == b'\n' {
             if pos > 0 && input[pos - 1] == b'\r' {
                 row_ends.push(RowEnd {
@@ -365,6 +420,312 @@ fn is_sep_scalar(byte: u8, separators: &[u8]) -> bool {
     }
 }
 
+// ---------------------------------------------------------------------------
+// UTF-16 scanner
+// ---------------------------------------------------------------------------
+//
+// Scans UTF-16 input in place, one 32-byte load per 16 code units. A code
+// unit is a structural character when one of its bytes is that ASCII byte and
+// the other is zero, so surrogate halves and other non-ASCII units never
+// match. The index holds code-unit positions, not byte positions.
+
+/// Gather the even bits of the lower 32 bits of `x` into its lower 16 bits.
+#[inline]
+fn even_bits(mut x: u64) -> u64 {
+    x &= 0x5555_5555;
+    x = (x | (x >> 1)) & 0x3333_3333;
+    x = (x | (x >> 2)) & 0x0F0F_0F0F;
+    x = (x | (x >> 4)) & 0x00FF_00FF;
+    x = (x | (x >> 8)) & 0x0000_FFFF;
+    x
+}
+
+/// Code unit `i` of UTF-16 input
+#[inline]
+pub fn utf16_unit(input: &[u8], i: usize, big_endian: bool) -> u16 {
+    let pair = [input[2 * i], input[2 * i + 1]];
+    if big_endian {
+        u16::from_be_bytes(pair)
+    } else {
+        u16::from_le_bytes(pair)
+    }
+}
+
+/// Scan UTF-16 input (without a BOM) and produce a `StructuralIndex` of
+/// code-unit positions.
+///
+/// `separators`, `escape` and the terminator byte must be ASCII; each matches
+/// the code unit of the same value. A trailing odd byte is not scanned.
+pub fn scan_structural_utf16(
+    input: &[u8],
+    big_endian: bool,
+    separators: &[u8],
+    escape: u8,
+    terminator: Terminator,
+) -> StructuralIndex {
+    let units = input.len() / 2;
+    let mut field_seps: Vec<u32> = Vec::with_capacity(units / 10 + 16);
+    let mut row_ends: Vec<RowEnd> = Vec::with_capacity(units / 50 + 4);
+    let mut pos = 0;
+    let mut quote_carry: u64 = 0;
+
+    {
+        const BYTES: usize = 2 * CHUNK;
+        const MASK_16: u64 = (1u64 << 16) - 1;
+        const MASK_32: u64 = (1u64 << 32) - 1;
+
+        let zero_splat = Simd::<u8, BYTES>::splat(0);
+        let esc_splat = Simd::<u8, BYTES>::splat(escape);
+        let lf_splat = Simd::<u8, BYTES>::splat(terminator.byte());
+        let cr_splat = Simd::<u8, BYTES>::splat(b'\r');
+
+        let sep_splats: Vec<Simd<u8, BYTES>> = separators
+            .iter()
+            .map(|&s| Simd::<u8, BYTES>::splat(s))
+            .collect();
+
+        while pos + CHUNK <= units {
+            let chunk = Simd::<u8, BYTES>::from_slice(&input[2 * pos..2 * pos + BYTES]);
+            let base = pos as u32;
+
+            // Units whose low byte matches `splat` and whose high byte is zero
+            let zero = chunk.simd_eq(zero_splat).to_bitmask() & MASK_32;
+            let units_eq = |splat: Simd<u8, BYTES>| {
+                let bytes = chunk.simd_eq(splat).to_bitmask() & MASK_32;
+                if big_endian {
+                    even_bits((bytes >> 1) & zero)
+                } else {
+                    even_bits(bytes & (zero >> 1))
+                }
+            };
+
+            let esc_mask = units_eq(esc_splat);
+
+            let raw_quoted = prefix_xor(esc_mask) & MASK_16;
+            let quoted = raw_quoted ^ (quote_carry.wrapping_neg() & MASK_16);
+
+            quote_carry ^= (esc_mask.count_ones() as u64) & 1;
+
+            let not_quoted = !quoted & MASK_16;
+
+            let mut sep_bits: u64 = 0;
+            for splat in &sep_splats {
+                sep_bits |= units_eq(*splat);
+            }
+            extract_positions(sep_bits & not_quoted, base, &mut field_seps);
+
+            let lf_bits = units_eq(lf_splat) & not_quoted;
+            let cr_bits = units_eq(cr_splat) & not_quoted;
+
+            emit_row_ends_utf16(
+                input,
+                big_endian,
+                pos,
+                lf_bits,
+                cr_bits,
+                terminator,
+                &mut row_ends,
+            );
+
+            pos += CHUNK;
+        }
+    }
+
+    let units = input.len() / 2;
+    scan_scalar_tail_units(
+        |i| (i < units).then(|| u32::from(utf16_unit(input, i, big_endian))),
+        pos,
+        separators,
+        escape,
+        terminator,
+        quote_carry != 0,
+        &mut field_seps,
+        &mut row_ends,
+    );
+
+    StructuralIndex {
+        field_seps,
+        row_ends,
+        input_len: units as u32,
+    }
+}
+
+/// Code unit `i` of UTF-32 input
+#[inline]
+pub fn utf32_unit(input: &[u8], i: usize, big_endian: bool) -> u32 {
+    let quad = [
+        input[4 * i],
+        input[4 * i + 1],
+        input[4 * i + 2],
+        input[4 * i + 3],
+    ];
+    if big_endian {
+        u32::from_be_bytes(quad)
+    } else {
+        u32::from_le_bytes(quad)
+    }
+}
+
+/// Scan UTF-32 input (without a BOM) and produce a `StructuralIndex` of
+/// code-unit positions, as `scan_structural_utf16` does for UTF-16. UTF-32
+/// input is rare, so this is only the scalar scan.
+///
+/// `separators`, `escape` and the terminator byte must be ASCII. Trailing
+/// bytes short of a unit are not scanned.
+pub fn scan_structural_utf32(
+    input: &[u8],
+    big_endian: bool,
+    separators: &[u8],
+    escape: u8,
+    terminator: Terminator,
+) -> StructuralIndex {
+    let units = input.len() / 4;
+    let mut field_seps: Vec<u32> = Vec::with_capacity(units / 10 + 16);
+    let mut row_ends: Vec<RowEnd> = Vec::with_capacity(units / 50 + 4);
+
+    scan_scalar_tail_units(
+        |i| (i < units).then(|| utf32_unit(input, i, big_endian)),
+        0,
+        separators,
+        escape,
+        terminator,
+        false,
+        &mut field_seps,
+        &mut row_ends,
+    );
+
+    StructuralIndex {
+        field_seps,
+        row_ends,
+        input_len: units as u32,
+    }
+}
+
+/// `emit_row_ends` for UTF-16 input, with code-unit positions.
+#[inline]
+fn emit_row_ends_utf16(
+    input: &[u8],
+    big_endian: bool,
+    chunk_start: usize,
+    mut lf_bits: u64,
+    cr_bits: u64,
+    terminator: Terminator,
+    out: &mut Vec<RowEnd>,
+) {
+    let units = input.len() / 2;
+    let unit = |i: usize| (i < units).then(|| utf16_unit(input, i, big_endian));
+    let cr = u16::from(b'\r');
+    let lf = u16::from(b'\n');
+
+    match terminator {
+        Terminator::Newline => {}
+        Terminator::AnyNewline => {
+            lf_bits |= cr_bits;
+        }
+        Terminator::Byte(_) => {
+            while lf_bits != 0 {
+                let bit = lf_bits.trailing_zeros();
+                out.push(RowEnd {
+                    pos: chunk_start as u32 + bit,
+                    len: 1,
+                });
+                lf_bits &= lf_bits - 1;
+            }
+            return;
+        }
+    }
+
+    while lf_bits != 0 {
+        let bit = lf_bits.trailing_zeros() as usize;
+        let abs_pos = chunk_start + bit;
+        lf_bits &= lf_bits - 1;
+        if unit(abs_pos) == Some(cr) {
+            // Bare \r (AnyNewline only); \r\n is emitted at the \n
+            if unit(abs_pos + 1) != Some(lf) {
+                out.push(RowEnd {
+                    pos: abs_pos as u32,
+                    len: 1,
+                });
+            }
+        } else if abs_pos > 0 && unit(abs_pos - 1) == Some(cr) {
+            out.push(RowEnd {
+                pos: (abs_pos - 1) as u32,
+                len: 2,
+            });
+        } else {
+            out.push(RowEnd {
+                pos: abs_pos as u32,
+                len: 1,
+            });
+        }
+    }
+}
+
+/// `scan_scalar_tail` for UTF-16 or UTF-32 input, from code unit `start`.
+/// `unit(i)` is code unit `i`, or `None` past the end of the input.
+#[allow(clippy::too_many_arguments)]
+fn scan_scalar_tail_units(
+    unit: impl Fn(usize) -> Option<u32>,
+    start: usize,
+    separators: &[u8],
+    escape: u8,
+    terminator: Terminator,
+    mut in_quotes: bool,
+    field_seps: &mut Vec<u32>,
+    row_ends: &mut Vec<RowEnd>,
+) -> bool {
+    let escape = u32::from(escape);
+    let cr = u32::from(b'\r');
+    let lf = u32::from(b'\n');
+    let is_sep = |u: u32| u8::try_from(u).is_ok_and(|byte| is_sep_scalar(byte, separators));
+    let mut pos = start;
+
+    while let Some(u) = unit(pos) {
+        if in_quotes {
+            if u == escape {
+                if unit(pos + 1) == Some(escape) {
+                    pos += 2;
+                    continue;
+                }
+                in_quotes = false;
+            }
+        } else if u == escape {
+            in_quotes = true;
+        } else if let Terminator::Byte(terminator) = terminator {
+            if u == u32::from(terminator) {
+                row_ends.push(RowEnd {
+                    pos: pos as u32,
+                    len: 1,
+                });
+            } else if is_sep(u) {
+                field_seps.push(pos as u32);
+            }
+        } else if u == cr && terminator == Terminator::AnyNewline && unit(pos + 1) != Some(lf) {
+            row_ends.push(RowEnd {
+                pos: pos as u32,
+                len: 1,
+            });
+        } else if u == lf {
+            if pos > 0 && unit(pos - 1) == Some(cr) {
+                row_ends.push(RowEnd {
+                    pos: (pos - 1) as u32,
+                    len: 2,
+                });
+            } else {
+                row_ends.push(RowEnd {
+                    pos: pos as u32,
+                    len: 1,
+                });
+            }
+        } else if is_sep(u) {
+            field_seps.push(pos as u32);
+        }
+        pos += 1;
+    }
+
+    in_quotes
+}
+
 // ---------------------------------------------------------------------------
 // Tests
 // ---------------------------------------------------------------------------
@@ -734,7 +1095,16 @@ mod tests {
         let mut seps = Vec::new();
         let mut ends = Vec::new();
 
-        let carry = scan_structural_incremental(input, 0, b",", b'"', false, &mut seps, &mut ends);
+        let carry = scan_structural_incremental(
+            input,
+            0,
+            b",",
+            b'"',
+            Terminator::Newline,
+            false,
+            &mut seps,
+            &mut ends,
+        );
 
         assert!(!carry);
         assert_eq!(seps, vec![1, 5]);
@@ -757,7 +1127,16 @@ mod tests {
         let mut seps = Vec::new();
         let mut ends = Vec::new();
 
-        let carry = scan_structural_incremental(input, 0, b",", b'"', true, &mut seps, &mut ends);
+        let carry = scan_structural_incremental(
+            input,
+            0,
+            b",",
+            b'"',
+            Terminator::Newline,
+            true,
+            &mut seps,
+            &mut ends,
+        );
 
         assert!(!carry, "quote closed at pos 11, should not be in quotes");
         assert_eq!(
@@ -767,4 +1146,212 @@ mod tests {
         );
         assert_eq!(ends, vec![RowEnd { pos: 17, len: 1 }]);
     }
+
+    // =======================================================================
+    // Configurable terminators
+    // =======================================================================
+
+    fn row_ends(input: &[u8], terminator: Terminator) -> Vec<RowEnd> {
+        scan_structural_with_terminator(input, b",", b'"', terminator).row_ends
+    }
+
+    #[test]
+    fn test_custom_byte_terminator_in_simd_and_tail() {
+        // 42 bytes: rows end at every '|' in the wide/16-byte paths and the tail
+        let input = b"aaaa,bbbb|\"c|c\",dddd|eeeeeeeeeeee\nffff|gg|";
+        let ends = row_ends(input, Terminator::Byte(b'|'));
+        let positions: Vec<u32> = ends.iter().map(|end| end.pos).collect();
+        assert_eq!(positions, vec![9, 20, 38, 41]);
+        assert!(ends.iter().all(|end| end.len == 1));
+    }
+
+    #[test]
+    fn test_cr_terminator_keeps_lf_as_data() {
+        let input = b"a,b\r\nc,d\r";
+        assert_eq!(
+            row_ends(input, Terminator::Byte(b'\r')),
+            vec![RowEnd { pos: 3, len: 1 }, RowEnd { pos: 8, len: 1 }]
+        );
+    }
+
+    #[test]
+    fn test_any_newline_matches_scalar_reference() {
+        let line = b"x,\"q\rq\"\r1,2\r\n3,4\n5\r\r";
+        let input = line.repeat(5);
+        let ends = row_ends(&input, Terminator::AnyNewline);
+
+        // Reference: \r\n is one terminator, bare \r and \n end rows,
+        // the quoted \r is data
+        let mut expected = Vec::new();
+        for rep in 0..5u32 {
+            let base = rep * line.len() as u32;
+            for (pos, len) in [(7, 1), (11, 2), (16, 1), (18, 1), (19, 1)] {
+                expected.push(RowEnd {
+                    pos: base + pos,
+                    len,
+                });
+            }
+        }
+        assert_eq!(ends, expected);
+    }
+
+    #[test]
+    fn test_any_newline_cr_at_end_of_incremental_scan() {
+        let input = b"a,b\r\nc";
+        let (mut seps, mut ends) = (Vec::new(), Vec::new());
+
+        scan_structural_incremental(
+            &input[..4],
+            0,
+            b",",
+            b'"',
+            Terminator::AnyNewline,
+            false,
+            &mut seps,
+            &mut ends,
+        );
+        assert_eq!(ends, vec![RowEnd { pos: 3, len: 1 }]);
+
+        // The \n completes \r\n, overlapping the bare \r row end
+        scan_structural_incremental(
+            input,
+            4,
+            b",",
+            b'"',
+            Terminator::AnyNewline,
+            false,
+            &mut seps,
+            &mut ends,
+        );
+        assert_eq!(
+            ends,
+            vec![RowEnd { pos: 3, len: 1 }, RowEnd { pos: 3, len: 2 }]
+        );
+    }
+
+    // =======================================================================
+    // UTF-16
+    // =======================================================================
+
+    #[test]
+    fn test_even_bits() {
+        assert_eq!(even_bits(0x5555_5555), 0xFFFF);
+        assert_eq!(even_bits(0xAAAA_AAAA), 0);
+        assert_eq!(even_bits(0b0100_0001), 0b1001);
+        assert_eq!(even_bits(1 << 30), 1 << 15);
+    }
+
+    #[test]
+    fn test_utf16_matches_byte_scan() {
+        // U+2C22 and U+0A2C have '"' and ',' as one byte; the emoji is a
+        // surrogate pair. All of them are data.
+        let line = "n\u{e9},\"q,\u{2c22}\r\n\"\u{a2c},\u{1F600}\r\nx\ry\n\"\"\"a\"\"\",\u{2c0d}\n";
+        let text = line.repeat(5);
+        let units: Vec<u16> = text.encode_utf16().collect();
+        // Same structure in one byte per unit
+        let shadow: Vec<u8> = units
+            .iter()
+            .map(|&u| u8::try_from(u).ok().filter(u8::is_ascii).unwrap_or(b'x'))
+            .collect();
+
+        for terminator in [
+            Terminator::Newline,
+            Terminator::AnyNewline,
+            Terminator::Byte(b'\r'),
+        ] {
+            let expected = scan_structural_with_terminator(&shadow, b",", b'"', terminator);
+            for big_endian in [false, true] {
+                let input: Vec<u8> = units
+                    .iter()
+                    .flat_map(|&u| {
+                        if big_endian {
+                            u.to_be_bytes()
+                        } else {
+                            u.to_le_bytes()
+                        }
+                    })
+                    .collect();
+                let idx = scan_structural_utf16(&input, big_endian, b",", b'"', terminator);
+                assert_eq!(idx.field_seps, expected.field_seps, "{terminator:?}");
+                assert_eq!(idx.row_ends, expected.row_ends, "{terminator:?}");
+                assert_eq!(idx.input_len, expected.input_len);
+            }
+        }
+    }
+
+    #[test]
+    fn test_utf32_matches_utf16_scan() {
+        let line = "n\u{e9},\"q,\u{2c22}\r\n\"\u{a2c},\u{1F600}\r\nx\ry\n\"\"\"a\"\"\",\u{2c0d}\n";
+        let text = line.repeat(5);
+        let units: Vec<u16> = text.encode_utf16().collect();
+        let utf16: Vec<u8> = units.iter().flat_map(|u| u.to_le_bytes()).collect();
+        // U+2C00 and U+2C0D have ',' as one of their UTF-32 bytes
+        let text = format!("{text}\u{2c00}\u{2c0d}");
+
+        for terminator in [
+            Terminator::Newline,
+            Terminator::AnyNewline,
+            Terminator::Byte(b'\r'),
+        ] {
+            let expected = scan_structural_utf16(&utf16, false, b",", b'"', terminator);
+            for big_endian in [false, true] {
+                let input: Vec<u8> = text
+                    .chars()
+                    .flat_map(|c| {
+                        if big_endian {
+                            u32::from(c).to_be_bytes()
+                        } else {
+                            u32::from(c).to_le_bytes()
+                        }
+                    })
+                    .collect();
+                let idx = scan_structural_utf32(&input, big_endian, b",", b'"', terminator);
+                // One UTF-32 unit per character; map positions to UTF-16 units
+                let chars: Vec<char> = text.chars().collect();
+                let utf16_pos = |pos: u32| {
+                    chars[..pos as usize]
+                        .iter()
+                        .map(|c| c.len_utf16() as u32)
+                        .sum::<u32>()
+                };
+                let seps: Vec<u32> = idx.field_seps.iter().map(|&p| utf16_pos(p)).collect();
+                assert_eq!(seps, expected.field_seps, "{terminator:?}");
+                let ends: Vec<(u32, u8)> = idx
+                    .row_ends
+                    .iter()
+                    .map(|end| (utf16_pos(end.pos), end.len))
+                    .collect();
+                let expected_ends: Vec<(u32, u8)> = expected
+                    .row_ends
+                    .iter()
+                    .map(|end| (end.pos, end.len))
+                    .collect();
+                assert_eq!(ends, expected_ends, "{terminator:?}");
+                assert_eq!(idx.input_len as usize, chars.len());
+            }
+        }
+    }
+
+    #[test]
+    fn test_utf32_partial_trailing_unit_is_not_scanned() {
+        let idx = scan_structural_utf32(
+            b"a\x00\x00\x00,\x00\x00\x00\n\x00\x00",
+            false,
+            b",",
+            b'"',
+            Terminator::Newline,
+        );
+        assert_eq!(idx.field_seps, vec![1]);
+        assert_eq!(idx.row_ends, vec![]);
+        assert_eq!(idx.input_len, 2);
+    }
+
+    #[test]
+    fn test_utf16_odd_trailing_byte_is_not_scanned() {
+        let idx =
+            scan_structural_utf16(b"a\x00,\x00b\x00\n", false, b",", b'"', Terminator::Newline);
+        assert_eq!(idx.field_seps, vec![1]);
+        assert_eq!(idx.row_ends, vec![]);
+        assert_eq!(idx.input_len, 3);
+    }
 }
@@ -21,6 +21,55 @@ mod atoms {
         error,
         mutex_poisoned,
         buffer_overflow,
+        drain,
+        row_width,
+        pad,
+        truncate,
+        duplicate_header,
+        missing_required_header,
+        unknown_header_atom,
+        collect_key_conflict,
+        internal_span_error,
+        encoded_checkpoint,
+        extra_columns,
+        missing_columns,
+        bytes_fed,
+        bytes_consumed,
+        rows_emitted,
+        physical_lines,
+        largest_row,
+        peak_buffer,
+        compactions,
+        separator,
+        escape,
+        newlines,
+        default,
+        chunk_size,
+        resume,
+        read_error,
+        enoent,
+        eacces,
+        eisdir,
+        eio,
+        invalid_encoding,
+        incomplete_encoding,
+        utf8,
+        latin1,
+        windows_1252,
+        iso_8859_15,
+        codepage,
+        auto,
+        utf16,
+        utf32,
+        little,
+        big,
+        replace,
+        transliterate,
+        unmappable,
+        invalid_utf8,
+        passthrough,
+        sep_directive,
+        struct_ = "__struct__",
     }
 }
 
@@ -70,6 +119,39 @@ fn decode_separators<'a>(term: Term<'a>) -> NifResult<Separators> {
     Err(Error::BadArg)
 }
 
+/// `Some(separators)` for a `{:sep_directive, separator}` separator term:
+/// `separator` unless the input starts with a `sep=` line
+fn decode_sep_directive<'a>(term: Term<'a>) -> NifResult<Option<Separators>> {
+    match term.decode::<(Atom, Term<'a>)>() {
+        Ok((tag, separator)) if tag == atoms::sep_directive() => {
+            Ok(Some(decode_separators(separator)?))
+        }
+        Ok(_) => Err(Error::BadArg),
+        Err(_) => Ok(None),
+    }
+}
+
+/// Decode the separator of a batch parse along with the input to parse. With
+/// `{:sep_directive, separator}`, a first line like `sep=;` picks the
+/// separator and is dropped: the input returned is a sub-binary after it.
+fn decode_input_separators<'a>(
+    input: Binary<'a>,
+    sep_term: Term<'a>,
+) -> NifResult<(Binary<'a>, Separators)> {
+    let Some(separators) = decode_sep_directive(sep_term)? else {
+        return Ok((input, decode_separators(sep_term)?));
+    };
+    match find_sep_directive(input.as_slice(), true) {
+        SepDirective::Found { separator, len } => Ok((
+            input.make_subbinary(len, input.len() - len)?,
+            Separators {
+                patterns: vec![separator],
+            },
+        )),
+        _ => Ok((input, separators)),
+    }
+}
+
 /// Decode escape from a Term.
 /// Accepts: integer 34 or binary <<34>> or binary <<36,36>>
 fn decode_escape<'a>(term: Term<'a>) -> NifResult<Escape> {
@@ -98,7 +180,7 @@ fn single_byte_seps(separators: &Separators) -> Vec<u8> {
     separators.patterns.iter().map(|p| p[0]).collect()
 }
 
-use core::Newlines;
+use core::{Newlines, Terminator};
 
 /// Decode newlines from a Term.
 /// Accepts: atom :default → default newlines, or list of binaries → custom newlines
@@ -121,7 +203,41 @@ fn decode_newlines<'a>(term: Term<'a>) -> NifResult<Newlines> {
     Err(Error::BadArg)
 }
 
-use resource::{StreamingParserEnum, StreamingParserRef, StreamingParserResource};
+/// Row terminator for the SIMD scanner paths, when separators and escape
+/// are single bytes and the newlines map to one (default, `\r`-aware, or a
+/// single custom byte that is not also a separator or the escape).
+fn simd_terminator(
+    separators: &Separators,
+    escape: &Escape,
+    newlines: &Newlines,
+) -> Option<Terminator> {
+    if !is_all_single_byte(separators, escape) {
+        return None;
+    }
+    match newlines.terminator()? {
+        Terminator::Byte(byte)
+            if byte == escape.bytes[0] || separators.patterns.iter().any(|p| p[0] == byte) =>
+        {
+            None
+        }
+        terminator => Some(terminator),
+    }
+}
+
+use resource::{
+    FedChunks, FileSource, SepDirectiveState, StreamingHeaders, StreamingParserEnum,
+    StreamingParserRef, StreamingParserResource,
+};
+
+/// Lock the streaming parser's file source. Take before `lock_parser`.
+fn lock_source(
+    parser: &StreamingParserResource,
+) -> NifResult<std::sync::MutexGuard<'_, Option<FileSource>>> {
+    parser
+        .source
+        .lock()
+        .map_err(|_| Error::RaiseTerm(Box::new(atoms::mutex_poisoned())))
+}
 
 fn lock_parser(
     parser: &StreamingParserResource,
@@ -132,18 +248,64 @@ fn lock_parser(
         .map_err(|_| Error::RaiseTerm(Box::new(atoms::mutex_poisoned())))
 }
 
+/// Lock the streaming parser's `sep=` state. Take after `lock_parser`.
+fn lock_directive(
+    parser: &StreamingParserResource,
+) -> NifResult<std::sync::MutexGuard<'_, Option<SepDirectiveState>>> {
+    parser
+        .directive
+        .lock()
+        .map_err(|_| Error::RaiseTerm(Box::new(atoms::mutex_poisoned())))
+}
+
+/// Lock the streaming parser's input decoder. Take after `lock_directive`.
+fn lock_decoder(
+    parser: &StreamingParserResource,
+) -> NifResult<std::sync::MutexGuard<'_, Option<Decoder>>> {
+    parser
+        .decoder
+        .lock()
+        .map_err(|_| Error::RaiseTerm(Box::new(atoms::mutex_poisoned())))
+}
+
+/// Lock the streaming parser's map output state. Take after `lock_decoder`.
+fn lock_headers(
+    parser: &StreamingParserResource,
+) -> NifResult<std::sync::MutexGuard<'_, Option<StreamingHeaders>>> {
+    parser
+        .headers
+        .lock()
+        .map_err(|_| Error::RaiseTerm(Box::new(atoms::mutex_poisoned())))
+}
+
+/// Lock the streaming parser's fed chunks. Take after `lock_headers`.
+fn lock_chunks(
+    parser: &StreamingParserResource,
+) -> NifResult<std::sync::MutexGuard<'_, FedChunks>> {
+    parser
+        .chunks
+        .lock()
+        .map_err(|_| Error::RaiseTerm(Box::new(atoms::mutex_poisoned())))
+}
+
+use strategy::directive::{find_sep_directive, SepDirective, MAX_LINE};
 use strategy::{
     contains_escape, field_needs_quoting_general, field_needs_quoting_simd,
     field_needs_quoting_simd_multi_sep, parse_csv_boundaries_general,
-    parse_csv_boundaries_general_with_newlines, parse_csv_boundaries_multi_sep,
-    parse_csv_boundaries_with_config, parse_csv_parallel_boundaries,
+    parse_csv_boundaries_general_with_newlines, parse_csv_boundaries_with_config,
+    parse_csv_boundaries_with_terminator, parse_csv_parallel_boundaries,
     parse_csv_parallel_boundaries_general, parse_csv_parallel_boundaries_general_with_newlines,
-    parse_csv_parallel_boundaries_multi_sep, parse_csv_parallel_boundaries_with_config,
-    unescape_field_general,
+    parse_csv_parallel_boundaries_with_terminator, unescape_field_general, Checkpoint, ReadError,
+    StreamField, WidthPolicy,
 };
 use term::{
-    boundaries_to_maps_hybrid, boundaries_to_maps_hybrid_general, boundaries_to_term_hybrid,
-    boundaries_to_term_hybrid_general, owned_rows_to_term,
+    boundaries_to_keywords_hybrid, boundaries_to_keywords_hybrid_general,
+    boundaries_to_maps_hybrid, boundaries_to_maps_hybrid_general, boundaries_to_rows_hybrid,
+    boundaries_to_term_hybrid, boundaries_to_term_hybrid_general, boundaries_to_tuples_hybrid,
+    boundaries_to_tuples_hybrid_general, copy_to_binary, keyword_keys, resolve_map_keys,
+    rows_to_keywords, rows_to_maps, rows_to_tuples, single_byte_boundaries_to_rows,
+    stream_rows_to_maps, stream_rows_to_term, wide_index_to_rows, ColumnPolicy, DuplicateHeaders,
+    ExtraColumns, HeaderKey, LoadedChunks, MapKeys, MissingColumns, RowShape, StructSpec,
 };
 
 // ============================================================================
@@ -275,7 +437,7 @@ fn parse_string_with_config<'a>(
     esc_term: Term<'a>,
     newlines_term: Term<'a>,
 ) -> NifResult<Term<'a>> {
-    let separators = decode_separators(sep_term)?;
+    let (input, separators) = decode_input_separators(input, sep_term)?;
     let escape = decode_escape(esc_term)?;
     let newlines = decode_newlines(newlines_term)?;
     let boundaries = dispatch_boundary_parse(input.as_slice(), &separators, &escape, &newlines);
@@ -302,7 +464,7 @@ fn parse_string_fast_with_config<'a>(
     esc_term: Term<'a>,
     newlines_term: Term<'a>,
 ) -> NifResult<Term<'a>> {
-    let separators = decode_separators(sep_term)?;
+    let (input, separators) = decode_input_separators(input, sep_term)?;
     let escape = decode_escape(esc_term)?;
     let newlines = decode_newlines(newlines_term)?;
     let boundaries = dispatch_boundary_parse(input.as_slice(), &separators, &escape, &newlines);
@@ -329,7 +491,7 @@ fn parse_string_indexed_with_config<'a>(
     esc_term: Term<'a>,
     newlines_term: Term<'a>,
 ) -> NifResult<Term<'a>> {
-    let separators = decode_separators(sep_term)?;
+    let (input, separators) = decode_input_separators(input, sep_term)?;
     let escape = decode_escape(esc_term)?;
     let newlines = decode_newlines(newlines_term)?;
     let boundaries = dispatch_boundary_parse(input.as_slice(), &separators, &escape, &newlines);
@@ -346,51 +508,288 @@ fn streaming_new() -> StreamingParserRef {
     ResourceArc::new(StreamingParserResource::new())
 }
 
-/// Create a new streaming parser with configurable separator(s), escape, and newlines
+/// Create a new streaming parser with configurable separator(s), escape, and
+/// newlines. With a `{:sep_directive, separator}` separator, input is held
+/// back until its first line is known; a `sep=;` line switches the parser to
+/// that separator and is dropped.
 #[rustler::nif]
 fn streaming_new_with_config<'a>(
     sep_term: Term<'a>,
     esc_term: Term<'a>,
     newlines_term: Term<'a>,
 ) -> NifResult<StreamingParserRef> {
-    let separators = decode_separators(sep_term)?;
+    let (inner, directive) = decode_streaming_parser(sep_term, esc_term, newlines_term)?;
+    Ok(ResourceArc::new(
+        StreamingParserResource::from_parser(inner).with_directive(directive),
+    ))
+}
+
+/// Build the streaming parser for a separator/escape/newlines config, and
+/// the `sep=` state for a `{:sep_directive, separator}` separator.
+fn decode_streaming_parser<'a>(
+    sep_term: Term<'a>,
+    esc_term: Term<'a>,
+    newlines_term: Term<'a>,
+) -> NifResult<(StreamingParserEnum, Option<SepDirectiveState>)> {
+    let (separators, directive) = match decode_sep_directive(sep_term)? {
+        Some(separators) => (separators, true),
+        None => (decode_separators(sep_term)?, false),
+    };
     let escape = decode_escape(esc_term)?;
     let newlines = decode_newlines(newlines_term)?;
 
+    let parser = streaming_parser(&separators, &escape, &newlines);
+    let directive =
+        directive.then(|| SepDirectiveState::new(separators.patterns, escape.bytes, newlines));
+    Ok((parser, directive))
+}
+
+/// The streaming parser variant for a config: single-byte fast path, general
+/// multi-byte, or general with custom newlines.
+fn streaming_parser(
+    separators: &Separators,
+    escape: &Escape,
+    newlines: &Newlines,
+) -> StreamingParserEnum {
+    if let Some(terminator) = simd_terminator(separators, escape, newlines) {
+        return StreamingParserEnum::with_terminator(
+            &single_byte_seps(separators),
+            escape.bytes[0],
+            terminator,
+        );
+    }
+
     if !newlines.is_default {
-        return Ok(ResourceArc::new(
-            StreamingParserResource::with_general_newlines(
-                separators.patterns,
-                escape.bytes,
-                newlines,
-            ),
-        ));
+        StreamingParserEnum::with_general_newlines(
+            separators.patterns.clone(),
+            escape.bytes.clone(),
+            newlines.clone(),
+        )
+    } else {
+        StreamingParserEnum::with_general(separators.patterns.clone(), escape.bytes.clone())
     }
+}
 
-    Ok(if is_all_single_byte(&separators, &escape) {
-        let esc = escape.bytes[0];
-        let sep_bytes = single_byte_seps(&separators);
-        if sep_bytes.len() == 1 {
-            ResourceArc::new(StreamingParserResource::with_config(sep_bytes[0], esc))
-        } else {
-            ResourceArc::new(StreamingParserResource::with_multi_sep(&sep_bytes, esc))
+/// The parser `state` was configured with, or with `separator` in place of
+/// the configured separators
+fn directive_parser(state: &SepDirectiveState, separator: Option<Vec<u8>>) -> StreamingParserEnum {
+    let separators = Separators {
+        patterns: separator.map_or_else(|| state.separators.clone(), |s| vec![s]),
+    };
+    let escape = Escape {
+        bytes: state.escape.clone(),
+    };
+    streaming_parser(&separators, &escape, &state.newlines)
+}
+
+/// While a parser looks for a `sep=` first line, add `chunk` (decoded, when
+/// the parser has an input encoding) to the input held back. Once the first
+/// line is known, switch the parser to the separator a directive names and
+/// feed it the held input after that line, keeping stream offsets. `at_end`
+/// says no more input follows.
+fn hold_for_directive(
+    inner: &mut StreamingParserEnum,
+    state: &mut SepDirectiveState,
+    decoder: Option<&mut Decoder>,
+    chunk: &[u8],
+    at_end: bool,
+) -> Result<(), ReadError> {
+    let Some(held) = state.held.as_mut() else {
+        return Ok(());
+    };
+    match decoder {
+        Some(decoder) => decoder
+            .decode_extend(held, chunk)
+            .map_err(ReadError::Decode)?,
+        None => held.extend_from_slice(chunk),
+    }
+    let skip = match find_sep_directive(held, at_end) {
+        SepDirective::Undecided => return Ok(()),
+        SepDirective::Absent => 0,
+        SepDirective::Found { separator, len } => {
+            inner.reconfigure(directive_parser(state, Some(separator)));
+            inner.resume_at(Checkpoint {
+                offset: len as u64,
+                rows: 0,
+            });
+            len
         }
-    } else {
-        ResourceArc::new(StreamingParserResource::with_general(
-            separators.patterns,
-            escape.bytes,
-        ))
-    })
+    };
+    let held = state.held.take().unwrap_or_default();
+    // Copied into the buffer, so rows are not cut as spans of a fed chunk
+    let rest = &held[skip..];
+    if inner.feed_from(&mut &*rest, rest.len())? < rest.len() {
+        return Err(ReadError::BufferOverflow);
+    }
+    Ok(())
+}
+
+/// At the end of the input, decide on a `sep=` first line still looked for
+fn finish_directive(
+    inner: &mut StreamingParserEnum,
+    directive: &mut Option<SepDirectiveState>,
+    decoder: Option<&mut Decoder>,
+) -> NifResult<()> {
+    match directive.as_mut().filter(|state| state.is_pending()) {
+        Some(state) => hold_for_directive(inner, state, decoder, &[], true).map_err(read_error),
+        None => Ok(()),
+    }
+}
+
+/// Create a streaming parser that resumes a parse from a checkpoint. `config`
+/// is `{separator, escape, newlines}`; `checkpoint` is `{offset, rows}` as
+/// returned by `streaming_checkpoint`. The caller feeds input from `offset`.
+/// A `sep=` first line is only looked for from offset 0: past it, pass the
+/// separator it named.
+#[rustler::nif]
+fn streaming_new_at<'a>(config: Term<'a>, checkpoint: (u64, u64)) -> NifResult<StreamingParserRef> {
+    let (sep_term, esc_term, newlines_term): (Term<'a>, Term<'a>, Term<'a>) = config.decode()?;
+    let (mut inner, directive) = decode_streaming_parser(sep_term, esc_term, newlines_term)?;
+    let (offset, rows) = checkpoint;
+    inner.resume_at(Checkpoint { offset, rows });
+    let directive = directive.filter(|_| offset == 0);
+    Ok(ResourceArc::new(
+        StreamingParserResource::from_parser(inner).with_directive(directive),
+    ))
+}
+
+/// Get `{offset, rows}`: the absolute byte offset just past the last row
+/// handed out by `streaming_next_rows`/`streaming_finalize`, and the number of
+/// rows handed out (header rows included). Offsets survive buffer compaction.
+/// Offsets count UTF-8 bytes, so a parser decoding another encoding (see
+/// `streaming_set_encoding`) raises `{:encoded_checkpoint, encoding}` rather
+/// than report an offset that doesn't seek back to its place in the input.
+#[rustler::nif]
+fn streaming_checkpoint(parser: StreamingParserRef) -> NifResult<(u64, u64)> {
+    let inner = lock_parser(&parser)?;
+    if let Some(decoder) = lock_decoder(&parser)?.as_ref() {
+        return Err(encoded_checkpoint(decoder.source()));
+    }
+    let checkpoint = inner.checkpoint();
+    Ok((checkpoint.offset, checkpoint.rows))
+}
+
+/// Reset a streaming parser for reuse on a new input: buffered data and
+/// pending rows are discarded, configuration and buffer capacity are kept.
+/// In map mode the next row is treated as the header row again, and a
+/// separator picked by a `sep=` line goes back to the configured one.
+#[rustler::nif]
+fn streaming_reset(parser: StreamingParserRef) -> NifResult<Atom> {
+    let mut inner = lock_parser(&parser)?;
+    inner.reset();
+    if let Some(state) = lock_directive(&parser)?.as_mut() {
+        inner.reconfigure(directive_parser(state, None));
+        state.reset();
+    }
+    if let Some(decoder) = lock_decoder(&parser)?.as_mut() {
+        decoder.reset();
+    }
+    if let Some(headers) = lock_headers(&parser)?.as_mut() {
+        headers.reset();
+    }
+    lock_chunks(&parser)?.clear();
+    Ok(atoms::ok())
+}
+
+/// Reset a streaming parser and switch it to a new `{separator, escape,
+/// newlines}` config (same formats as `streaming_new_with_config`). The
+/// buffer allocation, maximum buffer size and map mode are kept.
+#[rustler::nif]
+fn streaming_reconfigure<'a>(parser: StreamingParserRef, config: Term<'a>) -> NifResult<Atom> {
+    let (sep_term, esc_term, newlines_term): (Term<'a>, Term<'a>, Term<'a>) = config.decode()?;
+    let (next, directive) = decode_streaming_parser(sep_term, esc_term, newlines_term)?;
+    let mut inner = lock_parser(&parser)?;
+    inner.reconfigure(next);
+    *lock_directive(&parser)? = directive;
+    if let Some(decoder) = lock_decoder(&parser)?.as_mut() {
+        decoder.reset();
+    }
+    if let Some(headers) = lock_headers(&parser)?.as_mut() {
+        headers.reset();
+    }
+    lock_chunks(&parser)?.clear();
+    Ok(atoms::ok())
+}
+
+/// Feed a chunk of data to the streaming parser. The single-byte parser
+/// keeps the chunk so rows inside it are returned as sub-binaries.
+#[rustler::nif(schedule = "DirtyCpu")]
+fn streaming_feed<'a>(
+    env: Env<'a>,
+    parser: StreamingParserRef,
+    chunk: Binary<'a>,
+) -> NifResult<Term<'a>> {
+    let mut inner = lock_parser(&parser)?;
+    feed_chunk(env, &parser, &mut inner, chunk)?;
+    check_row_width(&inner)?;
+    Ok(feed_status(env, &inner))
 }
 
-/// Feed a chunk of data to the streaming parser
+/// Feed a chunk (or a list of chunks, in order) and take up to `max` rows in
+/// one call. Returns `{rows, status}`, the status (as for `streaming_feed`)
+/// being what is left after taking.
 #[rustler::nif(schedule = "DirtyCpu")]
-fn streaming_feed(parser: StreamingParserRef, chunk: Binary) -> NifResult<(usize, usize)> {
+fn streaming_feed_take<'a>(
+    env: Env<'a>,
+    parser: StreamingParserRef,
+    chunks: Term<'a>,
+    max: usize,
+) -> NifResult<(Term<'a>, Term<'a>)> {
+    let chunks: Vec<Binary<'a>> = match chunks.decode::<Binary<'a>>() {
+        Ok(chunk) => vec![chunk],
+        Err(_) => chunks.decode()?,
+    };
     let mut inner = lock_parser(&parser)?;
+    for chunk in chunks {
+        feed_chunk(env, &parser, &mut inner, chunk)?;
+    }
+    let mut headers = lock_headers(&parser)?;
+    let pending = headers.as_ref().map_or(0, StreamingHeaders::pending_rows);
+    let rows = inner.take_rows(max.saturating_add(pending));
+    let rows = build_streamed_rows(env, &parser, &inner, headers.as_mut(), rows)?;
+    Ok((rows, feed_status(env, &inner)))
+}
+
+/// `{available_rows, buffer_size}`, or `{:drain, available_rows, buffer_size}`
+/// when the pending row limit left bytes unscanned
+fn feed_status<'a>(env: Env<'a>, inner: &StreamingParserEnum) -> Term<'a> {
+    let (available, buffer_size) = (inner.available_rows(), inner.buffer_size());
+    if inner.needs_drain() {
+        (atoms::drain(), available, buffer_size).encode(env)
+    } else {
+        (available, buffer_size).encode(env)
+    }
+}
+
+/// Feed one chunk to a locked parser, keeping it for zero-copy rows (or
+/// decoding it, when the parser has an input encoding)
+fn feed_chunk<'a>(
+    env: Env<'a>,
+    parser: &StreamingParserResource,
+    inner: &mut StreamingParserEnum,
+    chunk: Binary<'a>,
+) -> NifResult<()> {
+    if let Some(state) = lock_directive(parser)?
+        .as_mut()
+        .filter(|state| state.is_pending())
+    {
+        let mut decoder = lock_decoder(parser)?;
+        return hold_for_directive(inner, state, decoder.as_mut(), chunk.as_slice(), false)
+            .map_err(read_error);
+    }
+    if let Some(decoder) = lock_decoder(parser)?.as_mut() {
+        return inner
+            .feed_decoded(chunk.as_slice(), decoder)
+            .map_err(read_error);
+    }
+    let chunk_start = inner.zero_copy_offset();
     inner
         .feed(chunk.as_slice())
         .map_err(|_| Error::RaiseTerm(Box::new(atoms::buffer_overflow())))?;
-    Ok((inner.available_rows(), inner.buffer_size()))
+    if let Some(start) = chunk_start {
+        lock_chunks(parser)?.push(env, start, chunk);
+    }
+    Ok(())
 }
 
 /// Take up to `max` rows from the streaming parser
@@ -401,16 +800,425 @@ fn streaming_next_rows<'a>(
     max: usize,
 ) -> NifResult<Term<'a>> {
     let mut inner = lock_parser(&parser)?;
-    let rows = inner.take_rows(max);
-    Ok(owned_rows_to_term(env, rows))
+    let mut headers = lock_headers(&parser)?;
+    // The header row doesn't count towards `max`
+    let pending = headers.as_ref().map_or(0, StreamingHeaders::pending_rows);
+    let rows = inner.take_rows(max.saturating_add(pending));
+    build_streamed_rows(env, &parser, &inner, headers.as_mut(), rows)
 }
 
 /// Finalize the streaming parser (get remaining partial row)
 #[rustler::nif(schedule = "DirtyCpu")]
 fn streaming_finalize<'a>(env: Env<'a>, parser: StreamingParserRef) -> NifResult<Term<'a>> {
     let mut inner = lock_parser(&parser)?;
+    let mut directive = lock_directive(&parser)?;
+    let mut decoder = lock_decoder(&parser)?;
+    finish_directive(&mut inner, &mut directive, decoder.as_mut())?;
+    finish_decoding(&mut inner, &mut decoder)?;
+    let mut headers = lock_headers(&parser)?;
     let rows = inner.finalize();
-    Ok(owned_rows_to_term(env, rows))
+    build_streamed_rows(env, &parser, &inner, headers.as_mut(), rows)
+}
+
+/// Bytes `streaming_open_file` parsers read at a time unless `:chunk_size` is given
+const DEFAULT_FILE_CHUNK: usize = 64 * 1024;
+
+/// Open `path` for a streaming parser that reads the file itself (see
+/// `streaming_read_rows`), so chunks never pass through the BEAM. `opts` is a
+/// keyword list: `:separator`, `:escape` and `:newlines` as for
+/// `streaming_new_with_config`, `:chunk_size`, and `:resume`, a `{offset,
+/// rows}` checkpoint to seek to. Returns `{:ok, parser}` or `{:error, reason}`.
+#[rustler::nif(schedule = "DirtyIo")]
+fn streaming_open_file<'a>(
+    env: Env<'a>,
+    path: String,
+    opts: Vec<(Atom, Term<'a>)>,
+) -> NifResult<Term<'a>> {
+    let option = |key: Atom| {
+        opts.iter()
+            .find(|(k, _)| *k == key)
+            .map(|(_, value)| *value)
+    };
+    let (mut inner, mut directive) = decode_streaming_parser(
+        option(atoms::separator()).unwrap_or_else(|| b','.encode(env)),
+        option(atoms::escape()).unwrap_or_else(|| b'"'.encode(env)),
+        option(atoms::newlines()).unwrap_or_else(|| atoms::default().encode(env)),
+    )?;
+    let chunk_size = match option(atoms::chunk_size()) {
+        Some(term) => term.decode::<usize>()?,
+        None => DEFAULT_FILE_CHUNK,
+    };
+    if chunk_size == 0 {
+        return Err(Error::BadArg);
+    }
+    let checkpoint: Option<(u64, u64)> = match option(atoms::resume()) {
+        Some(term) => term.decode()?,
+        None => None,
+    };
+
+    let mut file = match std::fs::File::open(&path) {
+        Ok(file) => file,
+        Err(error) => return Ok((atoms::error(), io_error_reason(&error)).encode(env)),
+    };
+    if let Some((offset, rows)) = checkpoint {
+        use std::io::{Read, Seek, SeekFrom};
+        // Resuming past a `sep=` line: read it from the start of the file
+        let state = if offset > 0 { directive.take() } else { None };
+        if let Some(state) = state {
+            let mut head = Vec::new();
+            if let Err(error) = (&mut file).take(MAX_LINE as u64).read_to_end(&mut head) {
+                return Ok((atoms::error(), io_error_reason(&error)).encode(env));
+            }
+            if let SepDirective::Found { separator, .. } = find_sep_directive(&head, true) {
+                inner = directive_parser(&state, Some(separator));
+            }
+        }
+        if let Err(error) = file.seek(SeekFrom::Start(offset)) {
+            return Ok((atoms::error(), io_error_reason(&error)).encode(env));
+        }
+        inner.resume_at(Checkpoint { offset, rows });
+    }
+    let source = FileSource {
+        file: Some(file),
+        chunk_size,
+    };
+    let parser = ResourceArc::new(
+        StreamingParserResource::with_source(inner, source).with_directive(directive),
+    );
+    Ok((atoms::ok(), parser).encode(env))
+}
+
+/// Take up to `max` rows from a parser opened by `streaming_open_file`,
+/// reading the file into the parser buffer until that many rows are complete.
+/// At the end of the file the last partial row is included and the file is
+/// closed; `[]` means every row has been returned. Raises `{:read_error,
+/// reason}` if a read fails.
+#[rustler::nif(schedule = "DirtyIo")]
+fn streaming_read_rows<'a>(
+    env: Env<'a>,
+    parser: StreamingParserRef,
+    max: usize,
+) -> NifResult<Term<'a>> {
+    let mut source = lock_source(&parser)?;
+    let source = source.as_mut().ok_or(Error::BadArg)?;
+    let mut inner = lock_parser(&parser)?;
+    let mut directive = lock_directive(&parser)?;
+    let mut decoder = lock_decoder(&parser)?;
+    let mut headers = lock_headers(&parser)?;
+    // The header row doesn't count towards `max`
+    let pending = headers.as_ref().map_or(0, StreamingHeaders::pending_rows);
+    let want = max.saturating_add(pending);
+
+    while inner.available_rows() < want && !inner.needs_drain() && inner.width_error().is_none() {
+        let Some(file) = source.file.as_mut() else {
+            break;
+        };
+        let held = directive.as_mut().filter(|state| state.is_pending());
+        let read = match (held, decoder.as_mut()) {
+            (Some(state), decoder) => {
+                read_held(&mut inner, file, source.chunk_size, state, decoder)
+            }
+            (None, Some(decoder)) => read_decoded(&mut inner, file, source.chunk_size, decoder),
+            (None, None) => inner.feed_from(file, source.chunk_size),
+        };
+        match read {
+            Ok(0) => source.file = None,
+            Ok(_) => {}
+            // Taking the complete rows frees buffer space for the next read
+            Err(ReadError::BufferOverflow) if inner.available_rows() > 0 => break,
+            Err(error) => return Err(read_error(error)),
+        }
+    }
+
+    let at_end = source.file.is_none() && inner.available_rows() < want && !inner.needs_drain();
+    let rows = if at_end {
+        finish_directive(&mut inner, &mut directive, decoder.as_mut())?;
+        finish_decoding(&mut inner, &mut decoder)?;
+        inner.finalize()
+    } else {
+        inner.take_rows(want)
+    };
+    build_streamed_rows(env, &parser, &inner, headers.as_mut(), rows)
+}
+
+/// Close the file of a parser opened by `streaming_open_file`. Rows already
+/// read can still be taken: `streaming_read_rows` returns them as it would at
+/// the end of the file.
+#[rustler::nif]
+fn streaming_close_file(parser: StreamingParserRef) -> NifResult<Atom> {
+    if let Some(source) = lock_source(&parser)?.as_mut() {
+        source.file = None;
+    }
+    Ok(atoms::ok())
+}
+
+/// Read up to `len` bytes of encoded input and decode them into the parser
+/// buffer. Returns the number of bytes read, 0 at end of input.
+fn read_decoded(
+    inner: &mut StreamingParserEnum,
+    file: &mut std::fs::File,
+    len: usize,
+    decoder: &mut Decoder,
+) -> Result<usize, ReadError> {
+    use std::io::Read;
+    let mut chunk = Vec::with_capacity(len);
+    file.take(len as u64)
+        .read_to_end(&mut chunk)
+        .map_err(ReadError::Io)?;
+    inner.feed_decoded(&chunk, decoder)?;
+    Ok(chunk.len())
+}
+
+/// Read up to `len` bytes while the parser looks for a `sep=` first line
+/// (see `hold_for_directive`). Returns the number of bytes read, 0 at end of
+/// input.
+fn read_held(
+    inner: &mut StreamingParserEnum,
+    file: &mut std::fs::File,
+    len: usize,
+    state: &mut SepDirectiveState,
+    decoder: Option<&mut Decoder>,
+) -> Result<usize, ReadError> {
+    use std::io::Read;
+    let mut chunk = Vec::with_capacity(len);
+    file.take(len as u64)
+        .read_to_end(&mut chunk)
+        .map_err(ReadError::Io)?;
+    hold_for_directive(inner, state, decoder, &chunk, chunk.is_empty())?;
+    Ok(chunk.len())
+}
+
+/// Raise term for input that could not be read or decoded into the parser
+/// buffer: `:buffer_overflow`, `{:read_error, reason}`, or
+/// `{:invalid_encoding | :incomplete_encoding, encoding, offset}`
+fn read_error(error: ReadError) -> Error {
+    match error {
+        ReadError::BufferOverflow => Error::RaiseTerm(Box::new(atoms::buffer_overflow())),
+        ReadError::Io(error) => {
+            Error::RaiseTerm(Box::new((atoms::read_error(), io_error_reason(&error))))
+        }
+        ReadError::Decode(error) => decode_error(error),
+    }
+}
+
+/// Raise `{:invalid_encoding, encoding, offset}`, or `{:incomplete_encoding,
+/// encoding, offset}` when the input ended part-way through a sequence
+fn decode_error(error: DecodeError) -> Error {
+    let kind = if error.incomplete {
+        atoms::incomplete_encoding()
+    } else {
+        atoms::invalid_encoding()
+    };
+    Error::RaiseTerm(Box::new((kind, error.source, error.offset)))
+}
+
+/// At the end of the input, decode bytes held back while looking for a BOM,
+/// then check the decoder isn't holding part of a sequence
+fn finish_decoding(
+    inner: &mut StreamingParserEnum,
+    decoder: &mut Option<Decoder>,
+) -> NifResult<()> {
+    let Some(decoder) = decoder.as_mut() else {
+        return Ok(());
+    };
+    if decoder.end_bom_sniffing() {
+        inner.feed_decoded(&[], decoder).map_err(read_error)?;
+    }
+    decoder.finish().map_err(decode_error)
+}
+
+/// Decode the input of a streaming parser from `encoding` (as for
+/// `decode_to_utf8`) to UTF-8 as it is fed, straight into the parser buffer.
+/// Sequences split across chunks are carried over; `:utf8` turns decoding
+/// off. Rows of decoded input are copied, not sub-binaries of the chunks.
+/// Checkpoints don't map back to encoded input, so a parser resumed from
+/// one (`streaming_new_at`, `streaming_open_file` with `:resume`) raises
+/// `{:encoded_checkpoint, encoding}` for any encoding but `:utf8`.
+#[rustler::nif]
+fn streaming_set_encoding<'a>(parser: StreamingParserRef, encoding: Term<'a>) -> NifResult<Atom> {
+    let decoder = match decode_source_encoding(encoding)? {
+        (source, true) => Some(Decoder::auto(source)),
+        (EncodingTarget::Utf8, false) => None,
+        (source, false) => Some(Decoder::new(source)),
+    };
+    let checkpoint = lock_parser(&parser)?.checkpoint();
+    if let Some(decoder) = &decoder {
+        if checkpoint.offset > 0 || checkpoint.rows > 0 {
+            return Err(encoded_checkpoint(decoder.source()));
+        }
+    }
+    *lock_decoder(&parser)? = decoder;
+    Ok(atoms::ok())
+}
+
+fn encoded_checkpoint(source: EncodingTarget) -> Error {
+    Error::RaiseTerm(Box::new((atoms::encoded_checkpoint(), source)))
+}
+
+/// POSIX reason atom for a file error, as `:file` functions report them
+fn io_error_reason(error: &std::io::Error) -> Atom {
+    match error.kind() {
+        std::io::ErrorKind::NotFound => atoms::enoent(),
+        std::io::ErrorKind::PermissionDenied => atoms::eacces(),
+        std::io::ErrorKind::IsADirectory => atoms::eisdir(),
+        _ => atoms::eio(),
+    }
+}
+
+/// Build lists (or maps, in map mode) from taken rows, then release the fed
+/// chunks no pending row can refer to any more.
+fn build_streamed_rows<'a>(
+    env: Env<'a>,
+    parser: &StreamingParserResource,
+    inner: &StreamingParserEnum,
+    headers: Option<&mut StreamingHeaders>,
+    rows: Vec<Vec<StreamField>>,
+) -> NifResult<Term<'a>> {
+    if rows.is_empty() {
+        check_row_width(inner)?;
+    }
+    let mut chunks = lock_chunks(parser)?;
+    let loaded = chunks.load(env)?;
+    let result = match headers {
+        None => stream_rows_to_term(env, &loaded, rows),
+        Some(h) => streamed_rows_to_maps(env, h, &loaded, rows),
+    };
+    chunks.release_before(inner.checkpoint().offset);
+    result
+}
+
+/// Raise `{:row_width, row, offset, expected, actual}` for a row rejected by
+/// the width check, once the rows before it have all been taken.
+fn check_row_width(inner: &StreamingParserEnum) -> NifResult<()> {
+    match inner.width_error() {
+        Some(error) if inner.available_rows() == 0 => Err(Error::RaiseTerm(Box::new((
+            atoms::row_width(),
+            error.row,
+            error.offset,
+            error.expected,
+            error.actual,
+        )))),
+        _ => Ok(()),
+    }
+}
+
+/// Put a streaming parser in header-aware mode: the first row is kept as the
+/// header (see `streaming_headers`) and every later row's field count is
+/// checked against it as rows are cut. `policy` is `:error`, `:pad`,
+/// `:truncate`, a list of `:pad`/`:truncate`, or `nil` to turn the mode off.
+#[rustler::nif]
+fn streaming_set_width_policy<'a>(parser: StreamingParserRef, policy: Term<'a>) -> NifResult<Atom> {
+    let policy = decode_width_policy(policy)?;
+    lock_parser(&parser)?.set_width_policy(policy);
+    Ok(atoms::ok())
+}
+
+/// Decode width policy: nil | :error | :pad | :truncate | [:pad | :truncate]
+fn decode_width_policy(term: Term) -> NifResult<Option<WidthPolicy>> {
+    let fixes: Vec<Atom> = match term.atom_to_string().as_deref() {
+        Ok("nil") => return Ok(None),
+        Ok("error") => Vec::new(),
+        Ok(_) => vec![term.decode()?],
+        Err(_) => term.decode()?,
+    };
+    let mut policy = WidthPolicy::default();
+    for fix in fixes {
+        if fix == atoms::pad() {
+            policy.pad = true;
+        } else if fix == atoms::truncate() {
+            policy.truncate = true;
+        } else {
+            return Err(Error::BadArg);
+        }
+    }
+    Ok(Some(policy))
+}
+
+/// Get the header row of a parser in header-aware mode (`nil` until it has
+/// been parsed)
+#[rustler::nif]
+fn streaming_headers<'a>(env: Env<'a>, parser: StreamingParserRef) -> NifResult<Term<'a>> {
+    let inner = lock_parser(&parser)?;
+    Ok(match inner.header() {
+        Some(header) => header
+            .iter()
+            .map(|field| copy_to_binary(env, field))
+            .collect::<Vec<_>>()
+            .encode(env),
+        None => rustler::types::atom::nil().encode(env),
+    })
+}
+
+/// Switch a streaming parser to map output. `header_mode` is `true` (first
+/// row = keys) or a list of binary/atom keys; `skip_first` drops the first
+/// row when explicit keys are given. `column_policy` is
+/// `{extra_columns, missing_columns}` as for `parse_to_maps`.
+#[rustler::nif]
+fn streaming_set_headers<'a>(
+    parser: StreamingParserRef,
+    header_mode_term: Term<'a>,
+    skip_first: bool,
+    duplicate_headers_term: Term<'a>,
+    column_policy_term: Term<'a>,
+) -> NifResult<Atom> {
+    let duplicate_headers = decode_duplicate_headers(duplicate_headers_term)?;
+    let column_policy = decode_column_policy(column_policy_term)?;
+    let (keys, skip_first) = match decode_header_mode(header_mode_term)? {
+        HeaderMode::Auto => (None, false),
+        HeaderMode::Explicit(key_terms) => {
+            let keys = key_terms
+                .into_iter()
+                .map(HeaderKey::from_term)
+                .collect::<NifResult<_>>()?;
+            (Some(keys), skip_first)
+        }
+    };
+    let extra_columns = match column_policy.extra {
+        ExtraColumns::Ignore => ExtraColumns::Ignore,
+        ExtraColumns::Error => ExtraColumns::Error,
+        ExtraColumns::Collect(key) => ExtraColumns::Collect(HeaderKey::from_term(key)?),
+    };
+    // Error row numbers continue from a resumed parser's checkpoint
+    let rows_taken = lock_parser(&parser)?.checkpoint().rows as usize;
+    *lock_headers(&parser)? = Some(StreamingHeaders {
+        keys_from_header: keys.is_none(),
+        keys,
+        skip_first,
+        skip_header: skip_first,
+        duplicate_headers,
+        extra_columns,
+        missing_columns: column_policy.missing,
+        rows_taken,
+    });
+    Ok(atoms::ok())
+}
+
+/// Convert streamed rows to maps, consuming the header row first if pending.
+/// Keys are interned once per call and shared by every map in the batch.
+fn streamed_rows_to_maps<'a>(
+    env: Env<'a>,
+    headers: &mut StreamingHeaders,
+    chunks: &LoadedChunks<'a>,
+    mut rows: Vec<Vec<StreamField>>,
+) -> NifResult<Term<'a>> {
+    headers.consume_header(&mut rows, chunks)?;
+    let keys = match &headers.keys {
+        Some(keys) if !rows.is_empty() => keys,
+        _ => return Ok(Term::list_new_empty(env)),
+    };
+    let key_terms = keys
+        .iter()
+        .map(|key| key.to_term(env))
+        .collect::<NifResult<Vec<_>>>()?;
+    let column_policy = ColumnPolicy {
+        extra: headers.extra_columns.to_term(env)?,
+        missing: headers.missing_columns,
+    };
+    let map_keys = resolve_map_keys(env, key_terms, headers.duplicate_headers)?
+        .with_column_policy(column_policy)?;
+    let first_row = headers.rows_taken + 1;
+    headers.rows_taken += rows.len();
+    stream_rows_to_maps(env, &map_keys, first_row, chunks, rows)
 }
 
 /// Get streaming parser status (available_rows, buffer_size, has_partial)
@@ -424,6 +1232,34 @@ fn streaming_status(parser: StreamingParserRef) -> NifResult<(usize, usize, bool
     ))
 }
 
+/// Get cumulative streaming parser counters as a map (cleared by
+/// `streaming_reset/1` and `streaming_reconfigure/2`)
+#[rustler::nif]
+fn streaming_stats<'a>(env: Env<'a>, parser: StreamingParserRef) -> NifResult<Term<'a>> {
+    let stats = lock_parser(&parser)?.stats();
+    let keys = [
+        atoms::bytes_fed(),
+        atoms::bytes_consumed(),
+        atoms::rows_emitted(),
+        atoms::physical_lines(),
+        atoms::largest_row(),
+        atoms::peak_buffer(),
+        atoms::compactions(),
+    ]
+    .map(|key| key.encode(env));
+    let values = [
+        stats.bytes_fed,
+        stats.bytes_consumed,
+        stats.rows_emitted,
+        stats.physical_lines,
+        stats.largest_row,
+        stats.peak_buffer,
+        stats.compactions,
+    ]
+    .map(|value| value.encode(env));
+    Term::map_from_term_arrays(env, &keys, &values)
+}
+
 /// Set the maximum buffer size (in bytes) for the streaming parser.
 /// Default is 256 MB. Raises on overflow during `streaming_feed/2`.
 #[rustler::nif]
@@ -433,6 +1269,16 @@ fn streaming_set_max_buffer(parser: StreamingParserRef, max: usize) -> NifResult
     Ok(atoms::ok())
 }
 
+/// Limit the complete rows a streaming parser queues: once `max` rows are
+/// pending, feeding stops scanning and keeps the rest of the buffer until
+/// rows are taken.
+#[rustler::nif]
+fn streaming_set_max_pending_rows(parser: StreamingParserRef, max: usize) -> NifResult<Atom> {
+    let mut inner = lock_parser(&parser)?;
+    inner.set_max_pending_rows(max);
+    Ok(atoms::ok())
+}
+
 // ============================================================================
 // Strategy E: Parallel Parser
 // ============================================================================
@@ -444,6 +1290,15 @@ fn dispatch_parallel_boundary_parse(
     escape: &Escape,
     newlines: &Newlines,
 ) -> Vec<Vec<(usize, usize)>> {
+    if let Some(terminator) = simd_terminator(separators, escape, newlines) {
+        let sep_bytes = single_byte_seps(separators);
+        return parse_csv_parallel_boundaries_with_terminator(
+            bytes,
+            &sep_bytes,
+            escape.bytes[0],
+            terminator,
+        );
+    }
     if !newlines.is_default {
         return parse_csv_parallel_boundaries_general_with_newlines(
             bytes,
@@ -452,17 +1307,7 @@ fn dispatch_parallel_boundary_parse(
             newlines,
         );
     }
-    if is_all_single_byte(separators, escape) {
-        let esc = escape.bytes[0];
-        let sep_bytes = single_byte_seps(separators);
-        if sep_bytes.len() == 1 {
-            parse_csv_parallel_boundaries_with_config(bytes, sep_bytes[0], esc)
-        } else {
-            parse_csv_parallel_boundaries_multi_sep(bytes, &sep_bytes, esc)
-        }
-    } else {
-        parse_csv_parallel_boundaries_general(bytes, &separators.patterns, &escape.bytes)
-    }
+    parse_csv_parallel_boundaries_general(bytes, &separators.patterns, &escape.bytes)
 }
 
 /// Parse CSV in parallel using rayon thread pool (boundary-based sub-binaries)
@@ -482,7 +1327,7 @@ fn parse_string_parallel_with_config<'a>(
     esc_term: Term<'a>,
     newlines_term: Term<'a>,
 ) -> NifResult<Term<'a>> {
-    let separators = decode_separators(sep_term)?;
+    let (input, separators) = decode_input_separators(input, sep_term)?;
     let escape = decode_escape(esc_term)?;
     let newlines = decode_newlines(newlines_term)?;
     let boundaries =
@@ -511,44 +1356,11 @@ fn parse_string_zero_copy_with_config<'a>(
     esc_term: Term<'a>,
     newlines_term: Term<'a>,
 ) -> NifResult<Term<'a>> {
-    let separators = decode_separators(sep_term)?;
+    let (input, separators) = decode_input_separators(input, sep_term)?;
     let escape = decode_escape(esc_term)?;
     let newlines = decode_newlines(newlines_term)?;
-    let bytes = input.as_slice();
-
-    if !newlines.is_default {
-        let boundaries = parse_csv_boundaries_general_with_newlines(
-            bytes,
-            &separators.patterns,
-            &escape.bytes,
-            &newlines,
-        );
-        return Ok(boundaries_to_term_hybrid_general(
-            env,
-            input,
-            boundaries,
-            &escape.bytes,
-        ));
-    }
-
-    if is_all_single_byte(&separators, &escape) {
-        let esc = escape.bytes[0];
-        let sep_bytes = single_byte_seps(&separators);
-        let boundaries = if sep_bytes.len() == 1 {
-            parse_csv_boundaries_with_config(bytes, sep_bytes[0], esc)
-        } else {
-            parse_csv_boundaries_multi_sep(bytes, &sep_bytes, esc)
-        };
-        Ok(boundaries_to_term_hybrid(env, input, boundaries, esc))
-    } else {
-        let boundaries = parse_csv_boundaries_general(bytes, &separators.patterns, &escape.bytes);
-        Ok(boundaries_to_term_hybrid_general(
-            env,
-            input,
-            boundaries,
-            &escape.bytes,
-        ))
-    }
+    let boundaries = dispatch_boundary_parse(input.as_slice(), &separators, &escape, &newlines);
+    Ok(dispatch_boundaries_to_term(env, input, boundaries, &escape))
 }
 
 // ============================================================================
@@ -581,6 +1393,85 @@ fn decode_header_mode<'a>(header_mode: Term<'a>) -> NifResult<HeaderMode<'a>> {
     Err(Error::BadArg)
 }
 
+/// Decode duplicate_headers atom: :last | :first | :error | :suffix | :list
+fn decode_duplicate_headers(term: Term) -> NifResult<DuplicateHeaders> {
+    let s = term.atom_to_string().map_err(|_| Error::BadArg)?;
+    match s.as_str() {
+        "last" => Ok(DuplicateHeaders::Last),
+        "first" => Ok(DuplicateHeaders::First),
+        "error" => Ok(DuplicateHeaders::Error),
+        "suffix" => Ok(DuplicateHeaders::Suffix),
+        "list" => Ok(DuplicateHeaders::List),
+        _ => Err(Error::BadArg),
+    }
+}
+
+/// Decode extra_columns: :ignore | :error | {:collect, key}
+fn decode_extra_columns<'a>(term: Term<'a>) -> NifResult<ExtraColumns<Term<'a>>> {
+    if let Ok((tag, key)) = term.decode::<(Term<'a>, Term<'a>)>() {
+        if tag.atom_to_string()? == "collect" {
+            return Ok(ExtraColumns::Collect(key));
+        }
+        return Err(Error::BadArg);
+    }
+    let s = term.atom_to_string().map_err(|_| Error::BadArg)?;
+    match s.as_str() {
+        "ignore" => Ok(ExtraColumns::Ignore),
+        "error" => Ok(ExtraColumns::Error),
+        _ => Err(Error::BadArg),
+    }
+}
+
+/// Decode missing_columns atom: :nil | :omit | :error
+fn decode_missing_columns(term: Term) -> NifResult<MissingColumns> {
+    let s = term.atom_to_string().map_err(|_| Error::BadArg)?;
+    match s.as_str() {
+        "nil" => Ok(MissingColumns::Nil),
+        "omit" => Ok(MissingColumns::Omit),
+        "error" => Ok(MissingColumns::Error),
+        _ => Err(Error::BadArg),
+    }
+}
+
+/// Decode column policy tuple: {extra_columns, missing_columns}
+fn decode_column_policy<'a>(term: Term<'a>) -> NifResult<ColumnPolicy<'a>> {
+    let (extra, missing) = term.decode::<(Term<'a>, Term<'a>)>()?;
+    Ok(ColumnPolicy {
+        extra: decode_extra_columns(extra)?,
+        missing: decode_missing_columns(missing)?,
+    })
+}
+
+/// Decode row_format for the headers NIFs: :map | :keyword |
+/// {:struct, module, [{field, default}], required_fields}
+fn decode_row_shape<'a>(term: Term<'a>) -> NifResult<RowShape<'a>> {
+    if let Ok((tag, module, fields, required)) =
+        term.decode::<(Term<'a>, Atom, Vec<(Atom, Term<'a>)>, Vec<Atom>)>()
+    {
+        if tag.atom_to_string()? != "struct" {
+            return Err(Error::BadArg);
+        }
+        let env = term.get_env();
+        return Ok(RowShape::Struct(StructSpec {
+            module: module.encode(env),
+            fields: fields
+                .into_iter()
+                .map(|(field, default)| (field.encode(env), default))
+                .collect(),
+            required: required
+                .into_iter()
+                .map(|field| field.encode(env))
+                .collect(),
+        }));
+    }
+    let s = term.atom_to_string().map_err(|_| Error::BadArg)?;
+    match s.as_str() {
+        "map" => Ok(RowShape::Map),
+        "keyword" => Ok(RowShape::Keyword),
+        _ => Err(Error::BadArg),
+    }
+}
+
 /// Dispatch to boundary-returning parser based on separator/escape/newlines config
 fn dispatch_boundary_parse(
     bytes: &[u8],
@@ -588,6 +1479,15 @@ fn dispatch_boundary_parse(
     escape: &Escape,
     newlines: &Newlines,
 ) -> Vec<Vec<(usize, usize)>> {
+    if let Some(terminator) = simd_terminator(separators, escape, newlines) {
+        let sep_bytes = single_byte_seps(separators);
+        return parse_csv_boundaries_with_terminator(
+            bytes,
+            &sep_bytes,
+            escape.bytes[0],
+            terminator,
+        );
+    }
     if !newlines.is_default {
         return parse_csv_boundaries_general_with_newlines(
             bytes,
@@ -596,17 +1496,7 @@ fn dispatch_boundary_parse(
             newlines,
         );
     }
-    if is_all_single_byte(separators, escape) {
-        let esc = escape.bytes[0];
-        let sep_bytes = single_byte_seps(separators);
-        if sep_bytes.len() == 1 {
-            parse_csv_boundaries_with_config(bytes, sep_bytes[0], esc)
-        } else {
-            parse_csv_boundaries_multi_sep(bytes, &sep_bytes, esc)
-        }
-    } else {
-        parse_csv_boundaries_general(bytes, &separators.patterns, &escape.bytes)
-    }
+    parse_csv_boundaries_general(bytes, &separators.patterns, &escape.bytes)
 }
 
 /// Dispatch between single-byte and general escape for term construction
@@ -625,16 +1515,58 @@ fn dispatch_boundaries_to_term<'a>(
 
 /// Dispatch between single-byte and general escape for maps construction
 fn dispatch_boundaries_to_maps<'a>(
+    env: Env<'a>,
+    input: Binary<'a>,
+    keys: &MapKeys<'a>,
+    first_row: usize,
+    boundaries: &[Vec<(usize, usize)>],
+    escape: &Escape,
+) -> NifResult<Term<'a>> {
+    if escape.bytes.len() == 1 {
+        boundaries_to_maps_hybrid(env, input, keys, first_row, boundaries, escape.bytes[0])
+    } else {
+        boundaries_to_maps_hybrid_general(env, input, keys, first_row, boundaries, &escape.bytes)
+    }
+}
+
+/// Dispatch between single-byte and general escape for keyword construction
+fn dispatch_boundaries_to_keywords<'a>(
     env: Env<'a>,
     input: Binary<'a>,
     keys: &[Term<'a>],
+    column_policy: &ColumnPolicy<'a>,
+    first_row: usize,
+    boundaries: &[Vec<(usize, usize)>],
+    escape: &Escape,
+) -> NifResult<Term<'a>> {
+    if escape.bytes.len() == 1 {
+        let esc = escape.bytes[0];
+        boundaries_to_keywords_hybrid(env, input, keys, column_policy, first_row, boundaries, esc)
+    } else {
+        let esc = &escape.bytes;
+        boundaries_to_keywords_hybrid_general(
+            env,
+            input,
+            keys,
+            column_policy,
+            first_row,
+            boundaries,
+            esc,
+        )
+    }
+}
+
+/// Dispatch between single-byte and general escape for tuple construction
+fn dispatch_boundaries_to_tuples<'a>(
+    env: Env<'a>,
+    input: Binary<'a>,
     boundaries: &[Vec<(usize, usize)>],
     escape: &Escape,
 ) -> Term<'a> {
     if escape.bytes.len() == 1 {
-        boundaries_to_maps_hybrid(env, input, keys, boundaries, escape.bytes[0])
+        boundaries_to_tuples_hybrid(env, input, boundaries, escape.bytes[0])
     } else {
-        boundaries_to_maps_hybrid_general(env, input, keys, boundaries, &escape.bytes)
+        boundaries_to_tuples_hybrid_general(env, input, boundaries, &escape.bytes)
     }
 }
 
@@ -696,106 +1628,247 @@ fn boundary_row_to_key_terms<'a>(
     }
 }
 
-/// Parse CSV and return list of maps. Dispatches to strategy internally.
-#[allow(clippy::too_many_arguments)]
+/// Parse CSV and return list of maps. Dispatches to strategy internally.
+#[allow(clippy::too_many_arguments)]
+#[rustler::nif(schedule = "DirtyCpu")]
+fn parse_to_maps<'a>(
+    env: Env<'a>,
+    input: Binary<'a>,
+    sep_term: Term<'a>,
+    esc_term: Term<'a>,
+    newlines_term: Term<'a>,
+    strategy: Term<'a>,
+    header_mode_term: Term<'a>,
+    skip_first: bool,
+    duplicate_headers_term: Term<'a>,
+    row_format_term: Term<'a>,
+    column_policy_term: Term<'a>,
+) -> NifResult<Term<'a>> {
+    let (input, separators) = decode_input_separators(input, sep_term)?;
+    let escape = decode_escape(esc_term)?;
+    let newlines = decode_newlines(newlines_term)?;
+    let header_mode = decode_header_mode(header_mode_term)?;
+    let map_opts = MapOptions {
+        duplicate_headers: decode_duplicate_headers(duplicate_headers_term)?,
+        shape: decode_row_shape(row_format_term)?,
+        column_policy: decode_column_policy(column_policy_term)?,
+    };
+    let strategy_str = strategy.atom_to_string().map_err(|_| Error::BadArg)?;
+    let bytes = input.as_slice();
+
+    match strategy_str.as_str() {
+        "basic" | "simd" | "indexed" | "zero_copy" => {
+            let all_boundaries = dispatch_boundary_parse(bytes, &separators, &escape, &newlines);
+            boundaries_to_maps(
+                env,
+                input,
+                &all_boundaries,
+                &escape,
+                header_mode,
+                skip_first,
+                map_opts,
+            )
+        }
+        _ => Err(Error::BadArg),
+    }
+}
+
+/// Parallel variant for parse_to_maps on dirty CPU scheduler
+#[allow(clippy::too_many_arguments)]
+#[rustler::nif(schedule = "DirtyCpu")]
+fn parse_to_maps_parallel<'a>(
+    env: Env<'a>,
+    input: Binary<'a>,
+    sep_term: Term<'a>,
+    esc_term: Term<'a>,
+    newlines_term: Term<'a>,
+    header_mode_term: Term<'a>,
+    skip_first: bool,
+    duplicate_headers_term: Term<'a>,
+    row_format_term: Term<'a>,
+    column_policy_term: Term<'a>,
+) -> NifResult<Term<'a>> {
+    let (input, separators) = decode_input_separators(input, sep_term)?;
+    let escape = decode_escape(esc_term)?;
+    let newlines = decode_newlines(newlines_term)?;
+    let header_mode = decode_header_mode(header_mode_term)?;
+    let map_opts = MapOptions {
+        duplicate_headers: decode_duplicate_headers(duplicate_headers_term)?,
+        shape: decode_row_shape(row_format_term)?,
+        column_policy: decode_column_policy(column_policy_term)?,
+    };
+    let bytes = input.as_slice();
+
+    let all_boundaries = dispatch_parallel_boundary_parse(bytes, &separators, &escape, &newlines);
+    boundaries_to_maps(
+        env,
+        input,
+        &all_boundaries,
+        &escape,
+        header_mode,
+        skip_first,
+        map_opts,
+    )
+}
+
+/// Map-output options shared by the headers NIFs
+struct MapOptions<'a> {
+    duplicate_headers: DuplicateHeaders,
+    shape: RowShape<'a>,
+    column_policy: ColumnPolicy<'a>,
+}
+
+/// Resolve header keys and build maps (or keyword lists) from parsed
+/// boundaries (shared by `parse_to_maps` and `parse_to_maps_parallel`).
+fn boundaries_to_maps<'a>(
+    env: Env<'a>,
+    input: Binary<'a>,
+    all_boundaries: &[Vec<(usize, usize)>],
+    escape: &Escape,
+    header_mode: HeaderMode<'a>,
+    skip_first: bool,
+    map_opts: MapOptions<'a>,
+) -> NifResult<Term<'a>> {
+    if all_boundaries.is_empty() {
+        return Ok(Term::list_new_empty(env));
+    }
+
+    let (key_terms, start) = match header_mode {
+        HeaderMode::Auto => (
+            boundary_row_to_key_terms(env, &input, &all_boundaries[0], escape),
+            1,
+        ),
+        HeaderMode::Explicit(key_terms) => (key_terms, if skip_first { 1 } else { 0 }),
+    };
+    let rows = &all_boundaries[start..];
+    let first_row = start + 1;
+
+    match resolve_row_keys(env, key_terms, map_opts)? {
+        RowKeys::Keyword(keys, policy) => {
+            dispatch_boundaries_to_keywords(env, input, &keys, &policy, first_row, rows, escape)
+        }
+        RowKeys::Map(map_keys) => {
+            dispatch_boundaries_to_maps(env, input, &map_keys, first_row, rows, escape)
+        }
+    }
+}
+
+/// Header keys resolved for the row shape of a headers NIF
+enum RowKeys<'a> {
+    Keyword(Vec<Term<'a>>, ColumnPolicy<'a>),
+    Map(MapKeys<'a>),
+}
+
+/// Resolve header key terms for `map_opts.shape`: keyword keys, or map keys
+/// (projected onto a struct's fields for `into:`) carrying the column policy.
+fn resolve_row_keys<'a>(
+    env: Env<'a>,
+    key_terms: Vec<Term<'a>>,
+    map_opts: MapOptions<'a>,
+) -> NifResult<RowKeys<'a>> {
+    let map_keys = match map_opts.shape {
+        RowShape::Keyword => {
+            let keys = keyword_keys(env, key_terms)?;
+            return Ok(RowKeys::Keyword(keys, map_opts.column_policy));
+        }
+        RowShape::Map => resolve_map_keys(env, key_terms, map_opts.duplicate_headers)?,
+        RowShape::Struct(spec) => {
+            resolve_map_keys(env, key_terms, map_opts.duplicate_headers)?.into_struct(env, &spec)?
+        }
+    };
+    Ok(RowKeys::Map(
+        map_keys.with_column_policy(map_opts.column_policy)?,
+    ))
+}
+
+/// Resolve header keys and build maps (or keyword lists) from rows of field
+/// terms, as `boundaries_to_maps` does for boundaries. Auto header keys are
+/// copied, so they don't hold on to the input.
+fn field_rows_to_maps<'a>(
+    env: Env<'a>,
+    rows: &[Vec<Term<'a>>],
+    header_mode: HeaderMode<'a>,
+    skip_first: bool,
+    map_opts: MapOptions<'a>,
+) -> NifResult<Term<'a>> {
+    if rows.is_empty() {
+        return Ok(Term::list_new_empty(env));
+    }
+
+    let (key_terms, start) = match header_mode {
+        HeaderMode::Auto => {
+            let keys = rows[0]
+                .iter()
+                .map(|key| Ok(copy_to_binary(env, key.decode::<Binary>()?.as_slice())))
+                .collect::<NifResult<_>>()?;
+            (keys, 1)
+        }
+        HeaderMode::Explicit(key_terms) => (key_terms, if skip_first { 1 } else { 0 }),
+    };
+    let rows = &rows[start..];
+    let first_row = start + 1;
+
+    match resolve_row_keys(env, key_terms, map_opts)? {
+        RowKeys::Keyword(keys, policy) => rows_to_keywords(env, &keys, &policy, first_row, rows),
+        RowKeys::Map(map_keys) => rows_to_maps(env, &map_keys, first_row, rows),
+    }
+}
+
+// ============================================================================
+// Tuple Rows NIFs
+// ============================================================================
+
+/// Parse CSV and return list of tuples. Dispatches to strategy internally.
 #[rustler::nif(schedule = "DirtyCpu")]
-fn parse_to_maps<'a>(
+fn parse_to_tuples<'a>(
     env: Env<'a>,
     input: Binary<'a>,
     sep_term: Term<'a>,
     esc_term: Term<'a>,
     newlines_term: Term<'a>,
     strategy: Term<'a>,
-    header_mode_term: Term<'a>,
-    skip_first: bool,
 ) -> NifResult<Term<'a>> {
-    let separators = decode_separators(sep_term)?;
+    let (input, separators) = decode_input_separators(input, sep_term)?;
     let escape = decode_escape(esc_term)?;
     let newlines = decode_newlines(newlines_term)?;
-    let header_mode = decode_header_mode(header_mode_term)?;
     let strategy_str = strategy.atom_to_string().map_err(|_| Error::BadArg)?;
     let bytes = input.as_slice();
 
     match strategy_str.as_str() {
         "basic" | "simd" | "indexed" | "zero_copy" => {
             let all_boundaries = dispatch_boundary_parse(bytes, &separators, &escape, &newlines);
-            if all_boundaries.is_empty() {
-                return Ok(Term::list_new_empty(env));
-            }
-
-            match header_mode {
-                HeaderMode::Auto => {
-                    let key_terms =
-                        boundary_row_to_key_terms(env, &input, &all_boundaries[0], &escape);
-                    Ok(dispatch_boundaries_to_maps(
-                        env,
-                        input,
-                        &key_terms,
-                        &all_boundaries[1..],
-                        &escape,
-                    ))
-                }
-                HeaderMode::Explicit(key_terms) => {
-                    let start = if skip_first { 1 } else { 0 };
-                    Ok(dispatch_boundaries_to_maps(
-                        env,
-                        input,
-                        &key_terms,
-                        &all_boundaries[start..],
-                        &escape,
-                    ))
-                }
-            }
+            Ok(dispatch_boundaries_to_tuples(
+                env,
+                input,
+                &all_boundaries,
+                &escape,
+            ))
         }
         _ => Err(Error::BadArg),
     }
 }
 
-/// Parallel variant for parse_to_maps on dirty CPU scheduler
+/// Parallel variant for parse_to_tuples on dirty CPU scheduler
 #[rustler::nif(schedule = "DirtyCpu")]
-fn parse_to_maps_parallel<'a>(
+fn parse_to_tuples_parallel<'a>(
     env: Env<'a>,
     input: Binary<'a>,
     sep_term: Term<'a>,
     esc_term: Term<'a>,
     newlines_term: Term<'a>,
-    header_mode_term: Term<'a>,
-    skip_first: bool,
 ) -> NifResult<Term<'a>> {
-    let separators = decode_separators(sep_term)?;
+    let (input, separators) = decode_input_separators(input, sep_term)?;
     let escape = decode_escape(esc_term)?;
     let newlines = decode_newlines(newlines_term)?;
-    let header_mode = decode_header_mode(header_mode_term)?;
     let bytes = input.as_slice();
 
     let all_boundaries = dispatch_parallel_boundary_parse(bytes, &separators, &escape, &newlines);
-
-    if all_boundaries.is_empty() {
-        return Ok(Term::list_new_empty(env));
-    }
-
-    match header_mode {
-        HeaderMode::Auto => {
-            let key_terms = boundary_row_to_key_terms(env, &input, &all_boundaries[0], &escape);
-            Ok(dispatch_boundaries_to_maps(
-                env,
-                input,
-                &key_terms,
-                &all_boundaries[1..],
-                &escape,
-            ))
-        }
-        HeaderMode::Explicit(key_terms) => {
-            let start = if skip_first { 1 } else { 0 };
-            Ok(dispatch_boundaries_to_maps(
-                env,
-                input,
-                &key_terms,
-                &all_boundaries[start..],
-                &escape,
-            ))
-        }
-    }
+    Ok(dispatch_boundaries_to_tuples(
+        env,
+        input,
+        &all_boundaries,
+        &escape,
+    ))
 }
 
 // ============================================================================
@@ -820,7 +1893,11 @@ fn decode_line_separator<'a>(term: Term<'a>) -> NifResult<Vec<u8>> {
 // Formula Escaping + Encoding Target
 // ============================================================================
 
-use strategy::encoding::EncodingTarget;
+use std::borrow::Cow;
+use strategy::encoding::{
+    codepage, codepage_numbers, DecodeError, Decoder, EncodingTarget, InvalidUtf8, Unmappable,
+    CP1252, ISO_8859_15,
+};
 
 /// Configuration for formula injection prevention.
 /// Each rule maps a trigger byte (first byte of a field) to a replacement prefix.
@@ -884,6 +1961,9 @@ fn decode_formula_config<'a>(term: Term<'a>) -> NifResult<FormulaConfig> {
 /// Decode encoding target from an Elixir term.
 /// - `:utf8` → EncodingTarget::Utf8
 /// - `:latin1` → EncodingTarget::Latin1
+/// - `:windows_1252` → EncodingTarget::Codepage(&CP1252)
+/// - `:iso_8859_15` → EncodingTarget::Codepage(&ISO_8859_15)
+/// - `{:codepage, number}` → EncodingTarget::Codepage for a supported codepage
 /// - `{:utf16, :little}` → EncodingTarget::Utf16Le
 /// - `{:utf16, :big}` → EncodingTarget::Utf16Be
 /// - `{:utf32, :little}` → EncodingTarget::Utf32Le
@@ -894,6 +1974,8 @@ fn decode_encoding_target(term: Term) -> NifResult<EncodingTarget> {
         return match s.as_str() {
             "utf8" => Ok(EncodingTarget::Utf8),
             "latin1" => Ok(EncodingTarget::Latin1),
+            "windows_1252" => Ok(EncodingTarget::Codepage(&CP1252)),
+            "iso_8859_15" => Ok(EncodingTarget::Codepage(&ISO_8859_15)),
             _ => Err(Error::BadArg),
         };
     }
@@ -904,6 +1986,12 @@ fn decode_encoding_target(term: Term) -> NifResult<EncodingTarget> {
         return Err(Error::BadArg);
     }
     let enc = tuple[0].atom_to_string().map_err(|_| Error::BadArg)?;
+    if enc == "codepage" {
+        let number: u32 = tuple[1].decode().map_err(|_| Error::BadArg)?;
+        return codepage(number)
+            .map(EncodingTarget::Codepage)
+            .ok_or(Error::BadArg);
+    }
     let endian = tuple[1].atom_to_string().map_err(|_| Error::BadArg)?;
 
     match (enc.as_str(), endian.as_str()) {
@@ -915,6 +2003,353 @@ fn decode_encoding_target(term: Term) -> NifResult<EncodingTarget> {
     }
 }
 
+/// Decode the encoding of parser input: an encoding as for `encode_string`,
+/// or `{:auto, default}` to take it from a BOM. Returns the encoding (the
+/// default for `:auto`) and whether to look for a BOM.
+fn decode_source_encoding(term: Term) -> NifResult<(EncodingTarget, bool)> {
+    if let Ok((tag, default)) = term.decode::<(Atom, Term)>() {
+        if tag == atoms::auto() {
+            return Ok((decode_encoding_target(default)?, true));
+        }
+    }
+    Ok((decode_encoding_target(term)?, false))
+}
+
+/// Decode `input` from `encoding` (as for `encode_string`, or `{:auto,
+/// default}` to use the encoding a BOM names and drop the BOM) to UTF-8, for
+/// parsing non-UTF-8 input. Validates and sizes the output in a first pass,
+/// then decodes straight into the new binary. Raises `{:invalid_encoding,
+/// encoding, offset}` or `{:incomplete_encoding, encoding, offset}`.
+#[rustler::nif(schedule = "DirtyCpu")]
+fn decode_to_utf8<'a>(
+    env: Env<'a>,
+    input: Binary<'a>,
+    encoding: Term<'a>,
+) -> NifResult<Binary<'a>> {
+    use strategy::encoding::{decode_into, decoded_len, sniff_bom};
+
+    let (mut source, auto) = decode_source_encoding(encoding)?;
+    let mut bom_len = 0;
+    if auto {
+        if let Some((found, len)) = sniff_bom(input.as_slice()) {
+            source = found;
+            bom_len = len;
+        }
+    }
+    if source == EncodingTarget::Utf8 {
+        return match bom_len {
+            0 => Ok(input),
+            _ => input.make_subbinary(bom_len, input.len() - bom_len),
+        };
+    }
+    let body = &input.as_slice()[bom_len..];
+    let len = decoded_len(body, source).map_err(decode_error_after(bom_len))?;
+    let mut output = NewBinary::new(env, len);
+    decode_into(output.as_mut_slice(), body, source);
+    Ok(output.into())
+}
+
+/// Parse `input` in `encoding` (as for `decode_to_utf8`, including `{:auto,
+/// default}`) to rows of UTF-8 field terms without decoding it first. UTF-16
+/// and UTF-32 input is indexed over its code units by `scan_structural_utf16`
+/// and `scan_structural_utf32`; Latin-1 and codepage input, which keep ASCII
+/// as it is, by the ordinary boundary parse over its bytes. Each field is then
+/// decoded as its term is built, and ASCII single-byte fields (like UTF-8
+/// ones) stay sub-binaries. Separators, escape and newlines the scanners can't
+/// match (anything but single ASCII bytes, or for single-byte input ASCII
+/// patterns and a one-byte escape), and a `{:sep_directive, separator}`
+/// separator, decode the whole input and parse that instead. Raises like
+/// `decode_to_utf8`.
+fn encoded_rows<'a>(
+    env: Env<'a>,
+    input: Binary<'a>,
+    encoding: Term<'a>,
+    sep_term: Term<'a>,
+    esc_term: Term<'a>,
+    newlines_term: Term<'a>,
+) -> NifResult<Vec<Vec<Term<'a>>>> {
+    use strategy::encoding::{decoded_len, sniff_bom};
+
+    let (mut source, auto) = decode_source_encoding(encoding)?;
+    let mut bom_len = 0;
+    if auto {
+        if let Some((found, len)) = sniff_bom(input.as_slice()) {
+            source = found;
+            bom_len = len;
+        }
+    }
+    let input = match bom_len {
+        0 => input,
+        _ => input.make_subbinary(bom_len, input.len() - bom_len)?,
+    };
+    let error = decode_error_after(bom_len);
+    let (separators, directive) = match decode_sep_directive(sep_term)? {
+        Some(separators) => (separators, true),
+        None => (decode_separators(sep_term)?, false),
+    };
+    let escape = decode_escape(esc_term)?;
+    let newlines = decode_newlines(newlines_term)?;
+    let bytes = input.as_slice();
+
+    let (width, big_endian) = match source {
+        EncodingTarget::Utf8 => {
+            let (input, separators) = decode_input_separators(input, sep_term)?;
+            let boundaries =
+                dispatch_boundary_parse(input.as_slice(), &separators, &escape, &newlines);
+            return Ok(boundaries_to_rows_hybrid(
+                env,
+                input,
+                &boundaries,
+                &escape.bytes,
+            ));
+        }
+        EncodingTarget::Latin1 | EncodingTarget::Codepage(_) => {
+            let ascii = escape.bytes.len() == 1
+                && escape.bytes.is_ascii()
+                && separators.patterns.iter().all(|p| p.is_ascii())
+                && newlines.patterns.iter().all(|p| p.is_ascii());
+            if directive || !ascii {
+                return decoded_rows(env, bytes, source, sep_term, &escape, &newlines, error);
+            }
+            let boundaries = dispatch_boundary_parse(bytes, &separators, &escape, &newlines);
+            return single_byte_boundaries_to_rows(env, input, source, boundaries, escape.bytes[0])
+                .map_err(error);
+        }
+        EncodingTarget::Utf16Le => (2, false),
+        EncodingTarget::Utf16Be => (2, true),
+        EncodingTarget::Utf32Le => (4, false),
+        EncodingTarget::Utf32Be => (4, true),
+    };
+    // A `sep=` line is looked for in the decoded input
+    let terminator = simd_terminator(&separators, &escape, &newlines).filter(|terminator| {
+        !directive
+            && escape.bytes[0].is_ascii()
+            && separators.patterns.iter().all(|p| p[0].is_ascii())
+            && !matches!(terminator, Terminator::Byte(byte) if !byte.is_ascii())
+    });
+    let Some(terminator) = terminator else {
+        return decoded_rows(env, bytes, source, sep_term, &escape, &newlines, error);
+    };
+
+    // The scanners skip a trailing partial unit; report it as decoding would
+    if bytes.len() % width != 0 {
+        decoded_len(bytes, source).map_err(&error)?;
+    }
+    let sep_bytes = single_byte_seps(&separators);
+    let esc = escape.bytes[0];
+    let index = match width {
+        2 => core::scan_structural_utf16(bytes, big_endian, &sep_bytes, esc, terminator),
+        _ => core::scan_structural_utf32(bytes, big_endian, &sep_bytes, esc, terminator),
+    };
+    wide_index_to_rows(env, bytes, source, &index, esc).map_err(error)
+}
+
+/// `decode_error` for input that followed a `bom_len`-byte BOM, with the
+/// offset counted from the start of the BOM
+fn decode_error_after(bom_len: usize) -> impl Fn(DecodeError) -> Error {
+    move |error| {
+        decode_error(DecodeError {
+            offset: error.offset + bom_len as u64,
+            ..error
+        })
+    }
+}
+
+/// Decode all of `input` from `source` and parse the UTF-8 copy to rows of
+/// field terms (sub-binaries of the copy). Decoding errors raise through
+/// `error`.
+fn decoded_rows<'a>(
+    env: Env<'a>,
+    input: &[u8],
+    source: EncodingTarget,
+    sep_term: Term<'a>,
+    escape: &Escape,
+    newlines: &Newlines,
+    error: impl Fn(DecodeError) -> Error,
+) -> NifResult<Vec<Vec<Term<'a>>>> {
+    use strategy::encoding::{decode_into, decoded_len};
+
+    let len = decoded_len(input, source).map_err(error)?;
+    let mut output = NewBinary::new(env, len);
+    decode_into(output.as_mut_slice(), input, source);
+    let utf8: Binary = output.into();
+    let (utf8, separators) = decode_input_separators(utf8, sep_term)?;
+    let boundaries = dispatch_boundary_parse(utf8.as_slice(), &separators, escape, newlines);
+    Ok(boundaries_to_rows_hybrid(
+        env,
+        utf8,
+        &boundaries,
+        &escape.bytes,
+    ))
+}
+
+/// Parse non-UTF-8 `input` to rows of UTF-8 binaries (see `encoded_rows`).
+#[rustler::nif(schedule = "DirtyCpu")]
+fn parse_string_encoded_with_config<'a>(
+    env: Env<'a>,
+    input: Binary<'a>,
+    encoding: Term<'a>,
+    sep_term: Term<'a>,
+    esc_term: Term<'a>,
+    newlines_term: Term<'a>,
+) -> NifResult<Term<'a>> {
+    let rows = encoded_rows(env, input, encoding, sep_term, esc_term, newlines_term)?;
+    Ok(rows.encode(env))
+}
+
+/// Parse non-UTF-8 `input` to a list of tuples (see `encoded_rows`).
+#[rustler::nif(schedule = "DirtyCpu")]
+fn parse_encoded_to_tuples<'a>(
+    env: Env<'a>,
+    input: Binary<'a>,
+    encoding: Term<'a>,
+    sep_term: Term<'a>,
+    esc_term: Term<'a>,
+    newlines_term: Term<'a>,
+) -> NifResult<Term<'a>> {
+    let rows = encoded_rows(env, input, encoding, sep_term, esc_term, newlines_term)?;
+    Ok(rows_to_tuples(env, &rows))
+}
+
+/// Parse non-UTF-8 `input` to a list of maps, keyword lists or structs (see
+/// `encoded_rows`, and `parse_to_maps` for the header options).
+#[allow(clippy::too_many_arguments)]
+#[rustler::nif(schedule = "DirtyCpu")]
+fn parse_encoded_to_maps<'a>(
+    env: Env<'a>,
+    input: Binary<'a>,
+    encoding: Term<'a>,
+    sep_term: Term<'a>,
+    esc_term: Term<'a>,
+    newlines_term: Term<'a>,
+    header_mode_term: Term<'a>,
+    skip_first: bool,
+    duplicate_headers_term: Term<'a>,
+    row_format_term: Term<'a>,
+    column_policy_term: Term<'a>,
+) -> NifResult<Term<'a>> {
+    let header_mode = decode_header_mode(header_mode_term)?;
+    let map_opts = MapOptions {
+        duplicate_headers: decode_duplicate_headers(duplicate_headers_term)?,
+        shape: decode_row_shape(row_format_term)?,
+        column_policy: decode_column_policy(column_policy_term)?,
+    };
+    let rows = encoded_rows(env, input, encoding, sep_term, esc_term, newlines_term)?;
+    field_rows_to_maps(env, &rows, header_mode, skip_first, map_opts)
+}
+
+/// Encoding as the Elixir term `decode_encoding_target` accepts (for errors)
+impl Encoder for EncodingTarget {
+    fn encode<'a>(&self, env: Env<'a>) -> Term<'a> {
+        match self {
+            EncodingTarget::Utf8 => atoms::utf8().encode(env),
+            EncodingTarget::Latin1 => atoms::latin1().encode(env),
+            EncodingTarget::Codepage(codepage) if *codepage == &CP1252 => {
+                atoms::windows_1252().encode(env)
+            }
+            EncodingTarget::Codepage(codepage) if *codepage == &ISO_8859_15 => {
+                atoms::iso_8859_15().encode(env)
+            }
+            EncodingTarget::Codepage(codepage) => (atoms::codepage(), codepage.number).encode(env),
+            EncodingTarget::Utf16Le => (atoms::utf16(), atoms::little()).encode(env),
+            EncodingTarget::Utf16Be => (atoms::utf16(), atoms::big()).encode(env),
+            EncodingTarget::Utf32Le => (atoms::utf32(), atoms::little()).encode(env),
+            EncodingTarget::Utf32Be => (atoms::utf32(), atoms::big()).encode(env),
+        }
+    }
+}
+
+/// Windows numbers of the codepages `{:codepage, number}` accepts, so the
+/// Elixir side validates against the tables instead of its own list
+#[rustler::nif]
+fn codepages() -> Vec<u32> {
+    codepage_numbers().collect()
+}
+
+/// Guess the encoding of `sample` (the start of some input): a list of
+/// `{encoding, confidence}` with confidence from 0.0 to 1.0, most likely
+/// first. A BOM gives a single candidate at 1.0.
+#[rustler::nif(schedule = "DirtyCpu")]
+fn detect_encoding<'a>(env: Env<'a>, sample: Binary<'a>) -> Term<'a> {
+    strategy::detect::detect_encoding(sample.as_slice()).encode(env)
+}
+
+/// Decode the `on_unmappable` policy for characters the target encoding has
+/// no byte for: `:replace` (with `?`), `{:replace, string}`, `:error` or
+/// `:transliterate`. Replacements are substituted before quoting and encoded
+/// with the field (see `substitute_unmappable`).
+fn decode_unmappable<'a>(term: Term<'a>) -> NifResult<Unmappable<'a>> {
+    if let Ok(s) = term.atom_to_string() {
+        return match s.as_str() {
+            "replace" => Ok(Unmappable::default()),
+            "error" => Ok(Unmappable::Error),
+            "transliterate" => Ok(Unmappable::Transliterate),
+            _ => Err(Error::BadArg),
+        };
+    }
+    let (tag, bytes): (Atom, Binary<'a>) = term.decode().map_err(|_| Error::BadArg)?;
+    if tag != atoms::replace() {
+        return Err(Error::BadArg);
+    }
+    Ok(Unmappable::Replace(bytes.as_slice()))
+}
+
+/// Raise `{:unmappable, encoding, row, column, character}` for the first
+/// character of field `column` of row `row` (both 0-based here, 1-based in
+/// the error) that `encoding` cannot represent
+fn unmappable_error(target: EncodingTarget, row: usize, column: usize, ch: char) -> Error {
+    Error::RaiseTerm(Box::new((
+        atoms::unmappable(),
+        target,
+        row + 1,
+        column + 1,
+        ch.to_string(),
+    )))
+}
+
+/// Decode the `invalid_utf8` policy: `:passthrough`, `:replace` or `:error`.
+fn decode_invalid_utf8(term: Term) -> NifResult<InvalidUtf8> {
+    match term.atom_to_string().map_err(|_| Error::BadArg)?.as_str() {
+        "passthrough" => Ok(InvalidUtf8::Passthrough),
+        "replace" => Ok(InvalidUtf8::Replace),
+        "error" => Ok(InvalidUtf8::Error),
+        _ => Err(Error::BadArg),
+    }
+}
+
+/// Apply the `invalid_utf8` policy to field `column` of row `row` (both
+/// 0-based), raising `{:invalid_utf8, row, column, offset}` (1-based row and
+/// column, byte offset in the field) for `:error`
+fn utf8_field(
+    bytes: &[u8],
+    invalid_utf8: InvalidUtf8,
+    row: usize,
+    column: usize,
+) -> NifResult<Cow<'_, [u8]>> {
+    strategy::encoding::check_utf8(bytes, invalid_utf8).map_err(|offset| {
+        Error::RaiseTerm(Box::new((
+            atoms::invalid_utf8(),
+            row + 1,
+            column + 1,
+            offset,
+        )))
+    })
+}
+
+/// Append a field's (possibly quoted) UTF-8 bytes to `buf` in `target`,
+/// applying the `on_unmappable` policy
+fn encode_field(
+    buf: &mut Vec<u8>,
+    utf8: &[u8],
+    target: EncodingTarget,
+    unmappable: Unmappable,
+    row: usize,
+    column: usize,
+) -> NifResult<()> {
+    strategy::encoding::encode_utf8_extend_with(buf, utf8, target, unmappable)
+        .map_err(|ch| unmappable_error(target, row, column, ch))
+}
+
 /// Decode reserved characters from an Erlang list of single-byte binaries.
 /// Returns a Vec<u8> of bytes that should trigger quoting.
 fn decode_reserved<'a>(term: Term<'a>) -> NifResult<Vec<u8>> {
@@ -973,6 +2408,12 @@ impl PostProcess {
 /// - FormulaOnly: prefix triggered fields with replacement bytes
 /// - EncodingOnly: convert all output to target encoding
 /// - Full: both formula escaping and encoding conversion
+///
+/// Field characters the target encoding cannot represent are handled by the
+/// `on_unmappable` policy; `:error` raises `{:unmappable, encoding, row,
+/// column, character}` for the first one. Fields that are not valid UTF-8
+/// are copied as-is, replaced with U+FFFD or rejected with `{:invalid_utf8,
+/// row, column, offset}` by the `invalid_utf8` policy.
 #[allow(clippy::too_many_arguments)]
 #[rustler::nif(schedule = "DirtyCpu")]
 fn encode_string<'a>(
@@ -984,6 +2425,8 @@ fn encode_string<'a>(
     formula_term: Term<'a>,
     encoding_term: Term<'a>,
     reserved_term: Term<'a>,
+    unmappable_term: Term<'a>,
+    invalid_utf8_term: Term<'a>,
 ) -> NifResult<Term<'a>> {
     let separators = decode_separators(sep_term)?;
     let escape = decode_escape(esc_term)?;
@@ -991,6 +2434,8 @@ fn encode_string<'a>(
     let formula = decode_formula_config(formula_term)?;
     let encoding = decode_encoding_target(encoding_term)?;
     let reserved = decode_reserved(reserved_term)?;
+    let unmappable = decode_unmappable(unmappable_term)?;
+    let invalid_utf8 = decode_invalid_utf8(invalid_utf8_term)?;
     let post = PostProcess::from(formula, encoding);
 
     let rows_iter: ListIterator<'a> = rows_term.decode().map_err(|_| Error::BadArg)?;
@@ -1003,6 +2448,7 @@ fn encode_string<'a>(
             &escape,
             &line_separator,
             &reserved,
+            invalid_utf8,
         ),
         PostProcess::FormulaOnly(formula) => encode_string_formula(
             env,
@@ -1012,6 +2458,7 @@ fn encode_string<'a>(
             &line_separator,
             &formula,
             &reserved,
+            invalid_utf8,
         ),
         PostProcess::EncodingOnly(target) => encode_string_encoding(
             env,
@@ -1020,7 +2467,9 @@ fn encode_string<'a>(
             &escape,
             &line_separator,
             target,
+            unmappable,
             &reserved,
+            invalid_utf8,
         ),
         PostProcess::Full(formula, target) => encode_string_full(
             env,
@@ -1030,7 +2479,9 @@ fn encode_string<'a>(
             &line_separator,
             &formula,
             target,
+            unmappable,
             &reserved,
+            invalid_utf8,
         ),
     }
 }
@@ -1044,6 +2495,7 @@ fn encode_string_none<'a>(
     escape: &Escape,
     line_separator: &[u8],
     reserved: &[u8],
+    invalid_utf8: InvalidUtf8,
 ) -> NifResult<Term<'a>> {
     use strategy::encode::{write_quoted_field, write_quoted_field_general};
 
@@ -1055,16 +2507,15 @@ fn encode_string_none<'a>(
         let dump_sep = sep_bytes[0];
         let multi_sep = sep_bytes.len() > 1;
 
-        for row_term in rows_iter {
+        for (row, row_term) in rows_iter.enumerate() {
             let field_iter: ListIterator<'a> = row_term.decode().map_err(|_| Error::BadArg)?;
-            let mut first = true;
-            for field_term in field_iter {
-                if !first {
+            for (column, field_term) in field_iter.enumerate() {
+                if column > 0 {
                     buf.push(dump_sep);
                 }
-                first = false;
                 let field_bin: Binary<'a> = field_term.decode().map_err(|_| Error::BadArg)?;
-                let field_bytes = field_bin.as_slice();
+                let field = utf8_field(field_bin.as_slice(), invalid_utf8, row, column)?;
+                let field_bytes: &[u8] = &field;
                 let needs_quoting = if multi_sep {
                     field_needs_quoting_simd_multi_sep(field_bytes, &sep_bytes, esc, reserved)
                 } else {
@@ -1082,16 +2533,15 @@ fn encode_string_none<'a>(
         let sep_pattern = &separators.patterns[0];
         let esc_pattern = &escape.bytes;
 
-        for row_term in rows_iter {
+        for (row, row_term) in rows_iter.enumerate() {
             let field_iter: ListIterator<'a> = row_term.decode().map_err(|_| Error::BadArg)?;
-            let mut first = true;
-            for field_term in field_iter {
-                if !first {
+            for (column, field_term) in field_iter.enumerate() {
+                if column > 0 {
                     buf.extend_from_slice(sep_pattern);
                 }
-                first = false;
                 let field_bin: Binary<'a> = field_term.decode().map_err(|_| Error::BadArg)?;
-                let field_bytes = field_bin.as_slice();
+                let field = utf8_field(field_bin.as_slice(), invalid_utf8, row, column)?;
+                let field_bytes: &[u8] = &field;
                 if field_needs_quoting_general(field_bytes, sep_pattern, esc_pattern, reserved) {
                     write_quoted_field_general(&mut buf, field_bytes, esc_pattern);
                 } else {
@@ -1114,6 +2564,7 @@ fn encode_string_none<'a>(
 /// NimbleCSV semantics:
 /// - Formula triggered + clean field → prefix ++ field (no quotes)
 /// - Formula triggered + dirty field → esc ++ prefix ++ escaped_inner ++ esc
+#[allow(clippy::too_many_arguments)]
 fn encode_string_formula<'a>(
     env: Env<'a>,
     rows_iter: ListIterator<'a>,
@@ -1122,6 +2573,7 @@ fn encode_string_formula<'a>(
     line_separator: &[u8],
     formula: &FormulaConfig,
     reserved: &[u8],
+    invalid_utf8: InvalidUtf8,
 ) -> NifResult<Term<'a>> {
     use strategy::encode::{
         write_quoted_field, write_quoted_field_general, write_quoted_field_inner,
@@ -1136,16 +2588,15 @@ fn encode_string_formula<'a>(
         let dump_sep = sep_bytes[0];
         let multi_sep = sep_bytes.len() > 1;
 
-        for row_term in rows_iter {
+        for (row, row_term) in rows_iter.enumerate() {
             let field_iter: ListIterator<'a> = row_term.decode().map_err(|_| Error::BadArg)?;
-            let mut first = true;
-            for field_term in field_iter {
-                if !first {
+            for (column, field_term) in field_iter.enumerate() {
+                if column > 0 {
                     buf.push(dump_sep);
                 }
-                first = false;
                 let field_bin: Binary<'a> = field_term.decode().map_err(|_| Error::BadArg)?;
-                let field_bytes = field_bin.as_slice();
+                let field = utf8_field(field_bin.as_slice(), invalid_utf8, row, column)?;
+                let field_bytes: &[u8] = &field;
                 let needs_quoting = if multi_sep {
                     field_needs_quoting_simd_multi_sep(field_bytes, &sep_bytes, esc, reserved)
                 } else {
@@ -1174,16 +2625,15 @@ fn encode_string_formula<'a>(
         let sep_pattern = &separators.patterns[0];
         let esc_pattern = &escape.bytes;
 
-        for row_term in rows_iter {
+        for (row, row_term) in rows_iter.enumerate() {
             let field_iter: ListIterator<'a> = row_term.decode().map_err(|_| Error::BadArg)?;
-            let mut first = true;
-            for field_term in field_iter {
-                if !first {
+            for (column, field_term) in field_iter.enumerate() {
+                if column > 0 {
                     buf.extend_from_slice(sep_pattern);
                 }
-                first = false;
                 let field_bin: Binary<'a> = field_term.decode().map_err(|_| Error::BadArg)?;
-                let field_bytes = field_bin.as_slice();
+                let field = utf8_field(field_bin.as_slice(), invalid_utf8, row, column)?;
+                let field_bytes: &[u8] = &field;
                 let needs_quoting =
                     field_needs_quoting_general(field_bytes, sep_pattern, esc_pattern, reserved);
 
@@ -1215,6 +2665,7 @@ fn encode_string_formula<'a>(
 
 /// PostProcess::EncodingOnly — non-UTF-8, no formula.
 /// Flat Vec<u8> buffer with scratch buffer for quoting → single NewBinary.
+#[allow(clippy::too_many_arguments)]
 fn encode_string_encoding<'a>(
     env: Env<'a>,
     rows_iter: ListIterator<'a>,
@@ -1222,10 +2673,12 @@ fn encode_string_encoding<'a>(
     escape: &Escape,
     line_separator: &[u8],
     target: EncodingTarget,
+    unmappable: Unmappable,
     reserved: &[u8],
+    invalid_utf8: InvalidUtf8,
 ) -> NifResult<Term<'a>> {
     use strategy::encode::{write_quoted_field, write_quoted_field_general};
-    use strategy::encoding::encode_utf8_extend;
+    use strategy::encoding::{encode_utf8_extend, substitute_unmappable};
 
     let mut buf: Vec<u8> = Vec::with_capacity(64 * 1024);
     let mut scratch: Vec<u8> = Vec::with_capacity(256);
@@ -1242,16 +2695,16 @@ fn encode_string_encoding<'a>(
         let dump_sep = sep_bytes[0];
         let multi_sep = sep_bytes.len() > 1;
 
-        for row_term in rows_iter {
+        for (row, row_term) in rows_iter.enumerate() {
             let field_iter: ListIterator<'a> = row_term.decode().map_err(|_| Error::BadArg)?;
-            let mut first = true;
-            for field_term in field_iter {
-                if !first {
+            for (column, field_term) in field_iter.enumerate() {
+                if column > 0 {
                     buf.extend_from_slice(&sep_encoded);
                 }
-                first = false;
                 let field_bin: Binary<'a> = field_term.decode().map_err(|_| Error::BadArg)?;
-                let field_bytes = field_bin.as_slice();
+                let field = utf8_field(field_bin.as_slice(), invalid_utf8, row, column)?;
+                let field = substitute_unmappable(&field, target, unmappable);
+                let field_bytes: &[u8] = &field;
                 let needs_quoting = if multi_sep {
                     field_needs_quoting_simd_multi_sep(field_bytes, &sep_bytes, esc, reserved)
                 } else {
@@ -1265,7 +2718,7 @@ fn encode_string_encoding<'a>(
                 } else {
                     field_bytes
                 };
-                encode_utf8_extend(&mut buf, utf8_src, target);
+                encode_field(&mut buf, utf8_src, target, unmappable, row, column)?;
             }
             buf.extend_from_slice(&ls_encoded);
         }
@@ -1273,16 +2726,16 @@ fn encode_string_encoding<'a>(
         let sep_pattern = &separators.patterns[0];
         let esc_pattern = &escape.bytes;
 
-        for row_term in rows_iter {
+        for (row, row_term) in rows_iter.enumerate() {
             let field_iter: ListIterator<'a> = row_term.decode().map_err(|_| Error::BadArg)?;
-            let mut first = true;
-            for field_term in field_iter {
-                if !first {
+            for (column, field_term) in field_iter.enumerate() {
+                if column > 0 {
                     buf.extend_from_slice(&sep_encoded);
                 }
-                first = false;
                 let field_bin: Binary<'a> = field_term.decode().map_err(|_| Error::BadArg)?;
-                let field_bytes = field_bin.as_slice();
+                let field = utf8_field(field_bin.as_slice(), invalid_utf8, row, column)?;
+                let field = substitute_unmappable(&field, target, unmappable);
+                let field_bytes: &[u8] = &field;
 
                 let utf8_src: &[u8] =
                     if field_needs_quoting_general(field_bytes, sep_pattern, esc_pattern, reserved)
@@ -1293,7 +2746,7 @@ fn encode_string_encoding<'a>(
                     } else {
                         field_bytes
                     };
-                encode_utf8_extend(&mut buf, utf8_src, target);
+                encode_field(&mut buf, utf8_src, target, unmappable, row, column)?;
             }
             buf.extend_from_slice(&ls_encoded);
         }
@@ -1316,13 +2769,15 @@ fn encode_string_full<'a>(
     line_separator: &[u8],
     formula: &FormulaConfig,
     target: EncodingTarget,
+    unmappable: Unmappable,
     reserved: &[u8],
+    invalid_utf8: InvalidUtf8,
 ) -> NifResult<Term<'a>> {
     use strategy::encode::{
         write_quoted_field, write_quoted_field_general, write_quoted_field_inner,
         write_quoted_field_inner_general,
     };
-    use strategy::encoding::encode_utf8_extend;
+    use strategy::encoding::{encode_utf8_extend, substitute_unmappable};
 
     let mut buf: Vec<u8> = Vec::with_capacity(64 * 1024);
     let mut scratch: Vec<u8> = Vec::with_capacity(256);
@@ -1338,16 +2793,16 @@ fn encode_string_full<'a>(
         let dump_sep = sep_bytes[0];
         let multi_sep = sep_bytes.len() > 1;
 
-        for row_term in rows_iter {
+        for (row, row_term) in rows_iter.enumerate() {
             let field_iter: ListIterator<'a> = row_term.decode().map_err(|_| Error::BadArg)?;
-            let mut first = true;
-            for field_term in field_iter {
-                if !first {
+            for (column, field_term) in field_iter.enumerate() {
+                if column > 0 {
                     buf.extend_from_slice(&sep_encoded);
                 }
-                first = false;
                 let field_bin: Binary<'a> = field_term.decode().map_err(|_| Error::BadArg)?;
-                let field_bytes = field_bin.as_slice();
+                let field = utf8_field(field_bin.as_slice(), invalid_utf8, row, column)?;
+                let field = substitute_unmappable(&field, target, unmappable);
+                let field_bytes: &[u8] = &field;
                 let needs_quoting = if multi_sep {
                     field_needs_quoting_simd_multi_sep(field_bytes, &sep_bytes, esc, reserved)
                 } else {
@@ -1361,12 +2816,12 @@ fn encode_string_full<'a>(
                         buf.extend_from_slice(prefix);
                         scratch.clear();
                         write_quoted_field_inner(&mut scratch, field_bytes, esc);
-                        encode_utf8_extend(&mut buf, &scratch, target);
+                        encode_field(&mut buf, &scratch, target, unmappable, row, column)?;
                         encode_utf8_extend(&mut buf, &[esc], target);
                     } else {
                         // Clean + formula: raw_prefix ++ encoded_field
                         buf.extend_from_slice(prefix);
-                        encode_utf8_extend(&mut buf, field_bytes, target);
+                        encode_field(&mut buf, field_bytes, target, unmappable, row, column)?;
                     }
                 } else {
                     let utf8_src: &[u8] = if needs_quoting {
@@ -1376,7 +2831,7 @@ fn encode_string_full<'a>(
                     } else {
                         field_bytes
                     };
-                    encode_utf8_extend(&mut buf, utf8_src, target);
+                    encode_field(&mut buf, utf8_src, target, unmappable, row, column)?;
                 }
             }
             buf.extend_from_slice(&ls_encoded);
@@ -1385,16 +2840,16 @@ fn encode_string_full<'a>(
         let sep_pattern = &separators.patterns[0];
         let esc_pattern = &escape.bytes;
 
-        for row_term in rows_iter {
+        for (row, row_term) in rows_iter.enumerate() {
             let field_iter: ListIterator<'a> = row_term.decode().map_err(|_| Error::BadArg)?;
-            let mut first = true;
-            for field_term in field_iter {
-                if !first {
+            for (column, field_term) in field_iter.enumerate() {
+                if column > 0 {
                     buf.extend_from_slice(&sep_encoded);
                 }
-                first = false;
                 let field_bin: Binary<'a> = field_term.decode().map_err(|_| Error::BadArg)?;
-                let field_bytes = field_bin.as_slice();
+                let field = utf8_field(field_bin.as_slice(), invalid_utf8, row, column)?;
+                let field = substitute_unmappable(&field, target, unmappable);
+                let field_bytes: &[u8] = &field;
                 let needs_quoting =
                     field_needs_quoting_general(field_bytes, sep_pattern, esc_pattern, reserved);
 
@@ -1404,11 +2859,11 @@ fn encode_string_full<'a>(
                         buf.extend_from_slice(prefix);
                         scratch.clear();
                         write_quoted_field_inner_general(&mut scratch, field_bytes, esc_pattern);
-                        encode_utf8_extend(&mut buf, &scratch, target);
+                        encode_field(&mut buf, &scratch, target, unmappable, row, column)?;
                         encode_utf8_extend(&mut buf, esc_pattern, target);
                     } else {
                         buf.extend_from_slice(prefix);
-                        encode_utf8_extend(&mut buf, field_bytes, target);
+                        encode_field(&mut buf, field_bytes, target, unmappable, row, column)?;
                     }
                 } else {
                     let utf8_src: &[u8] = if needs_quoting {
@@ -1418,7 +2873,7 @@ fn encode_string_full<'a>(
                     } else {
                         field_bytes
                     };
-                    encode_utf8_extend(&mut buf, utf8_src, target);
+                    encode_field(&mut buf, utf8_src, target, unmappable, row, column)?;
                 }
             }
             buf.extend_from_slice(&ls_encoded);
@@ -1431,12 +2886,103 @@ fn encode_string_full<'a>(
     Ok(bin_term)
 }
 
+/// Encode rows as a file Excel opens with every cell intact.
+///
+/// `variant` is `:utf16` for UTF-16LE with a BOM and tab separators (what
+/// Excel itself writes as "Unicode Text"), or `:utf8` for UTF-8 with a BOM
+/// and a `sep=` first line naming the module's separator. Each field goes
+/// through `excel_field` (formula prefix, `="00123"` for digit strings Excel
+/// would turn into numbers) before the usual quoting, so the `'` prefix is
+/// encoded like the rest of the field rather than written raw as `encode_string`
+/// does. The `invalid_utf8` policy applies as in `encode_string`.
+#[allow(clippy::too_many_arguments)]
+#[rustler::nif(schedule = "DirtyCpu")]
+fn encode_string_excel<'a>(
+    env: Env<'a>,
+    rows_term: Term<'a>,
+    variant_term: Term<'a>,
+    sep_term: Term<'a>,
+    esc_term: Term<'a>,
+    line_sep_term: Term<'a>,
+    reserved_term: Term<'a>,
+    invalid_utf8_term: Term<'a>,
+) -> NifResult<Term<'a>> {
+    use strategy::encode::{excel_field, write_quoted_field_general};
+    use strategy::encoding::encode_utf8_extend;
+
+    let separators = decode_separators(sep_term)?;
+    let escape = decode_escape(esc_term)?;
+    let line_separator = decode_line_separator(line_sep_term)?;
+    let reserved = decode_reserved(reserved_term)?;
+    let invalid_utf8 = decode_invalid_utf8(invalid_utf8_term)?;
+    let variant = variant_term.atom_to_string().map_err(|_| Error::BadArg)?;
+    let (target, sep_pattern) = match variant.as_str() {
+        "utf16" => (EncodingTarget::Utf16Le, b"\t".to_vec()),
+        "utf8" => (EncodingTarget::Utf8, separators.patterns[0].clone()),
+        _ => return Err(Error::BadArg),
+    };
+    // `sep=` names exactly one character; anything longer reads back as a row
+    let single_char = std::str::from_utf8(&sep_pattern).is_ok_and(|s| s.chars().count() == 1);
+    if target == EncodingTarget::Utf8 && !single_char {
+        return Err(Error::BadArg);
+    }
+    let esc_pattern = &escape.bytes;
+
+    let rows_iter: ListIterator<'a> = rows_term.decode().map_err(|_| Error::BadArg)?;
+
+    let mut buf: Vec<u8> = Vec::with_capacity(64 * 1024);
+    let mut scratch: Vec<u8> = Vec::with_capacity(256);
+
+    encode_utf8_extend(&mut buf, "\u{FEFF}".as_bytes(), target);
+    if target == EncodingTarget::Utf8 {
+        buf.extend_from_slice(b"sep=");
+        buf.extend_from_slice(&sep_pattern);
+        buf.extend_from_slice(&line_separator);
+    }
+
+    let mut sep_encoded: Vec<u8> = Vec::new();
+    encode_utf8_extend(&mut sep_encoded, &sep_pattern, target);
+    let mut ls_encoded: Vec<u8> = Vec::new();
+    encode_utf8_extend(&mut ls_encoded, &line_separator, target);
+
+    for (row, row_term) in rows_iter.enumerate() {
+        let field_iter: ListIterator<'a> = row_term.decode().map_err(|_| Error::BadArg)?;
+        for (column, field_term) in field_iter.enumerate() {
+            if column > 0 {
+                buf.extend_from_slice(&sep_encoded);
+            }
+            let field_bin: Binary<'a> = field_term.decode().map_err(|_| Error::BadArg)?;
+            let field = utf8_field(field_bin.as_slice(), invalid_utf8, row, column)?;
+            let field = excel_field(&field);
+            let field_bytes: &[u8] = &field;
+
+            let utf8_src: &[u8] =
+                if field_needs_quoting_general(field_bytes, &sep_pattern, esc_pattern, &reserved) {
+                    scratch.clear();
+                    write_quoted_field_general(&mut scratch, field_bytes, esc_pattern);
+                    &scratch
+                } else {
+                    field_bytes
+                };
+            encode_utf8_extend(&mut buf, utf8_src, target);
+        }
+        buf.extend_from_slice(&ls_encoded);
+    }
+
+    let mut new_bin = NewBinary::new(env, buf.len());
+    new_bin.as_mut_slice().copy_from_slice(&buf);
+    let bin_term: Term<'a> = new_bin.into();
+    Ok(bin_term)
+}
+
 /// Encode rows to CSV in parallel using rayon, returning iodata (list of binaries).
 ///
 /// Architecture:
 /// - Phase 1 (main thread): Walk Erlang lists, copy field bytes into owned Vecs
 /// - Phase 2 (rayon): Parallel CSV encoding — each chunk produces a flat Vec<u8>
-///   with formula prefixes applied. If encoding != UTF-8, convert entire chunk.
+///   with formula prefixes applied. If encoding != UTF-8, convert entire chunk,
+///   applying the `on_unmappable` policy as `encode_string` does. The
+///   `invalid_utf8` policy is applied while copying in phase 1.
 /// - Phase 3 (main thread): Wrap each chunk as a NewBinary, return as iodata list
 ///
 /// Only supports single-byte separator/escape (the common case for parallel workloads).
@@ -1453,12 +2999,16 @@ fn encode_string_parallel<'a>(
     formula_term: Term<'a>,
     encoding_term: Term<'a>,
     reserved_term: Term<'a>,
+    unmappable_term: Term<'a>,
+    invalid_utf8_term: Term<'a>,
 ) -> NifResult<Term<'a>> {
     use rayon::prelude::*;
     use strategy::encode::{
         field_needs_quoting_simd, write_quoted_field, write_quoted_field_inner,
     };
-    use strategy::encoding::encode_utf8_to_target;
+    use strategy::encoding::{
+        encode_utf8_extend_with, encode_utf8_to_target, substitute_unmappable,
+    };
     use strategy::parallel::{recommended_threads, run_parallel};
 
     let separators = decode_separators(sep_term)?;
@@ -1467,6 +3017,8 @@ fn encode_string_parallel<'a>(
     let formula = decode_formula_config(formula_term)?;
     let encoding = decode_encoding_target(encoding_term)?;
     let reserved = decode_reserved(reserved_term)?;
+    let unmappable = decode_unmappable(unmappable_term)?;
+    let invalid_utf8 = decode_invalid_utf8(invalid_utf8_term)?;
 
     // Only support single-byte sep/esc for the parallel path
     if !is_all_single_byte(&separators, &escape) {
@@ -1485,12 +3037,14 @@ fn encode_string_parallel<'a>(
     let rows_iter: ListIterator<'a> = rows_term.decode().map_err(|_| Error::BadArg)?;
     let mut all_rows: Vec<Vec<Vec<u8>>> = Vec::new();
 
-    for row_term in rows_iter {
+    for (row, row_term) in rows_iter.enumerate() {
         let field_iter: ListIterator<'a> = row_term.decode().map_err(|_| Error::BadArg)?;
         let mut row_fields: Vec<Vec<u8>> = Vec::new();
-        for field_term in field_iter {
+        for (column, field_term) in field_iter.enumerate() {
             let field_bin: Binary<'a> = field_term.decode().map_err(|_| Error::BadArg)?;
-            row_fields.push(field_bin.as_slice().to_vec());
+            let field = utf8_field(field_bin.as_slice(), invalid_utf8, row, column)?;
+            let field = substitute_unmappable(&field, encoding, unmappable);
+            row_fields.push(field.into_owned());
         }
         all_rows.push(row_fields);
     }
@@ -1514,12 +3068,16 @@ fn encode_string_parallel<'a>(
         line_separator.clone()
     };
 
-    let chunks: Vec<Vec<u8>> = run_parallel(|| {
+    // Each chunk fails with the (row, column, character) of its first
+    // unmappable character; chunks are checked in order below
+    let chunks: Vec<Result<Vec<u8>, (usize, usize, char)>> = run_parallel(|| {
         all_rows
             .par_chunks(chunk_size)
-            .map(|chunk_rows| {
+            .enumerate()
+            .map(|(chunk_index, chunk_rows)| {
                 let mut out = Vec::with_capacity(chunk_rows.len() * 128);
-                for row in chunk_rows {
+                for (row_offset, row) in chunk_rows.iter().enumerate() {
+                    let row_index = chunk_index * chunk_size + row_offset;
                     for (i, field) in row.iter().enumerate() {
                         if i > 0 {
                             if needs_encoding {
@@ -1550,10 +3108,12 @@ fn encode_string_parallel<'a>(
                                     let encoded_esc = encode_utf8_to_target(&[esc], encoding);
                                     let mut inner_buf = Vec::with_capacity(field.len() + 8);
                                     write_quoted_field_inner(&mut inner_buf, field, esc);
-                                    let encoded_inner = encode_utf8_to_target(&inner_buf, encoding);
                                     out.extend_from_slice(&encoded_esc);
                                     out.extend_from_slice(prefix);
-                                    out.extend_from_slice(&encoded_inner);
+                                    encode_utf8_extend_with(
+                                        &mut out, &inner_buf, encoding, unmappable,
+                                    )
+                                    .map_err(|ch| (row_index, i, ch))?;
                                     out.extend_from_slice(&encoded_esc);
                                 } else {
                                     // FormulaOnly: prefix inside quotes
@@ -1565,8 +3125,8 @@ fn encode_string_parallel<'a>(
                             } else if needs_encoding {
                                 // Clean + formula + encoding: prefix raw, field encoded
                                 out.extend_from_slice(prefix);
-                                let encoded = encode_utf8_to_target(field, encoding);
-                                out.extend_from_slice(&encoded);
+                                encode_utf8_extend_with(&mut out, field, encoding, unmappable)
+                                    .map_err(|ch| (row_index, i, ch))?;
                             } else {
                                 // Clean + formula, no encoding
                                 out.extend_from_slice(prefix);
@@ -1588,18 +3148,22 @@ fn encode_string_parallel<'a>(
                         };
 
                         if needs_encoding {
-                            let encoded = encode_utf8_to_target(&utf8_field, encoding);
-                            out.extend_from_slice(&encoded);
+                            encode_utf8_extend_with(&mut out, &utf8_field, encoding, unmappable)
+                                .map_err(|ch| (row_index, i, ch))?;
                         } else {
                             out.extend_from_slice(&utf8_field);
                         }
                     }
                     out.extend_from_slice(&ls_encoded);
                 }
-                out
+                Ok(out)
             })
             .collect()
     });
+    let chunks: Vec<Vec<u8>> = chunks
+        .into_iter()
+        .collect::<Result<_, _>>()
+        .map_err(|(row, column, ch)| unmappable_error(encoding, row, column, ch))?;
 
     // Phase 3: Build iodata from chunk results
     let chunk_terms: Vec<Term<'a>> = chunks
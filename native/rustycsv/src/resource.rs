@@ -3,9 +3,18 @@
 // This allows the streaming parser state to persist across NIF calls.
 // Supports both single-byte (fast path) and general (multi-byte) parsers.
 
-use crate::core::Newlines;
-use crate::strategy::{GeneralStreamingParser, GeneralStreamingParserNewlines, StreamingParser};
-use rustler::ResourceArc;
+use crate::core::{Newlines, Terminator};
+use crate::strategy::encoding::Decoder;
+use crate::strategy::{
+    Checkpoint, GeneralStreamingParser, GeneralStreamingParserNewlines, ReadError, RowWidthError,
+    StreamField, StreamStats, StreamingParser, WidthPolicy,
+};
+use crate::term::{DuplicateHeaders, ExtraColumns, HeaderKey, LoadedChunks, MissingColumns};
+use rustler::env::{OwnedEnv, SavedTerm};
+use rustler::{Binary, Encoder, Env, NifResult, ResourceArc};
+use std::collections::VecDeque;
+use std::fs::File;
+use std::io::Read;
 use std::sync::Mutex;
 
 /// Enum dispatching between single-byte and general streaming parsers
@@ -16,6 +25,33 @@ pub enum StreamingParserEnum {
 }
 
 impl StreamingParserEnum {
+    /// Single-byte parsers cut zero-copy rows (the resource keeps fed chunks)
+    pub fn with_config(separator: u8, escape: u8) -> Self {
+        let mut parser = StreamingParser::with_config(separator, escape);
+        parser.set_zero_copy(true);
+        StreamingParserEnum::SingleByte(parser)
+    }
+
+    pub fn with_terminator(separators: &[u8], escape: u8, terminator: Terminator) -> Self {
+        let mut parser = StreamingParser::with_terminator(separators, escape, terminator);
+        parser.set_zero_copy(true);
+        StreamingParserEnum::SingleByte(parser)
+    }
+
+    pub fn with_general(separators: Vec<Vec<u8>>, escape: Vec<u8>) -> Self {
+        StreamingParserEnum::General(GeneralStreamingParser::new(separators, escape))
+    }
+
+    pub fn with_general_newlines(
+        separators: Vec<Vec<u8>>,
+        escape: Vec<u8>,
+        newlines: Newlines,
+    ) -> Self {
+        StreamingParserEnum::GeneralNewlines(GeneralStreamingParserNewlines::new(
+            separators, escape, newlines,
+        ))
+    }
+
     pub fn feed(&mut self, chunk: &[u8]) -> Result<(), crate::strategy::streaming::BufferOverflow> {
         match self {
             StreamingParserEnum::SingleByte(p) => p.feed(chunk),
@@ -24,6 +60,33 @@ impl StreamingParserEnum {
         }
     }
 
+    /// Read up to `len` bytes from `reader` straight into the parser buffer
+    pub fn feed_from(&mut self, reader: &mut impl Read, len: usize) -> Result<usize, ReadError> {
+        match self {
+            StreamingParserEnum::SingleByte(p) => p.feed_from(reader, len),
+            StreamingParserEnum::General(p) => p.feed_from(reader, len),
+            StreamingParserEnum::GeneralNewlines(p) => p.feed_from(reader, len),
+        }
+    }
+
+    /// Decode `chunk` with `decoder` straight into the parser buffer
+    pub fn feed_decoded(&mut self, chunk: &[u8], decoder: &mut Decoder) -> Result<(), ReadError> {
+        match self {
+            StreamingParserEnum::SingleByte(p) => p.feed_decoded(chunk, decoder),
+            StreamingParserEnum::General(p) => p.feed_decoded(chunk, decoder),
+            StreamingParserEnum::GeneralNewlines(p) => p.feed_decoded(chunk, decoder),
+        }
+    }
+
+    /// Stream offset the next chunk starts at, when rows may be cut as spans
+    /// of it (the chunk must then be kept in `FedChunks`)
+    pub fn zero_copy_offset(&self) -> Option<u64> {
+        match self {
+            StreamingParserEnum::SingleByte(p) => Some(p.input_offset()),
+            _ => None,
+        }
+    }
+
     pub fn set_max_buffer_size(&mut self, max: usize) {
         match self {
             StreamingParserEnum::SingleByte(p) => p.set_max_buffer_size(max),
@@ -32,11 +95,65 @@ impl StreamingParserEnum {
         }
     }
 
-    pub fn take_rows(&mut self, max: usize) -> Vec<Vec<Vec<u8>>> {
+    /// Limit complete rows queued before scanning stops (see `needs_drain`)
+    pub fn set_max_pending_rows(&mut self, max: usize) {
         match self {
-            StreamingParserEnum::SingleByte(p) => p.take_rows(max),
-            StreamingParserEnum::General(p) => p.take_rows(max),
-            StreamingParserEnum::GeneralNewlines(p) => p.take_rows(max),
+            StreamingParserEnum::SingleByte(p) => p.set_max_pending_rows(max),
+            StreamingParserEnum::General(p) => p.set_max_pending_rows(max),
+            StreamingParserEnum::GeneralNewlines(p) => p.set_max_pending_rows(max),
+        }
+    }
+
+    /// Whether buffered bytes are left unscanned until rows are taken
+    pub fn needs_drain(&self) -> bool {
+        match self {
+            StreamingParserEnum::SingleByte(p) => p.needs_drain(),
+            StreamingParserEnum::General(p) => p.needs_drain(),
+            StreamingParserEnum::GeneralNewlines(p) => p.needs_drain(),
+        }
+    }
+
+    /// Enable header-aware mode (first row is the header, later rows are
+    /// fitted to its width) or disable it with `None`
+    pub fn set_width_policy(&mut self, policy: Option<WidthPolicy>) {
+        match self {
+            StreamingParserEnum::SingleByte(p) => p.set_width_policy(policy),
+            StreamingParserEnum::General(p) => p.set_width_policy(policy),
+            StreamingParserEnum::GeneralNewlines(p) => p.set_width_policy(policy),
+        }
+    }
+
+    fn width_policy(&self) -> Option<WidthPolicy> {
+        match self {
+            StreamingParserEnum::SingleByte(p) => p.width_policy(),
+            StreamingParserEnum::General(p) => p.width_policy(),
+            StreamingParserEnum::GeneralNewlines(p) => p.width_policy(),
+        }
+    }
+
+    /// The header row, once seen in header-aware mode
+    pub fn header(&self) -> Option<&[Vec<u8>]> {
+        match self {
+            StreamingParserEnum::SingleByte(p) => p.header(),
+            StreamingParserEnum::General(p) => p.header(),
+            StreamingParserEnum::GeneralNewlines(p) => p.header(),
+        }
+    }
+
+    /// The row width mismatch that stopped the parse, if any
+    pub fn width_error(&self) -> Option<RowWidthError> {
+        match self {
+            StreamingParserEnum::SingleByte(p) => p.width_error(),
+            StreamingParserEnum::General(p) => p.width_error(),
+            StreamingParserEnum::GeneralNewlines(p) => p.width_error(),
+        }
+    }
+
+    pub fn take_rows(&mut self, max: usize) -> Vec<Vec<StreamField>> {
+        match self {
+            StreamingParserEnum::SingleByte(p) => p.take_field_rows(max),
+            StreamingParserEnum::General(p) => owned_field_rows(p.take_rows(max)),
+            StreamingParserEnum::GeneralNewlines(p) => owned_field_rows(p.take_rows(max)),
         }
     }
 
@@ -64,60 +181,305 @@ impl StreamingParserEnum {
         }
     }
 
-    pub fn finalize(&mut self) -> Vec<Vec<Vec<u8>>> {
+    pub fn finalize(&mut self) -> Vec<Vec<StreamField>> {
         match self {
-            StreamingParserEnum::SingleByte(p) => p.finalize(),
-            StreamingParserEnum::General(p) => p.finalize(),
-            StreamingParserEnum::GeneralNewlines(p) => p.finalize(),
+            StreamingParserEnum::SingleByte(p) => p.finalize_fields(),
+            StreamingParserEnum::General(p) => owned_field_rows(p.finalize()),
+            StreamingParserEnum::GeneralNewlines(p) => owned_field_rows(p.finalize()),
+        }
+    }
+
+    /// Discard buffered data and rows, keeping configuration and allocations
+    pub fn reset(&mut self) {
+        match self {
+            StreamingParserEnum::SingleByte(p) => p.reset(),
+            StreamingParserEnum::General(p) => p.reset(),
+            StreamingParserEnum::GeneralNewlines(p) => p.reset(),
+        }
+    }
+
+    /// Cumulative counters since creation or the last reset
+    pub fn stats(&self) -> StreamStats {
+        match self {
+            StreamingParserEnum::SingleByte(p) => p.stats(),
+            StreamingParserEnum::General(p) => p.stats(),
+            StreamingParserEnum::GeneralNewlines(p) => p.stats(),
+        }
+    }
+
+    /// Offset and count of the rows handed out so far
+    pub fn checkpoint(&self) -> Checkpoint {
+        match self {
+            StreamingParserEnum::SingleByte(p) => p.checkpoint(),
+            StreamingParserEnum::General(p) => p.checkpoint(),
+            StreamingParserEnum::GeneralNewlines(p) => p.checkpoint(),
+        }
+    }
+
+    /// Continue offsets and row counts from `checkpoint` (parser must be fed
+    /// the input starting at `checkpoint.offset`)
+    pub fn resume_at(&mut self, checkpoint: Checkpoint) {
+        match self {
+            StreamingParserEnum::SingleByte(p) => p.resume_at(checkpoint),
+            StreamingParserEnum::General(p) => p.resume_at(checkpoint),
+            StreamingParserEnum::GeneralNewlines(p) => p.resume_at(checkpoint),
+        }
+    }
+
+    /// Replace this parser with `next` (a fresh parser for a new separator,
+    /// escape or newline config), carrying over the buffer allocation, the
+    /// buffer and pending row limits and the width policy. Buffered data and
+    /// rows are discarded.
+    pub fn reconfigure(&mut self, mut next: StreamingParserEnum) {
+        next.set_max_buffer_size(self.max_buffer_size());
+        next.set_max_pending_rows(self.max_pending_rows());
+        next.set_width_policy(self.width_policy());
+        next.reuse_buffer(self.take_buffer());
+        *self = next;
+    }
+
+    fn max_buffer_size(&self) -> usize {
+        match self {
+            StreamingParserEnum::SingleByte(p) => p.max_buffer_size(),
+            StreamingParserEnum::General(p) => p.max_buffer_size(),
+            StreamingParserEnum::GeneralNewlines(p) => p.max_buffer_size(),
+        }
+    }
+
+    fn max_pending_rows(&self) -> usize {
+        match self {
+            StreamingParserEnum::SingleByte(p) => p.max_pending_rows(),
+            StreamingParserEnum::General(p) => p.max_pending_rows(),
+            StreamingParserEnum::GeneralNewlines(p) => p.max_pending_rows(),
+        }
+    }
+
+    fn take_buffer(&mut self) -> Vec<u8> {
+        match self {
+            StreamingParserEnum::SingleByte(p) => p.take_buffer(),
+            StreamingParserEnum::General(p) => p.take_buffer(),
+            StreamingParserEnum::GeneralNewlines(p) => p.take_buffer(),
+        }
+    }
+
+    fn reuse_buffer(&mut self, buffer: Vec<u8>) {
+        match self {
+            StreamingParserEnum::SingleByte(p) => p.reuse_buffer(buffer),
+            StreamingParserEnum::General(p) => p.reuse_buffer(buffer),
+            StreamingParserEnum::GeneralNewlines(p) => p.reuse_buffer(buffer),
         }
     }
 }
 
-/// Wrapper for StreamingParser that can be stored in a ResourceArc
-pub struct StreamingParserResource {
-    pub inner: Mutex<StreamingParserEnum>,
+fn owned_field_rows(rows: Vec<Vec<Vec<u8>>>) -> Vec<Vec<StreamField>> {
+    rows.into_iter()
+        .map(|row| row.into_iter().map(StreamField::Owned).collect())
+        .collect()
 }
 
-impl StreamingParserResource {
-    pub fn new() -> Self {
-        StreamingParserResource {
-            inner: Mutex::new(StreamingParserEnum::SingleByte(StreamingParser::new())),
+/// A chunk fed to a zero-copy parser, saved in its own process-independent
+/// env so its binary outlives the `streaming_feed` call.
+struct FedChunk {
+    start: u64,
+    len: usize,
+    /// Holds the saved binary reference; freed when the chunk is dropped
+    _env: OwnedEnv,
+    binary: SavedTerm,
+}
+
+/// Fed chunks that rows still to be taken may be spans of, oldest first.
+/// Saving a refc binary only copies its reference, not the data.
+#[derive(Default)]
+pub struct FedChunks {
+    chunks: VecDeque<FedChunk>,
+}
+
+impl FedChunks {
+    /// Keep `chunk`, which the parser reads from stream offset `start`
+    pub fn push<'a>(&mut self, env: Env<'a>, start: u64, chunk: Binary<'a>) {
+        if chunk.is_empty() {
+            return;
         }
+        let owned_env = OwnedEnv::new();
+        let binary = owned_env.save(chunk.encode(env));
+        self.chunks.push_back(FedChunk {
+            start,
+            len: chunk.len(),
+            _env: owned_env,
+            binary,
+        });
     }
 
-    pub fn with_config(separator: u8, escape: u8) -> Self {
-        StreamingParserResource {
-            inner: Mutex::new(StreamingParserEnum::SingleByte(
-                StreamingParser::with_config(separator, escape),
-            )),
+    /// Load the kept chunks into `env` for building sub-binaries
+    pub fn load<'a>(&self, env: Env<'a>) -> NifResult<LoadedChunks<'a>> {
+        let chunks = self
+            .chunks
+            .iter()
+            .map(|chunk| Ok((chunk.start, chunk.binary.load(env).decode::<Binary>()?)))
+            .collect::<NifResult<_>>()?;
+        Ok(LoadedChunks::new(chunks))
+    }
+
+    /// Drop chunks ending at or before `offset` (every row up to there has
+    /// been taken, so no pending row can be a span of them)
+    pub fn release_before(&mut self, offset: u64) {
+        while let Some(chunk) = self.chunks.front() {
+            if chunk.start + chunk.len as u64 > offset {
+                break;
+            }
+            self.chunks.pop_front();
+        }
+    }
+
+    pub fn clear(&mut self) {
+        self.chunks.clear();
+    }
+}
+
+/// Map output state for a streaming parser (headers-to-maps).
+pub struct StreamingHeaders {
+    /// Map keys; `None` until the header row has been read (`headers: true`)
+    pub keys: Option<Vec<HeaderKey>>,
+    /// Drop the first row (header row of a file read with explicit keys)
+    pub skip_first: bool,
+    /// Header mode as configured, restored by `reset`
+    pub keys_from_header: bool,
+    pub skip_header: bool,
+    pub duplicate_headers: DuplicateHeaders,
+    pub extra_columns: ExtraColumns<HeaderKey>,
+    pub missing_columns: MissingColumns,
+    /// Records taken so far, including the header row (for error row numbers)
+    pub rows_taken: usize,
+}
+
+impl StreamingHeaders {
+    /// Leading rows still to be consumed before maps are produced (0 or 1)
+    pub fn pending_rows(&self) -> usize {
+        usize::from(self.keys.is_none() || self.skip_first)
+    }
+
+    /// Consume the header row (or the skipped first row) from the front of `rows`
+    pub fn consume_header(
+        &mut self,
+        rows: &mut Vec<Vec<StreamField>>,
+        chunks: &LoadedChunks,
+    ) -> NifResult<()> {
+        if rows.is_empty() || self.pending_rows() == 0 {
+            return Ok(());
+        }
+        self.rows_taken += 1;
+        if self.keys.is_none() {
+            let header = rows.remove(0);
+            self.keys = Some(
+                header
+                    .into_iter()
+                    .map(|field| chunks.field_bytes(field).map(HeaderKey::Binary))
+                    .collect::<NifResult<_>>()?,
+            );
+        } else if self.skip_first {
+            rows.remove(0);
+            self.skip_first = false;
+        }
+        Ok(())
+    }
+
+    /// Return to the state before any rows were taken (next row is a header again)
+    pub fn reset(&mut self) {
+        if self.keys_from_header {
+            self.keys = None;
+        }
+        self.skip_first = self.skip_header;
+        self.rows_taken = 0;
+    }
+}
+
+/// Looking for a `sep=` first line in a streaming parser's input
+pub struct SepDirectiveState {
+    /// The configured separators, escape and newlines, which the parser is
+    /// rebuilt from with the directive's separator (or on reset)
+    pub separators: Vec<Vec<u8>>,
+    pub escape: Vec<u8>,
+    pub newlines: Newlines,
+    /// Input held back (as UTF-8) until the first line is known; `None` once
+    /// it has been
+    pub held: Option<Vec<u8>>,
+}
+
+impl SepDirectiveState {
+    pub fn new(separators: Vec<Vec<u8>>, escape: Vec<u8>, newlines: Newlines) -> Self {
+        SepDirectiveState {
+            separators,
+            escape,
+            newlines,
+            held: Some(Vec::new()),
         }
     }
 
-    pub fn with_multi_sep(separators: &[u8], escape: u8) -> Self {
+    /// Whether the first line is still to be checked
+    pub fn is_pending(&self) -> bool {
+        self.held.is_some()
+    }
+
+    /// Check the first line of the next input again
+    pub fn reset(&mut self) {
+        self.held = Some(Vec::new());
+    }
+}
+
+/// File a parser opened by `streaming_open_file` reads its input from
+pub struct FileSource {
+    /// `None` once the end of the file was reached or the file was closed
+    pub file: Option<File>,
+    /// Bytes read into the parser buffer per read
+    pub chunk_size: usize,
+}
+
+/// Wrapper for StreamingParser that can be stored in a ResourceArc
+pub struct StreamingParserResource {
+    /// `Some` for parsers reading a file themselves. Always locked before `inner`.
+    pub source: Mutex<Option<FileSource>>,
+    pub inner: Mutex<StreamingParserEnum>,
+    /// `Some` when a `sep=` first line picks the separator. Always locked
+    /// after `inner`.
+    pub directive: Mutex<Option<SepDirectiveState>>,
+    /// `Some` when input is decoded to UTF-8 as it is fed. Always locked
+    /// after `directive`.
+    pub decoder: Mutex<Option<Decoder>>,
+    /// `Some` when rows are returned as maps. Always locked after `decoder`.
+    pub headers: Mutex<Option<StreamingHeaders>>,
+    /// Chunks backing zero-copy rows. Always locked after `headers`.
+    pub chunks: Mutex<FedChunks>,
+}
+
+impl StreamingParserResource {
+    pub fn from_parser(parser: StreamingParserEnum) -> Self {
         StreamingParserResource {
-            inner: Mutex::new(StreamingParserEnum::SingleByte(
-                StreamingParser::with_multi_sep(separators, escape),
-            )),
+            source: Mutex::new(None),
+            inner: Mutex::new(parser),
+            directive: Mutex::new(None),
+            decoder: Mutex::new(None),
+            headers: Mutex::new(None),
+            chunks: Mutex::new(FedChunks::default()),
         }
     }
 
-    pub fn with_general(separators: Vec<Vec<u8>>, escape: Vec<u8>) -> Self {
+    pub fn new() -> Self {
+        Self::from_parser(StreamingParserEnum::with_config(b',', b'"'))
+    }
+
+    /// A parser that reads its input from `source`
+    pub fn with_source(parser: StreamingParserEnum, source: FileSource) -> Self {
         StreamingParserResource {
-            inner: Mutex::new(StreamingParserEnum::General(GeneralStreamingParser::new(
-                separators, escape,
-            ))),
+            source: Mutex::new(Some(source)),
+            ..Self::from_parser(parser)
         }
     }
 
-    pub fn with_general_newlines(
-        separators: Vec<Vec<u8>>,
-        escape: Vec<u8>,
-        newlines: Newlines,
-    ) -> Self {
+    /// Look for a `sep=` first line as `directive` says (`None`: don't)
+    pub fn with_directive(self, directive: Option<SepDirectiveState>) -> Self {
         StreamingParserResource {
-            inner: Mutex::new(StreamingParserEnum::GeneralNewlines(
-                GeneralStreamingParserNewlines::new(separators, escape, newlines),
-            )),
+            directive: Mutex::new(directive),
+            ..self
         }
     }
 }
@@ -0,0 +1,379 @@
+// Character-encoding detection for input without a BOM
+//
+// Scores each encoding a sample might be in from UTF-8 validity, the
+// positions of NUL bytes (UTF-16 and UTF-32 text is mostly ASCII with a NUL
+// high byte), and how plausible the characters a single-byte codepage decodes
+// the high bytes to are as words.
+
+use super::encoding::{
+    decoded_len, sniff_bom, EncodingTarget, CP1250, CP1251, CP1252, CP437, CP850, ISO_8859_15,
+    KOI8_R,
+};
+
+/// Single-byte candidates, in the order ties are listed
+static SINGLE_BYTE: [EncodingTarget; 8] = [
+    EncodingTarget::Codepage(&CP1252),
+    EncodingTarget::Latin1,
+    EncodingTarget::Codepage(&ISO_8859_15),
+    EncodingTarget::Codepage(&CP1250),
+    EncodingTarget::Codepage(&CP1251),
+    EncodingTarget::Codepage(&KOI8_R),
+    EncodingTarget::Codepage(&CP850),
+    EncodingTarget::Codepage(&CP437),
+];
+
+/// Candidate encodings of `sample` with a confidence in `0.0..=1.0`, most
+/// likely first. Encodings with no chance are left out.
+///
+/// A BOM is certain. ASCII-only samples are reported as UTF-8 alone, since
+/// every ASCII-compatible encoding reads them the same. The sample may end
+/// part-way through a character.
+pub fn detect_encoding(sample: &[u8]) -> Vec<(EncodingTarget, f64)> {
+    if let Some((encoding, _)) = sniff_bom(sample) {
+        return vec![(encoding, 1.0)];
+    }
+
+    // NULs are rare in text, so they count against the byte-oriented
+    // encodings; in ASCII-heavy UTF-16 every other byte is one
+    let nuls = sample.iter().filter(|&&b| b == 0).count();
+    let byte_oriented = match sample.len() {
+        0 => 1.0,
+        len => (1.0 - 2.0 * nuls as f64 / len as f64).max(0.0),
+    };
+
+    let mut candidates = Vec::new();
+    if nuls > 0 {
+        for encoding in [
+            EncodingTarget::Utf32Le,
+            EncodingTarget::Utf32Be,
+            EncodingTarget::Utf16Le,
+            EncodingTarget::Utf16Be,
+        ] {
+            candidates.push((encoding, 0.95 * wide_score(sample, encoding)));
+        }
+    }
+
+    let utf8 = match std::str::from_utf8(sample) {
+        Ok(_) => true,
+        // Cut off at the end of the sample
+        Err(error) => error.error_len().is_none(),
+    };
+    let ascii = sample.iter().all(|&b| b < 0x80);
+    if utf8 {
+        candidates.push((EncodingTarget::Utf8, byte_oriented));
+    }
+    if !ascii {
+        // Valid UTF-8 with multi-byte sequences is rarely anything else
+        let weight = if utf8 { 0.45 } else { 0.9 };
+        for &encoding in &SINGLE_BYTE {
+            let score = match encoding {
+                EncodingTarget::Codepage(codepage) => {
+                    single_byte_score(sample, |b| codepage.decode(b))
+                }
+                _ => single_byte_score(sample, char::from),
+            };
+            candidates.push((encoding, weight * score * byte_oriented));
+        }
+    }
+
+    candidates.retain(|&(_, confidence)| confidence > 0.0);
+    for (_, confidence) in candidates.iter_mut() {
+        *confidence = (*confidence * 1000.0).round() / 1000.0;
+    }
+    // Stable, so ties keep the order above
+    candidates.sort_by(|a, b| b.1.total_cmp(&a.1));
+    candidates
+}
+
+/// Share of UTF-16/UTF-32 code units that are Latin, Greek or Cyrillic
+/// characters (which have a NUL high byte or a small one), or 0 if the sample
+/// is not valid in `encoding`
+fn wide_score(sample: &[u8], encoding: EncodingTarget) -> f64 {
+    let (width, big_endian) = match encoding {
+        EncodingTarget::Utf16Le => (2, false),
+        EncodingTarget::Utf16Be => (2, true),
+        EncodingTarget::Utf32Le => (4, false),
+        EncodingTarget::Utf32Be => (4, true),
+        _ => return 0.0,
+    };
+    let whole = &sample[..sample.len() - sample.len() % width];
+    match decoded_len(whole, encoding) {
+        Ok(_) => {}
+        Err(error) if error.incomplete => {}
+        Err(_) => return 0.0,
+    }
+
+    let mut units = 0;
+    let mut good = 0;
+    for unit in whole.chunks_exact(width) {
+        let value = unit
+            .iter()
+            .fold(0u32, |value, &b| (value << 8) | u32::from(b));
+        let value = if big_endian {
+            value
+        } else {
+            value.swap_bytes() >> (32 - 8 * width)
+        };
+        units += 1;
+        if value != 0 && value < 0x0500 {
+            good += 1;
+        }
+    }
+    match units {
+        0 => 0.0,
+        _ => good as f64 / units as f64,
+    }
+}
+
+/// How plausible `sample` is as text in a single-byte encoding, from 0 to 1.
+///
+/// Each word containing a high byte, and each high byte that decodes to
+/// something other than a letter, counts as one unit. A word is plausible when
+/// its letters share a script (non-Latin ones without ASCII letters mixed in,
+/// Latin ones with ASCII letters or at most two accented ones) and it has no
+/// capital after a lowercase letter. Mostly-lowercase text scores higher,
+/// which tells apart codepages that swap the cases (Windows-1251 and KOI8-R).
+fn single_byte_score(sample: &[u8], decode: impl Fn(u8) -> char) -> f64 {
+    let mut plausible = 0.0;
+    let mut units = 0.0;
+    let mut lower = 0usize;
+    let mut upper = 0usize;
+    let mut word = Word::default();
+
+    for &b in sample {
+        let ch = decode(b);
+        if ch.is_alphabetic() {
+            if b >= 0x80 {
+                lower += usize::from(ch.is_lowercase());
+                upper += usize::from(ch.is_uppercase());
+            }
+            word.push(ch, b >= 0x80);
+            continue;
+        }
+        if let Some(score) = word.finish() {
+            plausible += score;
+            units += 1.0;
+        }
+        if b >= 0x80 {
+            plausible += symbol_score(ch);
+            units += 1.0;
+        }
+    }
+    if let Some(score) = word.finish() {
+        plausible += score;
+        units += 1.0;
+    }
+
+    if units == 0.0 {
+        return 0.0;
+    }
+    let lowercase = match lower + upper {
+        0 => 1.0,
+        letters => lower as f64 / letters as f64,
+    };
+    plausible / units * (0.8 + 0.2 * lowercase)
+}
+
+#[derive(Clone, Copy, PartialEq, Eq)]
+enum Script {
+    Latin,
+    Greek,
+    Cyrillic,
+    Other,
+}
+
+fn script(ch: char) -> Script {
+    match ch {
+        '\u{0000}'..='\u{024F}' | '\u{1E00}'..='\u{1EFF}' => Script::Latin,
+        '\u{0370}'..='\u{03FF}' => Script::Greek,
+        '\u{0400}'..='\u{04FF}' => Script::Cyrillic,
+        _ => Script::Other,
+    }
+}
+
+/// A run of letters being scored by `single_byte_score`
+#[derive(Default)]
+struct Word {
+    ascii_letters: usize,
+    high_letters: usize,
+    script: Option<Script>,
+    mixed_scripts: bool,
+    case_break: bool,
+    after_lowercase: bool,
+}
+
+impl Word {
+    fn push(&mut self, ch: char, high: bool) {
+        if ch.is_uppercase() && self.after_lowercase {
+            self.case_break = true;
+        }
+        self.after_lowercase = ch.is_lowercase();
+        if !high {
+            self.ascii_letters += 1;
+            return;
+        }
+        self.high_letters += 1;
+        let script = script(ch);
+        if self.script.is_some_and(|seen| seen != script) {
+            self.mixed_scripts = true;
+        }
+        self.script = Some(script);
+    }
+
+    /// Score of the word, or `None` without high-byte letters; resets it
+    fn finish(&mut self) -> Option<f64> {
+        let word = std::mem::take(self);
+        if word.high_letters == 0 {
+            return None;
+        }
+        let plausible = !word.case_break
+            && !word.mixed_scripts
+            && match word.script {
+                Some(Script::Latin) => word.ascii_letters > 0 || word.high_letters <= 2,
+                Some(Script::Greek | Script::Cyrillic) => word.ascii_letters == 0,
+                _ => false,
+            };
+        Some(if plausible { 1.0 } else { 0.0 })
+    }
+}
+
+/// Plausibility of a high byte that decodes to a non-letter
+fn symbol_score(ch: char) -> f64 {
+    match ch {
+        // C1 controls, which Latin-1 and the undefined bytes decode to
+        '\u{0080}'..='\u{009F}' => 0.0,
+        // Latin-1 punctuation and symbols, general punctuation, currency
+        '\u{00A0}'..='\u{00BF}'
+        | '\u{00D7}'
+        | '\u{00F7}'
+        | '\u{2010}'..='\u{206F}'
+        | '\u{20A0}'..='\u{20CF}'
+        | '\u{2116}'
+        | '\u{2122}' => 1.0,
+        // Box drawing and blocks, as in the DOS codepages
+        '\u{2500}'..='\u{259F}' => 0.25,
+        _ => 0.5,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::strategy::encoding::encode_utf8_to_target;
+
+    fn top(sample: &[u8]) -> EncodingTarget {
+        detect_encoding(sample)[0].0
+    }
+
+    #[test]
+    fn test_bom_is_certain() {
+        assert_eq!(
+            detect_encoding(b"\xFF\xFEa\x00"),
+            vec![(EncodingTarget::Utf16Le, 1.0)]
+        );
+        assert_eq!(
+            detect_encoding(b"\xEF\xBB\xBFa"),
+            vec![(EncodingTarget::Utf8, 1.0)]
+        );
+    }
+
+    #[test]
+    fn test_ascii_is_utf8() {
+        assert_eq!(
+            detect_encoding(b"name,age\njohn,27\n"),
+            vec![(EncodingTarget::Utf8, 1.0)]
+        );
+        assert_eq!(detect_encoding(b""), vec![(EncodingTarget::Utf8, 1.0)]);
+    }
+
+    #[test]
+    fn test_utf8() {
+        let sample = "name,city\nJos\u{e9},Z\u{fc}rich\n".as_bytes();
+        let candidates = detect_encoding(sample);
+        assert_eq!(candidates[0], (EncodingTarget::Utf8, 1.0));
+        assert!(candidates[1..]
+            .iter()
+            .all(|&(_, confidence)| confidence < 0.5));
+
+        // Cut part-way through "é"
+        assert_eq!(top(&sample[..15]), EncodingTarget::Utf8);
+    }
+
+    #[test]
+    fn test_utf16_and_utf32_without_bom() {
+        let text = "name,note\nJos\u{e9},\u{41f}\u{440}\u{438}\u{432}\u{435}\u{442}\n";
+        for encoding in [
+            EncodingTarget::Utf16Le,
+            EncodingTarget::Utf16Be,
+            EncodingTarget::Utf32Le,
+            EncodingTarget::Utf32Be,
+        ] {
+            let sample = encode_utf8_to_target(text.as_bytes(), encoding);
+            let candidates = detect_encoding(&sample);
+            assert_eq!(candidates[0], (encoding, 0.95), "{encoding:?}");
+            // Cut part-way through a unit
+            assert_eq!(top(&sample[..sample.len() - 1]), encoding, "{encoding:?}");
+            assert!(
+                !candidates.iter().any(|&(e, _)| e == EncodingTarget::Utf8),
+                "{encoding:?}"
+            );
+        }
+    }
+
+    #[test]
+    fn test_single_byte() {
+        for (text, encoding) in [
+            (
+                "name,note\nJos\u{e9},\u{201C}caf\u{e9}\u{201D} \u{2013} \u{20AC}5\n",
+                EncodingTarget::Codepage(&CP1252),
+            ),
+            (
+                "name,city\nJos\u{e9},Z\u{fc}rich\n",
+                EncodingTarget::Codepage(&CP1252),
+            ),
+            (
+                "imi\u{119},miasto\n\u{141}ukasz,\u{141}\u{f3}d\u{17a}\n",
+                EncodingTarget::Codepage(&CP1250),
+            ),
+            (
+                "\u{418}\u{43c}\u{44f},\u{413}\u{43e}\u{440}\u{43e}\u{434}\n\
+                 \u{410}\u{43d}\u{43d}\u{430},\u{41c}\u{43e}\u{441}\u{43a}\u{432}\u{430}\n",
+                EncodingTarget::Codepage(&CP1251),
+            ),
+            (
+                "\u{418}\u{43c}\u{44f},\u{413}\u{43e}\u{440}\u{43e}\u{434}\n\
+                 \u{410}\u{43d}\u{43d}\u{430},\u{41c}\u{43e}\u{441}\u{43a}\u{432}\u{430}\n",
+                EncodingTarget::Codepage(&KOI8_R),
+            ),
+        ] {
+            let sample = encode_utf8_to_target(text.as_bytes(), encoding);
+            assert_eq!(top(&sample), encoding, "{text}");
+        }
+    }
+
+    #[test]
+    fn test_latin1_ties_windows_1252_without_c1_bytes() {
+        let candidates = detect_encoding(b"Jos\xe9,Z\xfcrich\n");
+        assert_eq!(candidates[0].0, EncodingTarget::Codepage(&CP1252));
+        assert_eq!(candidates[1].0, EncodingTarget::Latin1);
+        assert_eq!(candidates[0].1, candidates[1].1);
+
+        // 0x93/0x94 are C1 controls in Latin-1
+        let candidates = detect_encoding(b"\x93caf\xe9\x94\n");
+        let latin1 = candidates
+            .iter()
+            .find(|&&(e, _)| e == EncodingTarget::Latin1)
+            .map_or(0.0, |&(_, confidence)| confidence);
+        assert!(latin1 < candidates[0].1);
+    }
+
+    #[test]
+    fn test_confidences_are_sorted_and_bounded() {
+        let samples: [&[u8]; 4] = [b"\x00\x00\xff", b"\xc3(", b"a\x00\x00\x00\xff", b"\x80\x81"];
+        for sample in samples {
+            let candidates = detect_encoding(sample);
+            assert!(candidates.windows(2).all(|pair| pair[0].1 >= pair[1].1));
+            assert!(candidates.iter().all(|&(_, c)| c > 0.0 && c <= 1.0));
+        }
+    }
+}
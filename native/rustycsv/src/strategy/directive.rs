@@ -0,0 +1,156 @@
+// `sep=` directive detection
+//
+// Excel writes (and reads) a first line like `sep=;` naming the separator
+// of the rest of the file. The directive is one character after `sep=`,
+// ended by `\r\n`, `\n` or the end of the input. A UTF-8 BOM may come
+// first, as in the files Excel saves as "CSV UTF-8".
+
+const PREFIX: &[u8] = b"sep=";
+
+const BOM: &[u8] = b"\xEF\xBB\xBF";
+
+/// Longest line that can still be a directive: the prefix, a 4-byte UTF-8
+/// character and `\r\n`
+const MAX_DIRECTIVE: usize = PREFIX.len() + 4 + 2;
+
+/// Longest start of the input that can still hold a directive: a BOM and
+/// the longest directive line
+pub const MAX_LINE: usize = BOM.len() + MAX_DIRECTIVE;
+
+/// What the start of an input says about a `sep=` directive
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum SepDirective {
+    /// Too little input to tell yet (only when more may follow)
+    Undecided,
+    /// The input doesn't start with a directive
+    Absent,
+    /// A directive naming `separator`, on a first line `len` bytes long
+    /// (a BOM before it and the line ending included)
+    Found { separator: Vec<u8>, len: usize },
+}
+
+/// Look for a `sep=` directive at the start of `input`. `at_end` says no
+/// more input follows, so a first line without a line ending is complete.
+pub fn find_sep_directive(input: &[u8], at_end: bool) -> SepDirective {
+    let bom = if input.starts_with(BOM) { BOM.len() } else { 0 };
+    if bom == 0 && !at_end && !input.is_empty() && BOM.starts_with(input) {
+        return SepDirective::Undecided;
+    }
+    let input = &input[bom..];
+    if !PREFIX.starts_with(&input[..input.len().min(PREFIX.len())]) {
+        return SepDirective::Absent;
+    }
+    if input.len() < PREFIX.len() {
+        return if at_end {
+            SepDirective::Absent
+        } else {
+            SepDirective::Undecided
+        };
+    }
+
+    let rest = &input[PREFIX.len()..input.len().min(MAX_DIRECTIVE)];
+    let (value, len) = match rest.iter().position(|&b| b == b'\n') {
+        Some(newline) => (&rest[..newline], bom + PREFIX.len() + newline + 1),
+        None if at_end && input.len() < MAX_DIRECTIVE => (rest, bom + input.len()),
+        None if input.len() < MAX_DIRECTIVE => return SepDirective::Undecided,
+        None => return SepDirective::Absent,
+    };
+    let value = value.strip_suffix(b"\r").unwrap_or(value);
+
+    match std::str::from_utf8(value) {
+        Ok(s) if s.chars().count() == 1 => SepDirective::Found {
+            separator: value.to_vec(),
+            len,
+        },
+        _ => SepDirective::Absent,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn found(separator: &[u8], len: usize) -> SepDirective {
+        SepDirective::Found {
+            separator: separator.to_vec(),
+            len,
+        }
+    }
+
+    #[test]
+    fn test_directive_line_endings() {
+        assert_eq!(find_sep_directive(b"sep=;\na;b\n", true), found(b";", 6));
+        assert_eq!(find_sep_directive(b"sep=;\r\na;b\n", false), found(b";", 7));
+        assert_eq!(find_sep_directive(b"sep=\t\n", false), found(b"\t", 6));
+        assert_eq!(find_sep_directive(b"sep=|", true), found(b"|", 5));
+        assert_eq!(find_sep_directive(b"sep=|\r", true), found(b"|", 6));
+        assert_eq!(
+            find_sep_directive("sep=¦\nx".as_bytes(), true),
+            found("¦".as_bytes(), 7)
+        );
+    }
+
+    #[test]
+    fn test_directive_after_bom() {
+        assert_eq!(
+            find_sep_directive(b"\xEF\xBB\xBFsep=;\n", true),
+            found(b";", 9)
+        );
+        assert_eq!(
+            find_sep_directive(b"\xEF\xBB\xBFsep=;\r\na;b\n", false),
+            found(b";", 10)
+        );
+        assert_eq!(
+            find_sep_directive(b"\xEF\xBB\xBFsep=|", true),
+            found(b"|", 8)
+        );
+        assert_eq!(
+            find_sep_directive(b"\xEF\xBB\xBF", true),
+            SepDirective::Absent
+        );
+        // A BOM split across chunks
+        for input in [
+            &b"\xEF"[..],
+            b"\xEF\xBB",
+            b"\xEF\xBB\xBF",
+            b"\xEF\xBB\xBFsep=;",
+        ] {
+            assert_eq!(find_sep_directive(input, false), SepDirective::Undecided);
+        }
+    }
+
+    #[test]
+    fn test_not_a_directive() {
+        for input in [
+            &b"a,b\n"[..],
+            b"sep,x\n",
+            b"Sep=;\n",
+            b"sep=\n",
+            b"sep=;;\n",
+            b"sep=\r\n",
+            b"\xEF\xBB\xBFa,b\n",
+            b"\xEF\xBB\xBFsep=;;\n",
+            b"\xEFsep=;\n",
+            b"sep=\xFF\n",
+        ] {
+            assert_eq!(find_sep_directive(input, true), SepDirective::Absent);
+            assert_eq!(find_sep_directive(input, false), SepDirective::Absent);
+        }
+        assert_eq!(find_sep_directive(b"", true), SepDirective::Absent);
+        assert_eq!(find_sep_directive(b"se", true), SepDirective::Absent);
+        assert_eq!(find_sep_directive(b"sep=", true), SepDirective::Absent);
+        // No line ending within the longest possible directive line
+        assert_eq!(
+            find_sep_directive(b"sep=abcdefgh", false),
+            SepDirective::Absent
+        );
+    }
+
+    #[test]
+    fn test_undecided_until_first_line_known() {
+        for input in [&b""[..], b"s", b"sep", b"sep=", b"sep=;", b"sep=;\r"] {
+            assert_eq!(find_sep_directive(input, false), SepDirective::Undecided);
+        }
+        assert_eq!(find_sep_directive(b"x", false), SepDirective::Absent);
+    }
+}
@@ -10,6 +10,7 @@
 //   SIMD:    portable_simd 16/32-byte vectorized comparison (fastest)
 //   General: byte-by-byte for multi-byte separator/escape patterns
 
+use std::borrow::Cow;
 use std::simd::prelude::*;
 
 use crate::core::simd_scanner::CHUNK;
@@ -301,6 +302,64 @@ pub fn field_needs_quoting_general(
     false
 }
 
+// ==========================================================================
+// Excel: formula escaping and numbers-as-text
+// ==========================================================================
+
+/// Digits Excel keeps of a number; longer IDs lose their tail to zeros
+const EXCEL_DIGITS: usize = 15;
+
+/// Excel: the cell text to write for `field`.
+///
+/// Fields Excel would run as a formula (starting with `=`, `+`, `-`, `@`,
+/// tab or CR) get a `'` prefix, unless they are a plain number like
+/// `-12.50`. Digit strings Excel would turn into a number and mangle (a
+/// leading zero, or more than 15 digits) become `="00123"`, which Excel
+/// shows as the text `00123`. Anything else is returned as it is.
+pub fn excel_field(field: &[u8]) -> Cow<'_, [u8]> {
+    if excel_keep_as_text(field) {
+        let mut out = Vec::with_capacity(field.len() + 3);
+        out.extend_from_slice(b"=\"");
+        out.extend_from_slice(field);
+        out.push(b'"');
+        return Cow::Owned(out);
+    }
+    match field.first() {
+        Some(b'=' | b'+' | b'-' | b'@' | b'\t' | b'\r') if !is_plain_number(field) => {
+            let mut out = Vec::with_capacity(field.len() + 1);
+            out.push(b'\'');
+            out.extend_from_slice(field);
+            Cow::Owned(out)
+        }
+        _ => Cow::Borrowed(field),
+    }
+}
+
+#[inline]
+fn excel_keep_as_text(field: &[u8]) -> bool {
+    field.len() > 1
+        && field.iter().all(u8::is_ascii_digit)
+        && (field[0] == b'0' || field.len() > EXCEL_DIGITS)
+}
+
+/// An optionally signed decimal number: `7`, `-12.50`, `+.5`
+fn is_plain_number(field: &[u8]) -> bool {
+    let digits = match field.first() {
+        Some(b'+' | b'-') => &field[1..],
+        _ => field,
+    };
+    let mut seen_digit = false;
+    let mut seen_point = false;
+    for &b in digits {
+        match b {
+            b'0'..=b'9' => seen_digit = true,
+            b'.' if !seen_point => seen_point = true,
+            _ => return false,
+        }
+    }
+    seen_digit
+}
+
 // ==========================================================================
 // Tests
 // ==========================================================================
@@ -441,4 +500,22 @@ mod tests {
             &[b'@']
         ));
     }
+
+    #[test]
+    fn test_excel_field() {
+        assert_eq!(&*excel_field(b"hello"), b"hello");
+        assert_eq!(&*excel_field(b""), b"");
+        assert_eq!(&*excel_field(b"=SUM(A1:A2)"), b"'=SUM(A1:A2)");
+        assert_eq!(&*excel_field(b"@cmd"), b"'@cmd");
+        assert_eq!(&*excel_field(b"-"), b"'-");
+        assert_eq!(&*excel_field(b"-1+1"), b"'-1+1");
+        assert_eq!(&*excel_field(b"-12.50"), b"-12.50");
+        assert_eq!(&*excel_field(b"+3"), b"+3");
+        assert_eq!(&*excel_field(b"0"), b"0");
+        assert_eq!(&*excel_field(b"1234"), b"1234");
+        assert_eq!(&*excel_field(b"00123"), b"=\"00123\"");
+        assert_eq!(&*excel_field(b"123456789012345"), b"123456789012345");
+        assert_eq!(&*excel_field(b"1234567890123456"), b"=\"1234567890123456\"");
+        assert_eq!(&*excel_field(b"0.5"), b"0.5");
+    }
 }
@@ -1,13 +1,19 @@
-// UTF-8 → target encoding converters
+// UTF-8 ↔ other encoding converters
 //
 // Pure-Rust implementations for converting UTF-8 encoded bytes to other
-// character encodings. No external crate dependencies.
+// character encodings, and for decoding input in those encodings to UTF-8.
+// No external crate dependencies.
 
-/// Target encoding for output conversion.
+use std::borrow::Cow;
+use std::sync::OnceLock;
+
+/// Target encoding for output conversion (and source encoding for decoding).
 #[derive(Debug, Clone, Copy, PartialEq, Eq)]
 pub enum EncodingTarget {
     Utf8,
     Latin1,
+    /// Single-byte codepage from a mapping table
+    Codepage(&'static Codepage),
     Utf16Le,
     Utf16Be,
     Utf32Le,
@@ -22,6 +28,11 @@ pub fn encode_utf8_to_target(input: &[u8], target: EncodingTarget) -> Vec<u8> {
     match target {
         EncodingTarget::Utf8 => input.to_vec(),
         EncodingTarget::Latin1 => utf8_to_latin1(input),
+        EncodingTarget::Codepage(codepage) => {
+            let mut out = Vec::with_capacity(input.len());
+            extend_codepage(&mut out, input, codepage);
+            out
+        }
         EncodingTarget::Utf16Le => utf8_to_utf16(input, false),
         EncodingTarget::Utf16Be => utf8_to_utf16(input, true),
         EncodingTarget::Utf32Le => utf8_to_utf32(input, false),
@@ -105,6 +116,7 @@ pub fn encode_utf8_extend(out: &mut Vec<u8>, input: &[u8], target: EncodingTarge
     match target {
         EncodingTarget::Utf8 => out.extend_from_slice(input),
         EncodingTarget::Latin1 => extend_latin1(out, input),
+        EncodingTarget::Codepage(codepage) => extend_codepage(out, input, codepage),
         EncodingTarget::Utf16Le => extend_utf16(out, input, false),
         EncodingTarget::Utf16Be => extend_utf16(out, input, true),
         EncodingTarget::Utf32Le => extend_utf32(out, input, false),
@@ -113,28 +125,207 @@ pub fn encode_utf8_extend(out: &mut Vec<u8>, input: &[u8], target: EncodingTarge
 }
 
 fn extend_latin1(out: &mut Vec<u8>, input: &[u8]) {
+    let _ = extend_single_byte(out, input, latin1_byte, Unmappable::default());
+}
+
+fn extend_codepage(out: &mut Vec<u8>, input: &[u8], codepage: &Codepage) {
+    let _ = extend_single_byte(out, input, |ch| codepage.encode(ch), Unmappable::default());
+}
+
+/// What to write for a character the target encoding has no byte for.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Unmappable<'a> {
+    /// Write these bytes instead (`?` by default)
+    Replace(&'a [u8]),
+    /// Stop at the character and report it
+    Error,
+    /// Write an ASCII look-alike (`“` → `"`), or `?` when there is none
+    Transliterate,
+}
+
+impl Default for Unmappable<'_> {
+    fn default() -> Self {
+        Unmappable::Replace(b"?")
+    }
+}
+
+/// Encode UTF-8 bytes and append to output buffer, handling characters the
+/// target has no byte for as `unmappable` says.
+///
+/// Only the single-byte targets have such characters. For `Unmappable::Error`
+/// returns the first one, with everything before it already in `out`.
+pub fn encode_utf8_extend_with(
+    out: &mut Vec<u8>,
+    input: &[u8],
+    target: EncodingTarget,
+    unmappable: Unmappable,
+) -> Result<(), char> {
+    match target {
+        EncodingTarget::Latin1 => extend_single_byte(out, input, latin1_byte, unmappable),
+        EncodingTarget::Codepage(codepage) => {
+            extend_single_byte(out, input, |ch| codepage.encode(ch), unmappable)
+        }
+        _ => {
+            encode_utf8_extend(out, input, target);
+            Ok(())
+        }
+    }
+}
+
+fn latin1_byte(ch: char) -> Option<u8> {
+    u8::try_from(u32::from(ch)).ok()
+}
+
+/// What to do with field bytes that are not valid UTF-8.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum InvalidUtf8 {
+    /// Copy them as they are (the converters pass them through unchanged)
+    Passthrough,
+    /// Replace each invalid sequence with U+FFFD
+    Replace,
+    /// Stop at the first invalid sequence and report its offset
+    Error,
+}
+
+/// Apply the `invalid_utf8` policy to a field. Valid UTF-8 is borrowed as-is;
+/// for `InvalidUtf8::Error` returns the byte offset of the first invalid sequence.
+pub fn check_utf8(input: &[u8], invalid_utf8: InvalidUtf8) -> Result<Cow<'_, [u8]>, usize> {
+    if invalid_utf8 == InvalidUtf8::Passthrough {
+        return Ok(Cow::Borrowed(input));
+    }
+    match std::str::from_utf8(input) {
+        Ok(_) => Ok(Cow::Borrowed(input)),
+        Err(error) if invalid_utf8 == InvalidUtf8::Error => Err(error.valid_up_to()),
+        Err(_) => Ok(Cow::Owned(
+            String::from_utf8_lossy(input).into_owned().into_bytes(),
+        )),
+    }
+}
+
+/// With `Unmappable::Transliterate` or `Unmappable::Replace`, substitute the
+/// characters `target` has no byte for while the field is still UTF-8, so that
+/// quoting sees any quotes or separators the substitutes hold and they are
+/// encoded with the rest of the field. Characters of a replacement that the
+/// target can't encode either become `?`.
+pub fn substitute_unmappable<'a>(
+    input: &'a [u8],
+    target: EncodingTarget,
+    unmappable: Unmappable,
+) -> Cow<'a, [u8]> {
+    let encodes = |ch: char| match target {
+        EncodingTarget::Latin1 => latin1_byte(ch).is_some(),
+        EncodingTarget::Codepage(codepage) => codepage.encode(ch).is_some(),
+        _ => true,
+    };
+    if unmappable == Unmappable::Error || input.iter().all(|&b| b < 0x80) {
+        return Cow::Borrowed(input);
+    }
+    let s = match std::str::from_utf8(input) {
+        Ok(s) if !s.chars().all(encodes) => s,
+        _ => return Cow::Borrowed(input),
+    };
+    let replacement: String = match unmappable {
+        Unmappable::Replace(bytes) => String::from_utf8_lossy(bytes)
+            .chars()
+            .map(|ch| if encodes(ch) { ch } else { '?' })
+            .collect(),
+        _ => String::new(),
+    };
+    let mut out = String::with_capacity(s.len());
+    for ch in s.chars() {
+        if encodes(ch) {
+            out.push(ch);
+        } else if unmappable == Unmappable::Transliterate {
+            out.push_str(transliterate(ch).unwrap_or("?"));
+        } else {
+            out.push_str(&replacement);
+        }
+    }
+    Cow::Owned(out.into_bytes())
+}
+
+fn extend_single_byte(
+    out: &mut Vec<u8>,
+    input: &[u8],
+    encode: impl Fn(char) -> Option<u8>,
+    unmappable: Unmappable,
+) -> Result<(), char> {
     if input.iter().all(|&b| b < 0x80) {
         out.extend_from_slice(input);
-        return;
+        return Ok(());
     }
     let s = match std::str::from_utf8(input) {
         Ok(s) => s,
         Err(_) => {
             out.extend_from_slice(input);
-            return;
+            return Ok(());
         }
     };
     out.reserve(s.len());
     for ch in s.chars() {
-        let cp = ch as u32;
-        if cp <= 255 {
-            out.push(cp as u8);
-        } else {
-            out.push(b'?');
+        match (encode(ch), unmappable) {
+            (Some(byte), _) => out.push(byte),
+            (None, Unmappable::Replace(bytes)) => out.extend_from_slice(bytes),
+            (None, Unmappable::Error) => return Err(ch),
+            (None, Unmappable::Transliterate) => {
+                out.extend_from_slice(transliterate(ch).unwrap_or("?").as_bytes())
+            }
         }
     }
+    Ok(())
 }
 
+/// ASCII approximation of a character, for targets that lack it: typographic
+/// punctuation, and accented Latin letters without their marks.
+pub fn transliterate(ch: char) -> Option<&'static str> {
+    let ascii = match ch {
+        '\u{00C0}'..='\u{017F}' => LATIN_LETTERS[ch as usize - 0xC0],
+        '\u{02C6}' => "^",
+        '\u{02DC}' => "~",
+        '\u{2002}'..='\u{200A}' | '\u{202F}' => " ",
+        '\u{2010}'..='\u{2015}' | '\u{2212}' => "-",
+        '\u{2018}' | '\u{2019}' | '\u{201A}' | '\u{201B}' | '\u{2032}' => "'",
+        '\u{201C}' | '\u{201D}' | '\u{201E}' | '\u{201F}' | '\u{2033}' => "\"",
+        '\u{2022}' => "*",
+        '\u{2026}' => "...",
+        '\u{2039}' => "<",
+        '\u{203A}' => ">",
+        '\u{20AC}' => "EUR",
+        '\u{2122}' => "TM",
+        _ => return None,
+    };
+    Some(ascii)
+}
+
+/// U+00C0..U+017F (Latin-1 letters and Latin Extended-A) without diacritics.
+#[rustfmt::skip]
+const LATIN_LETTERS: [&str; 192] = [
+    "A", "A", "A", "A", "A", "A", "AE", "C", // U+00C0
+    "E", "E", "E", "E", "I", "I", "I", "I", // U+00C8
+    "D", "N", "O", "O", "O", "O", "O", "x", // U+00D0
+    "O", "U", "U", "U", "U", "Y", "TH", "ss", // U+00D8
+    "a", "a", "a", "a", "a", "a", "ae", "c", // U+00E0
+    "e", "e", "e", "e", "i", "i", "i", "i", // U+00E8
+    "d", "n", "o", "o", "o", "o", "o", "/", // U+00F0
+    "o", "u", "u", "u", "u", "y", "th", "y", // U+00F8
+    "A", "a", "A", "a", "A", "a", "C", "c", // U+0100
+    "C", "c", "C", "c", "C", "c", "D", "d", // U+0108
+    "D", "d", "E", "e", "E", "e", "E", "e", // U+0110
+    "E", "e", "E", "e", "G", "g", "G", "g", // U+0118
+    "G", "g", "G", "g", "H", "h", "H", "h", // U+0120
+    "I", "i", "I", "i", "I", "i", "I", "i", // U+0128
+    "I", "i", "IJ", "ij", "J", "j", "K", "k", // U+0130
+    "k", "L", "l", "L", "l", "L", "l", "L", // U+0138
+    "l", "L", "l", "N", "n", "N", "n", "N", // U+0140
+    "n", "'n", "N", "n", "O", "o", "O", "o", // U+0148
+    "O", "o", "OE", "oe", "R", "r", "R", "r", // U+0150
+    "R", "r", "S", "s", "S", "s", "S", "s", // U+0158
+    "S", "s", "T", "t", "T", "t", "T", "t", // U+0160
+    "U", "u", "U", "u", "U", "u", "U", "u", // U+0168
+    "U", "u", "U", "u", "W", "w", "Y", "y", // U+0170
+    "Y", "Z", "z", "Z", "z", "Z", "z", "s", // U+0178
+];
+
 fn extend_utf16(out: &mut Vec<u8>, input: &[u8], big_endian: bool) {
     let s = match std::str::from_utf8(input) {
         Ok(s) => s,
@@ -174,6 +365,564 @@ fn extend_utf32(out: &mut Vec<u8>, input: &[u8], big_endian: bool) {
     }
 }
 
+// ==========================================================================
+// Single-byte codepages
+// ==========================================================================
+
+/// A single-byte codepage: the character for each of the 256 bytes, and a
+/// reverse map from characters back to bytes, built on first use. Bytes
+/// 0x00-0x7F are ASCII in every codepage (the parsers rely on it), so a table
+/// only lists 0x80-0xFF.
+pub struct Codepage {
+    /// Windows codepage number, as in `{:codepage, number}`
+    pub number: u32,
+    chars: [u16; 256],
+    reverse: OnceLock<Vec<(u16, u8)>>,
+}
+
+impl Codepage {
+    const fn new(number: u32, high: [u16; 128]) -> Self {
+        let mut chars = [0u16; 256];
+        let mut i = 0;
+        while i < 256 {
+            chars[i] = if i < 0x80 { i as u16 } else { high[i - 0x80] };
+            i += 1;
+        }
+        Codepage {
+            number,
+            chars,
+            reverse: OnceLock::new(),
+        }
+    }
+
+    /// Character for a byte
+    pub fn decode(&self, b: u8) -> char {
+        char::from_u32(u32::from(self.chars[usize::from(b)])).unwrap_or('\u{FFFD}')
+    }
+
+    /// Byte for a character, if the codepage has one
+    pub fn encode(&self, ch: char) -> Option<u8> {
+        if ch.is_ascii() {
+            return Some(ch as u8);
+        }
+        let cp = u16::try_from(u32::from(ch)).ok()?;
+        let reverse = self.reverse.get_or_init(|| {
+            let mut reverse: Vec<(u16, u8)> = (0x80..=0xFF)
+                .map(|b| (self.chars[usize::from(b)], b))
+                .collect();
+            reverse.sort_unstable();
+            reverse
+        });
+        reverse
+            .binary_search_by_key(&cp, |&(c, _)| c)
+            .ok()
+            .map(|i| reverse[i].1)
+    }
+}
+
+impl PartialEq for Codepage {
+    fn eq(&self, other: &Self) -> bool {
+        self.number == other.number
+    }
+}
+
+impl Eq for Codepage {}
+
+impl std::fmt::Debug for Codepage {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "Codepage({})", self.number)
+    }
+}
+
+/// Look up a supported codepage by its Windows number
+pub fn codepage(number: u32) -> Option<&'static Codepage> {
+    CODEPAGES
+        .into_iter()
+        .find(|codepage| codepage.number == number)
+}
+
+/// Windows numbers of the supported codepages, in table order
+pub fn codepage_numbers() -> impl Iterator<Item = u32> {
+    CODEPAGES.into_iter().map(|codepage| codepage.number)
+}
+
+/// Every supported codepage. Adding one means adding its table below and
+/// listing it here.
+static CODEPAGES: [&Codepage; 7] = [
+    &CP437,
+    &CP850,
+    &CP1250,
+    &CP1251,
+    &CP1252,
+    &KOI8_R,
+    &ISO_8859_15,
+];
+
+/// IBM PC / DOS US
+#[rustfmt::skip]
+pub static CP437: Codepage = Codepage::new(
+    437,
+    [
+        0x00C7, 0x00FC, 0x00E9, 0x00E2, 0x00E4, 0x00E0, 0x00E5, 0x00E7, // 0x80
+        0x00EA, 0x00EB, 0x00E8, 0x00EF, 0x00EE, 0x00EC, 0x00C4, 0x00C5, // 0x88
+        0x00C9, 0x00E6, 0x00C6, 0x00F4, 0x00F6, 0x00F2, 0x00FB, 0x00F9, // 0x90
+        0x00FF, 0x00D6, 0x00DC, 0x00A2, 0x00A3, 0x00A5, 0x20A7, 0x0192, // 0x98
+        0x00E1, 0x00ED, 0x00F3, 0x00FA, 0x00F1, 0x00D1, 0x00AA, 0x00BA, // 0xA0
+        0x00BF, 0x2310, 0x00AC, 0x00BD, 0x00BC, 0x00A1, 0x00AB, 0x00BB, // 0xA8
+        0x2591, 0x2592, 0x2593, 0x2502, 0x2524, 0x2561, 0x2562, 0x2556, // 0xB0
+        0x2555, 0x2563, 0x2551, 0x2557, 0x255D, 0x255C, 0x255B, 0x2510, // 0xB8
+        0x2514, 0x2534, 0x252C, 0x251C, 0x2500, 0x253C, 0x255E, 0x255F, // 0xC0
+        0x255A, 0x2554, 0x2569, 0x2566, 0x2560, 0x2550, 0x256C, 0x2567, // 0xC8
+        0x2568, 0x2564, 0x2565, 0x2559, 0x2558, 0x2552, 0x2553, 0x256B, // 0xD0
+        0x256A, 0x2518, 0x250C, 0x2588, 0x2584, 0x258C, 0x2590, 0x2580, // 0xD8
+        0x03B1, 0x00DF, 0x0393, 0x03C0, 0x03A3, 0x03C3, 0x00B5, 0x03C4, // 0xE0
+        0x03A6, 0x0398, 0x03A9, 0x03B4, 0x221E, 0x03C6, 0x03B5, 0x2229, // 0xE8
+        0x2261, 0x00B1, 0x2265, 0x2264, 0x2320, 0x2321, 0x00F7, 0x2248, // 0xF0
+        0x00B0, 0x2219, 0x00B7, 0x221A, 0x207F, 0x00B2, 0x25A0, 0x00A0, // 0xF8
+    ],
+);
+
+/// DOS Western European
+#[rustfmt::skip]
+pub static CP850: Codepage = Codepage::new(
+    850,
+    [
+        0x00C7, 0x00FC, 0x00E9, 0x00E2, 0x00E4, 0x00E0, 0x00E5, 0x00E7, // 0x80
+        0x00EA, 0x00EB, 0x00E8, 0x00EF, 0x00EE, 0x00EC, 0x00C4, 0x00C5, // 0x88
+        0x00C9, 0x00E6, 0x00C6, 0x00F4, 0x00F6, 0x00F2, 0x00FB, 0x00F9, // 0x90
+        0x00FF, 0x00D6, 0x00DC, 0x00F8, 0x00A3, 0x00D8, 0x00D7, 0x0192, // 0x98
+        0x00E1, 0x00ED, 0x00F3, 0x00FA, 0x00F1, 0x00D1, 0x00AA, 0x00BA, // 0xA0
+        0x00BF, 0x00AE, 0x00AC, 0x00BD, 0x00BC, 0x00A1, 0x00AB, 0x00BB, // 0xA8
+        0x2591, 0x2592, 0x2593, 0x2502, 0x2524, 0x00C1, 0x00C2, 0x00C0, // 0xB0
+        0x00A9, 0x2563, 0x2551, 0x2557, 0x255D, 0x00A2, 0x00A5, 0x2510, // 0xB8
+        0x2514, 0x2534, 0x252C, 0x251C, 0x2500, 0x253C, 0x00E3, 0x00C3, // 0xC0
+        0x255A, 0x2554, 0x2569, 0x2566, 0x2560, 0x2550, 0x256C, 0x00A4, // 0xC8
+        0x00F0, 0x00D0, 0x00CA, 0x00CB, 0x00C8, 0x0131, 0x00CD, 0x00CE, // 0xD0
+        0x00CF, 0x2518, 0x250C, 0x2588, 0x2584, 0x00A6, 0x00CC, 0x2580, // 0xD8
+        0x00D3, 0x00DF, 0x00D4, 0x00D2, 0x00F5, 0x00D5, 0x00B5, 0x00FE, // 0xE0
+        0x00DE, 0x00DA, 0x00DB, 0x00D9, 0x00FD, 0x00DD, 0x00AF, 0x00B4, // 0xE8
+        0x00AD, 0x00B1, 0x2017, 0x00BE, 0x00B6, 0x00A7, 0x00F7, 0x00B8, // 0xF0
+        0x00B0, 0x00A8, 0x00B7, 0x00B9, 0x00B3, 0x00B2, 0x25A0, 0x00A0, // 0xF8
+    ],
+);
+
+/// Windows Central European
+#[rustfmt::skip]
+pub static CP1250: Codepage = Codepage::new(
+    1250,
+    [
+        0x20AC, 0x0081, 0x201A, 0x0083, 0x201E, 0x2026, 0x2020, 0x2021, // 0x80
+        0x0088, 0x2030, 0x0160, 0x2039, 0x015A, 0x0164, 0x017D, 0x0179, // 0x88
+        0x0090, 0x2018, 0x2019, 0x201C, 0x201D, 0x2022, 0x2013, 0x2014, // 0x90
+        0x0098, 0x2122, 0x0161, 0x203A, 0x015B, 0x0165, 0x017E, 0x017A, // 0x98
+        0x00A0, 0x02C7, 0x02D8, 0x0141, 0x00A4, 0x0104, 0x00A6, 0x00A7, // 0xA0
+        0x00A8, 0x00A9, 0x015E, 0x00AB, 0x00AC, 0x00AD, 0x00AE, 0x017B, // 0xA8
+        0x00B0, 0x00B1, 0x02DB, 0x0142, 0x00B4, 0x00B5, 0x00B6, 0x00B7, // 0xB0
+        0x00B8, 0x0105, 0x015F, 0x00BB, 0x013D, 0x02DD, 0x013E, 0x017C, // 0xB8
+        0x0154, 0x00C1, 0x00C2, 0x0102, 0x00C4, 0x0139, 0x0106, 0x00C7, // 0xC0
+        0x010C, 0x00C9, 0x0118, 0x00CB, 0x011A, 0x00CD, 0x00CE, 0x010E, // 0xC8
+        0x0110, 0x0143, 0x0147, 0x00D3, 0x00D4, 0x0150, 0x00D6, 0x00D7, // 0xD0
+        0x0158, 0x016E, 0x00DA, 0x0170, 0x00DC, 0x00DD, 0x0162, 0x00DF, // 0xD8
+        0x0155, 0x00E1, 0x00E2, 0x0103, 0x00E4, 0x013A, 0x0107, 0x00E7, // 0xE0
+        0x010D, 0x00E9, 0x0119, 0x00EB, 0x011B, 0x00ED, 0x00EE, 0x010F, // 0xE8
+        0x0111, 0x0144, 0x0148, 0x00F3, 0x00F4, 0x0151, 0x00F6, 0x00F7, // 0xF0
+        0x0159, 0x016F, 0x00FA, 0x0171, 0x00FC, 0x00FD, 0x0163, 0x02D9, // 0xF8
+    ],
+);
+
+/// Windows Cyrillic
+#[rustfmt::skip]
+pub static CP1251: Codepage = Codepage::new(
+    1251,
+    [
+        0x0402, 0x0403, 0x201A, 0x0453, 0x201E, 0x2026, 0x2020, 0x2021, // 0x80
+        0x20AC, 0x2030, 0x0409, 0x2039, 0x040A, 0x040C, 0x040B, 0x040F, // 0x88
+        0x0452, 0x2018, 0x2019, 0x201C, 0x201D, 0x2022, 0x2013, 0x2014, // 0x90
+        0x0098, 0x2122, 0x0459, 0x203A, 0x045A, 0x045C, 0x045B, 0x045F, // 0x98
+        0x00A0, 0x040E, 0x045E, 0x0408, 0x00A4, 0x0490, 0x00A6, 0x00A7, // 0xA0
+        0x0401, 0x00A9, 0x0404, 0x00AB, 0x00AC, 0x00AD, 0x00AE, 0x0407, // 0xA8
+        0x00B0, 0x00B1, 0x0406, 0x0456, 0x0491, 0x00B5, 0x00B6, 0x00B7, // 0xB0
+        0x0451, 0x2116, 0x0454, 0x00BB, 0x0458, 0x0405, 0x0455, 0x0457, // 0xB8
+        0x0410, 0x0411, 0x0412, 0x0413, 0x0414, 0x0415, 0x0416, 0x0417, // 0xC0
+        0x0418, 0x0419, 0x041A, 0x041B, 0x041C, 0x041D, 0x041E, 0x041F, // 0xC8
+        0x0420, 0x0421, 0x0422, 0x0423, 0x0424, 0x0425, 0x0426, 0x0427, // 0xD0
+        0x0428, 0x0429, 0x042A, 0x042B, 0x042C, 0x042D, 0x042E, 0x042F, // 0xD8
+        0x0430, 0x0431, 0x0432, 0x0433, 0x0434, 0x0435, 0x0436, 0x0437, // 0xE0
+        0x0438, 0x0439, 0x043A, 0x043B, 0x043C, 0x043D, 0x043E, 0x043F, // 0xE8
+        0x0440, 0x0441, 0x0442, 0x0443, 0x0444, 0x0445, 0x0446, 0x0447, // 0xF0
+        0x0448, 0x0449, 0x044A, 0x044B, 0x044C, 0x044D, 0x044E, 0x044F, // 0xF8
+    ],
+);
+
+/// Windows Western European. The five bytes it leaves undefined
+/// (0x81, 0x8D, 0x8F, 0x90, 0x9D) map to the C1 controls, as browsers decode them
+#[rustfmt::skip]
+pub static CP1252: Codepage = Codepage::new(
+    1252,
+    [
+        0x20AC, 0x0081, 0x201A, 0x0192, 0x201E, 0x2026, 0x2020, 0x2021, // 0x80
+        0x02C6, 0x2030, 0x0160, 0x2039, 0x0152, 0x008D, 0x017D, 0x008F, // 0x88
+        0x0090, 0x2018, 0x2019, 0x201C, 0x201D, 0x2022, 0x2013, 0x2014, // 0x90
+        0x02DC, 0x2122, 0x0161, 0x203A, 0x0153, 0x009D, 0x017E, 0x0178, // 0x98
+        0x00A0, 0x00A1, 0x00A2, 0x00A3, 0x00A4, 0x00A5, 0x00A6, 0x00A7, // 0xA0
+        0x00A8, 0x00A9, 0x00AA, 0x00AB, 0x00AC, 0x00AD, 0x00AE, 0x00AF, // 0xA8
+        0x00B0, 0x00B1, 0x00B2, 0x00B3, 0x00B4, 0x00B5, 0x00B6, 0x00B7, // 0xB0
+        0x00B8, 0x00B9, 0x00BA, 0x00BB, 0x00BC, 0x00BD, 0x00BE, 0x00BF, // 0xB8
+        0x00C0, 0x00C1, 0x00C2, 0x00C3, 0x00C4, 0x00C5, 0x00C6, 0x00C7, // 0xC0
+        0x00C8, 0x00C9, 0x00CA, 0x00CB, 0x00CC, 0x00CD, 0x00CE, 0x00CF, // 0xC8
+        0x00D0, 0x00D1, 0x00D2, 0x00D3, 0x00D4, 0x00D5, 0x00D6, 0x00D7, // 0xD0
+        0x00D8, 0x00D9, 0x00DA, 0x00DB, 0x00DC, 0x00DD, 0x00DE, 0x00DF, // 0xD8
+        0x00E0, 0x00E1, 0x00E2, 0x00E3, 0x00E4, 0x00E5, 0x00E6, 0x00E7, // 0xE0
+        0x00E8, 0x00E9, 0x00EA, 0x00EB, 0x00EC, 0x00ED, 0x00EE, 0x00EF, // 0xE8
+        0x00F0, 0x00F1, 0x00F2, 0x00F3, 0x00F4, 0x00F5, 0x00F6, 0x00F7, // 0xF0
+        0x00F8, 0x00F9, 0x00FA, 0x00FB, 0x00FC, 0x00FD, 0x00FE, 0x00FF, // 0xF8
+    ],
+);
+
+/// KOI8-R Russian
+#[rustfmt::skip]
+pub static KOI8_R: Codepage = Codepage::new(
+    20866,
+    [
+        0x2500, 0x2502, 0x250C, 0x2510, 0x2514, 0x2518, 0x251C, 0x2524, // 0x80
+        0x252C, 0x2534, 0x253C, 0x2580, 0x2584, 0x2588, 0x258C, 0x2590, // 0x88
+        0x2591, 0x2592, 0x2593, 0x2320, 0x25A0, 0x2219, 0x221A, 0x2248, // 0x90
+        0x2264, 0x2265, 0x00A0, 0x2321, 0x00B0, 0x00B2, 0x00B7, 0x00F7, // 0x98
+        0x2550, 0x2551, 0x2552, 0x0451, 0x2553, 0x2554, 0x2555, 0x2556, // 0xA0
+        0x2557, 0x2558, 0x2559, 0x255A, 0x255B, 0x255C, 0x255D, 0x255E, // 0xA8
+        0x255F, 0x2560, 0x2561, 0x0401, 0x2562, 0x2563, 0x2564, 0x2565, // 0xB0
+        0x2566, 0x2567, 0x2568, 0x2569, 0x256A, 0x256B, 0x256C, 0x00A9, // 0xB8
+        0x044E, 0x0430, 0x0431, 0x0446, 0x0434, 0x0435, 0x0444, 0x0433, // 0xC0
+        0x0445, 0x0438, 0x0439, 0x043A, 0x043B, 0x043C, 0x043D, 0x043E, // 0xC8
+        0x043F, 0x044F, 0x0440, 0x0441, 0x0442, 0x0443, 0x0436, 0x0432, // 0xD0
+        0x044C, 0x044B, 0x0437, 0x0448, 0x044D, 0x0449, 0x0447, 0x044A, // 0xD8
+        0x042E, 0x0410, 0x0411, 0x0426, 0x0414, 0x0415, 0x0424, 0x0413, // 0xE0
+        0x0425, 0x0418, 0x0419, 0x041A, 0x041B, 0x041C, 0x041D, 0x041E, // 0xE8
+        0x041F, 0x042F, 0x0420, 0x0421, 0x0422, 0x0423, 0x0416, 0x0412, // 0xF0
+        0x042C, 0x042B, 0x0417, 0x0428, 0x042D, 0x0429, 0x0427, 0x042A, // 0xF8
+    ],
+);
+
+/// ISO-8859-15 / Latin-9: Latin-1 with `€`, `Š`, `Œ` etc. in eight places
+#[rustfmt::skip]
+pub static ISO_8859_15: Codepage = Codepage::new(
+    28605,
+    [
+        0x0080, 0x0081, 0x0082, 0x0083, 0x0084, 0x0085, 0x0086, 0x0087, // 0x80
+        0x0088, 0x0089, 0x008A, 0x008B, 0x008C, 0x008D, 0x008E, 0x008F, // 0x88
+        0x0090, 0x0091, 0x0092, 0x0093, 0x0094, 0x0095, 0x0096, 0x0097, // 0x90
+        0x0098, 0x0099, 0x009A, 0x009B, 0x009C, 0x009D, 0x009E, 0x009F, // 0x98
+        0x00A0, 0x00A1, 0x00A2, 0x00A3, 0x20AC, 0x00A5, 0x0160, 0x00A7, // 0xA0
+        0x0161, 0x00A9, 0x00AA, 0x00AB, 0x00AC, 0x00AD, 0x00AE, 0x00AF, // 0xA8
+        0x00B0, 0x00B1, 0x00B2, 0x00B3, 0x017D, 0x00B5, 0x00B6, 0x00B7, // 0xB0
+        0x017E, 0x00B9, 0x00BA, 0x00BB, 0x0152, 0x0153, 0x0178, 0x00BF, // 0xB8
+        0x00C0, 0x00C1, 0x00C2, 0x00C3, 0x00C4, 0x00C5, 0x00C6, 0x00C7, // 0xC0
+        0x00C8, 0x00C9, 0x00CA, 0x00CB, 0x00CC, 0x00CD, 0x00CE, 0x00CF, // 0xC8
+        0x00D0, 0x00D1, 0x00D2, 0x00D3, 0x00D4, 0x00D5, 0x00D6, 0x00D7, // 0xD0
+        0x00D8, 0x00D9, 0x00DA, 0x00DB, 0x00DC, 0x00DD, 0x00DE, 0x00DF, // 0xD8
+        0x00E0, 0x00E1, 0x00E2, 0x00E3, 0x00E4, 0x00E5, 0x00E6, 0x00E7, // 0xE0
+        0x00E8, 0x00E9, 0x00EA, 0x00EB, 0x00EC, 0x00ED, 0x00EE, 0x00EF, // 0xE8
+        0x00F0, 0x00F1, 0x00F2, 0x00F3, 0x00F4, 0x00F5, 0x00F6, 0x00F7, // 0xF0
+        0x00F8, 0x00F9, 0x00FA, 0x00FB, 0x00FC, 0x00FD, 0x00FE, 0x00FF, // 0xF8
+    ],
+);
+
+// ==========================================================================
+// Decoders — source encoding → UTF-8
+// ==========================================================================
+
+/// Error decoding input to UTF-8: the source encoding, the byte offset of the
+/// bad sequence, and whether the input ended part-way through it rather than
+/// being invalid.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct DecodeError {
+    pub source: EncodingTarget,
+    pub offset: u64,
+    pub incomplete: bool,
+}
+
+/// Decode the complete sequences at the start of `input`, passing the UTF-8
+/// bytes to `emit`. Returns how many bytes were consumed (all but a trailing
+/// incomplete sequence), or the offset of an invalid sequence.
+fn decode_units(
+    input: &[u8],
+    source: EncodingTarget,
+    mut emit: impl FnMut(&[u8]),
+) -> Result<usize, usize> {
+    let mut utf8 = [0u8; 4];
+    match source {
+        EncodingTarget::Utf8 => {
+            emit(input);
+            Ok(input.len())
+        }
+        EncodingTarget::Latin1 | EncodingTarget::Codepage(_) => {
+            // ASCII runs are copied as-is; 0x80-0xFF become two or three bytes
+            let mut start = 0;
+            for (i, &b) in input.iter().enumerate() {
+                if b >= 0x80 {
+                    let ch = match source {
+                        EncodingTarget::Codepage(codepage) => codepage.decode(b),
+                        _ => char::from(b),
+                    };
+                    emit(&input[start..i]);
+                    emit(ch.encode_utf8(&mut utf8).as_bytes());
+                    start = i + 1;
+                }
+            }
+            emit(&input[start..]);
+            Ok(input.len())
+        }
+        EncodingTarget::Utf16Le | EncodingTarget::Utf16Be => {
+            let big_endian = source == EncodingTarget::Utf16Be;
+            let unit = |i: usize| {
+                let bytes = [input[i], input[i + 1]];
+                if big_endian {
+                    u16::from_be_bytes(bytes)
+                } else {
+                    u16::from_le_bytes(bytes)
+                }
+            };
+            let mut i = 0;
+            while i + 2 <= input.len() {
+                let high = unit(i);
+                let (cp, len) = match high {
+                    0xD800..=0xDBFF => {
+                        if i + 4 > input.len() {
+                            break;
+                        }
+                        let low = unit(i + 2);
+                        if !(0xDC00..=0xDFFF).contains(&low) {
+                            return Err(i);
+                        }
+                        let cp = 0x10000 + ((u32::from(high) - 0xD800) << 10);
+                        (cp + (u32::from(low) - 0xDC00), 4)
+                    }
+                    0xDC00..=0xDFFF => return Err(i),
+                    _ => (u32::from(high), 2),
+                };
+                let ch = char::from_u32(cp).ok_or(i)?;
+                emit(ch.encode_utf8(&mut utf8).as_bytes());
+                i += len;
+            }
+            Ok(i)
+        }
+        EncodingTarget::Utf32Le | EncodingTarget::Utf32Be => {
+            let mut i = 0;
+            while i + 4 <= input.len() {
+                let bytes = [input[i], input[i + 1], input[i + 2], input[i + 3]];
+                let cp = if source == EncodingTarget::Utf32Be {
+                    u32::from_be_bytes(bytes)
+                } else {
+                    u32::from_le_bytes(bytes)
+                };
+                let ch = char::from_u32(cp).ok_or(i)?;
+                emit(ch.encode_utf8(&mut utf8).as_bytes());
+                i += 4;
+            }
+            Ok(i)
+        }
+    }
+}
+
+/// Length of `input` decoded to UTF-8, validating it. With `decode_into`,
+/// lets callers decode straight into an exactly-sized output binary.
+pub fn decoded_len(input: &[u8], source: EncodingTarget) -> Result<usize, DecodeError> {
+    let mut len = 0;
+    match decode_units(input, source, |bytes| len += bytes.len()) {
+        Ok(consumed) if consumed == input.len() => Ok(len),
+        Ok(consumed) => Err(DecodeError {
+            source,
+            offset: consumed as u64,
+            incomplete: true,
+        }),
+        Err(offset) => Err(DecodeError {
+            source,
+            offset: offset as u64,
+            incomplete: false,
+        }),
+    }
+}
+
+/// Decode `input` into `out`, which must be `decoded_len(input, source)` bytes
+pub fn decode_into(out: &mut [u8], input: &[u8], source: EncodingTarget) {
+    let mut pos = 0;
+    let _ = decode_units(input, source, |bytes| {
+        out[pos..pos + bytes.len()].copy_from_slice(bytes);
+        pos += bytes.len();
+    });
+}
+
+/// Decode `input` from `source` to UTF-8.
+pub fn decode_to_utf8(input: &[u8], source: EncodingTarget) -> Result<Vec<u8>, DecodeError> {
+    let mut out = vec![0; decoded_len(input, source)?];
+    decode_into(&mut out, input, source);
+    Ok(out)
+}
+
+/// Byte order marks, longest first where one starts with another
+const BOMS: [(&[u8], EncodingTarget); 5] = [
+    (&[0xFF, 0xFE, 0x00, 0x00], EncodingTarget::Utf32Le),
+    (&[0x00, 0x00, 0xFE, 0xFF], EncodingTarget::Utf32Be),
+    (&[0xEF, 0xBB, 0xBF], EncodingTarget::Utf8),
+    (&[0xFF, 0xFE], EncodingTarget::Utf16Le),
+    (&[0xFE, 0xFF], EncodingTarget::Utf16Be),
+];
+
+/// The encoding a byte order mark at the start of `input` names, and the
+/// length of the BOM.
+pub fn sniff_bom(input: &[u8]) -> Option<(EncodingTarget, usize)> {
+    BOMS.iter()
+        .find(|(bom, _)| input.starts_with(bom))
+        .map(|&(bom, encoding)| (encoding, bom.len()))
+}
+
+/// Incremental decoder for chunked input. A sequence split across chunks is
+/// held back until the next chunk completes it.
+#[derive(Debug, Clone, Copy)]
+pub struct Decoder {
+    source: EncodingTarget,
+    /// Start of a sequence cut off at the end of the last chunk (or, while
+    /// sniffing, the bytes that may be a BOM)
+    pending: [u8; 4],
+    pending_len: usize,
+    /// Input bytes decoded so far (not counting `pending`)
+    offset: u64,
+    /// For `Decoder::auto`: the encoding when the input has no BOM
+    bom_default: Option<EncodingTarget>,
+    /// Still reading the first bytes to look for a BOM
+    sniffing: bool,
+}
+
+impl Decoder {
+    pub fn new(source: EncodingTarget) -> Self {
+        Decoder {
+            source,
+            pending: [0; 4],
+            pending_len: 0,
+            offset: 0,
+            bom_default: None,
+            sniffing: false,
+        }
+    }
+
+    /// Decoder that picks the encoding from a BOM at the start of the input,
+    /// dropping the BOM, and decodes from `default` when there is none.
+    pub fn auto(default: EncodingTarget) -> Self {
+        Decoder {
+            bom_default: Some(default),
+            sniffing: true,
+            ..Decoder::new(default)
+        }
+    }
+
+    pub fn source(&self) -> EncodingTarget {
+        self.source
+    }
+
+    /// Decode `chunk` and append the UTF-8 to `out`. Error offsets count from
+    /// the start of the input, across chunks.
+    pub fn decode_extend(&mut self, out: &mut Vec<u8>, chunk: &[u8]) -> Result<(), DecodeError> {
+        let mut chunk = chunk;
+        if self.sniffing {
+            let held = self.pending_len;
+            let take = (self.pending.len() - held).min(chunk.len());
+            self.pending[held..held + take].copy_from_slice(&chunk[..take]);
+            self.pending_len += take;
+            chunk = &chunk[take..];
+            if self.may_be_bom() {
+                return Ok(());
+            }
+            self.end_bom_sniffing();
+        }
+        while self.pending_len > 0 {
+            // Complete the held-back bytes from the front of this chunk
+            let held = self.pending_len;
+            let take = (self.pending.len() - held).min(chunk.len());
+            let mut head = self.pending;
+            head[held..held + take].copy_from_slice(&chunk[..take]);
+            let consumed = self.decode_complete(out, &head[..held + take])?;
+            if consumed >= held {
+                chunk = &chunk[consumed - held..];
+                self.pending_len = 0;
+            } else {
+                // Only after a BOM check can complete sequences be held back
+                head.copy_within(consumed..held + take, 0);
+                self.pending = head;
+                self.pending_len = held + take - consumed;
+                chunk = &chunk[take..];
+                if consumed == 0 {
+                    // Still incomplete: the chunk was shorter than the sequence
+                    return Ok(());
+                }
+            }
+        }
+        let consumed = self.decode_complete(out, chunk)?;
+        let rest = &chunk[consumed..];
+        self.pending[..rest.len()].copy_from_slice(rest);
+        self.pending_len = rest.len();
+        Ok(())
+    }
+
+    /// Whether the bytes read so far could still grow into a BOM
+    fn may_be_bom(&self) -> bool {
+        let head = &self.pending[..self.pending_len];
+        BOMS.iter()
+            .any(|(bom, _)| bom.len() > head.len() && bom.starts_with(head))
+    }
+
+    /// Stop looking for a BOM: pick the encoding from the bytes read so far
+    /// and drop the BOM. At the end of a short input, call this and then
+    /// decode an empty chunk to flush the held bytes. Returns whether any
+    /// bytes are held.
+    pub fn end_bom_sniffing(&mut self) -> bool {
+        if self.sniffing {
+            self.sniffing = false;
+            let head = &self.pending[..self.pending_len];
+            if let Some((source, bom_len)) = sniff_bom(head) {
+                self.source = source;
+                self.pending.copy_within(bom_len..self.pending_len, 0);
+                self.pending_len -= bom_len;
+                self.offset += bom_len as u64;
+            }
+        }
+        self.pending_len > 0
+    }
+
+    fn decode_complete(&mut self, out: &mut Vec<u8>, input: &[u8]) -> Result<usize, DecodeError> {
+        match decode_units(input, self.source, |bytes| out.extend_from_slice(bytes)) {
+            Ok(consumed) => {
+                self.offset += consumed as u64;
+                Ok(consumed)
+            }
+            Err(offset) => Err(DecodeError {
+                source: self.source,
+                offset: self.offset + offset as u64,
+                incomplete: false,
+            }),
+        }
+    }
+
+    /// Check that the input did not end part-way through a sequence
+    pub fn finish(&self) -> Result<(), DecodeError> {
+        if self.pending_len > 0 {
+            return Err(DecodeError {
+                source: self.source,
+                offset: self.offset,
+                incomplete: true,
+            });
+        }
+        Ok(())
+    }
+
+    /// Start over on a new input
+    pub fn reset(&mut self) {
+        *self = match self.bom_default {
+            Some(default) => Decoder::auto(default),
+            None => Decoder::new(self.source),
+        };
+    }
+}
+
 // ==========================================================================
 // Tests
 // ==========================================================================
@@ -315,4 +1064,448 @@ mod tests {
         encode_utf8_extend(&mut out, b"X", EncodingTarget::Utf16Le);
         assert_eq!(out, &[1, 2, 3, 0x58, 0x00]);
     }
+
+    // unmappable character tests
+
+    fn encode_with(input: &str, target: EncodingTarget, unmappable: Unmappable) -> Vec<u8> {
+        let mut out = Vec::new();
+        encode_utf8_extend_with(&mut out, input.as_bytes(), target, unmappable).unwrap();
+        out
+    }
+
+    #[test]
+    fn test_unmappable_replace() {
+        let input = "caf\u{e9} \u{201C}ok\u{201D}";
+        assert_eq!(
+            encode_with(input, EncodingTarget::Latin1, Unmappable::default()),
+            b"caf\xe9 ?ok?"
+        );
+        assert_eq!(
+            encode_with(input, EncodingTarget::Latin1, Unmappable::Replace(b"")),
+            b"caf\xe9 ok"
+        );
+        assert_eq!(
+            encode_with(input, EncodingTarget::Latin1, Unmappable::Replace(b"[?]")),
+            b"caf\xe9 [?]ok[?]"
+        );
+        // Windows-1252 has curly quotes
+        assert_eq!(
+            encode_with(input, EncodingTarget::Codepage(&CP1252), Unmappable::Error),
+            b"caf\xe9 \x93ok\x94"
+        );
+    }
+
+    #[test]
+    fn test_unmappable_error() {
+        let mut out = Vec::new();
+        let result = encode_utf8_extend_with(
+            &mut out,
+            "a\u{e9}\u{2013}b\u{2014}".as_bytes(),
+            EncodingTarget::Latin1,
+            Unmappable::Error,
+        );
+        assert_eq!(result, Err('\u{2013}'));
+        assert_eq!(out, b"a\xe9");
+
+        // Wide targets can encode everything
+        let mut out = Vec::new();
+        let result = encode_utf8_extend_with(
+            &mut out,
+            "\u{2013}".as_bytes(),
+            EncodingTarget::Utf16Le,
+            Unmappable::Error,
+        );
+        assert_eq!(result, Ok(()));
+        assert_eq!(out, &[0x13, 0x20]);
+    }
+
+    #[test]
+    fn test_unmappable_transliterate() {
+        let input =
+            "\u{201C}Hi\u{201D} \u{2018}x\u{2019} 1\u{2013}2\u{2014}3\u{2026} \u{2022} \u{20AC}5";
+        assert_eq!(
+            encode_with(input, EncodingTarget::Latin1, Unmappable::Transliterate),
+            b"\"Hi\" 'x' 1-2-3... * EUR5"
+        );
+        // Only characters the target lacks are transliterated
+        assert_eq!(
+            encode_with(
+                input,
+                EncodingTarget::Codepage(&CP1252),
+                Unmappable::Transliterate
+            ),
+            b"\x93Hi\x94 \x91x\x92 1\x962\x973\x85 \x95 \x805"
+        );
+        assert_eq!(
+            encode_with(
+                "Ł\u{f3}d\u{17a} \u{152}uvre \u{e9}t\u{e9} \u{4e2d}",
+                EncodingTarget::Latin1,
+                Unmappable::Transliterate
+            ),
+            b"L\xf3dz OEuvre \xe9t\xe9 ?"
+        );
+        // Latin-1 letters are transliterated for codepages without them
+        assert_eq!(
+            encode_with(
+                "\u{c9}cole \u{df}",
+                EncodingTarget::Codepage(&CP1251),
+                Unmappable::Transliterate
+            ),
+            b"Ecole ss"
+        );
+    }
+
+    #[test]
+    fn test_substitute_unmappable() {
+        let input = "\u{201C}\u{e9}\u{201D}".as_bytes();
+        assert_eq!(
+            substitute_unmappable(input, EncodingTarget::Latin1, Unmappable::Transliterate),
+            Cow::Owned::<[u8]>("\"\u{e9}\"".as_bytes().to_vec())
+        );
+        assert_eq!(
+            substitute_unmappable(input, EncodingTarget::Latin1, Unmappable::default()),
+            Cow::Owned::<[u8]>("?\u{e9}?".as_bytes().to_vec())
+        );
+        // Replacements are text: quoted like the field and encoded with it
+        assert_eq!(
+            substitute_unmappable(input, EncodingTarget::Latin1, Unmappable::Replace(b",")),
+            Cow::Owned::<[u8]>(",\u{e9},".as_bytes().to_vec())
+        );
+        assert_eq!(
+            substitute_unmappable(
+                input,
+                EncodingTarget::Latin1,
+                Unmappable::Replace("\u{bf}\u{2026}".as_bytes())
+            ),
+            Cow::Owned::<[u8]>("\u{bf}?\u{e9}\u{bf}?".as_bytes().to_vec())
+        );
+        for (target, unmappable) in [
+            (EncodingTarget::Codepage(&CP1252), Unmappable::Transliterate),
+            (EncodingTarget::Utf16Le, Unmappable::Transliterate),
+            (EncodingTarget::Latin1, Unmappable::Error),
+        ] {
+            assert!(matches!(
+                substitute_unmappable(input, target, unmappable),
+                Cow::Borrowed(_)
+            ));
+        }
+    }
+
+    #[test]
+    fn test_check_utf8() {
+        let valid = "caf\u{e9}".as_bytes();
+        let invalid = b"ok\xe9!\xff";
+        for policy in [
+            InvalidUtf8::Passthrough,
+            InvalidUtf8::Replace,
+            InvalidUtf8::Error,
+        ] {
+            assert_eq!(check_utf8(valid, policy), Ok(Cow::Borrowed(valid)));
+        }
+        assert_eq!(
+            check_utf8(invalid, InvalidUtf8::Passthrough),
+            Ok(Cow::Borrowed(&invalid[..]))
+        );
+        assert_eq!(
+            check_utf8(invalid, InvalidUtf8::Replace),
+            Ok(Cow::Owned::<[u8]>(
+                "ok\u{FFFD}!\u{FFFD}".as_bytes().to_vec()
+            ))
+        );
+        assert_eq!(check_utf8(invalid, InvalidUtf8::Error), Err(2));
+    }
+
+    #[test]
+    fn test_transliterations_are_ascii() {
+        for cp in 0..0x3000 {
+            if let Some(ascii) = char::from_u32(cp).and_then(transliterate) {
+                assert!(!ascii.is_empty() && ascii.is_ascii(), "U+{cp:04X}");
+            }
+        }
+    }
+
+    // decoder tests
+
+    const ALL: [EncodingTarget; 5] = [
+        EncodingTarget::Latin1,
+        EncodingTarget::Utf16Le,
+        EncodingTarget::Utf16Be,
+        EncodingTarget::Utf32Le,
+        EncodingTarget::Utf32Be,
+    ];
+
+    #[test]
+    fn test_decode_round_trip() {
+        for source in ALL {
+            let text = if source == EncodingTarget::Latin1 {
+                "a,caf\u{e9}\n\u{ff}\u{80}"
+            } else {
+                "a,caf\u{e9}\n\u{1F600}\u{20ac}"
+            };
+            let encoded = encode_utf8_to_target(text.as_bytes(), source);
+            assert_eq!(decoded_len(&encoded, source), Ok(text.len()));
+            assert_eq!(decode_to_utf8(&encoded, source).unwrap(), text.as_bytes());
+        }
+    }
+
+    #[test]
+    fn test_codepage_tables() {
+        for codepage in CODEPAGES {
+            let mut seen = std::collections::HashSet::new();
+            for b in 0..=255u8 {
+                let ch = codepage.decode(b);
+                assert_ne!(ch, '\u{FFFD}', "{codepage:?} byte {b:#04X}");
+                assert!(seen.insert(ch), "{codepage:?} maps {ch:?} twice");
+                assert_eq!(codepage.encode(ch), Some(b), "{codepage:?} byte {b:#04X}");
+            }
+            assert_eq!(super::codepage(codepage.number), Some(codepage));
+        }
+        assert_eq!(super::codepage(1253), None);
+        let numbers: Vec<u32> = codepage_numbers().collect();
+        assert_eq!(numbers, [437, 850, 1250, 1251, 1252, 20866, 28605]);
+    }
+
+    #[test]
+    fn test_codepage_samples() {
+        let samples: [(&Codepage, &str, &[u8]); 5] = [
+            (
+                &CP437,
+                "\u{2554}\u{2550}\u{00e9}\u{00b5}",
+                b"\xc9\xcd\x82\xe6",
+            ),
+            (&CP850, "\u{00c1}\u{00f5}\u{20ac}", b"\xb5\xe4?"),
+            (&CP1250, "\u{0141}\u{00f3}d\u{017a}", b"\xa3\xf3d\x9f"),
+            (
+                &CP1251,
+                "\u{041f}\u{0440}\u{0438}\u{0432}\u{0435}\u{0442}",
+                b"\xcf\xf0\xe8\xe2\xe5\xf2",
+            ),
+            (
+                &KOI8_R,
+                "\u{041f}\u{0440}\u{0438}\u{0432}\u{0435}\u{0442}",
+                b"\xf0\xd2\xc9\xd7\xc5\xd4",
+            ),
+        ];
+        for (codepage, text, bytes) in samples {
+            let target = EncodingTarget::Codepage(codepage);
+            assert_eq!(encode_utf8_to_target(text.as_bytes(), target), bytes);
+            if !bytes.contains(&b'?') {
+                assert_eq!(decode_to_utf8(bytes, target).unwrap(), text.as_bytes());
+            }
+        }
+    }
+
+    #[test]
+    fn test_windows_1252_high_block() {
+        let cp1252 = EncodingTarget::Codepage(&CP1252);
+        let text = "\u{20ac}5 \u{201c}quoted\u{201d} \u{2014} na\u{ef}ve";
+        let encoded = encode_utf8_to_target(text.as_bytes(), cp1252);
+        assert_eq!(encoded, b"\x805 \x93quoted\x94 \x97 na\xefve");
+        assert_eq!(decode_to_utf8(&encoded, cp1252).unwrap(), text.as_bytes());
+        // Undefined bytes decode to C1 controls and encode back
+        let decoded = decode_to_utf8(&[0x81, 0x9D], cp1252).unwrap();
+        assert_eq!(decoded, "\u{81}\u{9d}".as_bytes());
+        assert_eq!(encode_utf8_to_target(&decoded, cp1252), &[0x81, 0x9D]);
+        // C1 controls Windows-1252 reassigns have no byte
+        assert_eq!(
+            encode_utf8_to_target("\u{80}\u{100}".as_bytes(), cp1252),
+            b"??"
+        );
+    }
+
+    #[test]
+    fn test_iso_8859_15_changes() {
+        let latin9 = EncodingTarget::Codepage(&ISO_8859_15);
+        let text = "\u{20ac}\u{160}\u{161}\u{17d}\u{17e}\u{152}\u{153}\u{178}\u{e9}";
+        let encoded = encode_utf8_to_target(text.as_bytes(), latin9);
+        assert_eq!(
+            encoded,
+            &[0xA4, 0xA6, 0xA8, 0xB4, 0xB8, 0xBC, 0xBD, 0xBE, 0xE9]
+        );
+        assert_eq!(decode_to_utf8(&encoded, latin9).unwrap(), text.as_bytes());
+        // The Latin-1 characters it replaced are unmappable
+        assert_eq!(
+            encode_utf8_to_target("\u{a4}\u{bd}\u{85}".as_bytes(), latin9),
+            &[b'?', b'?', 0x85]
+        );
+    }
+
+    #[test]
+    fn test_decode_latin1_high_bytes() {
+        let decoded = decode_to_utf8(&[b'x', 0xe9, 0xa0], EncodingTarget::Latin1).unwrap();
+        assert_eq!(decoded, "x\u{e9}\u{a0}".as_bytes());
+    }
+
+    #[test]
+    fn test_decode_errors() {
+        let error = |input: &[u8], source| {
+            let error = decoded_len(input, source).unwrap_err();
+            assert_eq!(error.source, source);
+            (error.offset, error.incomplete)
+        };
+        // Lone low surrogate, high surrogate not followed by a low one
+        assert_eq!(
+            error(&[0x41, 0x00, 0x00, 0xDC], EncodingTarget::Utf16Le),
+            (2, false)
+        );
+        assert_eq!(
+            error(&[0xD8, 0x3D, 0x00, 0x41], EncodingTarget::Utf16Be),
+            (0, false)
+        );
+        // Odd trailing byte, unpaired high surrogate at the end
+        assert_eq!(
+            error(&[0x41, 0x00, 0x42], EncodingTarget::Utf16Le),
+            (2, true)
+        );
+        assert_eq!(error(&[0x3D, 0xD8], EncodingTarget::Utf16Le), (0, true));
+        // Out of range and surrogate codepoints
+        assert_eq!(error(&[0, 0, 0x11, 0], EncodingTarget::Utf32Le), (0, false));
+        assert_eq!(
+            error(&[0, 0, 0, 0x41, 0, 0, 0xD8, 0], EncodingTarget::Utf32Be),
+            (4, false)
+        );
+    }
+
+    #[test]
+    fn test_decoder_every_split() {
+        let text = "id,name\n1,\u{1F600}\u{e9}\n";
+        for source in [EncodingTarget::Utf16Le, EncodingTarget::Utf32Be] {
+            let encoded = encode_utf8_to_target(text.as_bytes(), source);
+            for a in 0..=encoded.len() {
+                for b in a..=encoded.len() {
+                    let mut decoder = Decoder::new(source);
+                    let mut out = Vec::new();
+                    for chunk in [&encoded[..a], &encoded[a..b], &encoded[b..]] {
+                        decoder.decode_extend(&mut out, chunk).unwrap();
+                    }
+                    assert_eq!(decoder.finish(), Ok(()));
+                    assert_eq!(out, text.as_bytes(), "{source:?} split at {a}, {b}");
+                }
+            }
+        }
+    }
+
+    #[test]
+    fn test_sniff_bom() {
+        assert_eq!(
+            sniff_bom(&[0xFF, 0xFE, 0, 0, 0x41]),
+            Some((EncodingTarget::Utf32Le, 4))
+        );
+        assert_eq!(
+            sniff_bom(&[0xFF, 0xFE, 0x41, 0]),
+            Some((EncodingTarget::Utf16Le, 2))
+        );
+        assert_eq!(sniff_bom(&[0xFE, 0xFF]), Some((EncodingTarget::Utf16Be, 2)));
+        assert_eq!(
+            sniff_bom(&[0, 0, 0xFE, 0xFF]),
+            Some((EncodingTarget::Utf32Be, 4))
+        );
+        assert_eq!(sniff_bom(b"\xEF\xBB\xBFa"), Some((EncodingTarget::Utf8, 3)));
+        assert_eq!(sniff_bom(b"\xEF\xBBa"), None);
+        assert_eq!(sniff_bom(b""), None);
+    }
+
+    /// Decode `chunks` with an auto decoder, flushing at the end like a parser
+    fn decode_auto(default: EncodingTarget, chunks: &[&[u8]]) -> Result<Vec<u8>, DecodeError> {
+        let mut decoder = Decoder::auto(default);
+        let mut out = Vec::new();
+        for chunk in chunks {
+            decoder.decode_extend(&mut out, chunk)?;
+        }
+        if decoder.end_bom_sniffing() {
+            decoder.decode_extend(&mut out, &[])?;
+        }
+        decoder.finish()?;
+        Ok(out)
+    }
+
+    #[test]
+    fn test_auto_decoder_every_split() {
+        let text = "id,name\n1,\u{e9}\n";
+        for (bom, source) in BOMS {
+            let mut input = bom.to_vec();
+            input.extend(encode_utf8_to_target(text.as_bytes(), source));
+            for a in 0..=input.len() {
+                let chunks: [&[u8]; 2] = [&input[..a], &input[a..]];
+                let decoded = decode_auto(EncodingTarget::Latin1, &chunks);
+                assert_eq!(decoded.unwrap(), text.as_bytes(), "{source:?} split at {a}");
+            }
+        }
+        // No BOM: the default encoding
+        let input = encode_utf8_to_target(text.as_bytes(), EncodingTarget::Utf16Be);
+        for a in 0..=input.len() {
+            let decoded = decode_auto(EncodingTarget::Utf16Be, &[&input[..a], &input[a..]]);
+            assert_eq!(decoded.unwrap(), text.as_bytes(), "split at {a}");
+        }
+    }
+
+    #[test]
+    fn test_auto_decoder_short_input() {
+        // Bytes that start like a BOM but end the input are the default encoding
+        assert_eq!(
+            decode_auto(EncodingTarget::Latin1, &[b"\xFF"]).unwrap(),
+            "\u{ff}".as_bytes()
+        );
+        assert_eq!(
+            decode_auto(EncodingTarget::Latin1, &[b"\xEF", b"\xBB"]).unwrap(),
+            "\u{ef}\u{bb}".as_bytes()
+        );
+        assert_eq!(
+            decode_auto(EncodingTarget::Utf8, &[b"\xEF\xBB\xBF"]).unwrap(),
+            b""
+        );
+        assert_eq!(decode_auto(EncodingTarget::Utf8, &[]).unwrap(), b"");
+        // A BOM followed by half a UTF-16 unit; offsets count the BOM
+        assert_eq!(
+            decode_auto(EncodingTarget::Utf8, &[b"\xFE\xFF\x00"]),
+            Err(DecodeError {
+                source: EncodingTarget::Utf16Be,
+                offset: 2,
+                incomplete: true
+            })
+        );
+    }
+
+    #[test]
+    fn test_auto_decoder_reset_sniffs_again() {
+        let mut decoder = Decoder::auto(EncodingTarget::Latin1);
+        let mut out = Vec::new();
+        decoder.decode_extend(&mut out, b"\xFF\xFEa\x00").unwrap();
+        assert_eq!(decoder.source(), EncodingTarget::Utf16Le);
+        decoder.reset();
+        out.clear();
+        decoder.decode_extend(&mut out, b"caf\xE9").unwrap();
+        assert_eq!(decoder.source(), EncodingTarget::Latin1);
+        assert_eq!(out, "caf\u{e9}".as_bytes());
+    }
+
+    #[test]
+    fn test_decoder_errors_count_from_input_start() {
+        let mut decoder = Decoder::new(EncodingTarget::Utf16Le);
+        let mut out = Vec::new();
+        decoder
+            .decode_extend(&mut out, &[0x41, 0x00, 0x3D])
+            .unwrap();
+        assert_eq!(
+            decoder.finish(),
+            Err(DecodeError {
+                source: EncodingTarget::Utf16Le,
+                offset: 2,
+                incomplete: true
+            })
+        );
+        assert_eq!(
+            decoder.decode_extend(&mut out, &[0xD8, 0x41, 0x00]),
+            Err(DecodeError {
+                source: EncodingTarget::Utf16Le,
+                offset: 2,
+                incomplete: false
+            })
+        );
+        assert_eq!(out, b"A");
+
+        decoder.reset();
+        decoder.decode_extend(&mut out, &[0x42, 0x00]).unwrap();
+        assert_eq!(out, b"AB");
+    }
 }
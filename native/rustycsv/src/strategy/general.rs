@@ -8,8 +8,15 @@
 // since multi-byte delimiters are uncommon.
 
 use std::borrow::Cow;
+use std::io::Read;
 
+use super::encoding::Decoder;
+use super::streaming::{
+    decode_into_buffer, read_into_buffer, Checkpoint, HeaderRow, ReadError, RowQueue,
+    RowWidthError, StreamStats, WidthPolicy,
+};
 use crate::core::newlines::{match_newline, Newlines};
+use crate::core::Terminator;
 
 // ============================================================================
 // Helpers
@@ -548,13 +555,21 @@ fn parse_row_boundaries_general(
 /// Streaming parser that handles multi-byte separators and escapes
 pub struct GeneralStreamingParser {
     buffer: Vec<u8>,
-    complete_rows: Vec<Vec<Vec<u8>>>,
+    complete_rows: RowQueue,
+    /// Absolute stream offset of `buffer[0]` (bytes compacted away so far)
+    base_offset: u64,
     partial_row_start: usize,
     scan_pos: usize,
     in_quotes: bool,
     separators: Vec<Vec<u8>>,
     escape: Vec<u8>,
     max_buffer_size: usize,
+    /// Complete rows to queue before scanning stops (`usize::MAX` = no limit)
+    max_pending_rows: usize,
+    /// Scanning stopped at `max_pending_rows` with unscanned bytes left
+    held_back: bool,
+    header_row: HeaderRow,
+    stats: StreamStats,
 }
 
 impl GeneralStreamingParser {
@@ -562,13 +577,18 @@ impl GeneralStreamingParser {
         use super::streaming::DEFAULT_MAX_BUFFER;
         GeneralStreamingParser {
             buffer: Vec::new(),
-            complete_rows: Vec::new(),
+            complete_rows: RowQueue::default(),
+            base_offset: 0,
             partial_row_start: 0,
             scan_pos: 0,
             in_quotes: false,
             separators,
             escape,
             max_buffer_size: DEFAULT_MAX_BUFFER,
+            max_pending_rows: usize::MAX,
+            held_back: false,
+            header_row: HeaderRow::default(),
+            stats: StreamStats::default(),
         }
     }
 
@@ -577,6 +597,36 @@ impl GeneralStreamingParser {
             return Err(super::streaming::BufferOverflow);
         }
         self.buffer.extend_from_slice(chunk);
+        self.stats
+            .record_feed(chunk, self.buffer.len(), Terminator::Newline);
+        self.process_buffer();
+        Ok(())
+    }
+
+    /// Read up to `len` bytes from `reader` straight into the buffer and
+    /// parse them. Returns the number of bytes read, 0 at end of input.
+    pub fn feed_from(&mut self, reader: &mut impl Read, len: usize) -> Result<usize, ReadError> {
+        let start = self.buffer.len();
+        let read = read_into_buffer(&mut self.buffer, reader, len, self.max_buffer_size)?;
+        self.stats.record_feed(
+            &self.buffer[start..],
+            self.buffer.len(),
+            Terminator::Newline,
+        );
+        self.process_buffer();
+        Ok(read)
+    }
+
+    /// Decode `chunk` (input in `decoder`'s encoding) straight into the
+    /// buffer as UTF-8 and parse it.
+    pub fn feed_decoded(&mut self, chunk: &[u8], decoder: &mut Decoder) -> Result<(), ReadError> {
+        let start = self.buffer.len();
+        decode_into_buffer(&mut self.buffer, decoder, chunk, self.max_buffer_size)?;
+        self.stats.record_feed(
+            &self.buffer[start..],
+            self.buffer.len(),
+            Terminator::Newline,
+        );
         self.process_buffer();
         Ok(())
     }
@@ -585,11 +635,44 @@ impl GeneralStreamingParser {
         self.max_buffer_size = max;
     }
 
+    pub fn set_max_pending_rows(&mut self, max: usize) {
+        self.max_pending_rows = max.max(1);
+    }
+
+    pub fn max_pending_rows(&self) -> usize {
+        self.max_pending_rows
+    }
+
+    pub fn needs_drain(&self) -> bool {
+        self.held_back
+    }
+
+    pub fn set_width_policy(&mut self, policy: Option<WidthPolicy>) {
+        self.header_row.set_policy(policy);
+    }
+
+    pub fn width_policy(&self) -> Option<WidthPolicy> {
+        self.header_row.policy()
+    }
+
+    pub fn header(&self) -> Option<&[Vec<u8>]> {
+        self.header_row.header()
+    }
+
+    pub fn width_error(&self) -> Option<RowWidthError> {
+        self.header_row.error()
+    }
+
     fn process_buffer(&mut self) {
         let mut pos = self.scan_pos;
         let esc_len = self.escape.len();
+        self.held_back = false;
 
-        while pos < self.buffer.len() {
+        while pos < self.buffer.len() && self.header_row.error().is_none() {
+            if self.complete_rows.len() >= self.max_pending_rows {
+                self.held_back = true;
+                break;
+            }
             if self.in_quotes {
                 if starts_with_escape(&self.buffer, pos, &self.escape) {
                     if starts_with_escape(&self.buffer, pos + esc_len, &self.escape) {
@@ -605,27 +688,21 @@ impl GeneralStreamingParser {
                 self.in_quotes = true;
                 pos += esc_len;
             } else if self.buffer[pos] == b'\n' {
-                let row_end = pos;
-                let row = self.parse_row_owned(self.partial_row_start, row_end);
-                if !row.is_empty() {
-                    self.complete_rows.push(row);
+                if !self.end_row(pos, 1) {
+                    pos = self.partial_row_start;
+                    break;
                 }
                 pos += 1;
-                self.partial_row_start = pos;
-                self.in_quotes = false;
             } else if self.buffer[pos] == b'\r' {
                 // Only treat \r as line ending when followed by \n (CRLF).
                 // Bare \r is data per RFC 4180 and NimbleCSV behavior.
                 if pos + 1 < self.buffer.len() {
                     if self.buffer[pos + 1] == b'\n' {
-                        let row_end = pos;
-                        let row = self.parse_row_owned(self.partial_row_start, row_end);
-                        if !row.is_empty() {
-                            self.complete_rows.push(row);
+                        if !self.end_row(pos, 2) {
+                            pos = self.partial_row_start;
+                            break;
                         }
                         pos += 2;
-                        self.partial_row_start = pos;
-                        self.in_quotes = false;
                     } else {
                         pos += 1;
                     }
@@ -644,7 +721,8 @@ impl GeneralStreamingParser {
         }
     }
 
-    fn parse_row_owned(&self, start: usize, end: usize) -> Vec<Vec<u8>> {
+    fn parse_row_owned(&mut self, start: usize, end: usize) -> Vec<Vec<u8>> {
+        self.stats.record_row(end.saturating_sub(start));
         if start >= end {
             return Vec::new();
         }
@@ -653,17 +731,58 @@ impl GeneralStreamingParser {
         parse_line_fields_owned_general(line, &self.separators, &self.escape)
     }
 
+    /// Cut the row ending at `row_end` (followed by a `nl_len`-byte newline)
+    /// and queue it. Returns false if the width check rejects it, leaving
+    /// `partial_row_start` at its start.
+    fn end_row(&mut self, row_end: usize, nl_len: usize) -> bool {
+        let start = self.partial_row_start;
+        let row = self.parse_row_owned(start, row_end);
+        self.partial_row_start = row_end + nl_len;
+        self.in_quotes = false;
+        if self.push_row(row, start) {
+            return true;
+        }
+        self.partial_row_start = start;
+        false
+    }
+
+    /// Queue a row that starts at `start` and ends at `partial_row_start`
+    /// (blank lines are dropped). Returns false if the width check rejects it.
+    fn push_row(&mut self, mut row: Vec<Vec<u8>>, start: usize) -> bool {
+        if row.is_empty() {
+            return true;
+        }
+        if self.header_row.wants_header() {
+            self.header_row.set_header(row.clone());
+        } else {
+            let row_number = self.complete_rows.next_row_number();
+            let offset = self.base_offset + start as u64;
+            if !self.header_row.fit(&mut row, row_number, offset) {
+                return false;
+            }
+        }
+        let end_offset = self.base_offset + self.partial_row_start as u64;
+        self.complete_rows.push(row, end_offset);
+        true
+    }
+
     fn compact_buffer(&mut self) {
         if self.partial_row_start > 0 {
             self.buffer.drain(0..self.partial_row_start);
+            self.base_offset += self.partial_row_start as u64;
             self.scan_pos -= self.partial_row_start;
             self.partial_row_start = 0;
+            self.stats.record_compaction();
         }
     }
 
     pub fn take_rows(&mut self, max: usize) -> Vec<Vec<Vec<u8>>> {
-        let take_count = max.min(self.complete_rows.len());
-        self.complete_rows.drain(0..take_count).collect()
+        let rows = self.complete_rows.take(max);
+        self.stats.record_taken(rows.len());
+        if self.held_back && !rows.is_empty() {
+            self.process_buffer();
+        }
+        rows
     }
 
     pub fn available_rows(&self) -> usize {
@@ -679,14 +798,63 @@ impl GeneralStreamingParser {
     }
 
     pub fn finalize(&mut self) -> Vec<Vec<Vec<u8>>> {
-        if self.partial_row_start < self.buffer.len() {
-            let row = self.parse_row_owned(self.partial_row_start, self.buffer.len());
-            if !row.is_empty() {
-                self.complete_rows.push(row);
-            }
-            self.partial_row_start = self.buffer.len();
+        if self.held_back {
+            let max_pending_rows = std::mem::replace(&mut self.max_pending_rows, usize::MAX);
+            self.process_buffer();
+            self.max_pending_rows = max_pending_rows;
+        }
+        if self.partial_row_start < self.buffer.len() && self.header_row.error().is_none() {
+            self.end_row(self.buffer.len(), 0);
         }
-        std::mem::take(&mut self.complete_rows)
+        self.take_rows(usize::MAX)
+    }
+
+    /// Reset the parser state, keeping the buffer allocation
+    pub fn reset(&mut self) {
+        self.buffer.clear();
+        self.complete_rows.clear();
+        self.base_offset = 0;
+        self.partial_row_start = 0;
+        self.scan_pos = 0;
+        self.in_quotes = false;
+        self.held_back = false;
+        self.header_row.reset();
+        self.stats = StreamStats::default();
+    }
+
+    /// Take the (emptied) buffer allocation for reuse by another parser
+    pub fn take_buffer(&mut self) -> Vec<u8> {
+        self.reset();
+        std::mem::take(&mut self.buffer)
+    }
+
+    /// Adopt a buffer allocation from another parser; its contents are discarded
+    pub fn reuse_buffer(&mut self, mut buffer: Vec<u8>) {
+        buffer.clear();
+        self.reset();
+        self.buffer = buffer;
+    }
+
+    pub fn max_buffer_size(&self) -> usize {
+        self.max_buffer_size
+    }
+
+    /// Cumulative counters since creation or the last `reset`
+    pub fn stats(&self) -> StreamStats {
+        self.stats
+            .snapshot(self.buffer.len() - self.partial_row_start)
+    }
+
+    pub fn checkpoint(&self) -> Checkpoint {
+        self.complete_rows.checkpoint()
+    }
+
+    /// Start an empty parser at `checkpoint`: offsets continue from
+    /// `checkpoint.offset` and the emitted row count from `checkpoint.rows`
+    pub fn resume_at(&mut self, checkpoint: Checkpoint) {
+        self.reset();
+        self.base_offset = checkpoint.offset;
+        self.complete_rows.resume_at(checkpoint);
     }
 }
 
@@ -1201,14 +1369,25 @@ fn parse_row_boundaries_general_with_newlines(
 /// Streaming parser with custom newline support.
 pub struct GeneralStreamingParserNewlines {
     buffer: Vec<u8>,
-    complete_rows: Vec<Vec<Vec<u8>>>,
+    complete_rows: RowQueue,
+    /// Absolute stream offset of `buffer[0]` (bytes compacted away so far)
+    base_offset: u64,
     partial_row_start: usize,
     scan_pos: usize,
     in_quotes: bool,
     separators: Vec<Vec<u8>>,
     escape: Vec<u8>,
     newlines: Newlines,
+    /// Line breaks counted in `stats`: the single-byte newline or `\r`/`\n`
+    /// set when the patterns map to one, `\n` otherwise
+    line_breaks: Terminator,
     max_buffer_size: usize,
+    /// Complete rows to queue before scanning stops (`usize::MAX` = no limit)
+    max_pending_rows: usize,
+    /// Scanning stopped at `max_pending_rows` with unscanned bytes left
+    held_back: bool,
+    header_row: HeaderRow,
+    stats: StreamStats,
 }
 
 impl GeneralStreamingParserNewlines {
@@ -1216,14 +1395,20 @@ impl GeneralStreamingParserNewlines {
         use super::streaming::DEFAULT_MAX_BUFFER;
         GeneralStreamingParserNewlines {
             buffer: Vec::new(),
-            complete_rows: Vec::new(),
+            complete_rows: RowQueue::default(),
+            base_offset: 0,
             partial_row_start: 0,
             scan_pos: 0,
             in_quotes: false,
             separators,
             escape,
+            line_breaks: newlines.terminator().unwrap_or(Terminator::Newline),
             newlines,
             max_buffer_size: DEFAULT_MAX_BUFFER,
+            max_pending_rows: usize::MAX,
+            held_back: false,
+            header_row: HeaderRow::default(),
+            stats: StreamStats::default(),
         }
     }
 
@@ -1232,6 +1417,30 @@ impl GeneralStreamingParserNewlines {
             return Err(super::streaming::BufferOverflow);
         }
         self.buffer.extend_from_slice(chunk);
+        self.stats
+            .record_feed(chunk, self.buffer.len(), self.line_breaks);
+        self.process_buffer();
+        Ok(())
+    }
+
+    /// Read up to `len` bytes from `reader` straight into the buffer and
+    /// parse them. Returns the number of bytes read, 0 at end of input.
+    pub fn feed_from(&mut self, reader: &mut impl Read, len: usize) -> Result<usize, ReadError> {
+        let start = self.buffer.len();
+        let read = read_into_buffer(&mut self.buffer, reader, len, self.max_buffer_size)?;
+        self.stats
+            .record_feed(&self.buffer[start..], self.buffer.len(), self.line_breaks);
+        self.process_buffer();
+        Ok(read)
+    }
+
+    /// Decode `chunk` (input in `decoder`'s encoding) straight into the
+    /// buffer as UTF-8 and parse it.
+    pub fn feed_decoded(&mut self, chunk: &[u8], decoder: &mut Decoder) -> Result<(), ReadError> {
+        let start = self.buffer.len();
+        decode_into_buffer(&mut self.buffer, decoder, chunk, self.max_buffer_size)?;
+        self.stats
+            .record_feed(&self.buffer[start..], self.buffer.len(), self.line_breaks);
         self.process_buffer();
         Ok(())
     }
@@ -1240,12 +1449,45 @@ impl GeneralStreamingParserNewlines {
         self.max_buffer_size = max;
     }
 
+    pub fn set_max_pending_rows(&mut self, max: usize) {
+        self.max_pending_rows = max.max(1);
+    }
+
+    pub fn max_pending_rows(&self) -> usize {
+        self.max_pending_rows
+    }
+
+    pub fn needs_drain(&self) -> bool {
+        self.held_back
+    }
+
+    pub fn set_width_policy(&mut self, policy: Option<WidthPolicy>) {
+        self.header_row.set_policy(policy);
+    }
+
+    pub fn width_policy(&self) -> Option<WidthPolicy> {
+        self.header_row.policy()
+    }
+
+    pub fn header(&self) -> Option<&[Vec<u8>]> {
+        self.header_row.header()
+    }
+
+    pub fn width_error(&self) -> Option<RowWidthError> {
+        self.header_row.error()
+    }
+
     fn process_buffer(&mut self) {
         let mut pos = self.scan_pos;
         let esc_len = self.escape.len();
         let max_nl_len = self.newlines.max_pattern_len();
+        self.held_back = false;
 
-        while pos < self.buffer.len() {
+        while pos < self.buffer.len() && self.header_row.error().is_none() {
+            if self.complete_rows.len() >= self.max_pending_rows {
+                self.held_back = true;
+                break;
+            }
             if self.in_quotes {
                 if starts_with_escape(&self.buffer, pos, &self.escape) {
                     if starts_with_escape(&self.buffer, pos + esc_len, &self.escape) {
@@ -1267,28 +1509,22 @@ impl GeneralStreamingParserNewlines {
                     // Check shorter patterns that do fit
                     let nl_len = match_newline(&self.buffer, pos, &self.newlines);
                     if nl_len > 0 {
-                        let row_end = pos;
-                        let row = self.parse_row_owned(self.partial_row_start, row_end);
-                        if !row.is_empty() {
-                            self.complete_rows.push(row);
+                        if !self.end_row(pos, nl_len) {
+                            pos = self.partial_row_start;
+                            break;
                         }
                         pos += nl_len;
-                        self.partial_row_start = pos;
-                        self.in_quotes = false;
                     } else {
                         break;
                     }
                 } else {
                     let nl_len = match_newline(&self.buffer, pos, &self.newlines);
                     if nl_len > 0 {
-                        let row_end = pos;
-                        let row = self.parse_row_owned(self.partial_row_start, row_end);
-                        if !row.is_empty() {
-                            self.complete_rows.push(row);
+                        if !self.end_row(pos, nl_len) {
+                            pos = self.partial_row_start;
+                            break;
                         }
                         pos += nl_len;
-                        self.partial_row_start = pos;
-                        self.in_quotes = false;
                     } else {
                         pos += 1;
                     }
@@ -1303,7 +1539,8 @@ impl GeneralStreamingParserNewlines {
         }
     }
 
-    fn parse_row_owned(&self, start: usize, end: usize) -> Vec<Vec<u8>> {
+    fn parse_row_owned(&mut self, start: usize, end: usize) -> Vec<Vec<u8>> {
+        self.stats.record_row(end.saturating_sub(start));
         if start >= end {
             return Vec::new();
         }
@@ -1312,17 +1549,58 @@ impl GeneralStreamingParserNewlines {
         parse_line_fields_owned_general(line, &self.separators, &self.escape)
     }
 
+    /// Cut the row ending at `row_end` (followed by a `nl_len`-byte newline)
+    /// and queue it. Returns false if the width check rejects it, leaving
+    /// `partial_row_start` at its start.
+    fn end_row(&mut self, row_end: usize, nl_len: usize) -> bool {
+        let start = self.partial_row_start;
+        let row = self.parse_row_owned(start, row_end);
+        self.partial_row_start = row_end + nl_len;
+        self.in_quotes = false;
+        if self.push_row(row, start) {
+            return true;
+        }
+        self.partial_row_start = start;
+        false
+    }
+
+    /// Queue a row that starts at `start` and ends at `partial_row_start`
+    /// (blank lines are dropped). Returns false if the width check rejects it.
+    fn push_row(&mut self, mut row: Vec<Vec<u8>>, start: usize) -> bool {
+        if row.is_empty() {
+            return true;
+        }
+        if self.header_row.wants_header() {
+            self.header_row.set_header(row.clone());
+        } else {
+            let row_number = self.complete_rows.next_row_number();
+            let offset = self.base_offset + start as u64;
+            if !self.header_row.fit(&mut row, row_number, offset) {
+                return false;
+            }
+        }
+        let end_offset = self.base_offset + self.partial_row_start as u64;
+        self.complete_rows.push(row, end_offset);
+        true
+    }
+
     fn compact_buffer(&mut self) {
         if self.partial_row_start > 0 {
             self.buffer.drain(0..self.partial_row_start);
+            self.base_offset += self.partial_row_start as u64;
             self.scan_pos -= self.partial_row_start;
             self.partial_row_start = 0;
+            self.stats.record_compaction();
         }
     }
 
     pub fn take_rows(&mut self, max: usize) -> Vec<Vec<Vec<u8>>> {
-        let take_count = max.min(self.complete_rows.len());
-        self.complete_rows.drain(0..take_count).collect()
+        let rows = self.complete_rows.take(max);
+        self.stats.record_taken(rows.len());
+        if self.held_back && !rows.is_empty() {
+            self.process_buffer();
+        }
+        rows
     }
 
     pub fn available_rows(&self) -> usize {
@@ -1338,14 +1616,63 @@ impl GeneralStreamingParserNewlines {
     }
 
     pub fn finalize(&mut self) -> Vec<Vec<Vec<u8>>> {
-        if self.partial_row_start < self.buffer.len() {
-            let row = self.parse_row_owned(self.partial_row_start, self.buffer.len());
-            if !row.is_empty() {
-                self.complete_rows.push(row);
-            }
-            self.partial_row_start = self.buffer.len();
+        if self.held_back {
+            let max_pending_rows = std::mem::replace(&mut self.max_pending_rows, usize::MAX);
+            self.process_buffer();
+            self.max_pending_rows = max_pending_rows;
         }
-        std::mem::take(&mut self.complete_rows)
+        if self.partial_row_start < self.buffer.len() && self.header_row.error().is_none() {
+            self.end_row(self.buffer.len(), 0);
+        }
+        self.take_rows(usize::MAX)
+    }
+
+    /// Reset the parser state, keeping the buffer allocation
+    pub fn reset(&mut self) {
+        self.buffer.clear();
+        self.complete_rows.clear();
+        self.base_offset = 0;
+        self.partial_row_start = 0;
+        self.scan_pos = 0;
+        self.in_quotes = false;
+        self.held_back = false;
+        self.header_row.reset();
+        self.stats = StreamStats::default();
+    }
+
+    /// Take the (emptied) buffer allocation for reuse by another parser
+    pub fn take_buffer(&mut self) -> Vec<u8> {
+        self.reset();
+        std::mem::take(&mut self.buffer)
+    }
+
+    /// Adopt a buffer allocation from another parser; its contents are discarded
+    pub fn reuse_buffer(&mut self, mut buffer: Vec<u8>) {
+        buffer.clear();
+        self.reset();
+        self.buffer = buffer;
+    }
+
+    pub fn max_buffer_size(&self) -> usize {
+        self.max_buffer_size
+    }
+
+    /// Cumulative counters since creation or the last `reset`
+    pub fn stats(&self) -> StreamStats {
+        self.stats
+            .snapshot(self.buffer.len() - self.partial_row_start)
+    }
+
+    pub fn checkpoint(&self) -> Checkpoint {
+        self.complete_rows.checkpoint()
+    }
+
+    /// Start an empty parser at `checkpoint`: offsets continue from
+    /// `checkpoint.offset` and the emitted row count from `checkpoint.rows`
+    pub fn resume_at(&mut self, checkpoint: Checkpoint) {
+        self.reset();
+        self.base_offset = checkpoint.offset;
+        self.complete_rows.resume_at(checkpoint);
     }
 }
 
@@ -1497,4 +1824,77 @@ mod tests {
         assert_eq!(rows[0], vec![b"a".to_vec(), b"b".to_vec()]);
         assert_eq!(rows[1], vec![b"1".to_vec(), b"2".to_vec()]);
     }
+
+    #[test]
+    fn test_general_streaming_max_pending_rows() {
+        let seps = vec![b"::".to_vec()];
+        let esc = b"\"".to_vec();
+        let mut parser = GeneralStreamingParser::new(seps, esc);
+        parser.set_max_pending_rows(1);
+        parser.feed(b"a::b\r\n\"c\nd\"::e\nf").unwrap();
+        assert_eq!(parser.available_rows(), 1);
+        assert!(parser.needs_drain());
+
+        let mut rows = parser.take_rows(1);
+        assert_eq!(parser.available_rows(), 1);
+        rows.extend(parser.take_rows(1));
+        assert!(!parser.needs_drain());
+        rows.extend(parser.finalize());
+        assert_eq!(
+            rows,
+            vec![
+                vec![b"a".to_vec(), b"b".to_vec()],
+                vec![b"c\nd".to_vec(), b"e".to_vec()],
+                vec![b"f".to_vec()],
+            ]
+        );
+    }
+
+    #[test]
+    fn test_general_streaming_feed_from_reader() {
+        let seps = vec![b"::".to_vec()];
+        let esc = b"\"".to_vec();
+        let mut parser = GeneralStreamingParser::new(seps, esc);
+        let mut reader = &b"a::\"b\nc\"\n1::2"[..];
+        while parser.feed_from(&mut reader, 3).unwrap() > 0 {}
+        assert_eq!(
+            parser.finalize(),
+            vec![
+                vec![b"a".to_vec(), b"b\nc".to_vec()],
+                vec![b"1".to_vec(), b"2".to_vec()],
+            ]
+        );
+
+        let newlines = Newlines::custom(vec![b"|".to_vec()]);
+        let mut parser =
+            GeneralStreamingParserNewlines::new(vec![b"::".to_vec()], b"\"".to_vec(), newlines);
+        let mut reader = &b"a::b|c::d|"[..];
+        while parser.feed_from(&mut reader, 3).unwrap() > 0 {}
+        assert_eq!(parser.take_rows(10).len(), 2);
+        assert_eq!(parser.stats().bytes_fed, 10);
+    }
+
+    #[test]
+    fn test_general_streaming_width_policy() {
+        let seps = vec![b"::".to_vec()];
+        let esc = b"\"".to_vec();
+        let mut parser = GeneralStreamingParser::new(seps, esc);
+        parser.set_width_policy(Some(WidthPolicy {
+            pad: true,
+            truncate: false,
+        }));
+        parser.feed(b"a::b\r\n1\r\n2::3::4\r\n5::6\r\n").unwrap();
+
+        assert_eq!(parser.header(), Some(&[b"a".to_vec(), b"b".to_vec()][..]));
+        assert_eq!(
+            parser.take_rows(10),
+            vec![
+                vec![b"a".to_vec(), b"b".to_vec()],
+                vec![b"1".to_vec(), b"".to_vec()],
+            ]
+        );
+        let error = parser.width_error().unwrap();
+        assert_eq!((error.row, error.offset, error.actual), (3, 9, 3));
+        assert!(parser.finalize().is_empty());
+    }
 }
@@ -1,6 +1,8 @@
 // CSV parsing strategies
 
+pub mod detect;
 pub mod direct;
+pub mod directive;
 pub mod encode;
 pub mod encoding;
 pub mod general;
@@ -6,35 +6,380 @@
 // Key design:
 // - Owns data (Vec<u8>) because input chunks are temporary
 // - Buffers incomplete rows until more data arrives
+// - In zero-copy mode, rows that lie inside the chunk they were completed by
+//   are cut as stream ranges (`StreamField::Span`) so the NIF layer can return
+//   sub-binaries of that chunk; rows spanning chunks are copied
 // - Returns rows in batches to reduce NIF call overhead
+// - Scans each fed chunk once with the SIMD structural scanner, carrying the
+//   quote state across chunks; rows are cut from the recorded separator and
+//   row-end positions without a second pass
 
-use crate::core::{extract_field_owned_with_escape, is_separator};
+use super::encoding::{DecodeError, Decoder};
+use crate::core::{
+    extract_field_owned_with_escape, scan_structural_incremental, RowEnd, Terminator,
+};
+use std::io::Read;
 
 /// Default maximum buffer size for streaming parsers (256 MB).
 pub const DEFAULT_MAX_BUFFER: usize = 256 * 1024 * 1024;
 
+/// Bytes scanned at a time while a pending-row limit is set, bounding the
+/// rescan after the limit stops a scan part-way
+const PENDING_SCAN_WINDOW: usize = 64 * 1024;
+
 /// Error returned when a streaming `feed()` would exceed the buffer limit.
 #[derive(Debug, Clone, Copy, PartialEq, Eq)]
 pub struct BufferOverflow;
 
+/// Error returned by a streaming `feed_from()` or `feed_decoded()`: the
+/// buffer is full, the reader failed, or the input is not valid in its
+/// encoding.
+#[derive(Debug)]
+pub enum ReadError {
+    BufferOverflow,
+    Io(std::io::Error),
+    Decode(DecodeError),
+}
+
+/// Append up to `len` bytes from `reader` to a parser buffer, reading into
+/// the buffer itself rather than through an intermediate chunk. Stops short
+/// of `max_buffer_size`; returns the number of bytes read (0 at end of input).
+pub(crate) fn read_into_buffer(
+    buffer: &mut Vec<u8>,
+    reader: &mut impl Read,
+    len: usize,
+    max_buffer_size: usize,
+) -> Result<usize, ReadError> {
+    let room = max_buffer_size.saturating_sub(buffer.len());
+    if room == 0 {
+        return Err(ReadError::BufferOverflow);
+    }
+    reader
+        .take(len.min(room) as u64)
+        .read_to_end(buffer)
+        .map_err(ReadError::Io)
+}
+
+/// Decode `chunk` with `decoder` and append the UTF-8 to a parser buffer.
+/// On error (the decoded bytes would pass `max_buffer_size`, or the chunk is
+/// invalid) the buffer and decoder are left as they were.
+pub(crate) fn decode_into_buffer(
+    buffer: &mut Vec<u8>,
+    decoder: &mut Decoder,
+    chunk: &[u8],
+    max_buffer_size: usize,
+) -> Result<(), ReadError> {
+    let (start, saved) = (buffer.len(), *decoder);
+    let result = match decoder.decode_extend(buffer, chunk) {
+        Ok(()) if buffer.len() > max_buffer_size => Err(ReadError::BufferOverflow),
+        Ok(()) => Ok(()),
+        Err(error) => Err(ReadError::Decode(error)),
+    };
+    if result.is_err() {
+        buffer.truncate(start);
+        *decoder = saved;
+    }
+    result
+}
+
+/// Resume point of a streaming parse: the absolute byte offset just past the
+/// last row handed out by `take_rows`/`finalize`, and how many rows that was.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct Checkpoint {
+    pub offset: u64,
+    pub rows: u64,
+}
+
+/// Cumulative counters of a streaming parse, cleared by `reset`
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct StreamStats {
+    /// Bytes passed to `feed`
+    pub bytes_fed: u64,
+    /// Bytes parsed into complete rows (terminators and blank lines included)
+    pub bytes_consumed: u64,
+    /// Rows handed out by `take_rows`/`finalize`
+    pub rows_emitted: u64,
+    /// Line breaks fed, including those inside quoted fields
+    pub physical_lines: u64,
+    /// Longest row in bytes, without its terminator
+    pub largest_row: u64,
+    /// Largest buffer size reached, in bytes
+    pub peak_buffer: u64,
+    /// Times processed data was drained from the front of the buffer
+    pub compactions: u64,
+    /// Last byte fed was `\r` (a `\r\n` split across chunks is one line)
+    ends_with_cr: bool,
+}
+
+impl StreamStats {
+    /// Count a fed chunk; `buffer_len` is the buffer size after appending it
+    pub fn record_feed(&mut self, chunk: &[u8], buffer_len: usize, terminator: Terminator) {
+        let count = |byte: u8| chunk.iter().filter(|&&b| b == byte).count() as u64;
+        self.physical_lines += match terminator {
+            Terminator::Newline => count(b'\n'),
+            Terminator::Byte(byte) => count(byte),
+            Terminator::AnyNewline => {
+                let split_crlf = self.ends_with_cr && chunk.first() == Some(&b'\n');
+                let crlf = chunk.windows(2).filter(|w| w == b"\r\n").count() as u64;
+                count(b'\n') + count(b'\r') - crlf - u64::from(split_crlf)
+            }
+        };
+        if let Some(&last) = chunk.last() {
+            self.ends_with_cr = last == b'\r';
+        }
+        self.bytes_fed += chunk.len() as u64;
+        self.peak_buffer = self.peak_buffer.max(buffer_len as u64);
+    }
+
+    /// Count a complete row of `len` bytes
+    #[inline]
+    pub fn record_row(&mut self, len: usize) {
+        self.largest_row = self.largest_row.max(len as u64);
+    }
+
+    pub fn record_taken(&mut self, rows: usize) {
+        self.rows_emitted += rows as u64;
+    }
+
+    pub fn record_compaction(&mut self) {
+        self.compactions += 1;
+    }
+
+    /// Counters with `bytes_consumed` derived from the `pending` bytes still
+    /// buffered after the last complete row
+    pub fn snapshot(&self, pending: usize) -> StreamStats {
+        StreamStats {
+            bytes_consumed: self.bytes_fed - pending as u64,
+            ..*self
+        }
+    }
+}
+
+/// A field cut by the single-byte parser: a range of the input stream
+/// (absolute offset and length) that needs no unescaping and lies inside the
+/// chunk its row was completed by, or bytes that had to be copied.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum StreamField {
+    Span(u64, usize),
+    Owned(Vec<u8>),
+}
+
+impl Default for StreamField {
+    /// An empty field (used to pad short rows)
+    fn default() -> Self {
+        StreamField::Owned(Vec::new())
+    }
+}
+
+/// A `StreamField::Span` reached a caller wanting owned bytes: its bytes
+/// are in a fed chunk the parser doesn't keep (zero-copy mode)
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct UnresolvedSpan {
+    pub start: u64,
+    pub len: usize,
+}
+
+impl StreamField {
+    /// Owned bytes of a field cut without zero-copy
+    fn into_owned(self) -> Result<Vec<u8>, UnresolvedSpan> {
+        match self {
+            StreamField::Owned(bytes) => Ok(bytes),
+            StreamField::Span(start, len) => Err(UnresolvedSpan { start, len }),
+        }
+    }
+}
+
+/// Fixes applied to rows whose field count differs from the header row;
+/// a mismatch without a fix is a `RowWidthError`
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct WidthPolicy {
+    /// Pad short rows with empty fields
+    pub pad: bool,
+    /// Drop the extra fields of long rows
+    pub truncate: bool,
+}
+
+/// A row whose field count differs from the header row's: its record
+/// number (header = 1) and absolute byte offset
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct RowWidthError {
+    pub row: u64,
+    pub offset: u64,
+    pub expected: usize,
+    pub actual: usize,
+}
+
+/// Header-aware mode shared by the streaming parsers: the first row queued
+/// is kept as the header and later rows are checked against its width as
+/// they are cut. A rejected row stops the parse before it.
+#[derive(Debug, Clone, Default)]
+pub struct HeaderRow {
+    policy: Option<WidthPolicy>,
+    header: Option<Vec<Vec<u8>>>,
+    error: Option<RowWidthError>,
+}
+
+impl HeaderRow {
+    /// Enable header-aware mode with `policy`, or disable it with `None`
+    pub fn set_policy(&mut self, policy: Option<WidthPolicy>) {
+        self.policy = policy;
+    }
+
+    pub fn policy(&self) -> Option<WidthPolicy> {
+        self.policy
+    }
+
+    pub fn header(&self) -> Option<&[Vec<u8>]> {
+        self.header.as_deref()
+    }
+
+    pub fn error(&self) -> Option<RowWidthError> {
+        self.error
+    }
+
+    /// Whether the next row queued is the header row
+    pub fn wants_header(&self) -> bool {
+        self.policy.is_some() && self.header.is_none()
+    }
+
+    pub fn set_header(&mut self, header: Vec<Vec<u8>>) {
+        self.header = Some(header);
+    }
+
+    /// Fit `row` to the header width. Returns false, recording the error,
+    /// when the mismatch has no fix and the row must not be queued.
+    pub fn fit<F: Default>(&mut self, row: &mut Vec<F>, row_number: u64, offset: u64) -> bool {
+        let (Some(policy), Some(header)) = (self.policy, &self.header) else {
+            return true;
+        };
+        let expected = header.len();
+        if row.len() < expected && policy.pad {
+            row.resize_with(expected, F::default);
+        } else if row.len() > expected && policy.truncate {
+            row.truncate(expected);
+        } else if row.len() != expected {
+            self.error = Some(RowWidthError {
+                row: row_number,
+                offset,
+                expected,
+                actual: row.len(),
+            });
+            return false;
+        }
+        true
+    }
+
+    /// Forget the header row and any error, keeping the policy
+    pub fn reset(&mut self) {
+        self.header = None;
+        self.error = None;
+    }
+}
+
+/// Complete rows waiting to be taken, each with the absolute offset of the
+/// byte after its terminator. Shared by all streaming parser variants.
+#[derive(Default)]
+pub struct RowQueue<R = Vec<Vec<u8>>> {
+    rows: Vec<R>,
+    end_offsets: Vec<u64>,
+    checkpoint: Checkpoint,
+}
+
+impl<R> RowQueue<R> {
+    pub fn push(&mut self, row: R, end_offset: u64) {
+        self.rows.push(row);
+        self.end_offsets.push(end_offset);
+    }
+
+    /// Take up to `max` rows from the front, advancing the checkpoint
+    pub fn take(&mut self, max: usize) -> Vec<R> {
+        let count = max.min(self.rows.len());
+        if count == 0 {
+            return Vec::new();
+        }
+        self.checkpoint.offset = self.end_offsets[count - 1];
+        self.checkpoint.rows += count as u64;
+        self.end_offsets.drain(0..count);
+        if count == self.rows.len() {
+            std::mem::take(&mut self.rows)
+        } else {
+            self.rows.drain(0..count).collect()
+        }
+    }
+
+    pub fn take_all(&mut self) -> Vec<R> {
+        self.take(usize::MAX)
+    }
+
+    pub fn len(&self) -> usize {
+        self.rows.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.rows.is_empty()
+    }
+
+    pub fn checkpoint(&self) -> Checkpoint {
+        self.checkpoint
+    }
+
+    /// Record number (1-based, counting taken and queued rows) of the next
+    /// row pushed
+    pub fn next_row_number(&self) -> u64 {
+        self.checkpoint.rows + self.rows.len() as u64 + 1
+    }
+
+    /// Drop queued rows and start counting from `checkpoint`
+    pub fn resume_at(&mut self, checkpoint: Checkpoint) {
+        self.rows.clear();
+        self.end_offsets.clear();
+        self.checkpoint = checkpoint;
+    }
+
+    pub fn clear(&mut self) {
+        self.resume_at(Checkpoint::default());
+    }
+}
+
 /// State for streaming CSV parser
 pub struct StreamingParser {
     /// Buffer holding unprocessed data
     buffer: Vec<u8>,
     /// Complete rows ready to be taken
-    complete_rows: Vec<Vec<Vec<u8>>>,
+    complete_rows: RowQueue<Vec<StreamField>>,
+    /// Absolute stream offset of `buffer[0]` (bytes compacted away so far)
+    base_offset: u64,
+    /// Absolute stream offset of the first byte of the last fed chunk
+    /// (`u64::MAX` after `feed_from`, which has no chunk to cut spans of)
+    chunk_start: u64,
+    /// Cut rows inside the last fed chunk as `StreamField::Span`s
+    zero_copy: bool,
     /// Position where the current (incomplete) row starts
     partial_row_start: usize,
     /// Position where we left off scanning (resume point)
     scan_pos: usize,
     /// Track if we're inside quotes (important for multi-chunk quoted fields)
     in_quotes: bool,
+    /// Unquoted separator positions in the partial row (buffer offsets)
+    field_seps: Vec<u32>,
+    /// Row ends found by the last scan (reused between feeds)
+    row_ends: Vec<RowEnd>,
     /// Field separator characters (supports multiple separators for NimbleCSV compatibility)
     separators: Vec<u8>,
     /// Quote/escape character
     escape: u8,
+    /// Row terminator (`\n`/`\r\n` by default)
+    terminator: Terminator,
     /// Maximum buffer size in bytes
     max_buffer_size: usize,
+    /// Complete rows to queue before scanning stops (`usize::MAX` = no limit)
+    max_pending_rows: usize,
+    /// Scanning stopped at `max_pending_rows` with unscanned bytes left
+    held_back: bool,
+    /// Header row and width check (header-aware mode)
+    header_row: HeaderRow,
+    /// Cumulative counters (see `stats`)
+    stats: StreamStats,
 }
 
 impl StreamingParser {
@@ -45,29 +390,36 @@ impl StreamingParser {
 
     /// Create a new streaming parser with configurable separator and escape
     pub fn with_config(separator: u8, escape: u8) -> Self {
-        StreamingParser {
-            buffer: Vec::new(),
-            complete_rows: Vec::new(),
-            partial_row_start: 0,
-            scan_pos: 0,
-            in_quotes: false,
-            separators: vec![separator],
-            escape,
-            max_buffer_size: DEFAULT_MAX_BUFFER,
-        }
+        Self::with_multi_sep(&[separator], escape)
     }
 
     /// Create a new streaming parser with multiple separator support
     pub fn with_multi_sep(separators: &[u8], escape: u8) -> Self {
+        Self::with_terminator(separators, escape, Terminator::Newline)
+    }
+
+    /// Create a new streaming parser with a single-byte custom row terminator
+    /// (or bare `\r` recognised as a newline, see `Terminator`)
+    pub fn with_terminator(separators: &[u8], escape: u8, terminator: Terminator) -> Self {
         StreamingParser {
             buffer: Vec::new(),
-            complete_rows: Vec::new(),
+            complete_rows: RowQueue::default(),
+            base_offset: 0,
+            chunk_start: 0,
+            zero_copy: false,
             partial_row_start: 0,
             scan_pos: 0,
             in_quotes: false,
+            field_seps: Vec::new(),
+            row_ends: Vec::new(),
             separators: separators.to_vec(),
             escape,
+            terminator,
             max_buffer_size: DEFAULT_MAX_BUFFER,
+            max_pending_rows: usize::MAX,
+            held_back: false,
+            header_row: HeaderRow::default(),
+            stats: StreamStats::default(),
         }
     }
 
@@ -78,155 +430,279 @@ impl StreamingParser {
             return Err(BufferOverflow);
         }
         // Append chunk to buffer
+        self.chunk_start = self.input_offset();
         self.buffer.extend_from_slice(chunk);
+        self.stats
+            .record_feed(chunk, self.buffer.len(), self.terminator);
 
         // Process buffer to find complete rows
         self.process_buffer();
         Ok(())
     }
 
-    /// Set the maximum buffer size in bytes.
+    /// Read up to `len` bytes from `reader` straight into the buffer and
+    /// parse them. Returns the number of bytes read, 0 at end of input.
+    pub fn feed_from(&mut self, reader: &mut impl Read, len: usize) -> Result<usize, ReadError> {
+        let start = self.buffer.len();
+        let read = read_into_buffer(&mut self.buffer, reader, len, self.max_buffer_size)?;
+        self.chunk_start = u64::MAX;
+        self.stats
+            .record_feed(&self.buffer[start..], self.buffer.len(), self.terminator);
+        self.process_buffer();
+        Ok(read)
+    }
+
+    /// Decode `chunk` (input in `decoder`'s encoding) straight into the
+    /// buffer as UTF-8 and parse it. Offsets and stats then count UTF-8 bytes.
+    pub fn feed_decoded(&mut self, chunk: &[u8], decoder: &mut Decoder) -> Result<(), ReadError> {
+        let start = self.buffer.len();
+        decode_into_buffer(&mut self.buffer, decoder, chunk, self.max_buffer_size)?;
+        self.chunk_start = u64::MAX;
+        self.stats
+            .record_feed(&self.buffer[start..], self.buffer.len(), self.terminator);
+        self.process_buffer();
+        Ok(())
+    }
+
+    /// Set the maximum buffer size in bytes. Capped at 4 GB, the range of
+    /// the scanner's `u32` positions.
     pub fn set_max_buffer_size(&mut self, max: usize) {
-        self.max_buffer_size = max;
+        self.max_buffer_size = max.min(u32::MAX as usize);
+    }
+
+    /// Stop scanning once `max` complete rows are queued; the rest of the
+    /// buffer is scanned as rows are taken
+    pub fn set_max_pending_rows(&mut self, max: usize) {
+        self.max_pending_rows = max.max(1);
+    }
+
+    /// Get the pending row limit
+    pub fn max_pending_rows(&self) -> usize {
+        self.max_pending_rows
     }
 
-    /// Process the buffer to extract complete rows
+    /// Whether buffered bytes are left unscanned until rows are taken
+    pub fn needs_drain(&self) -> bool {
+        self.held_back
+    }
+
+    /// Keep the first row as the header and check later rows' widths
+    /// against it (`None` turns header-aware mode off)
+    pub fn set_width_policy(&mut self, policy: Option<WidthPolicy>) {
+        self.header_row.set_policy(policy);
+    }
+
+    pub fn width_policy(&self) -> Option<WidthPolicy> {
+        self.header_row.policy()
+    }
+
+    /// The header row, once seen in header-aware mode
+    pub fn header(&self) -> Option<&[Vec<u8>]> {
+        self.header_row.header()
+    }
+
+    /// The row width mismatch that stopped the parse, if any. Rows before
+    /// it can still be taken; nothing after it is parsed.
+    pub fn width_error(&self) -> Option<RowWidthError> {
+        self.header_row.error()
+    }
+
+    /// Cut rows completed inside a single fed chunk as stream ranges instead
+    /// of copying their fields (see `StreamField`). The caller must keep every
+    /// chunk until the rows starting in it have been taken.
+    pub fn set_zero_copy(&mut self, enabled: bool) {
+        self.zero_copy = enabled;
+    }
+
+    /// Absolute stream offset the next fed chunk starts at
+    pub fn input_offset(&self) -> u64 {
+        self.base_offset + self.buffer.len() as u64
+    }
+
+    /// Process the buffer to extract complete rows. With a pending-row limit
+    /// the buffer is scanned in windows and scanning stops at the limit.
     fn process_buffer(&mut self) {
-        // Resume from where we left off scanning
-        let mut pos = self.scan_pos;
-        let escape = self.escape;
-
-        while pos < self.buffer.len() {
-            let byte = self.buffer[pos];
-
-            if self.in_quotes {
-                if byte == escape {
-                    if pos + 1 < self.buffer.len() && self.buffer[pos + 1] == escape {
-                        pos += 2;
-                        continue;
-                    }
-                    self.in_quotes = false;
-                }
-                pos += 1;
-            } else if byte == escape {
-                self.in_quotes = true;
-                pos += 1;
-            } else if byte == b'\n' {
-                // Found end of row
-                let row_end = pos;
-                let row = self.parse_row_owned(self.partial_row_start, row_end);
-                if !row.is_empty() {
-                    self.complete_rows.push(row);
-                }
-                pos += 1;
-                self.partial_row_start = pos;
-                // Reset quote state for next row
-                self.in_quotes = false;
-            } else if byte == b'\r' {
-                // Only treat \r as line ending when followed by \n (CRLF).
-                // Bare \r is data per RFC 4180 and NimbleCSV behavior.
-                if pos + 1 < self.buffer.len() {
-                    if self.buffer[pos + 1] == b'\n' {
-                        // CRLF: end of row
-                        let row_end = pos;
-                        let row = self.parse_row_owned(self.partial_row_start, row_end);
-                        if !row.is_empty() {
-                            self.complete_rows.push(row);
-                        }
-                        pos += 2; // skip \r\n
-                        self.partial_row_start = pos;
-                        self.in_quotes = false;
-                    } else {
-                        // Bare \r followed by non-\n: treat as data
-                        pos += 1;
-                    }
-                } else {
-                    // \r at end of buffer: can't tell if \n follows.
-                    // Stop scanning here; next feed() will resolve it.
-                    break;
-                }
-            } else {
-                pos += 1;
+        self.held_back = false;
+        while self.scan_pos < self.buffer.len() && self.header_row.error().is_none() {
+            if self.complete_rows.len() >= self.max_pending_rows {
+                self.held_back = true;
+                break;
             }
+            let scan_end = if self.max_pending_rows == usize::MAX {
+                self.buffer.len()
+            } else {
+                self.buffer.len().min(self.scan_pos + PENDING_SCAN_WINDOW)
+            };
+            self.scan_rows(scan_end);
         }
 
-        // Save scan position for resuming later
-        self.scan_pos = pos;
-
         // Compact buffer: remove processed data to prevent unbounded growth
         if self.partial_row_start > 0 && self.partial_row_start >= self.buffer.len() / 2 {
             self.compact_buffer();
         }
     }
 
-    /// Parse a row from buffer range into owned fields
-    fn parse_row_owned(&self, start: usize, end: usize) -> Vec<Vec<u8>> {
+    /// Scan `buffer[scan_pos..scan_end]` and queue the rows it completes.
+    /// If the pending-row limit is reached or the width check rejects a row,
+    /// scanning rewinds to the start of the next row (outside quotes, no
+    /// separators seen yet).
+    fn scan_rows(&mut self, scan_end: usize) {
+        // Scan only the new bytes; quote state carries over from the
+        // previous scan and separators of the partial row are kept.
+        let mut row_ends = std::mem::take(&mut self.row_ends);
+        row_ends.clear();
+        self.in_quotes = scan_structural_incremental(
+            &self.buffer[..scan_end],
+            self.scan_pos,
+            &self.separators,
+            self.escape,
+            self.terminator,
+            self.in_quotes,
+            &mut self.field_seps,
+            &mut row_ends,
+        );
+        self.scan_pos = scan_end;
+
+        // A bare `\r` row end followed by `\n` in this scan is re-emitted as
+        // `\r\n` starting before `partial_row_start`; it cuts an empty row.
+        let mut sep_idx = 0;
+        for row_end in &row_ends {
+            if self.complete_rows.len() >= self.max_pending_rows {
+                self.scan_pos = self.partial_row_start;
+                self.in_quotes = false;
+                sep_idx = self.field_seps.len();
+                break;
+            }
+            let start = self.partial_row_start;
+            let end = row_end.pos as usize;
+            let seps = &self.field_seps[sep_idx..];
+            let row_seps = &seps[..seps.partition_point(|&s| (s as usize) < end)];
+            let row = self.cut_row(start, end, row_seps);
+            self.stats.record_row(end.saturating_sub(start));
+            sep_idx += row_seps.len();
+            self.partial_row_start = end + row_end.len as usize;
+            if !self.push_row(row, start) {
+                self.partial_row_start = start;
+                self.scan_pos = start;
+                self.in_quotes = false;
+                sep_idx = self.field_seps.len();
+                break;
+            }
+        }
+        self.field_seps.drain(..sep_idx);
+        self.row_ends = row_ends;
+    }
+
+    /// Cut a row from a buffer range, splitting at the scanned separator
+    /// positions `seps` (all within `start..end`)
+    fn cut_row(&self, start: usize, end: usize, seps: &[u32]) -> Vec<StreamField> {
         if start >= end {
             return Vec::new();
         }
 
-        let line = &self.buffer[start..end];
-        let mut fields = Vec::new();
-        let mut pos = 0;
-        let mut field_start = 0;
-        let mut in_quotes = false;
-        let separators = &self.separators;
-        let escape = self.escape;
-
-        while pos < line.len() {
-            let byte = line[pos];
-
-            if in_quotes {
-                if byte == escape {
-                    if pos + 1 < line.len() && line[pos + 1] == escape {
-                        pos += 2;
-                        continue;
-                    }
-                    in_quotes = false;
-                }
-                pos += 1;
-            } else if byte == escape {
-                in_quotes = true;
-                pos += 1;
-            } else if is_separator(byte, separators) {
-                fields.push(extract_field_owned_with_escape(
-                    line,
-                    field_start,
-                    pos,
-                    escape,
-                ));
-                pos += 1;
-                field_start = pos;
-            } else {
-                pos += 1;
-            }
+        // Zero-copy only when the whole row is inside the last fed chunk
+        let spans = self.zero_copy && self.base_offset + start as u64 >= self.chunk_start;
+        let mut fields = Vec::with_capacity(seps.len() + 1);
+        let mut field_start = start;
+        for &sep in seps {
+            let sep = sep as usize;
+            fields.push(self.cut_field(field_start, sep, spans));
+            field_start = sep + 1;
         }
 
         // Last field
-        fields.push(extract_field_owned_with_escape(
-            line,
-            field_start,
-            pos,
-            escape,
-        ));
+        fields.push(self.cut_field(field_start, end, spans));
 
         fields
     }
 
+    /// Cut a field: a span of the unquoted content unless it is empty, has
+    /// doubled escapes, or `spans` is off, in which case it is copied
+    #[inline]
+    fn cut_field(&self, start: usize, end: usize, spans: bool) -> StreamField {
+        if spans && start < end {
+            let field = &self.buffer[start..end];
+            let escape = self.escape;
+            let quoted = field.len() >= 2 && field[0] == escape && field[field.len() - 1] == escape;
+            let offset = self.base_offset + start as u64;
+            if !quoted {
+                return StreamField::Span(offset, field.len());
+            }
+            if !field[1..field.len() - 1].contains(&escape) {
+                return StreamField::Span(offset + 1, field.len() - 2);
+            }
+        }
+        StreamField::Owned(extract_field_owned_with_escape(
+            &self.buffer,
+            start,
+            end,
+            self.escape,
+        ))
+    }
+
+    /// Queue a row that starts at `start` and ends at `partial_row_start`
+    /// (blank lines are dropped). Returns false if the width check rejects it.
+    fn push_row(&mut self, mut row: Vec<StreamField>, start: usize) -> bool {
+        if row.is_empty() {
+            return true;
+        }
+        if self.header_row.wants_header() {
+            let header = row.iter().map(|field| self.field_bytes(field)).collect();
+            self.header_row.set_header(header);
+        } else {
+            let row_number = self.complete_rows.next_row_number();
+            let offset = self.base_offset + start as u64;
+            if !self.header_row.fit(&mut row, row_number, offset) {
+                return false;
+            }
+        }
+        let end_offset = self.base_offset + self.partial_row_start as u64;
+        self.complete_rows.push(row, end_offset);
+        true
+    }
+
+    /// Bytes of a field just cut from the buffer
+    fn field_bytes(&self, field: &StreamField) -> Vec<u8> {
+        match field {
+            StreamField::Span(offset, len) => {
+                let start = (offset - self.base_offset) as usize;
+                self.buffer[start..start + len].to_vec()
+            }
+            StreamField::Owned(bytes) => bytes.clone(),
+        }
+    }
+
     /// Compact buffer by removing already-processed data
     fn compact_buffer(&mut self) {
         if self.partial_row_start > 0 {
             self.buffer.drain(0..self.partial_row_start);
+            self.base_offset += self.partial_row_start as u64;
             // Adjust positions after compaction
+            let shift = self.partial_row_start as u32;
+            for sep in &mut self.field_seps {
+                *sep -= shift;
+            }
             self.scan_pos -= self.partial_row_start;
             self.partial_row_start = 0;
+            self.stats.record_compaction();
         }
     }
 
-    /// Take up to `max` complete rows from the parser
-    pub fn take_rows(&mut self, max: usize) -> Vec<Vec<Vec<u8>>> {
-        let take_count = max.min(self.complete_rows.len());
-        self.complete_rows.drain(0..take_count).collect()
+    /// Take up to `max` complete rows from the parser as owned bytes. Fails
+    /// in zero-copy mode once a row was cut as spans (use `take_field_rows`).
+    pub fn take_rows(&mut self, max: usize) -> Result<Vec<Vec<Vec<u8>>>, UnresolvedSpan> {
+        into_owned_rows(self.take_field_rows(max))
+    }
+
+    /// Take up to `max` complete rows as cut (spans in zero-copy mode).
+    /// Scanning held back by the pending-row limit resumes afterwards.
+    pub fn take_field_rows(&mut self, max: usize) -> Vec<Vec<StreamField>> {
+        let rows = self.complete_rows.take(max);
+        self.stats.record_taken(rows.len());
+        if self.held_back && !rows.is_empty() {
+            self.process_buffer();
+        }
+        rows
     }
 
     /// Check how many complete rows are available
@@ -244,30 +720,90 @@ impl StreamingParser {
         self.buffer.len()
     }
 
-    /// Finalize parsing - treat any remaining data as the last row
-    pub fn finalize(&mut self) -> Vec<Vec<Vec<u8>>> {
-        // Process any remaining partial row
-        if self.partial_row_start < self.buffer.len() {
-            let row = self.parse_row_owned(self.partial_row_start, self.buffer.len());
-            if !row.is_empty() {
-                self.complete_rows.push(row);
-            }
+    /// Finalize parsing - treat any remaining data as the last row. Rows are
+    /// owned bytes, as for `take_rows`.
+    pub fn finalize(&mut self) -> Result<Vec<Vec<Vec<u8>>>, UnresolvedSpan> {
+        into_owned_rows(self.finalize_fields())
+    }
+
+    /// Finalize parsing, returning the remaining rows as cut
+    pub fn finalize_fields(&mut self) -> Vec<Vec<StreamField>> {
+        // Scan bytes held back by the pending-row limit
+        if self.held_back {
+            let max_pending_rows = std::mem::replace(&mut self.max_pending_rows, usize::MAX);
+            self.process_buffer();
+            self.max_pending_rows = max_pending_rows;
+        }
+
+        // Process any remaining partial row (unless a rejected row stopped
+        // the parse before it)
+        if self.partial_row_start < self.buffer.len() && self.header_row.error().is_none() {
+            let start = self.partial_row_start;
+            let row = self.cut_row(start, self.buffer.len(), &self.field_seps);
+            self.stats.record_row(self.buffer.len() - start);
             self.partial_row_start = self.buffer.len();
+            if self.push_row(row, start) {
+                self.field_seps.clear();
+            } else {
+                self.partial_row_start = start;
+            }
         }
 
         // Take all remaining rows
-        std::mem::take(&mut self.complete_rows)
+        self.take_field_rows(usize::MAX)
     }
 
-    /// Reset the parser state
-    #[allow(dead_code)]
+    /// Reset the parser state, keeping the buffer allocation
     pub fn reset(&mut self) {
         self.buffer.clear();
         self.complete_rows.clear();
+        self.base_offset = 0;
+        self.chunk_start = 0;
         self.partial_row_start = 0;
         self.scan_pos = 0;
         self.in_quotes = false;
-        // separator and escape are preserved
+        self.held_back = false;
+        self.field_seps.clear();
+        self.header_row.reset();
+        self.stats = StreamStats::default();
+        // separator, escape and zero-copy mode are preserved
+    }
+
+    /// Take the (emptied) buffer allocation for reuse by another parser
+    pub fn take_buffer(&mut self) -> Vec<u8> {
+        self.reset();
+        std::mem::take(&mut self.buffer)
+    }
+
+    /// Adopt a buffer allocation from another parser; its contents are discarded
+    pub fn reuse_buffer(&mut self, mut buffer: Vec<u8>) {
+        buffer.clear();
+        self.reset();
+        self.buffer = buffer;
+    }
+
+    /// Get the maximum buffer size in bytes
+    pub fn max_buffer_size(&self) -> usize {
+        self.max_buffer_size
+    }
+
+    /// Cumulative counters since creation or the last `reset`
+    pub fn stats(&self) -> StreamStats {
+        self.stats
+            .snapshot(self.buffer.len() - self.partial_row_start)
+    }
+
+    /// Offset and count of the rows handed out so far
+    pub fn checkpoint(&self) -> Checkpoint {
+        self.complete_rows.checkpoint()
+    }
+
+    /// Start an empty parser at `checkpoint`: offsets continue from
+    /// `checkpoint.offset` and the emitted row count from `checkpoint.rows`
+    pub fn resume_at(&mut self, checkpoint: Checkpoint) {
+        self.reset();
+        self.base_offset = checkpoint.offset;
+        self.complete_rows.resume_at(checkpoint);
     }
 
     /// Get the separators
@@ -295,9 +831,16 @@ impl Default for StreamingParser {
     }
 }
 
+fn into_owned_rows(rows: Vec<Vec<StreamField>>) -> Result<Vec<Vec<Vec<u8>>>, UnresolvedSpan> {
+    rows.into_iter()
+        .map(|row| row.into_iter().map(StreamField::into_owned).collect())
+        .collect()
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
+    use crate::strategy::encoding::{encode_utf8_to_target, EncodingTarget};
 
     // Common scenarios moved to tests/conformance.rs.
     // Only unique streaming-specific tests remain here.
@@ -311,7 +854,7 @@ mod tests {
         parser.feed(b"c\n1,2,3\n").unwrap();
         assert_eq!(parser.available_rows(), 2);
 
-        let rows = parser.take_rows(10);
+        let rows = parser.take_rows(10).unwrap();
         assert_eq!(rows[0], vec![b"a".to_vec(), b"b".to_vec(), b"c".to_vec()]);
     }
 
@@ -324,7 +867,7 @@ mod tests {
         parser.feed(b"world\",c\n").unwrap();
         assert_eq!(parser.available_rows(), 1);
 
-        let rows = parser.take_rows(10);
+        let rows = parser.take_rows(10).unwrap();
         assert_eq!(
             rows[0],
             vec![b"a".to_vec(), b"hello world".to_vec(), b"c".to_vec()]
@@ -336,10 +879,10 @@ mod tests {
         let mut parser = StreamingParser::new();
         parser.feed(b"a,b,c\n1,2,3").unwrap();
 
-        let rows1 = parser.take_rows(10);
+        let rows1 = parser.take_rows(10).unwrap();
         assert_eq!(rows1.len(), 1);
 
-        let rows2 = parser.finalize();
+        let rows2 = parser.finalize().unwrap();
         assert_eq!(rows2.len(), 1);
         assert_eq!(rows2[0], vec![b"1".to_vec(), b"2".to_vec(), b"3".to_vec()]);
     }
@@ -349,19 +892,490 @@ mod tests {
         let mut parser = StreamingParser::new();
         parser.feed(b"a\nb\nc\nd\n").unwrap();
 
-        let rows1 = parser.take_rows(2);
+        let rows1 = parser.take_rows(2).unwrap();
         assert_eq!(rows1.len(), 2);
 
-        let rows2 = parser.take_rows(10);
+        let rows2 = parser.take_rows(10).unwrap();
         assert_eq!(rows2.len(), 2);
     }
 
+    #[test]
+    fn test_streaming_every_split_matches_single_feed() {
+        // Long enough to cross SIMD chunk boundaries, with quoted separators,
+        // quoted newlines, escaped quotes, CRLF and an empty line.
+        let input: &[u8] = b"id,name,note\r\n1,\"Smith, John\",\"line one\nline two\"\n\n\
+2,plain,\"say \"\"hi\"\"\"\r\n3,,\"trailing,comma,\"\n4,last,row";
+
+        let mut whole = StreamingParser::new();
+        whole.feed(input).unwrap();
+        let mut expected = whole.take_rows(usize::MAX).unwrap();
+        expected.extend(whole.finalize().unwrap());
+        assert_eq!(expected.len(), 5);
+        assert_eq!(expected[2][2], b"say \"hi\"".to_vec());
+
+        for split in 0..=input.len() {
+            let mut parser = StreamingParser::new();
+            parser.feed(&input[..split]).unwrap();
+            parser.feed(&input[split..]).unwrap();
+            let mut rows = parser.take_rows(usize::MAX).unwrap();
+            rows.extend(parser.finalize().unwrap());
+            assert_eq!(rows, expected, "split at {split}");
+        }
+    }
+
+    #[test]
+    fn test_streaming_byte_at_a_time_with_compaction() {
+        let input = b"a,\"b\nc\",d\r\n".repeat(40);
+        let mut parser = StreamingParser::new();
+        for byte in &input {
+            parser.feed(std::slice::from_ref(byte)).unwrap();
+        }
+        let rows = parser.take_rows(usize::MAX).unwrap();
+        assert_eq!(rows.len(), 40);
+        assert!(rows
+            .iter()
+            .all(|row| row == &vec![b"a".to_vec(), b"b\nc".to_vec(), b"d".to_vec()]));
+        assert!(parser.buffer_size() < input.len());
+    }
+
+    #[test]
+    fn test_reset_keeps_capacity() {
+        let mut parser = StreamingParser::with_config(b';', b'"');
+        parser.feed(b"a;b\n\"partial").unwrap();
+        let capacity = parser.buffer.capacity();
+
+        parser.reset();
+        assert_eq!(parser.buffer_size(), 0);
+        assert_eq!(parser.available_rows(), 0);
+        assert!(!parser.has_partial());
+        assert_eq!(parser.buffer.capacity(), capacity);
+
+        parser.feed(b"c;d\n").unwrap();
+        assert_eq!(
+            parser.take_rows(10).unwrap(),
+            vec![vec![b"c".to_vec(), b"d".to_vec()]]
+        );
+    }
+
+    #[test]
+    fn test_checkpoint_tracks_emitted_rows_across_compaction() {
+        let mut parser = StreamingParser::new();
+        parser.feed(b"a,b\r\n\"x\ny\",2\n\n3,4\n5").unwrap();
+        assert_eq!(parser.checkpoint(), Checkpoint::default());
+
+        parser.take_rows(1).unwrap();
+        assert_eq!(parser.checkpoint(), Checkpoint { offset: 5, rows: 1 });
+
+        // Blank line is skipped; checkpoint ends after "3,4\n"
+        parser.take_rows(10).unwrap();
+        assert_eq!(
+            parser.checkpoint(),
+            Checkpoint {
+                offset: 18,
+                rows: 3
+            }
+        );
+
+        // Compaction drops consumed bytes but offsets stay absolute
+        parser.feed(b",6\n7,8").unwrap();
+        parser.take_rows(10).unwrap();
+        assert_eq!(
+            parser.checkpoint(),
+            Checkpoint {
+                offset: 22,
+                rows: 4
+            }
+        );
+
+        parser.finalize().unwrap();
+        assert_eq!(
+            parser.checkpoint(),
+            Checkpoint {
+                offset: 25,
+                rows: 5
+            }
+        );
+    }
+
+    #[test]
+    fn test_resume_at_checkpoint() {
+        let input: &[u8] = b"h1,h2\n1,2\n3,4\n5,6\n";
+        let mut parser = StreamingParser::new();
+        parser.feed(input).unwrap();
+        parser.take_rows(2).unwrap();
+        let checkpoint = parser.checkpoint();
+
+        let mut resumed = StreamingParser::new();
+        resumed.resume_at(checkpoint);
+        resumed.feed(&input[checkpoint.offset as usize..]).unwrap();
+        assert_eq!(
+            resumed.take_rows(10).unwrap(),
+            vec![
+                vec![b"3".to_vec(), b"4".to_vec()],
+                vec![b"5".to_vec(), b"6".to_vec()]
+            ]
+        );
+        assert_eq!(
+            resumed.checkpoint(),
+            Checkpoint {
+                offset: input.len() as u64,
+                rows: 4
+            }
+        );
+    }
+
+    #[test]
+    fn test_zero_copy_spans_rows_inside_one_chunk() {
+        let mut parser = StreamingParser::new();
+        parser.set_zero_copy(true);
+        parser.feed(b"a,\"b\"\"c\",\"d\",\n1,").unwrap();
+        parser.feed(b"2\n3,4\n").unwrap();
+
+        let rows = parser.take_field_rows(10);
+        // Unquoted and quoted-without-escapes fields are spans; doubled
+        // escapes and empty fields are copied
+        assert_eq!(
+            rows[0],
+            vec![
+                StreamField::Span(0, 1),
+                StreamField::Owned(b"b\"c".to_vec()),
+                StreamField::Span(10, 1),
+                StreamField::Owned(Vec::new()),
+            ]
+        );
+        // "1,2" started in the previous chunk
+        assert_eq!(
+            rows[1],
+            vec![
+                StreamField::Owned(b"1".to_vec()),
+                StreamField::Owned(b"2".to_vec())
+            ]
+        );
+        assert_eq!(
+            rows[2],
+            vec![StreamField::Span(18, 1), StreamField::Span(20, 1)]
+        );
+
+        parser.feed(b"5").unwrap();
+        assert_eq!(
+            parser.finalize_fields(),
+            vec![vec![StreamField::Span(22, 1)]]
+        );
+    }
+
+    #[test]
+    fn test_zero_copy_spans_are_not_owned_rows() {
+        let mut parser = StreamingParser::new();
+        parser.set_zero_copy(true);
+        parser.feed(b"ab,c\n").unwrap();
+        assert_eq!(
+            parser.take_rows(10),
+            Err(UnresolvedSpan { start: 0, len: 2 })
+        );
+    }
+
+    #[test]
+    fn test_streaming_custom_byte_terminator() {
+        let mut parser = StreamingParser::with_terminator(b",", b'"', Terminator::Byte(b'|'));
+        parser.feed(b"a,\"b|c\"|d\n").unwrap();
+        parser.feed(b",e|").unwrap();
+        assert_eq!(
+            parser.take_rows(10).unwrap(),
+            vec![
+                vec![b"a".to_vec(), b"b|c".to_vec()],
+                vec![b"d\n".to_vec(), b"e".to_vec()]
+            ]
+        );
+    }
+
+    #[test]
+    fn test_streaming_any_newline_every_split() {
+        let input: &[u8] = b"a,b\r1,\"x\ry\"\r\n2,3\n\r4,5\r";
+        let expected = vec![
+            vec![b"a".to_vec(), b"b".to_vec()],
+            vec![b"1".to_vec(), b"x\ry".to_vec()],
+            vec![b"2".to_vec(), b"3".to_vec()],
+            vec![b"4".to_vec(), b"5".to_vec()],
+        ];
+
+        for split in 0..=input.len() {
+            let mut parser = StreamingParser::with_terminator(b",", b'"', Terminator::AnyNewline);
+            parser.feed(&input[..split]).unwrap();
+            parser.feed(&input[split..]).unwrap();
+            let mut rows = parser.take_rows(usize::MAX).unwrap();
+            rows.extend(parser.finalize().unwrap());
+            assert_eq!(rows, expected, "split at {split}");
+            assert_eq!(parser.checkpoint().offset, input.len() as u64);
+            assert_eq!(parser.stats().physical_lines, 6, "split at {split}");
+        }
+    }
+
+    #[test]
+    fn test_streaming_max_pending_rows() {
+        let mut parser = StreamingParser::new();
+        parser.set_max_pending_rows(2);
+        parser.feed(b"a,1\nb,\"x\ny\"\nc,3\nd,4\ne").unwrap();
+        assert_eq!(parser.available_rows(), 2);
+        assert!(parser.needs_drain());
+
+        assert_eq!(
+            parser.take_rows(1).unwrap(),
+            vec![vec![b"a".to_vec(), b"1".to_vec()]]
+        );
+        assert_eq!(parser.available_rows(), 2);
+        assert!(parser.needs_drain());
+
+        let mut rows = parser.take_rows(10).unwrap();
+        assert_eq!(rows.len(), 2);
+        assert!(!parser.needs_drain());
+        rows.extend(parser.finalize().unwrap());
+        assert_eq!(
+            rows,
+            vec![
+                vec![b"b".to_vec(), b"x\ny".to_vec()],
+                vec![b"c".to_vec(), b"3".to_vec()],
+                vec![b"d".to_vec(), b"4".to_vec()],
+                vec![b"e".to_vec()],
+            ]
+        );
+    }
+
+    #[test]
+    fn test_streaming_max_pending_rows_across_scan_windows() {
+        let mut input = Vec::new();
+        for i in 0..20_000 {
+            input.extend_from_slice(format!("{i},\"q{i}\r\n\"\r\n").as_bytes());
+        }
+        let mut unlimited = StreamingParser::new();
+        unlimited.feed(&input).unwrap();
+        let expected = unlimited.take_rows(usize::MAX).unwrap();
+
+        let mut parser = StreamingParser::new();
+        parser.set_max_pending_rows(1000);
+        parser.feed(&input).unwrap();
+        let mut rows = Vec::new();
+        while parser.available_rows() > 0 {
+            assert!(parser.available_rows() <= 1000);
+            rows.extend(parser.take_rows(300).unwrap());
+        }
+        rows.extend(parser.finalize().unwrap());
+        assert_eq!(rows.len(), 20_000);
+        assert_eq!(rows, expected);
+        assert_eq!(parser.checkpoint().offset, input.len() as u64);
+    }
+
+    #[test]
+    fn test_streaming_finalize_ignores_max_pending_rows() {
+        let mut parser = StreamingParser::with_terminator(b",", b'"', Terminator::AnyNewline);
+        parser.set_max_pending_rows(1);
+        parser.feed(b"a\rb\r\nc\n").unwrap();
+        assert!(parser.needs_drain());
+        assert_eq!(
+            parser.finalize().unwrap(),
+            vec![
+                vec![b"a".to_vec()],
+                vec![b"b".to_vec()],
+                vec![b"c".to_vec()]
+            ]
+        );
+    }
+
+    #[test]
+    fn test_streaming_width_error_stops_before_row() {
+        let mut parser = StreamingParser::new();
+        parser.set_zero_copy(true);
+        parser.set_width_policy(Some(WidthPolicy::default()));
+        parser.feed(b"id,\"na\"\"me\"\n1,a\n2\n3,c\n").unwrap();
+
+        assert_eq!(
+            parser.header(),
+            Some(&[b"id".to_vec(), b"na\"me".to_vec()][..])
+        );
+        assert_eq!(
+            parser.width_error(),
+            Some(RowWidthError {
+                row: 3,
+                offset: 16,
+                expected: 2,
+                actual: 1
+            })
+        );
+        assert_eq!(parser.take_field_rows(10).len(), 2);
+        assert!(parser.finalize_fields().is_empty());
+        assert_eq!(parser.checkpoint().offset, 16);
+
+        parser.reset();
+        assert_eq!(parser.header(), None);
+        assert_eq!(parser.width_error(), None);
+    }
+
+    #[test]
+    fn test_streaming_width_pad_and_truncate() {
+        let mut parser = StreamingParser::new();
+        parser.set_width_policy(Some(WidthPolicy {
+            pad: true,
+            truncate: true,
+        }));
+        parser.feed(b"a,b,c\n1\n2,x,y,z\n").unwrap();
+        parser.feed(b"3,4").unwrap();
+
+        let mut rows = parser.take_rows(10).unwrap();
+        rows.extend(parser.finalize().unwrap());
+        assert_eq!(
+            rows,
+            vec![
+                vec![b"a".to_vec(), b"b".to_vec(), b"c".to_vec()],
+                vec![b"1".to_vec(), b"".to_vec(), b"".to_vec()],
+                vec![b"2".to_vec(), b"x".to_vec(), b"y".to_vec()],
+                vec![b"3".to_vec(), b"4".to_vec(), b"".to_vec()],
+            ]
+        );
+        assert_eq!(parser.width_error(), None);
+    }
+
+    #[test]
+    fn test_streaming_width_error_on_last_row() {
+        let mut parser = StreamingParser::new();
+        parser.set_width_policy(Some(WidthPolicy {
+            pad: true,
+            truncate: false,
+        }));
+        parser.feed(b"a,b\n1,2,3").unwrap();
+        assert_eq!(parser.take_rows(10).unwrap().len(), 1);
+        assert!(parser.finalize().unwrap().is_empty());
+        assert_eq!(
+            parser.width_error(),
+            Some(RowWidthError {
+                row: 2,
+                offset: 4,
+                expected: 2,
+                actual: 3
+            })
+        );
+    }
+
+    #[test]
+    fn test_streaming_stats() {
+        let mut parser = StreamingParser::new();
+        parser.feed(b"id,name\n1,\"a\nb\"\n").unwrap();
+        parser.feed(b"2,c").unwrap();
+        assert_eq!(parser.take_rows(1).unwrap().len(), 1);
+
+        let stats = parser.stats();
+        assert_eq!(stats.bytes_fed, 19);
+        assert_eq!(stats.bytes_consumed, 16);
+        assert_eq!(stats.rows_emitted, 1);
+        assert_eq!(stats.physical_lines, 3);
+        assert_eq!(stats.largest_row, 7);
+        assert_eq!(stats.peak_buffer, 16);
+        assert_eq!(stats.compactions, 1);
+
+        assert_eq!(parser.finalize().unwrap().len(), 2);
+        let stats = parser.stats();
+        assert_eq!(stats.bytes_consumed, 19);
+        assert_eq!(stats.rows_emitted, 3);
+
+        parser.reset();
+        assert_eq!(parser.stats(), StreamStats::default());
+    }
+
+    #[test]
+    fn test_streaming_feed_from_reader() {
+        let mut parser = StreamingParser::new();
+        parser.set_zero_copy(true);
+        let mut reader = &b"a,\"b\nc\"\n1,2\n3"[..];
+        while parser.feed_from(&mut reader, 4).unwrap() > 0 {}
+
+        // Nothing backs the read bytes on the BEAM side, so rows are copied
+        assert_eq!(
+            parser.take_field_rows(10),
+            vec![
+                vec![
+                    StreamField::Owned(b"a".to_vec()),
+                    StreamField::Owned(b"b\nc".to_vec())
+                ],
+                vec![
+                    StreamField::Owned(b"1".to_vec()),
+                    StreamField::Owned(b"2".to_vec())
+                ],
+            ]
+        );
+        assert_eq!(
+            parser.finalize_fields(),
+            vec![vec![StreamField::Owned(b"3".to_vec())]]
+        );
+        assert_eq!(parser.stats().bytes_fed, 13);
+    }
+
+    #[test]
+    fn test_streaming_feed_from_buffer_overflow() {
+        let mut parser = StreamingParser::new();
+        parser.set_max_buffer_size(4);
+        let mut reader = &b"abcdef\n"[..];
+        assert_eq!(parser.feed_from(&mut reader, 16).unwrap(), 4);
+        assert!(matches!(
+            parser.feed_from(&mut reader, 16),
+            Err(ReadError::BufferOverflow)
+        ));
+    }
+
+    #[test]
+    fn test_streaming_feed_decoded_across_chunks() {
+        let text = "a,\"\u{e9}\n\u{1F600}\"\n1,2\n";
+        let encoded = encode_utf8_to_target(text.as_bytes(), EncodingTarget::Utf16Le);
+        for split in 0..=encoded.len() {
+            let mut parser = StreamingParser::new();
+            parser.set_zero_copy(true);
+            let mut decoder = Decoder::new(EncodingTarget::Utf16Le);
+            parser
+                .feed_decoded(&encoded[..split], &mut decoder)
+                .unwrap();
+            parser
+                .feed_decoded(&encoded[split..], &mut decoder)
+                .unwrap();
+            assert_eq!(
+                parser.take_field_rows(10),
+                vec![
+                    vec![
+                        StreamField::Owned(b"a".to_vec()),
+                        StreamField::Owned("\u{e9}\n\u{1F600}".as_bytes().to_vec())
+                    ],
+                    vec![
+                        StreamField::Owned(b"1".to_vec()),
+                        StreamField::Owned(b"2".to_vec())
+                    ],
+                ]
+            );
+        }
+    }
+
+    #[test]
+    fn test_streaming_feed_decoded_errors_leave_buffer() {
+        let mut parser = StreamingParser::new();
+        parser.set_max_buffer_size(4);
+        let mut decoder = Decoder::new(EncodingTarget::Latin1);
+        parser.feed_decoded(b"ab", &mut decoder).unwrap();
+        // Three bytes decode to five, past the limit
+        assert!(matches!(
+            parser.feed_decoded(&[0xe9, 0xe9, b'c'], &mut decoder),
+            Err(ReadError::BufferOverflow)
+        ));
+        assert_eq!(parser.buffer_size(), 2);
+
+        let mut decoder = Decoder::new(EncodingTarget::Utf32Le);
+        assert!(matches!(
+            parser.feed_decoded(&[0, 0, 0x11, 0], &mut decoder),
+            Err(ReadError::Decode(_))
+        ));
+        assert_eq!(parser.finalize().unwrap(), vec![vec![b"ab".to_vec()]]);
+    }
+
     #[test]
     fn test_streaming_bare_cr_is_data() {
         let mut parser = StreamingParser::new();
         parser.feed(b"a\rb\n").unwrap();
 
-        let rows = parser.take_rows(10);
+        let rows = parser.take_rows(10).unwrap();
         assert_eq!(rows.len(), 1);
         assert_eq!(rows[0], vec![b"a\rb".to_vec()]);
     }
@@ -375,7 +1389,7 @@ mod tests {
         parser.feed(b"\nc,d\n").unwrap();
         assert_eq!(parser.available_rows(), 2);
 
-        let rows = parser.take_rows(10);
+        let rows = parser.take_rows(10).unwrap();
         assert_eq!(rows[0], vec![b"a".to_vec(), b"b".to_vec()]);
         assert_eq!(rows[1], vec![b"c".to_vec(), b"d".to_vec()]);
     }
@@ -389,7 +1403,7 @@ mod tests {
         parser.feed(b"b\n").unwrap();
         assert_eq!(parser.available_rows(), 1);
 
-        let rows = parser.take_rows(10);
+        let rows = parser.take_rows(10).unwrap();
         assert_eq!(rows[0], vec![b"a\rb".to_vec()]);
     }
 }
@@ -4,7 +4,7 @@
 // that can be used to create BEAM sub-binaries referencing the original input.
 // Uses the SIMD structural scanner for fast boundary detection.
 
-use crate::core::scan_structural;
+use crate::core::{scan_structural_with_terminator, Terminator};
 
 /// Parse CSV and return field boundaries (zero-copy approach)
 #[allow(dead_code)]
@@ -18,21 +18,7 @@ pub fn parse_csv_boundaries_with_config(
     separator: u8,
     escape: u8,
 ) -> Vec<Vec<(usize, usize)>> {
-    let idx = scan_structural(input, &[separator], escape);
-    let mut rows = Vec::with_capacity(idx.row_count());
-
-    for row in idx.rows_with_fields() {
-        let boundaries: Vec<(usize, usize)> = row
-            .fields
-            .map(|(fs, fe)| (fs as usize, fe as usize))
-            .collect();
-
-        if !boundaries.is_empty() {
-            rows.push(boundaries);
-        }
-    }
-
-    rows
+    parse_csv_boundaries_with_terminator(input, &[separator], escape, Terminator::Newline)
 }
 
 /// Parse CSV with multiple separator support, returning boundaries
@@ -41,11 +27,18 @@ pub fn parse_csv_boundaries_multi_sep(
     separators: &[u8],
     escape: u8,
 ) -> Vec<Vec<(usize, usize)>> {
-    if separators.len() == 1 {
-        return parse_csv_boundaries_with_config(input, separators[0], escape);
-    }
+    parse_csv_boundaries_with_terminator(input, separators, escape, Terminator::Newline)
+}
 
-    let idx = scan_structural(input, separators, escape);
+/// Parse CSV with single-byte separators, escape and row terminator
+/// (e.g. `|` or `\r` custom newlines), returning boundaries
+pub fn parse_csv_boundaries_with_terminator(
+    input: &[u8],
+    separators: &[u8],
+    escape: u8,
+    terminator: Terminator,
+) -> Vec<Vec<(usize, usize)>> {
+    let idx = scan_structural_with_terminator(input, separators, escape, terminator);
     let mut rows = Vec::with_capacity(idx.row_count());
 
     for row in idx.rows_with_fields() {
@@ -1,36 +1,19 @@
 // Shared term building utilities for converting Rust data to Elixir terms
 
-use rustler::{Binary, Env, NewBinary, Term};
+use rustler::types::tuple::make_tuple;
+use rustler::{Atom, Binary, Env, Error, NewBinary, NifResult, Term};
 
-/// Convert a list of byte-like fields to an Elixir cons-list of binaries.
-/// Works with any iterator of `AsRef<[u8]>` items (Vec<u8>, Cow<[u8]>, &[u8], etc).
-fn fields_to_term_inner<'a>(
-    env: Env<'a>,
-    fields: impl DoubleEndedIterator<Item = impl AsRef<[u8]>>,
-) -> Term<'a> {
-    let mut list = Term::list_new_empty(env);
-    for field in fields.rev() {
-        let bytes = field.as_ref();
-        let mut binary = NewBinary::new(env, bytes.len());
-        binary.as_mut_slice().copy_from_slice(bytes);
-        let binary_term: Term = binary.into();
-        list = list.list_prepend(binary_term);
-    }
-    list
-}
-
-/// Convert owned rows to Elixir term (for streaming/parallel parsers)
-pub fn owned_rows_to_term<'a>(env: Env<'a>, rows: Vec<Vec<Vec<u8>>>) -> Term<'a> {
-    let mut list = Term::list_new_empty(env);
-    for row in rows.into_iter().rev() {
-        list = list.list_prepend(owned_fields_to_term(env, row));
-    }
-    list
-}
+use crate::core::simd_scanner::{utf16_unit, utf32_unit};
+use crate::core::StructuralIndex;
+use crate::strategy::encoding::{decode_into, decoded_len, DecodeError, EncodingTarget};
+use crate::strategy::StreamField;
 
-/// Convert owned fields to an Elixir list of binaries
-pub fn owned_fields_to_term<'a>(env: Env<'a>, fields: Vec<Vec<u8>>) -> Term<'a> {
-    fields_to_term_inner(env, fields.into_iter())
+/// Copy bytes into a new binary term
+#[inline]
+pub fn copy_to_binary<'a>(env: Env<'a>, bytes: &[u8]) -> Term<'a> {
+    let mut binary = NewBinary::new(env, bytes.len());
+    binary.as_mut_slice().copy_from_slice(bytes);
+    binary.into()
 }
 
 // ============================================================================
@@ -55,6 +38,94 @@ fn make_subbinary<'a>(env: Env<'a>, input: &Binary<'a>, start: usize, len: usize
 
 pub(crate) use crate::core::unescape_field;
 
+// ============================================================================
+// Streaming Rows (sub-binaries of fed chunks)
+// ============================================================================
+
+/// Chunks fed to a streaming parser, loaded into the caller's env, with the
+/// absolute stream offset each one starts at (ascending).
+pub struct LoadedChunks<'a> {
+    chunks: Vec<(u64, Binary<'a>)>,
+}
+
+impl<'a> LoadedChunks<'a> {
+    pub fn new(chunks: Vec<(u64, Binary<'a>)>) -> Self {
+        LoadedChunks { chunks }
+    }
+
+    /// The chunk holding the stream range `start..start + len`, and the
+    /// range's offset within it
+    fn locate(&self, start: u64, len: usize) -> Option<(&Binary<'a>, usize)> {
+        let idx = self
+            .chunks
+            .partition_point(|(chunk_start, _)| *chunk_start <= start)
+            .checked_sub(1)?;
+        let (chunk_start, chunk) = &self.chunks[idx];
+        let offset = (start - chunk_start) as usize;
+        (offset + len <= chunk.len()).then_some((chunk, offset))
+    }
+
+    /// Field as a term: a sub-binary of its chunk for spans, a copy otherwise.
+    /// Raises `:internal_span_error` for a span outside the loaded chunks
+    /// (a chunk was released too early), rather than return wrong data.
+    pub fn field_to_term(&self, env: Env<'a>, field: &StreamField) -> NifResult<Term<'a>> {
+        match field {
+            StreamField::Owned(bytes) => Ok(copy_to_binary(env, bytes)),
+            StreamField::Span(start, len) => {
+                let (chunk, offset) = self.locate(*start, *len).ok_or_else(span_error)?;
+                Ok(make_subbinary(env, chunk, offset, *len))
+            }
+        }
+    }
+
+    /// Field bytes (for header rows, which become owned keys)
+    pub fn field_bytes(&self, field: StreamField) -> NifResult<Vec<u8>> {
+        match field {
+            StreamField::Owned(bytes) => Ok(bytes),
+            StreamField::Span(start, len) => {
+                let (chunk, offset) = self.locate(start, len).ok_or_else(span_error)?;
+                Ok(chunk.as_slice()[offset..offset + len].to_vec())
+            }
+        }
+    }
+}
+
+fn span_error() -> Error {
+    Error::RaiseTerm(Box::new(crate::atoms::internal_span_error()))
+}
+
+/// Load each streamed field as a term (see `LoadedChunks::field_to_term`)
+fn stream_row_terms<'a>(
+    env: Env<'a>,
+    chunks: &LoadedChunks<'a>,
+    rows: Vec<Vec<StreamField>>,
+) -> NifResult<Vec<Vec<Term<'a>>>> {
+    rows.iter()
+        .map(|row| {
+            row.iter()
+                .map(|field| chunks.field_to_term(env, field))
+                .collect()
+        })
+        .collect()
+}
+
+/// Convert streaming parser rows to an Elixir list of lists of binaries
+pub fn stream_rows_to_term<'a>(
+    env: Env<'a>,
+    chunks: &LoadedChunks<'a>,
+    rows: Vec<Vec<StreamField>>,
+) -> NifResult<Term<'a>> {
+    let mut list = Term::list_new_empty(env);
+    for row in rows.into_iter().rev() {
+        let mut row_list = Term::list_new_empty(env);
+        for field in row.iter().rev() {
+            row_list = row_list.list_prepend(chunks.field_to_term(env, field)?);
+        }
+        list = list.list_prepend(row_list);
+    }
+    Ok(list)
+}
+
 /// Convert a single field to a term, using sub-binary when possible (hybrid Cow approach)
 /// - Unquoted fields: sub-binary (zero-copy)
 /// - Quoted without escapes: sub-binary of inner content (zero-copy)
@@ -116,6 +187,27 @@ pub fn boundaries_to_term_hybrid<'a>(
     list
 }
 
+/// Convert field boundaries to rows of field terms (sub-binaries where
+/// possible), for builders that take rows already converted
+pub fn boundaries_to_rows_hybrid<'a>(
+    env: Env<'a>,
+    input: Binary<'a>,
+    boundaries: &[Vec<(usize, usize)>],
+    escape: &[u8],
+) -> Vec<Vec<Term<'a>>> {
+    boundaries
+        .iter()
+        .map(|row| {
+            row.iter()
+                .map(|&(start, end)| match escape {
+                    [escape] => field_to_term_hybrid(env, &input, start, end, *escape),
+                    _ => field_to_term_hybrid_general(env, &input, start, end, escape),
+                })
+                .collect()
+        })
+        .collect()
+}
+
 // ============================================================================
 // General Multi-Byte Escape Support (for zero-copy path)
 // ============================================================================
@@ -186,10 +278,463 @@ pub fn boundaries_to_term_hybrid_general<'a>(
     list
 }
 
+// ============================================================================
+// UTF-16 and UTF-32 Rows (fields transcoded to UTF-8 as they are built)
+// ============================================================================
+
+/// Width in bytes of a UTF-16 or UTF-32 code unit
+fn unit_width(source: EncodingTarget) -> usize {
+    match source {
+        EncodingTarget::Utf32Le | EncodingTarget::Utf32Be => 4,
+        _ => 2,
+    }
+}
+
+/// Code unit `i` of UTF-16 or UTF-32 `input`
+fn code_unit(input: &[u8], i: usize, source: EncodingTarget) -> u32 {
+    match source {
+        EncodingTarget::Utf16Le => u32::from(utf16_unit(input, i, false)),
+        EncodingTarget::Utf16Be => u32::from(utf16_unit(input, i, true)),
+        EncodingTarget::Utf32Le => utf32_unit(input, i, false),
+        _ => utf32_unit(input, i, true),
+    }
+}
+
+/// Convert one UTF-16 or UTF-32 field (code units `start..end`) to a UTF-8
+/// binary, stripping the quotes and unescaping doubled ones. Decodes straight
+/// into the new binary unless there are quotes to unescape, which go through
+/// `scratch`.
+fn wide_field_to_term<'a>(
+    env: Env<'a>,
+    input: &[u8],
+    source: EncodingTarget,
+    (mut start, mut end): (usize, usize),
+    escape: u8,
+    scratch: &mut Vec<u8>,
+) -> Result<Term<'a>, DecodeError> {
+    let width = unit_width(source);
+    let escape_unit = u32::from(escape);
+    let quoted = end - start >= 2
+        && code_unit(input, start, source) == escape_unit
+        && code_unit(input, end - 1, source) == escape_unit;
+    if quoted {
+        start += 1;
+        end -= 1;
+    }
+
+    let bytes = &input[width * start..width * end];
+    // A sequence cut off by a separator or quote is invalid, not incomplete
+    let len = decoded_len(bytes, source).map_err(|error| DecodeError {
+        offset: error.offset + (width * start) as u64,
+        incomplete: error.incomplete && width * end == input.len(),
+        ..error
+    })?;
+    let escaped = quoted && (start..end).any(|i| code_unit(input, i, source) == escape_unit);
+
+    if escaped {
+        scratch.clear();
+        scratch.resize(len, 0);
+        decode_into(scratch, bytes, source);
+        return Ok(copy_to_binary(env, &unescape_field(scratch, escape)));
+    }
+    let mut binary = NewBinary::new(env, len);
+    decode_into(binary.as_mut_slice(), bytes, source);
+    Ok(binary.into())
+}
+
+/// Convert a `StructuralIndex` of UTF-16 or UTF-32 `input` (from
+/// `scan_structural_utf16` or `scan_structural_utf32`) to rows of UTF-8
+/// binaries, for the list, tuple and map builders. Fields are built in input
+/// order, so an error names the first invalid sequence.
+pub fn wide_index_to_rows<'a>(
+    env: Env<'a>,
+    input: &[u8],
+    source: EncodingTarget,
+    index: &StructuralIndex,
+    escape: u8,
+) -> Result<Vec<Vec<Term<'a>>>, DecodeError> {
+    let mut scratch = Vec::new();
+    let mut rows = Vec::with_capacity(index.row_count());
+    for row in index.rows_with_fields() {
+        let fields = row
+            .fields
+            .map(|(start, end)| {
+                wide_field_to_term(
+                    env,
+                    input,
+                    source,
+                    (start as usize, end as usize),
+                    escape,
+                    &mut scratch,
+                )
+            })
+            .collect::<Result<Vec<_>, _>>()?;
+        rows.push(fields);
+    }
+    Ok(rows)
+}
+
+// ============================================================================
+// Single-Byte Rows (Latin-1 and codepages, fields decoded as they are built)
+// ============================================================================
+
+/// Convert one field of single-byte `input` to a UTF-8 binary. ASCII fields
+/// read the same in UTF-8 and stay sub-binaries, as in `field_to_term_hybrid`;
+/// the others are decoded into a new binary, unescaping through `scratch`.
+fn single_byte_field_to_term<'a>(
+    env: Env<'a>,
+    input: &Binary<'a>,
+    source: EncodingTarget,
+    (mut start, mut end): (usize, usize),
+    escape: u8,
+    scratch: &mut Vec<u8>,
+) -> Result<Term<'a>, DecodeError> {
+    let field = &input.as_slice()[start..end];
+    if field.is_ascii() {
+        return Ok(field_to_term_hybrid(env, input, start, end, escape));
+    }
+    let quoted = field.len() >= 2 && field[0] == escape && field[field.len() - 1] == escape;
+    if quoted {
+        start += 1;
+        end -= 1;
+    }
+
+    let bytes = &input.as_slice()[start..end];
+    let len = decoded_len(bytes, source)?;
+    if quoted && bytes.contains(&escape) {
+        scratch.clear();
+        scratch.resize(len, 0);
+        decode_into(scratch, bytes, source);
+        return Ok(copy_to_binary(env, &unescape_field(scratch, escape)));
+    }
+    let mut binary = NewBinary::new(env, len);
+    decode_into(binary.as_mut_slice(), bytes, source);
+    Ok(binary.into())
+}
+
+/// Convert field boundaries of Latin-1 or codepage `input` (parsed as bytes,
+/// with ASCII separators, escape and newlines) to rows of UTF-8 binaries,
+/// without decoding the whole input first.
+pub fn single_byte_boundaries_to_rows<'a>(
+    env: Env<'a>,
+    input: Binary<'a>,
+    source: EncodingTarget,
+    boundaries: Vec<Vec<(usize, usize)>>,
+    escape: u8,
+) -> Result<Vec<Vec<Term<'a>>>, DecodeError> {
+    let mut scratch = Vec::new();
+    let mut rows = Vec::with_capacity(boundaries.len());
+    for row in boundaries {
+        let fields = row
+            .into_iter()
+            .map(|field| {
+                single_byte_field_to_term(env, &input, source, field, escape, &mut scratch)
+            })
+            .collect::<Result<Vec<_>, _>>()?;
+        rows.push(fields);
+    }
+    Ok(rows)
+}
+
 // ============================================================================
 // Map Builders (for headers-to-maps feature)
 // ============================================================================
 
+/// Policy for header keys that occur more than once.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum DuplicateHeaders {
+    /// Last column with the key wins (default)
+    Last,
+    /// First column with the key wins
+    First,
+    /// Raise `{:duplicate_header, name}`
+    Error,
+    /// Rename repeats: `key`, `key_2`, `key_3`, ...
+    Suffix,
+    /// Collect the values of all columns with the key into a list
+    List,
+}
+
+/// Owned header key for parsers that outlive a single NIF call (streaming).
+/// Key terms are rebuilt from these in each call's env.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum HeaderKey {
+    Binary(Vec<u8>),
+    Atom(String),
+}
+
+impl HeaderKey {
+    /// Capture a binary or atom key term
+    pub fn from_term(term: Term) -> NifResult<Self> {
+        if let Ok(bin) = term.decode::<Binary>() {
+            return Ok(HeaderKey::Binary(bin.as_slice().to_vec()));
+        }
+        if let Ok(name) = term.atom_to_string() {
+            return Ok(HeaderKey::Atom(name));
+        }
+        Err(Error::BadArg)
+    }
+
+    pub fn to_term<'a>(&self, env: Env<'a>) -> NifResult<Term<'a>> {
+        match self {
+            HeaderKey::Binary(bytes) => {
+                let mut binary = NewBinary::new(env, bytes.len());
+                binary.as_mut_slice().copy_from_slice(bytes);
+                Ok(binary.into())
+            }
+            HeaderKey::Atom(name) => Ok(Atom::from_str(env, name)?.encode(env)),
+        }
+    }
+}
+
+/// Header keys resolved against a `DuplicateHeaders` policy.
+/// `keys` are unique; `columns[i]` lists the row columns that feed `keys[i]`,
+/// and `fallbacks[i]` is the value used when no column feeds it (`None` = nil,
+/// or omitted under `MissingColumns::Omit`). `width` is the header column count.
+pub struct MapKeys<'a> {
+    keys: Vec<Term<'a>>,
+    columns: Vec<Vec<usize>>,
+    fallbacks: Vec<Option<Term<'a>>>,
+    policy: DuplicateHeaders,
+    width: usize,
+    column_policy: ColumnPolicy<'a>,
+}
+
+/// What to do with fields beyond the header width. `K` is a key term, or an
+/// owned `HeaderKey` for streaming parsers.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum ExtraColumns<K> {
+    /// Drop them (default)
+    Ignore,
+    /// Raise `{:extra_columns, row, expected, actual}`
+    Error,
+    /// Collect them into a list under the given key
+    Collect(K),
+}
+
+impl ExtraColumns<HeaderKey> {
+    pub fn to_term<'a>(&self, env: Env<'a>) -> NifResult<ExtraColumns<Term<'a>>> {
+        Ok(match self {
+            ExtraColumns::Ignore => ExtraColumns::Ignore,
+            ExtraColumns::Error => ExtraColumns::Error,
+            ExtraColumns::Collect(key) => ExtraColumns::Collect(key.to_term(env)?),
+        })
+    }
+}
+
+/// What to do when a row has fewer fields than the header.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum MissingColumns {
+    /// Missing keys map to nil (default)
+    Nil,
+    /// Missing keys are left out of the row
+    Omit,
+    /// Raise `{:missing_columns, row, expected, actual}`
+    Error,
+}
+
+/// Row width policies for headers-to-maps output.
+pub struct ColumnPolicy<'a> {
+    pub extra: ExtraColumns<Term<'a>>,
+    pub missing: MissingColumns,
+}
+
+impl Default for ColumnPolicy<'_> {
+    fn default() -> Self {
+        ColumnPolicy {
+            extra: ExtraColumns::Ignore,
+            missing: MissingColumns::Nil,
+        }
+    }
+}
+
+/// Width violation: `(tag, row, expected, actual)`.
+type WidthError = (Atom, usize, usize, usize);
+
+impl ColumnPolicy<'_> {
+    /// Record a width violation for this row. Rows are built back to front,
+    /// so the last one recorded is the earliest offending row.
+    fn check_width(
+        &self,
+        width: usize,
+        row_len: usize,
+        row_number: usize,
+        first_error: &mut Option<WidthError>,
+    ) {
+        if row_len > width && self.extra == ExtraColumns::Error {
+            *first_error = Some((crate::atoms::extra_columns(), row_number, width, row_len));
+        } else if row_len < width && self.missing == MissingColumns::Error {
+            *first_error = Some((crate::atoms::missing_columns(), row_number, width, row_len));
+        }
+    }
+}
+
+/// Struct target for `into:` — module, `(field, default)` pairs, and the
+/// fields that must be present in the header.
+pub struct StructSpec<'a> {
+    pub module: Term<'a>,
+    pub fields: Vec<(Term<'a>, Term<'a>)>,
+    pub required: Vec<Term<'a>>,
+}
+
+impl<'a> MapKeys<'a> {
+    /// Project resolved header keys onto a struct's fields. Headers match a
+    /// field by atom or by name (binary headers from `headers: true`);
+    /// unmatched headers are dropped and unmatched fields take their default.
+    /// Raises `{:missing_required_header, name}` for an unmatched required field.
+    pub fn into_struct(self, env: Env<'a>, spec: &StructSpec<'a>) -> NifResult<MapKeys<'a>> {
+        let names: Vec<Option<String>> = self.keys.iter().map(|k| key_atom_name(*k)).collect();
+        let mut keys = Vec::with_capacity(spec.fields.len() + 1);
+        let mut columns = Vec::with_capacity(spec.fields.len() + 1);
+        let mut fallbacks = Vec::with_capacity(spec.fields.len() + 1);
+
+        keys.push(crate::atoms::struct_().encode(env));
+        columns.push(Vec::new());
+        fallbacks.push(Some(spec.module));
+
+        for &(field, default) in &spec.fields {
+            let field_name = field.atom_to_string().map_err(|_| Error::BadArg)?;
+            let matched = self.keys.iter().zip(&names).position(|(key, name)| {
+                *key == field || name.as_deref() == Some(field_name.as_str())
+            });
+            match matched {
+                Some(i) => columns.push(self.columns[i].clone()),
+                None if spec.required.contains(&field) => {
+                    return Err(Error::RaiseTerm(Box::new((
+                        crate::atoms::missing_required_header(),
+                        field_name,
+                    ))));
+                }
+                None => columns.push(Vec::new()),
+            }
+            keys.push(field);
+            fallbacks.push(Some(default));
+        }
+
+        Ok(MapKeys {
+            keys,
+            columns,
+            fallbacks,
+            policy: self.policy,
+            width: self.width,
+            column_policy: self.column_policy,
+        })
+    }
+
+    /// Apply extra/missing column policies to rows built with these keys.
+    /// Raises `{:collect_key_conflict, name}` when the `{:collect, key}` key
+    /// is also a header key, whose column it would overwrite.
+    pub fn with_column_policy(mut self, column_policy: ColumnPolicy<'a>) -> NifResult<Self> {
+        if let ExtraColumns::Collect(key) = column_policy.extra {
+            if self.keys.contains(&key) {
+                return Err(Error::RaiseTerm(Box::new((
+                    crate::atoms::collect_key_conflict(),
+                    key_name(key),
+                ))));
+            }
+        }
+        self.column_policy = column_policy;
+        Ok(self)
+    }
+}
+
+/// Name a key would have as an atom: the atom's own name, or the UTF-8
+/// contents of a binary key.
+fn key_atom_name(key: Term) -> Option<String> {
+    if let Ok(bin) = key.decode::<Binary>() {
+        return std::str::from_utf8(bin.as_slice()).ok().map(str::to_owned);
+    }
+    key.atom_to_string().ok()
+}
+
+/// Resolve raw header keys (one per column) into unique map keys.
+/// Runs once per parse, before any rows are converted.
+pub fn resolve_map_keys<'a>(
+    env: Env<'a>,
+    keys: Vec<Term<'a>>,
+    policy: DuplicateHeaders,
+) -> NifResult<MapKeys<'a>> {
+    let keys = if policy == DuplicateHeaders::Suffix {
+        suffix_duplicate_keys(env, keys)?
+    } else {
+        keys
+    };
+
+    let width = keys.len();
+    let mut unique: Vec<Term<'a>> = Vec::with_capacity(keys.len());
+    let mut columns: Vec<Vec<usize>> = Vec::with_capacity(keys.len());
+    for (col, key) in keys.into_iter().enumerate() {
+        match unique.iter().position(|k| *k == key) {
+            Some(_) if policy == DuplicateHeaders::Error => {
+                return Err(Error::RaiseTerm(Box::new((
+                    crate::atoms::duplicate_header(),
+                    key_name(key),
+                ))));
+            }
+            Some(i) => columns[i].push(col),
+            None => {
+                unique.push(key);
+                columns.push(vec![col]);
+            }
+        }
+    }
+
+    Ok(MapKeys {
+        fallbacks: vec![None; unique.len()],
+        keys: unique,
+        columns,
+        policy,
+        width,
+        column_policy: ColumnPolicy::default(),
+    })
+}
+
+/// Rename repeated keys to `key_2`, `key_3`, ... skipping any name that is
+/// already taken by another column.
+fn suffix_duplicate_keys<'a>(env: Env<'a>, keys: Vec<Term<'a>>) -> NifResult<Vec<Term<'a>>> {
+    let mut out: Vec<Term<'a>> = Vec::with_capacity(keys.len());
+    for &key in &keys {
+        if !out.contains(&key) {
+            out.push(key);
+            continue;
+        }
+        let mut n = 2;
+        loop {
+            let candidate = suffixed_key(env, key, n)?;
+            if !keys.contains(&candidate) && !out.contains(&candidate) {
+                out.push(candidate);
+                break;
+            }
+            n += 1;
+        }
+    }
+    Ok(out)
+}
+
+/// Append `_n` to a binary or atom key.
+fn suffixed_key<'a>(env: Env<'a>, key: Term<'a>, n: usize) -> NifResult<Term<'a>> {
+    let suffix = format!("_{n}");
+    if let Ok(bin) = key.decode::<Binary<'a>>() {
+        let name = bin.as_slice();
+        let mut binary = NewBinary::new(env, name.len() + suffix.len());
+        binary.as_mut_slice()[..name.len()].copy_from_slice(name);
+        binary.as_mut_slice()[name.len()..].copy_from_slice(suffix.as_bytes());
+        return Ok(binary.into());
+    }
+    if let Ok(name) = key.atom_to_string() {
+        return Ok(Atom::from_str(env, &format!("{name}{suffix}"))?.encode(env));
+    }
+    Err(Error::BadArg)
+}
+
+/// Human-readable key name for error reporting.
+fn key_name(key: Term) -> String {
+    if let Ok(bin) = key.decode::<Binary>() {
+        return String::from_utf8_lossy(bin.as_slice()).into_owned();
+    }
+    key.atom_to_string().unwrap_or_else(|_| format!("{key:?}"))
+}
+
 /// Build a map from key/value term arrays.
 /// Uses fast O(n) path, falls back to incremental for duplicate keys.
 fn make_map<'a>(env: Env<'a>, keys: &[Term<'a>], values: &[Term<'a>]) -> Term<'a> {
@@ -208,43 +753,94 @@ fn make_map<'a>(env: Env<'a>, keys: &[Term<'a>], values: &[Term<'a>]) -> Term<'a
 
 /// Generic map builder: iterates rows in reverse, converts each field to a Term,
 /// fills missing columns with nil, and builds a cons-list of maps.
+/// Keys fed by several columns are resolved per `MapKeys::policy`, and rows
+/// wider or narrower than the header per `MapKeys::column_policy`.
+/// `first_row` is the 1-based record number of the first row, for errors.
 fn rows_to_maps_inner<'a, R>(
     env: Env<'a>,
-    keys: &[Term<'a>],
-    rows: impl DoubleEndedIterator<Item = R>,
+    map_keys: &MapKeys<'a>,
+    first_row: usize,
+    rows: impl DoubleEndedIterator<Item = R> + ExactSizeIterator,
     field_count: impl Fn(&R) -> usize,
     field_to_term: impl Fn(Env<'a>, &R, usize) -> Term<'a>,
-) -> Term<'a> {
-    let num_keys = keys.len();
+) -> NifResult<Term<'a>> {
+    let width = map_keys.width;
+    let column_policy = &map_keys.column_policy;
     let nil_term = atom::nil().encode(env);
-    let mut value_terms = vec![nil_term; num_keys];
+    let mut row_keys: Vec<Term<'a>> = Vec::with_capacity(map_keys.keys.len() + 1);
+    let mut row_values: Vec<Term<'a>> = Vec::with_capacity(map_keys.keys.len() + 1);
+    let mut first_error = None;
     let mut list = Term::list_new_empty(env);
 
-    for row in rows.rev() {
+    for (offset, row) in rows.enumerate().rev() {
         let row_len = field_count(&row);
-        for (i, val) in value_terms.iter_mut().enumerate() {
-            *val = if i < row_len {
-                field_to_term(env, &row, i)
+        column_policy.check_width(width, row_len, first_row + offset, &mut first_error);
+        if first_error.is_some() {
+            continue;
+        }
+
+        let column_term = |col: usize| {
+            if col < row_len {
+                field_to_term(env, &row, col)
             } else {
                 nil_term
+            }
+        };
+        row_keys.clear();
+        row_values.clear();
+        let slots = map_keys.columns.iter().zip(map_keys.fallbacks.iter());
+        for (&key, (cols, &fallback)) in map_keys.keys.iter().zip(slots) {
+            let missing = !cols.is_empty() && cols.iter().all(|&col| col >= row_len);
+            let value = match (cols.as_slice(), map_keys.policy) {
+                _ if missing && column_policy.missing == MissingColumns::Omit => match fallback {
+                    Some(fallback) => fallback,
+                    None => continue,
+                },
+                ([col], _) => column_term(*col),
+                ([first, ..], DuplicateHeaders::First) => column_term(*first),
+                (cols, DuplicateHeaders::List) => {
+                    let mut values = Term::list_new_empty(env);
+                    for &col in cols.iter().rev() {
+                        values = values.list_prepend(column_term(col));
+                    }
+                    values
+                }
+                ([.., last], _) => column_term(*last),
+                ([], _) => fallback.unwrap_or(nil_term),
             };
+            row_keys.push(key);
+            row_values.push(value);
         }
-        list = list.list_prepend(make_map(env, keys, &value_terms));
+        if let ExtraColumns::Collect(key) = column_policy.extra {
+            let mut extras = Term::list_new_empty(env);
+            for col in (width..row_len).rev() {
+                extras = extras.list_prepend(field_to_term(env, &row, col));
+            }
+            row_keys.push(key);
+            row_values.push(extras);
+        }
+        list = list.list_prepend(make_map(env, &row_keys, &row_values));
+    }
+
+    match first_error {
+        Some(error) => Err(Error::RaiseTerm(Box::new(error))),
+        None => Ok(list),
     }
-    list
 }
 
 /// Convert boundary rows to maps with sub-binary hybrid approach (single-byte escape).
 pub fn boundaries_to_maps_hybrid<'a>(
     env: Env<'a>,
     input: Binary<'a>,
-    keys: &[Term<'a>],
+    keys: &MapKeys<'a>,
+    first_row: usize,
     boundaries: &[Vec<(usize, usize)>],
     escape: u8,
-) -> Term<'a> {
+) -> NifResult<Term<'a>> {
     rows_to_maps_inner(
         env,
         keys,
+        first_row,
         boundaries.iter(),
         |row| row.len(),
         |env, row, i| {
@@ -258,13 +854,258 @@ pub fn boundaries_to_maps_hybrid<'a>(
 pub fn boundaries_to_maps_hybrid_general<'a>(
     env: Env<'a>,
     input: Binary<'a>,
-    keys: &[Term<'a>],
+    keys: &MapKeys<'a>,
+    first_row: usize,
     boundaries: &[Vec<(usize, usize)>],
     escape: &[u8],
-) -> Term<'a> {
+) -> NifResult<Term<'a>> {
     rows_to_maps_inner(
         env,
         keys,
+        first_row,
+        boundaries.iter(),
+        |row| row.len(),
+        |env, row, i| {
+            let (start, end) = row[i];
+            field_to_term_hybrid_general(env, &input, start, end, escape)
+        },
+    )
+}
+
+/// Convert rows of field terms to maps.
+pub fn rows_to_maps<'a>(
+    env: Env<'a>,
+    keys: &MapKeys<'a>,
+    first_row: usize,
+    rows: &[Vec<Term<'a>>],
+) -> NifResult<Term<'a>> {
+    rows_to_maps_inner(
+        env,
+        keys,
+        first_row,
+        rows.iter(),
+        |row| row.len(),
+        |_env, row, i| row[i],
+    )
+}
+
+/// Convert streaming parser rows to maps.
+pub fn stream_rows_to_maps<'a>(
+    env: Env<'a>,
+    keys: &MapKeys<'a>,
+    first_row: usize,
+    chunks: &LoadedChunks<'a>,
+    rows: Vec<Vec<StreamField>>,
+) -> NifResult<Term<'a>> {
+    let rows = stream_row_terms(env, chunks, rows)?;
+    rows_to_maps(env, keys, first_row, &rows)
+}
+
+// ============================================================================
+// Tuple and Keyword Builders (for row_format feature)
+// ============================================================================
+
+/// Shape of rows produced by the headers NIFs.
+pub enum RowShape<'a> {
+    /// `%{key => value}`, duplicate keys resolved per `DuplicateHeaders`
+    Map,
+    /// `[{key, value}]` in column order, duplicates preserved
+    Keyword,
+    /// `%Module{}` holding only the struct's fields (`into:`)
+    Struct(StructSpec<'a>),
+}
+
+/// Generic tuple builder: one `{field, ...}` tuple per row.
+fn rows_to_tuples_inner<'a, R>(
+    env: Env<'a>,
+    rows: impl DoubleEndedIterator<Item = R>,
+    field_count: impl Fn(&R) -> usize,
+    field_to_term: impl Fn(Env<'a>, &R, usize) -> Term<'a>,
+) -> Term<'a> {
+    let mut fields: Vec<Term<'a>> = Vec::new();
+    let mut list = Term::list_new_empty(env);
+
+    for row in rows.rev() {
+        fields.clear();
+        fields.extend((0..field_count(&row)).map(|i| field_to_term(env, &row, i)));
+        list = list.list_prepend(make_tuple(env, &fields));
+    }
+
+    list
+}
+
+/// Convert rows of field terms to tuples.
+pub fn rows_to_tuples<'a>(env: Env<'a>, rows: &[Vec<Term<'a>>]) -> Term<'a> {
+    rows_to_tuples_inner(env, rows.iter(), |row| row.len(), |_env, row, i| row[i])
+}
+
+/// Convert boundary rows to tuples with single-byte escape hybrid approach.
+pub fn boundaries_to_tuples_hybrid<'a>(
+    env: Env<'a>,
+    input: Binary<'a>,
+    boundaries: &[Vec<(usize, usize)>],
+    escape: u8,
+) -> Term<'a> {
+    rows_to_tuples_inner(
+        env,
+        boundaries.iter(),
+        |row| row.len(),
+        |env, row, i| {
+            let (start, end) = row[i];
+            field_to_term_hybrid(env, &input, start, end, escape)
+        },
+    )
+}
+
+/// Convert boundary rows to tuples with multi-byte escape hybrid approach.
+pub fn boundaries_to_tuples_hybrid_general<'a>(
+    env: Env<'a>,
+    input: Binary<'a>,
+    boundaries: &[Vec<(usize, usize)>],
+    escape: &[u8],
+) -> Term<'a> {
+    rows_to_tuples_inner(
+        env,
+        boundaries.iter(),
+        |row| row.len(),
+        |env, row, i| {
+            let (start, end) = row[i];
+            field_to_term_hybrid_general(env, &input, start, end, escape)
+        },
+    )
+}
+
+/// Convert header keys to keyword keys: atoms pass through, binaries
+/// (`headers: true`) become existing atoms only, so header text can't grow
+/// the atom table. Raises `{:unknown_header_atom, name}` for a header with
+/// no atom. Anything else is a BadArg.
+pub fn keyword_keys<'a>(env: Env<'a>, keys: Vec<Term<'a>>) -> NifResult<Vec<Term<'a>>> {
+    keys.into_iter()
+        .map(|key| {
+            if key.is_atom() {
+                return Ok(key);
+            }
+            let bin = key.decode::<Binary>()?;
+            match Atom::try_from_bytes(env, bin.as_slice())? {
+                Some(atom) => Ok(atom.encode(env)),
+                None => Err(Error::RaiseTerm(Box::new((
+                    crate::atoms::unknown_header_atom(),
+                    key,
+                )))),
+            }
+        })
+        .collect()
+}
+
+/// Generic keyword builder: one `[{key, value}, ...]` list per row, in
+/// column order. Rows wider or narrower than `keys` follow `column_policy`
+/// (`Omit` drops the pair, `Collect` appends `{key, extras}`).
+fn rows_to_keywords_inner<'a, R>(
+    env: Env<'a>,
+    keys: &[Term<'a>],
+    column_policy: &ColumnPolicy<'a>,
+    first_row: usize,
+    rows: impl DoubleEndedIterator<Item = R> + ExactSizeIterator,
+    field_count: impl Fn(&R) -> usize,
+    field_to_term: impl Fn(Env<'a>, &R, usize) -> Term<'a>,
+) -> NifResult<Term<'a>> {
+    let width = keys.len();
+    let nil_term = atom::nil().encode(env);
+    let mut first_error = None;
+    let mut list = Term::list_new_empty(env);
+
+    for (offset, row) in rows.enumerate().rev() {
+        let row_len = field_count(&row);
+        column_policy.check_width(width, row_len, first_row + offset, &mut first_error);
+        if first_error.is_some() {
+            continue;
+        }
+
+        let mut pairs = Term::list_new_empty(env);
+        if let ExtraColumns::Collect(key) = column_policy.extra {
+            let mut extras = Term::list_new_empty(env);
+            for col in (width..row_len).rev() {
+                extras = extras.list_prepend(field_to_term(env, &row, col));
+            }
+            pairs = pairs.list_prepend(make_tuple(env, &[key, extras]));
+        }
+        for (i, &key) in keys.iter().enumerate().rev() {
+            let value = if i < row_len {
+                field_to_term(env, &row, i)
+            } else if column_policy.missing == MissingColumns::Omit {
+                continue;
+            } else {
+                nil_term
+            };
+            pairs = pairs.list_prepend(make_tuple(env, &[key, value]));
+        }
+        list = list.list_prepend(pairs);
+    }
+
+    match first_error {
+        Some(error) => Err(Error::RaiseTerm(Box::new(error))),
+        None => Ok(list),
+    }
+}
+
+/// Convert rows of field terms to keyword lists.
+pub fn rows_to_keywords<'a>(
+    env: Env<'a>,
+    keys: &[Term<'a>],
+    column_policy: &ColumnPolicy<'a>,
+    first_row: usize,
+    rows: &[Vec<Term<'a>>],
+) -> NifResult<Term<'a>> {
+    rows_to_keywords_inner(
+        env,
+        keys,
+        column_policy,
+        first_row,
+        rows.iter(),
+        |row| row.len(),
+        |_env, row, i| row[i],
+    )
+}
+
+/// Convert boundary rows to keyword lists with single-byte escape hybrid approach.
+pub fn boundaries_to_keywords_hybrid<'a>(
+    env: Env<'a>,
+    input: Binary<'a>,
+    keys: &[Term<'a>],
+    column_policy: &ColumnPolicy<'a>,
+    first_row: usize,
+    boundaries: &[Vec<(usize, usize)>],
+    escape: u8,
+) -> NifResult<Term<'a>> {
+    rows_to_keywords_inner(
+        env,
+        keys,
+        column_policy,
+        first_row,
+        boundaries.iter(),
+        |row| row.len(),
+        |env, row, i| {
+            let (start, end) = row[i];
+            field_to_term_hybrid(env, &input, start, end, escape)
+        },
+    )
+}
+
+/// Convert boundary rows to keyword lists with multi-byte escape hybrid approach.
+pub fn boundaries_to_keywords_hybrid_general<'a>(
+    env: Env<'a>,
+    input: Binary<'a>,
+    keys: &[Term<'a>],
+    column_policy: &ColumnPolicy<'a>,
+    first_row: usize,
+    boundaries: &[Vec<(usize, usize)>],
+    escape: &[u8],
+) -> NifResult<Term<'a>> {
+    rows_to_keywords_inner(
+        env,
+        keys,
+        column_policy,
+        first_row,
         boundaries.iter(),
         |row| row.len(),
         |env, row, i| {
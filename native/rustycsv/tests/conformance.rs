@@ -57,8 +57,8 @@ fn boundaries_to_strings(input: &[u8], boundaries: Vec<Vec<(usize, usize)>>) ->
 fn streaming_to_strings(input: &[u8], sep: u8) -> Vec<Vec<String>> {
     let mut parser = StreamingParser::with_config(sep, b'"');
     parser.feed(input).unwrap();
-    let mut rows = parser.take_rows(usize::MAX);
-    rows.extend(parser.finalize());
+    let mut rows = parser.take_rows(usize::MAX).unwrap();
+    rows.extend(parser.finalize().unwrap());
     // Streaming skips empty rows, consistent with parallel.
     // Filter for comparison.
     owned_to_strings(rows)
@@ -267,6 +267,8 @@ use rustycsv::strategy::general::{
     parse_csv_parallel_general_with_newlines, GeneralStreamingParser,
     GeneralStreamingParserNewlines,
 };
+use rustycsv::strategy::parallel::parse_csv_parallel_boundaries_with_terminator;
+use rustycsv::strategy::zero_copy::parse_csv_boundaries_with_terminator;
 
 macro_rules! conformance_general {
     ($name:ident, input: $input:expr, seps: $seps:expr, esc: $esc:expr, expected: $expected:expr) => {
@@ -383,6 +385,31 @@ macro_rules! conformance_custom_newline {
             rows.extend(parser.finalize());
             let stream = owned_to_strings(rows);
             assert_eq!(stream, expected_nonempty, "FAILED: custom_nl streaming");
+
+            // SIMD fast path for single-byte terminators
+            if let (Some(terminator), [_], [sep]) = (nl.terminator(), esc.as_slice(), &seps[..]) {
+                let (sep, esc) = (&sep[..], esc[0]);
+                assert_eq!(
+                    parse_csv_boundaries_with_terminator(input, sep, esc, terminator),
+                    boundaries,
+                    "FAILED: custom_nl SIMD boundaries"
+                );
+                assert_eq!(
+                    parse_csv_parallel_boundaries_with_terminator(input, sep, esc, terminator),
+                    boundaries,
+                    "FAILED: custom_nl SIMD parallel boundaries"
+                );
+
+                let mut parser = StreamingParser::with_terminator(sep, esc, terminator);
+                parser.feed(input).unwrap();
+                let mut rows = parser.take_rows(usize::MAX).unwrap();
+                rows.extend(parser.finalize().unwrap());
+                let stream = owned_to_strings(rows);
+                assert_eq!(
+                    stream, expected_nonempty,
+                    "FAILED: custom_nl SIMD streaming"
+                );
+            }
         }
     };
 }
@@ -395,3 +422,26 @@ conformance_custom_newline!(
     nl: Newlines::custom(vec![b"|".to_vec()]),
     expected: vec![vec!["a", "b"], vec!["1", "2"]]
 );
+
+conformance_custom_newline!(
+    custom_newline_bare_cr,
+    input: b"a,\"b\rc\"\r1,2\r\n3\r",
+    seps: vec![b",".to_vec()],
+    esc: b"\"".to_vec(),
+    nl: Newlines::custom(vec![b"\r".to_vec()]),
+    expected: vec![vec!["a", "b\rc"], vec!["1", "2"], vec!["\n3"]]
+);
+
+conformance_custom_newline!(
+    custom_newline_any,
+    input: b"a,b\r1,2\r\n3,4\n5,\"x\r\ny\"\r",
+    seps: vec![b",".to_vec()],
+    esc: b"\"".to_vec(),
+    nl: Newlines::custom(vec![b"\r\n".to_vec(), b"\n".to_vec(), b"\r".to_vec()]),
+    expected: vec![
+        vec!["a", "b"],
+        vec!["1", "2"],
+        vec!["3", "4"],
+        vec!["5", "x\r\ny"]
+    ]
+);
@@ -23,6 +23,20 @@ defmodule CustomNewlineTest do
     newlines: ["<br>", "|"]
   )
 
+  RustyCSV.define(CrNewline,
+    separator: ",",
+    escape: "\"",
+    newlines: ["\r"]
+  )
+
+  RustyCSV.define(AnyNewline,
+    separator: ",",
+    escape: "\"",
+    newlines: ["\r\n", "\n", "\r"]
+  )
+
+  @strategies [:basic, :simd, :indexed, :parallel, :zero_copy]
+
   # ============================================================
   # Single-byte custom newline
   # ============================================================
@@ -128,6 +142,55 @@ defmodule CustomNewlineTest do
     end
   end
 
+  # ============================================================
+  # Bare CR newlines (SIMD fast path)
+  # ============================================================
+
+  describe "bare \\r newlines" do
+    test "\\r only: \\n is data" do
+      csv = "a,\"b\rc\"\r1,2\r\n3\r"
+      expected = [["a", "b\rc"], ["1", "2"], ["\n3"]]
+
+      for strategy <- @strategies do
+        assert CrNewline.parse_string(csv, skip_headers: false, strategy: strategy) == expected,
+               "Failed for strategy: #{strategy}"
+      end
+    end
+
+    test "\\r\\n, \\n and \\r mixed in one file" do
+      csv = "a,b\r1,2\r\n3,4\n5,\"x\r\ny\"\r"
+      expected = [["a", "b"], ["1", "2"], ["3", "4"], ["5", "x\r\ny"]]
+
+      for strategy <- @strategies do
+        assert AnyNewline.parse_string(csv, skip_headers: false, strategy: strategy) == expected,
+               "Failed for strategy: #{strategy}"
+      end
+    end
+
+    test "streaming with \\r\\n split across chunks" do
+      result =
+        ["a,b\r", "\n1,2\r", "3,4\r\n"]
+        |> AnyNewline.parse_stream(skip_headers: false)
+        |> Enum.to_list()
+
+      assert result == [["a", "b"], ["1", "2"], ["3", "4"]]
+    end
+
+    test "long input crosses SIMD chunks" do
+      rows = for i <- 1..200, do: ["#{i}", "value #{i}"]
+      csv = Enum.map_join(rows, "\r", &Enum.join(&1, ","))
+
+      assert CrNewline.parse_string(csv, skip_headers: false) == rows
+
+      assert csv
+             |> String.codepoints()
+             |> Enum.chunk_every(37)
+             |> Enum.map(&Enum.join/1)
+             |> CrNewline.parse_stream(skip_headers: false)
+             |> Enum.to_list() == rows
+    end
+  end
+
   # ============================================================
   # Default newlines still work
   # ============================================================
@@ -18,6 +18,20 @@ defmodule RustyCSV.EncodingTest do
     dump_bom: true
   )
 
+  RustyCSV.define(TestUTF32LE,
+    separator: ",",
+    escape: "\"",
+    encoding: {:utf32, :little},
+    trim_bom: true
+  )
+
+  RustyCSV.define(TestUTF32BE,
+    separator: ",",
+    escape: "\"",
+    encoding: {:utf32, :big},
+    trim_bom: true
+  )
+
   RustyCSV.define(TestLatin1,
     separator: ",",
     escape: "\"",
@@ -26,6 +40,32 @@ defmodule RustyCSV.EncodingTest do
     dump_bom: false
   )
 
+  RustyCSV.define(TestWindows1252,
+    separator: ";",
+    escape: "\"",
+    encoding: :windows_1252,
+    dump_bom: true
+  )
+
+  RustyCSV.define(TestLatin9,
+    separator: ",",
+    escape: "\"",
+    encoding: :iso_8859_15
+  )
+
+  RustyCSV.define(TestCP1251,
+    separator: ",",
+    escape: "\"",
+    encoding: {:codepage, 1251}
+  )
+
+  RustyCSV.define(TestAuto,
+    separator: ",",
+    escape: "\"",
+    encoding: :auto,
+    default_encoding: :windows_1252
+  )
+
   RustyCSV.define(TestUTF8WithBOM,
     separator: ",",
     escape: "\"",
@@ -103,6 +143,166 @@ defmodule RustyCSV.EncodingTest do
     end
   end
 
+  defmodule TestUTF16Row do
+    defstruct [:name, :note]
+  end
+
+  @wide_modules [
+    {TestUTF16LE, {:utf16, :little}},
+    {TestUTF16BE, {:utf16, :big}},
+    {TestUTF32LE, {:utf32, :little}},
+    {TestUTF32BE, {:utf32, :big}}
+  ]
+
+  describe "UTF-16 and UTF-32 scanning" do
+    # U+2C22 and U+0A2C each have a quote or comma as one of their bytes
+    @utf16_csv "name,note\r\n\"Jos\u00E9\",\"say \"\"hi\"\", \u2C22\nbye\"\r\n" <>
+                 "\u{1F600},\u0A2C\r\n,\n"
+
+    test "parses without decoding the input first" do
+      expected = RustyCSV.RFC4180.parse_string(@utf16_csv, skip_headers: false)
+      assert [_, ["José", "say \"hi\", \u2C22\nbye"], ["😀", "\u0A2C"], ["", ""]] = expected
+
+      tuples = Enum.map(tl(expected), &List.to_tuple/1)
+
+      for {module, encoding} <- @wide_modules do
+        input = :unicode.characters_to_binary(@utf16_csv, :utf8, encoding)
+        assert module.parse_string(input, skip_headers: false) == expected
+        assert module.parse_string(input) == tl(expected)
+        assert module.parse_string(input, row_format: :tuple) == tuples
+      end
+    end
+
+    test "builds maps, keyword lists and structs from the scanned fields" do
+      maps = RustyCSV.RFC4180.parse_string(@utf16_csv, headers: true)
+      keywords = RustyCSV.RFC4180.parse_string(@utf16_csv, headers: [:name, :note])
+      assert [%{"name" => "José"} | _] = maps
+
+      for {module, encoding} <- @wide_modules do
+        input = :unicode.characters_to_binary(@utf16_csv, :utf8, encoding)
+        assert module.parse_string(input, headers: true) == maps
+        assert module.parse_string(input, headers: [:name, :note]) == keywords
+
+        assert module.parse_string(input, headers: [:name, :note], row_format: :keyword) ==
+                 Enum.map(keywords, &[name: &1.name, note: &1.note])
+
+        assert [%TestUTF16Row{name: "José", note: "say \"hi\", \u2C22\nbye"} | _] =
+                 module.parse_string(input, headers: true, into: TestUTF16Row)
+      end
+    end
+
+    test "reports header and width errors like UTF-8 input" do
+      input = :unicode.characters_to_binary("a,a\n1,2,3\n", :utf8, {:utf16, :little})
+
+      assert_raise RustyCSV.ParseError, ~s(Duplicate header: "a"), fn ->
+        TestUTF16LE.parse_string(input, headers: true, duplicate_headers: :error)
+      end
+
+      assert_raise RustyCSV.ParseError, "Row 2 has 3 fields, expected 2", fn ->
+        TestUTF16LE.parse_string(input, headers: true, extra_columns: :error)
+      end
+
+      assert_raise RustyCSV.ParseError, "Invalid {:utf16, :little} sequence at byte 2", fn ->
+        TestUTF16LE.parse_string(<<?a, 0, 0x3D, 0xD8, ?,, 0, ?b, 0>>, headers: true)
+      end
+    end
+
+    test "accepts but ignores :strategy, and rejects an invalid one" do
+      input = :unicode.characters_to_binary("a,b\n1,2\n", :utf8, {:utf16, :little})
+
+      for strategy <- [:basic, :simd, :indexed, :parallel, :zero_copy] do
+        assert TestUTF16LE.parse_string(input, strategy: strategy) == [["1", "2"]]
+      end
+
+      assert_raise ArgumentError, ~r/invalid :strategy option/, fn ->
+        TestUTF16LE.parse_string(input, strategy: :bogus)
+      end
+
+      assert_raise ArgumentError, ~r/invalid :strategy option/, fn ->
+        TestLatin1.parse_string("a,b\n", strategy: :bogus)
+      end
+    end
+
+    test "reports invalid sequences at their byte offset" do
+      # Lone high surrogate before a separator
+      assert_raise RustyCSV.ParseError, "Invalid {:utf16, :little} sequence at byte 2", fn ->
+        TestUTF16LE.parse_string(<<?a, 0, 0x3D, 0xD8, ?,, 0, ?b, 0>>)
+      end
+
+      assert_raise RustyCSV.ParseError, "Incomplete {:utf16, :little} sequence at byte 4", fn ->
+        TestUTF16LE.parse_string(<<?a, 0, ?,, 0, ?b>>)
+      en
+      # Code point above U+10FFFF
+      assert_raise RustyCSV.ParseError, "Invalid {:utf32, :little} sequence at byte 8", fn ->
+        TestUTF32LE.parse_string(<<?a, 0, 0, 0, ?,, 0, 0, 0, 0, 0, 0x11, 0>>, headers: true)
+      end
+
+      assert_raise RustyCSV.ParseError, "Incomplete {:utf32, :big} sequence at byte 8", fn ->
+        TestUTF32BE.parse_string(<<0, 0, 0, ?a, 0, 0, 0, ?,, 0, 0>>, row_format: :tuple)
+      end
+    end
+
+    test "falls back to decoding for multi-byte separators" do
+      encoding = {:utf16, :little}
+      input = :unicode.characters_to_binary("a::b\nc::d\n", :utf8, encoding)
+
+      rows =
+        RustyCSV.Native.parse_string_encoded_with_config(input, encoding, "::", "\"", :default)
+
+      assert rows == [["a", "b"], ["c", "d"]]
+    end
+  end
+
+  describe "single-byte scanning" do
+    @cp1251_csv "name,note\r\n\"\xCF\xF0\xE8\",\"say \"\"hi\"\", \xE4\xE0\nbye\"\r\n" <>
+                  "plain,\"x\"\r\n"
+    # Long enough that the input is a refc binary, not a heap binary
+    @padding String.duplicate("x", 100)
+
+    test "parses without decoding the input first" do
+      expected = [["При", "say \"hi\", да\nbye"], ["plain", "x"]]
+      assert TestCP1251.parse_string(@cp1251_csv) == expected
+      assert hd(TestCP1251.parse_string(@cp1251_csv, skip_headers: false)) == ["name", "note"]
+
+      assert TestCP1251.parse_string(@cp1251_csv, row_format: :tuple) ==
+               Enum.map(expected, &List.to_tuple/1)
+    end
+
+    test "builds maps and keyword lists from the scanned fields" do
+      assert [%{"name" => "При", "note" => "say \"hi\", да\nbye"}, %{"name" => "plain"}] =
+               TestCP1251.parse_string(@cp1251_csv, headers: true)
+
+      assert [[name: "При", note: _], [name: "plain", note: "x"]] =
+               TestCP1251.parse_string(@cp1251_csv, headers: [:name, :note], row_format: :keyword)
+
+      assert_raise RustyCSV.ParseError, "Row 2 has 3 fields, expected 2", fn ->
+        TestLatin1.parse_string("a,b\n1,2,\xE9\n", headers: true, extra_columns: :error)
+      end
+    end
+
+    test "keeps ASCII fields as sub-binaries of the input" do
+      input = "#{@padding},\"b\"\nc\xE9,d\n"
+      [[a, b], [c, d]] = TestLatin1.parse_string(input, skip_headers: false)
+      assert {a, b, c, d} == {@padding, "b", "cé", "d"}
+      assert :binary.referenced_byte_size(a) == byte_size(input)
+      assert :binary.referenced_byte_size(b) == byte_size(input)
+      assert :binary.referenced_byte_size(c) == byte_size(c)
+    end
+
+    test "falls back to decoding for non-ASCII separators" do
+      rows =
+        RustyCSV.Native.parse_string_encoded_with_config(
+          "a\xA6b\nc\xA6\xE9\n",
+          :latin1,
+          "¦",
+          "\"",
+          :default
+        )
+
+      assert rows == [["a", "b"], ["c", "é"]]
+    end
+  end
+
   describe "Latin-1" do
     test "parses Latin-1 encoded data" do
       # Latin-1 characters: "caf\xe9" (café)
@@ -128,6 +328,374 @@ defmodule RustyCSV.EncodingTest do
     end
   end
 
+  describe "Windows-1252" do
+    test "parses curly quotes and the euro sign in 0x80-0x9F" do
+      data = "item;price\n\x93Deluxe\x94 \x96 caf\xe9;\x805\n"
+
+      result = TestWindows1252.parse_string(data, skip_headers: false)
+      assert result == [["item", "price"], ["“Deluxe” – café", "€5"]]
+    end
+
+    test "dumps to Windows-1252 without a BOM" do
+      rows = [["note"], ["it’s €5…"], ["Ω"]]
+      result = TestWindows1252.dump_to_iodata(rows) |> IO.iodata_to_binary()
+
+      assert result == "note\nit\x92s \x805\x85\n?\n"
+    end
+
+    test "round-trip Windows-1252 through streaming" do
+      original = [["Œuvre", "Šárka"], ["™", "ž"]]
+      encoded = TestWindows1252.dump_to_iodata(original) |> IO.iodata_to_binary()
+
+      chunks = for <<byte <- encoded>>, do: <<byte>>
+      result = chunks |> TestWindows1252.parse_stream(skip_headers: false) |> Enum.to_list()
+      assert result == original
+    end
+  end
+
+  describe "ISO-8859-15" do
+    test "parses the bytes that differ from Latin-1" do
+      data = "a,b\n\xa4,\xbc\xbd\xbe\n"
+
+      assert TestLatin9.parse_string(data, skip_headers: false) == [
+               ["a", "b"],
+               ["€", "ŒœŸ"]
+             ]
+    end
+
+    test "dumps the euro sign and replaces characters it dropped from Latin-1" do
+      result = TestLatin9.dump_to_iodata([["€", "½"]]) |> IO.iodata_to_binary()
+      assert result == "\xa4,?\n"
+    end
+  end
+
+  describe "{:codepage, number}" do
+    test "parses and dumps Windows-1251" do
+      data =
+        "\xc8\xec\xff,\xc3\xee\xf0\xee\xe4\n" <>
+          "\xc0\xed\xed\xe0,\xcc\xee\xf1\xea\xe2\xe0\n"
+      rows = [["Имя", "Город"], ["Анна", "Москва"]]
+
+      assert TestCP1251.parse_string(data, skip_headers: false) == rows
+      assert TestCP1251.dump_to_iodata(rows) |> IO.iodata_to_binary() == data
+    end
+
+    test "round-trips KOI8-R, CP437 and CP850" do
+      for {codepage, text, bytes} <- [
+            {20866, "Привет", <<0xF0, 0xD2, 0xC9, 0xD7, 0xC5, 0xD4>>},
+            {437, "╔═╗é", <<0xC9, 0xCD, 0xBB, 0x82>>},
+            {850, "Ñandú", <<0xA5, "and", 0xA3>>}
+          ] do
+        module = Module.concat(__MODULE__, "CP#{codepage}")
+        RustyCSV.define(module, encoding: {:codepage, codepage})
+
+        assert RustyCSV.Native.decode_to_utf8(bytes, {:codepage, codepage}) == text
+        assert module.dump_to_iodata([[text]]) |> IO.iodata_to_binary() == bytes <> "\n"
+        assert module.parse_string(bytes <> "\n", skip_headers: false) == [[text]]
+      end
+    end
+
+    test ":windows_1252 is codepage 1252" do
+      rows = [["“quoted” €"]]
+      RustyCSV.define(TestCodepage1252, encoding: {:codepage, 1252})
+
+      assert TestCodepage1252.dump_to_iodata(rows) |> IO.iodata_to_binary() ==
+               TestWindows1252.dump_to_iodata(rows) |> IO.iodata_to_binary()
+    end
+
+    test "raises on an unsupported codepage" do
+      assert_raise ArgumentError, ~r/Invalid encoding: \{:codepage, 1253\}/, fn ->
+        RustyCSV.define(InvalidCodepage, encoding: {:codepage, 1253})
+      end
+    end
+
+    test "accepts the codepages the NIF has tables for" do
+      assert RustyCSV.Native.codepages() == [437, 850, 1250, 1251, 1252, 20866, 28605]
+
+      for codepage <- RustyCSV.Native.codepages() do
+        assert RustyCSV.Native.decode_to_utf8("a", {:codepage, codepage}) == "a"
+      end
+
+      assert_raise ArgumentError, ~r/n in \[437, 850,/, fn ->
+        RustyCSV.define(InvalidCodepageName, encoding: {:codepage, "1252"})
+      end
+    end
+  end
+
+  @auto_text "name,note\nJosé,“hi” €\n"
+  @auto_rows [["name", "note"], ["José", "“hi” €"]]
+  @auto_bom_encodings [
+    :utf8,
+    {:utf16, :little},
+    {:utf16, :big},
+    {:utf32, :little},
+    {:utf32, :big}
+  ]
+
+  describe "encoding: :auto" do
+    test "picks the encoding from a UTF-8, UTF-16 or UTF-32 BOM" do
+      for encoding <- @auto_bom_encodings do
+        assert TestAuto.parse_string(with_bom(encoding), skip_headers: false) == @auto_rows,
+               inspect(encoding)
+      end
+    end
+
+    test "falls back to the default encoding without a BOM" do
+      data = "name,note\nJos\xe9,\x93hi\x94 \x80\n"
+      assert TestAuto.parse_string(data, skip_headers: false) == @auto_rows
+    end
+
+    test "builds tuples and maps for each BOM" do
+      [header | rows] = @auto_rows
+      maps = Enum.map(rows, &(header |> Enum.zip(&1) |> Map.new()))
+      tuples = Enum.map(rows, &List.to_tuple/1)
+      no_bom = "name,note\nJos\xe9,\x93hi\x94 \x80\n"
+
+      for input <- [no_bom | Enum.map(@auto_bom_encodings, &with_bom/1)] do
+        assert TestAuto.parse_string(input, row_format: :tuple) == tuples
+        assert TestAuto.parse_string(input, headers: true) == maps
+      end
+    end
+
+    test "applies :strategy to UTF-8 input and reports offsets past the BOM" do
+      assert TestAuto.parse_string(with_bom(:utf8), strategy: :parallel) == tl(@auto_rows)
+
+      assert_raise RustyCSV.ParseError, "Invalid {:utf16, :little} sequence at byte 4", fn ->
+        TestAuto.parse_string(<<0xFF, 0xFE, ?a, 0, 0x00, 0xDC>>, headers: true)
+      end
+    end
+
+    test "streams input with a BOM" do
+      for encoding <- [:utf8, {:utf16, :big}, {:utf32, :little}] do
+        chunks = for <<byte <- with_bom(encoding)>>, do: <<byte>>
+        result = chunks |> TestAuto.parse_stream(skip_headers: false) |> Enum.to_list()
+        assert result == @auto_rows, inspect(encoding)
+      end
+
+      assert ["\xFF"] |> TestAuto.parse_stream(skip_headers: false) |> Enum.to_list() == [["ÿ"]]
+    end
+
+    test "dumps in the default encoding" do
+      assert TestAuto.dump_to_iodata(@auto_rows) |> IO.iodata_to_binary() ==
+               "name,note\nJos\xe9,\x93hi\x94 \x80\n"
+
+      assert Keyword.get(TestAuto.options(), :default_encoding) == :windows_1252
+    end
+
+    test "decode_to_utf8/2 accepts {:auto, default}" do
+      assert RustyCSV.Native.decode_to_utf8(with_bom(:utf8), {:auto, :latin1}) == @auto_text
+      assert RustyCSV.Native.decode_to_utf8("caf\xe9", {:auto, :latin1}) == "café"
+
+      assert_raise ErlangError, ~r/:invalid_encoding, {:utf16, :little}, 4/, fn ->
+        RustyCSV.Native.decode_to_utf8(<<0xFF, 0xFE, ?a, 0, 0x00, 0xDC>>, {:auto, :utf8})
+      end
+    end
+
+    test "raises on an invalid default encoding" do
+      assert_raise ArgumentError, ~r/Invalid encoding: :auto/, fn ->
+        RustyCSV.define(InvalidAuto, encoding: :auto, default_encoding: :auto)
+      end
+    end
+  end
+
+  @unmappable_rows [["name", "note"], ["Łukasz", "“hi” – 1…2"]]
+
+  describe "on_unmappable" do
+    test "replaces with ? by default, or with the given bytes" do
+      assert TestLatin1.dump_to_iodata(@unmappable_rows) |> IO.iodata_to_binary() ==
+               "name,note\n?ukasz,?hi? ? 1?2\n"
+
+      RustyCSV.define(TestReplaceEmpty, encoding: :latin1, on_unmappable: {:replace, ""})
+
+      assert TestReplaceEmpty.dump_to_iodata(@unmappable_rows) |> IO.iodata_to_binary() ==
+               "name,note\nukasz,hi  12\n"
+    end
+
+    test "{:replace, string} is quoted and encoded like the field" do
+      RustyCSV.define(TestReplaceComma, encoding: :latin1, on_unmappable: {:replace, ","})
+
+      assert TestReplaceComma.dump_to_iodata(@unmappable_rows) |> IO.iodata_to_binary() ==
+               "name,note\n\",ukasz\",\",hi, , 1,2\"\n"
+
+      RustyCSV.define(TestReplaceLatin1, encoding: :latin1, on_unmappable: {:replace, "¿"})
+
+      assert TestReplaceLatin1.dump_to_iodata([["Łukasz"]]) |> IO.iodata_to_binary() ==
+               "\xBFukasz\n"
+
+      assert TestReplaceLatin1.dump_to_iodata([["Łukasz"]], strategy: :parallel)
+             |> IO.iodata_to_binary() == "\xBFukasz\n"
+
+      assert_raise ArgumentError, ~r/Invalid on_unmappable/, fn ->
+        RustyCSV.define(InvalidReplace, encoding: :latin1, on_unmappable: {:replace, <<0xFF>>})
+      end
+    end
+
+    test ":transliterate writes ASCII look-alikes" do
+      RustyCSV.define(TestTransliterate, encoding: :latin1, on_unmappable: :transliterate)
+
+      assert TestTransliterate.dump_to_iodata(@unmappable_rows) |> IO.iodata_to_binary() ==
+               "name,note\nLukasz,\"\"\"hi\"\" - 1...2\"\n"
+
+      # Windows-1252 has curly quotes and dashes of its own
+      RustyCSV.define(TestTransliterate1252,
+        encoding: :windows_1252,
+        on_unmappable: :transliterate
+      )
+
+      assert TestTransliterate1252.dump_to_iodata([["Łódź €5", "中"]])
+             |> IO.iodata_to_binary() == "L\xf3dz \x805,?\n"
+    end
+
+    test ":error raises naming the row and column" do
+      RustyCSV.define(TestUnmappableError, encoding: :latin1, on_unmappable: :error)
+      rows = [["a", "café"], ["b", "c"], ["d", "x – y"]]
+
+      for strategy <- [nil, :parallel] do
+        assert_raise RustyCSV.EncodeError,
+                     ~s(Cannot encode "–" (U+2013) in :latin1 at row 3, column 2),
+                     fn -> TestUnmappableError.dump_to_iodata(rows, strategy: strategy) end
+      end
+
+      assert_raise RustyCSV.EncodeError, ~r/at row 3, column 2/, fn ->
+        rows |> TestUnmappableError.dump_to_stream() |> Enum.to_list()
+      end
+
+      assert TestUnmappableError.dump_to_iodata([["café"]]) |> IO.iodata_to_binary() ==
+               "caf\xe9\n"
+    end
+
+    test "does not affect Unicode encodings" do
+      RustyCSV.define(TestUnmappableUTF16, encoding: {:utf16, :little}, on_unmappable: :error)
+
+      assert TestUnmappableUTF16.dump_to_iodata([["–"]]) |> IO.iodata_to_binary() ==
+               :unicode.characters_to_binary("–\n", :utf8, {:utf16, :little})
+    end
+
+    test "raises on an invalid policy" do
+      assert_raise ArgumentError, ~r/Invalid on_unmappable: :skip/, fn ->
+        RustyCSV.define(InvalidUnmappable, on_unmappable: :skip)
+      end
+    end
+  end
+
+  @invalid_rows [["name", "note"], ["ok", "caf\xe9 \xff"]]
+
+  describe "invalid_utf8 dump option" do
+    test ":passthrough copies the bytes by default" do
+      assert RustyCSV.RFC4180.dump_to_iodata(@invalid_rows) |> IO.iodata_to_binary() ==
+               "name,note\r\nok,caf\xe9 \xff\r\n"
+
+      assert TestLatin1.dump_to_iodata(@invalid_rows, invalid_utf8: :passthrough)
+             |> IO.iodata_to_binary() == "name,note\nok,caf\xe9 \xff\n"
+    end
+
+    test ":replace writes U+FFFD in the output encoding" do
+      assert RustyCSV.RFC4180.dump_to_iodata(@invalid_rows, invalid_utf8: :replace)
+             |> IO.iodata_to_binary() == "name,note\r\nok,caf� �\r\n"
+
+      utf16 =
+        :unicode.characters_to_binary("name,note\nok,caf� �\n", :utf8, {:utf16, :little})
+
+      assert TestUTF16LE.dump_to_iodata(@invalid_rows, invalid_utf8: :replace)
+             |> IO.iodata_to_binary() == <<0xFF, 0xFE>> <> utf16
+
+      # U+FFFD is then unmappable in Latin-1
+      assert TestLatin1.dump_to_iodata(@invalid_rows, invalid_utf8: :replace)
+             |> IO.iodata_to_binary() == "name,note\nok,caf? ?\n"
+    end
+
+    test ":error raises naming the row and field" do
+      RustyCSV.define(TestInvalidFormula, escape_formula: %{["="] => "'"})
+      message = "Invalid UTF-8 in row 2, field 2 at byte 3"
+
+      for module <- [RustyCSV.RFC4180, TestInvalidFormula, TestLatin1, TestUTF16LE],
+          strategy <- [nil, :parallel] do
+        assert_raise RustyCSV.EncodeError, message, fn ->
+          module.dump_to_iodata(@invalid_rows, invalid_utf8: :error, strategy: strategy)
+        end
+      end
+
+      assert RustyCSV.RFC4180.dump_to_iodata([["café"]], invalid_utf8: :error)
+             |> IO.iodata_to_binary() == "café\r\n"
+    end
+
+    test "raises on an invalid value" do
+      assert_raise ArgumentError, ~r/invalid_utf8 must be/, fn ->
+        RustyCSV.RFC4180.dump_to_iodata(@invalid_rows, invalid_utf8: :skip)
+      end
+    end
+  end
+
+  @excel_rows [
+    ["id", "amount", "note"],
+    ["00123", "-12.50", "=1+1"],
+    ["1234567890123456", "7", "@x"]
+  ]
+
+  describe "excel dump option" do
+    test ":utf8 writes a BOM, a sep= line and protected cells" do
+      assert RustyCSV.RFC4180.dump_to_iodata(@excel_rows, excel: :utf8) ==
+               "\uFEFFsep=,\r\nid,amount,note\r\n" <>
+                 ~s("=""00123""",-12.50,'=1+1\r\n) <>
+                 ~s("=""1234567890123456""",7,'@x\r\n)
+    end
+
+    test "true writes tab-separated UTF-16LE with a BOM" do
+      result = RustyCSV.RFC4180.dump_to_iodata(@excel_rows, excel: true)
+      assert result == RustyCSV.RFC4180.dump_to_iodata(@excel_rows, excel: :utf16)
+      assert <<0xFF, 0xFE, _::binary>> = result
+
+      assert RustyCSV.Spreadsheet.parse_string(result, skip_headers: false) == [
+               ["id", "amount", "note"],
+               [~s(="00123"), "-12.50", "'=1+1"],
+               [~s(="1234567890123456"), "7", "'@x"]
+             ]
+    end
+
+    test "replaces the module's encoding and formula settings" do
+      RustyCSV.define(TestExcelFormula, escape_formula: %{["="] => "!"})
+
+      for module <- [TestLatin1, TestUTF16LE, TestExcelFormula] do
+        assert module.dump_to_iodata([["=x", "ü"]], excel: true) ==
+                 <<0xFF, 0xFE>> <>
+                   :unicode.characters_to_binary("'=x\tü\n", :utf8, {:utf16, :little})
+      end
+    end
+
+    test "quotes fields holding a tab or a quote" do
+      assert RustyCSV.RFC4180.dump_to_iodata([["a\tb", ~s(say "hi")]], excel: :utf16) ==
+               <<0xFF, 0xFE>> <>
+                 :unicode.characters_to_binary(
+                   ~s("a\tb"\t"say ""hi"""\r\n),
+                   :utf8,
+                   {:utf16, :little}
+                 )
+    end
+
+    test "applies invalid_utf8" do
+      assert_raise RustyCSV.EncodeError, "Invalid UTF-8 in row 2, field 2 at byte 3", fn ->
+        RustyCSV.RFC4180.dump_to_iodata(@invalid_rows, excel: true, invalid_utf8: :error)
+      end
+    end
+
+    test ":utf8 raises for a multi-character separator" do
+      RustyCSV.define(TestExcelDoubleColon, separator: "::")
+
+      assert_raise ArgumentError, ~r/single-character separator, got: "::"/, fn ->
+        TestExcelDoubleColon.dump_to_iodata(@excel_rows, excel: :utf8)
+      end
+
+      assert <<0xFF, 0xFE, _::binary>> =
+               TestExcelDoubleColon.dump_to_iodata(@excel_rows, excel: :utf16)
+    end
+
+    test "raises on an invalid value" do
+      assert_raise ArgumentError, ~r/excel must be/, fn ->
+        RustyCSV.RFC4180.dump_to_iodata(@excel_rows, excel: :utf32)
+      end
+    end
+  end
+
   describe "UTF-8 with BOM" do
     test "parses UTF-8 with BOM" do
       # UTF-8 BOM + data
@@ -207,6 +775,98 @@ defmodule RustyCSV.EncodingTest do
 
       assert result == [["a", "b"], ["1", "2"]]
     end
+
+    test "handles a surrogate pair split across UTF-32 chunks" do
+      full_data = :unicode.characters_to_binary("a,😀\n1,2\n", :utf8, {:utf32, :big})
+
+      chunks = for <<byte <- full_data>>, do: <<byte>>
+
+      result =
+        chunks
+        |> RustyCSV.Streaming.stream_enumerable(encoding: {:utf32, :big})
+        |> Enum.to_list()
+
+      assert result == [["a", "😀"], ["1", "2"]]
+    end
+
+    test "decodes chunks fed to a parser with streaming_set_encoding/2" do
+      parser = RustyCSV.Native.streaming_new()
+      assert :ok = RustyCSV.Native.streaming_set_encoding(parser, {:utf16, :big})
+
+      data = :unicode.characters_to_binary("x,ü\n", :utf8, {:utf16, :big})
+      RustyCSV.Native.streaming_feed(parser, binary_part(data, 0, 5))
+      RustyCSV.Native.streaming_feed(parser, binary_part(data, 5, byte_size(data) - 5))
+
+      assert RustyCSV.Native.streaming_next_rows(parser, 10) == [["x", "ü"]]
+    end
+
+    test "raises ParseError on an invalid sequence in a stream" do
+      assert_raise RustyCSV.ParseError, "Invalid {:utf16, :little} sequence at byte 4", fn ->
+        [<<?a, 0, ?\n, 0>>, <<0x00, 0xDC, ?\n, 0>>]
+        |> TestUTF16LE.parse_stream(skip_headers: false)
+        |> Enum.to_list()
+      end
+    end
+  end
+
+  describe "decode_to_utf8/2" do
+    test "decodes each supported encoding" do
+      text = "name,café\n😀,1\n"
+
+      for encoding <- [{:utf16, :little}, {:utf16, :big}, {:utf32, :little}, {:utf32, :big}] do
+        input = :unicode.characters_to_binary(text, :utf8, encoding)
+        assert RustyCSV.Native.decode_to_utf8(input, encoding) == text
+      end
+
+      assert RustyCSV.Native.decode_to_utf8(<<"caf", 0xE9>>, :latin1) == "café"
+      assert RustyCSV.Native.decode_to_utf8("café", :utf8) == "café"
+    end
+
+    test "raises with the encoding and byte offset" do
+      assert_raise ErlangError, ~r/:invalid_encoding, {:utf32, :little}, 4/, fn ->
+        RustyCSV.Native.decode_to_utf8(<<?a, 0, 0, 0, 0, 0, 0x11, 0>>, {:utf32, :little})
+      end
+
+      assert_raise ErlangError, ~r/:incomplete_encoding, {:utf16, :big}, 2/, fn ->
+        RustyCSV.Native.decode_to_utf8(<<0, ?a, 0xD8>>, {:utf16, :big})
+      end
+    end
+  end
+
+  describe "detect_encoding/1" do
+    test "a BOM is certain" do
+      assert RustyCSV.Native.detect_encoding(with_bom({:utf16, :big})) == [{{:utf16, :big}, 1.0}]
+      assert RustyCSV.Native.detect_encoding(with_bom(:utf8)) == [{:utf8, 1.0}]
+    end
+
+    test "ASCII and valid UTF-8 are UTF-8" do
+      assert RustyCSV.Native.detect_encoding("name,age\njohn,27\n") == [{:utf8, 1.0}]
+      assert [{:utf8, 1.0} | rest] = RustyCSV.Native.detect_encoding("name\ncafé,Zürich\n")
+      assert Enum.all?(rest, fn {_encoding, confidence} -> confidence < 0.5 end)
+    end
+
+    test "UTF-16 and UTF-32 without a BOM" do
+      text = "name,city\nJosé,Zürich\n"
+
+      for encoding <- [{:utf16, :little}, {:utf16, :big}, {:utf32, :little}, {:utf32, :big}] do
+        sample = :unicode.characters_to_binary(text, :utf8, encoding)
+        assert [{^encoding, _confidence} | _] = RustyCSV.Native.detect_encoding(sample)
+      end
+    end
+
+    test "single-byte encodings" do
+      sample = <<0x93, "caf", 0xE9, 0x94, " ", 0x96, " ", 0x80, "5\n">>
+      assert [{:windows_1252, 0.9} | _] = RustyCSV.Native.detect_encoding(sample)
+
+      sample = <<0xCC, 0xEE, 0xF1, 0xEA, 0xE2, 0xE0, ?,, 0xC8, 0xEC, 0xFF, ?\n>>
+      assert [{{:codepage, 1251}, _confidence} | _] = RustyCSV.Native.detect_encoding(sample)
+    end
+
+    test "the top candidate decodes the sample" do
+      sample = <<"caf", 0xE9, ",cr", 0xE8, "me\n">>
+      [{encoding, _confidence} | _] = RustyCSV.Native.detect_encoding(sample)
+      assert RustyCSV.Native.decode_to_utf8(sample, encoding) == "café,crème\n"
+    end
   end
 
   describe "error handling" do
@@ -224,6 +884,12 @@ defmodule RustyCSV.EncodingTest do
         TestUTF16LE.parse_string(invalid_data)
       end
     end
+
+    test "reports the byte offset of an incomplete sequence" do
+      assert_raise RustyCSV.ParseError, "Incomplete {:utf16, :little} sequence at byte 4", fn ->
+        TestUTF16LE.parse_string(<<?a, 0, ?\n, 0, 0x3D, 0xD8>>)
+      end
+    end
   end
 
   describe "encoding options" do
@@ -234,6 +900,11 @@ defmodule RustyCSV.EncodingTest do
     end
   end
 
+  defp with_bom(encoding) do
+    :unicode.encoding_to_bom(encoding) <>
+      :unicode.characters_to_binary(@auto_text, :utf8, encoding)
+  end
+
   # Helper to wrap a successful result
   defp wrap_ok(binary) when is_binary(binary), do: {:ok, binary}
   defp wrap_ok(other), do: {:error, other}
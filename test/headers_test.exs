@@ -312,6 +312,111 @@ defmodule RustyCSV.HeadersTest do
     end
   end
 
+  # ============================================================================
+  # Duplicate header policies
+  # ============================================================================
+
+  describe "duplicate_headers" do
+    @dup_input "id,Comment,Comment,Comment\n1,a,b,c\n2,d,,\n"
+
+    for strategy <- @strategies do
+      @tag strategy: strategy
+      test ":last keeps the last column (#{strategy})" do
+        result =
+          CSV.parse_string(@dup_input,
+            headers: true,
+            duplicate_headers: :last,
+            strategy: unquote(strategy)
+          )
+
+        assert result == [%{"id" => "1", "Comment" => "c"}, %{"id" => "2", "Comment" => ""}]
+      end
+
+      @tag strategy: strategy
+      test ":first keeps the first column (#{strategy})" do
+        result =
+          CSV.parse_string(@dup_input,
+            headers: true,
+            duplicate_headers: :first,
+            strategy: unquote(strategy)
+          )
+
+        assert result == [%{"id" => "1", "Comment" => "a"}, %{"id" => "2", "Comment" => "d"}]
+      end
+
+      @tag strategy: strategy
+      test ":suffix renames repeats (#{strategy})" do
+        result =
+          CSV.parse_string(@dup_input,
+            headers: true,
+            duplicate_headers: :suffix,
+            strategy: unquote(strategy)
+          )
+
+        assert result == [
+                 %{"id" => "1", "Comment" => "a", "Comment_2" => "b", "Comment_3" => "c"},
+                 %{"id" => "2", "Comment" => "d", "Comment_2" => "", "Comment_3" => ""}
+               ]
+      end
+
+      @tag strategy: strategy
+      test ":list collects all values (#{strategy})" do
+        result =
+          CSV.parse_string(@dup_input,
+            headers: true,
+            duplicate_headers: :list,
+            strategy: unquote(strategy)
+          )
+
+        assert result == [
+                 %{"id" => "1", "Comment" => ["a", "b", "c"]},
+                 %{"id" => "2", "Comment" => ["d", "", ""]}
+               ]
+      end
+
+      @tag strategy: strategy
+      test ":error raises ParseError (#{strategy})" do
+        assert_raise RustyCSV.ParseError, ~r/Duplicate header: "Comment"/, fn ->
+          CSV.parse_string(@dup_input,
+            headers: true,
+            duplicate_headers: :error,
+            strategy: unquote(strategy)
+          )
+        end
+      end
+    end
+
+    test "default is :last" do
+      assert CSV.parse_string("a,a\n1,2\n", headers: true) == [%{"a" => "2"}]
+    end
+
+    test ":suffix skips names already taken" do
+      result = CSV.parse_string("a,a,a_2\n1,2,3\n", headers: true, duplicate_headers: :suffix)
+      assert result == [%{"a" => "1", "a_3" => "2", "a_2" => "3"}]
+    end
+
+    test ":suffix with explicit atom keys" do
+      result = CSV.parse_string("x,y\n1,2\n", headers: [:a, :a], duplicate_headers: :suffix)
+      assert result == [%{a: "1", a_2: "2"}]
+    end
+
+    test ":list fills missing columns with nil" do
+      result = CSV.parse_string("a,b,a\n1,2\n", headers: true, duplicate_headers: :list)
+      assert result == [%{"a" => ["1", nil], "b" => "2"}]
+    end
+
+    test ":error with unique headers parses normally" do
+      result = CSV.parse_string("a,b\n1,2\n", headers: true, duplicate_headers: :error)
+      assert result == [%{"a" => "1", "b" => "2"}]
+    end
+
+    test "invalid policy raises ArgumentError" do
+      assert_raise ArgumentError, ~r/invalid :duplicate_headers option/, fn ->
+        CSV.parse_string("a,a\n1,2\n", headers: true, duplicate_headers: :merge)
+      end
+    end
+  end
+
   # ============================================================================
   # Streaming with headers
   # ============================================================================
@@ -400,6 +505,186 @@ defmodule RustyCSV.HeadersTest do
       assert hd(result) == %{"id" => "1", "val" => "v1"}
       assert List.last(result) == %{"id" => "200", "val" => "v200"}
     end
+
+    test "batch_size counts data rows, not the header row" do
+      parser = RustyCSV.Native.streaming_new()
+      :ok = RustyCSV.Native.streaming_set_headers(parser, true, true, :last, {:ignore, nil})
+      RustyCSV.Native.streaming_feed(parser, "id\n1\n2\n3\n")
+
+      assert RustyCSV.Native.streaming_next_rows(parser, 2) == [%{"id" => "1"}, %{"id" => "2"}]
+      assert RustyCSV.Native.streaming_next_rows(parser, 2) == [%{"id" => "3"}]
+    end
+
+    test "header only, no trailing newline" do
+      assert ["a,b"] |> CSV.parse_stream(headers: true) |> Enum.to_list() == []
+    end
+
+    test "final row without trailing newline" do
+      result = ["a,b\n1,2"] |> CSV.parse_stream(headers: true) |> Enum.to_list()
+      assert result == [%{"a" => "1", "b" => "2"}]
+    end
+
+    test "duplicate_headers applies to streams" do
+      input = ["a,a,b\n", "1,2,3\n"]
+
+      listed = input |> CSV.parse_stream(headers: true, duplicate_headers: :list) |> Enum.to_list()
+      assert listed == [%{"a" => ["1", "2"], "b" => "3"}]
+
+      suffixed =
+        input |> CSV.parse_stream(headers: true, duplicate_headers: :suffix) |> Enum.to_list()
+
+      assert suffixed == [%{"a" => "1", "a_2" => "2", "b" => "3"}]
+
+      assert_raise RustyCSV.ParseError, ~r/Duplicate header: "a"/, fn ->
+        input |> CSV.parse_stream(headers: true, duplicate_headers: :error) |> Enum.to_list()
+      end
+    end
+
+    test "stream agrees with parse_string across policies" do
+      input = "id,c,c\n1,x,y\n2,,z\n"
+
+      for policy <- [:last, :first, :suffix, :list] do
+        opts = [headers: true, duplicate_headers: policy]
+        assert [input] |> CSV.parse_stream(opts) |> Enum.to_list() == CSV.parse_string(input, opts)
+      end
+    end
+
+    test "Streaming.parse_chunks with headers" do
+      result = RustyCSV.Streaming.parse_chunks(["x,y\n1,", "2\n3,4"], headers: [:a, :b])
+      assert result == [%{a: "1", b: "2"}, %{a: "3", b: "4"}]
+    end
+
+    @tag :tmp_dir
+    test "Streaming.stream_file with headers", %{tmp_dir: tmp_dir} do
+      path = Path.join(tmp_dir, "people.csv")
+      File.write!(path, "name,age\njohn,27\njane,30\n")
+
+      result = path |> RustyCSV.Streaming.stream_file(headers: true) |> Enum.to_list()
+
+      assert result == [
+               %{"name" => "john", "age" => "27"},
+               %{"name" => "jane", "age" => "30"}
+             ]
+    end
+  end
+
+  # ============================================================================
+  # extra_columns / missing_columns
+  # ============================================================================
+
+  describe "extra_columns and missing_columns" do
+    for strategy <- @strategies do
+      @tag strategy: strategy
+      test "collect extras and omit missing (#{strategy})" do
+        result =
+          CSV.parse_string("a,b\n1,2,3,4\n5\n6,7\n",
+            headers: true,
+            extra_columns: {:collect, "_rest"},
+            missing_columns: :omit,
+            strategy: unquote(strategy)
+          )
+
+        assert result == [
+                 %{"a" => "1", "b" => "2", "_rest" => ["3", "4"]},
+                 %{"a" => "5", "_rest" => []},
+                 %{"a" => "6", "b" => "7", "_rest" => []}
+               ]
+      end
+
+      @tag strategy: strategy
+      test ":error names the first bad row (#{strategy})" do
+        input = "a,b\n1,2\n3,4,5\n6\n"
+
+        assert_raise RustyCSV.ParseError, "Row 3 has 3 fields, expected 2", fn ->
+          CSV.parse_string(input,
+            headers: true,
+            extra_columns: :error,
+            strategy: unquote(strategy)
+          )
+        end
+
+        assert_raise RustyCSV.ParseError, "Row 4 has 1 fields, expected 2", fn ->
+          CSV.parse_string(input,
+            headers: true,
+            missing_columns: :error,
+            strategy: unquote(strategy)
+          )
+        end
+      end
+    end
+
+    test "defaults keep nil fill and ignore extras" do
+      result = CSV.parse_string("a,b\n1\n1,2,3\n", headers: true)
+      assert result == [%{"a" => "1", "b" => nil}, %{"a" => "1", "b" => "2"}]
+    end
+
+    test "explicit keys with skip_headers: false count from row 1" do
+      assert_raise RustyCSV.ParseError, "Row 1 has 3 fields, expected 2", fn ->
+        CSV.parse_string("1,2,3\n",
+          headers: [:a, :b],
+          skip_headers: false,
+          extra_columns: :error
+        )
+      end
+    end
+
+    test "keyword rows" do
+      result =
+        CSV.parse_string("a,b\n1\n",
+          headers: true,
+          row_format: :keyword,
+          missing_columns: :omit
+        )
+
+      assert result == [[a: "1"]]
+    end
+
+    test "parse_stream reports rows across batches" do
+      stream = ["a,b\n1,2\n", "3,4\n5,6,7\n"]
+
+      assert_raise RustyCSV.ParseError, "Row 4 has 3 fields, expected 2", fn ->
+        stream
+        |> CSV.parse_stream(headers: true, extra_columns: :error, batch_size: 1)
+        |> Enum.to_list()
+      end
+    end
+
+    test "parse_stream collects extras" do
+      result =
+        ["a\n1,2\n"]
+        |> CSV.parse_stream(headers: true, extra_columns: {:collect, :rest})
+        |> Enum.to_list()
+
+      assert result == [%{"a" => "1", rest: ["2"]}]
+    end
+
+    test "a collect key that is also a header raises" do
+      for strategy <- @strategies do
+        assert_raise RustyCSV.ParseError, ~r/"rest", which is also a header/, fn ->
+          CSV.parse_string("a,rest\n1,2,3\n",
+            headers: true,
+            extra_columns: {:collect, "rest"},
+            strategy: strategy
+          )
+        end
+      end
+
+      assert_raise RustyCSV.ParseError, ~r/which is also a header/, fn ->
+        ["a,rest\n1,2,3\n"]
+        |> CSV.parse_stream(headers: true, extra_columns: {:collect, "rest"})
+        |> Enum.to_list()
+      end
+    end
+
+    test "invalid options raise ArgumentError" do
+      assert_raise ArgumentError, ~r/invalid :extra_columns option/, fn ->
+        CSV.parse_string("a\n1\n", headers: true, extra_columns: :keep)
+      end
+
+      assert_raise ArgumentError, ~r/invalid :missing_columns option/, fn ->
+        CSV.parse_string("a\n1\n", headers: true, missing_columns: "")
+      end
+    end
   end
 
   # ============================================================================
@@ -457,6 +742,12 @@ defmodule RustyCSV.HeadersTest do
         ["a,b\n"] |> CSV.parse_stream(headers: :invalid) |> Enum.to_list()
       end
     end
+
+    test "parse_stream raises before the stream is enumerated" do
+      assert_raise ArgumentError, ~r/invalid :headers option/, fn ->
+        CSV.parse_stream(["a,b\n"], headers: "a,b")
+      end
+    end
   end
 
   # ============================================================================
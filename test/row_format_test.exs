@@ -0,0 +1,234 @@
+defmodule RustyCSV.RowFormatTest do
+  use ExUnit.Case, async: true
+
+  alias RustyCSV.RFC4180, as: CSV
+
+  @strategies [:basic, :simd, :indexed, :parallel, :zero_copy]
+
+  defmodule Person do
+    defstruct [:name, :age, country: "NZ"]
+  end
+
+  RustyCSV.define(RustyCSV.TestRowFormat.MultiEsc,
+    separator: ";",
+    escape: "$$"
+  )
+
+  # ============================================================================
+  # row_format: :tuple
+  # ============================================================================
+
+  describe "row_format: :tuple" do
+    for strategy <- @strategies do
+      @tag strategy: strategy
+      test "returns tuples (#{strategy})" do
+        input = ~s(a,b,c\n1,"x ""y""",3\n4,,\n)
+        result = CSV.parse_string(input, row_format: :tuple, strategy: unquote(strategy))
+        assert result == [{"1", "x \"y\"", "3"}, {"4", "", ""}]
+      end
+
+      @tag strategy: strategy
+      test "matches list output (#{strategy})" do
+        input = "a,b\r\n1,2,3\n\n4\n"
+        lists = CSV.parse_string(input, skip_headers: false, strategy: unquote(strategy))
+
+        tuples =
+          CSV.parse_string(input,
+            skip_headers: false,
+            row_format: :tuple,
+            strategy: unquote(strategy)
+          )
+
+        assert tuples == Enum.map(lists, &List.to_tuple/1)
+      end
+    end
+
+    test "elem/2 access" do
+      [row] = CSV.parse_string("a,b,c\n1,2,3\n", row_format: :tuple)
+      assert elem(row, 2) == "3"
+    end
+
+    test "empty input" do
+      assert CSV.parse_string("", row_format: :tuple) == []
+    end
+
+    test "multi-byte escape" do
+      result =
+        RustyCSV.TestRowFormat.MultiEsc.parse_string("a;b\n$$x;y$$;2\n", row_format: :tuple)
+
+      assert result == [{"x;y", "2"}]
+    end
+  end
+
+  # ============================================================================
+  # row_format: :keyword
+  # ============================================================================
+
+  describe "row_format: :keyword" do
+    for strategy <- @strategies do
+      @tag strategy: strategy
+      test "header row becomes atom keys (#{strategy})" do
+        result =
+          CSV.parse_string("name,age\njohn,27\n",
+            headers: true,
+            row_format: :keyword,
+            strategy: unquote(strategy)
+          )
+
+        assert result == [[name: "john", age: "27"]]
+      end
+
+      @tag strategy: strategy
+      test "preserves duplicates and column order (#{strategy})" do
+        result =
+          CSV.parse_string("tag,id,tag\na,1,b\n",
+            headers: true,
+            row_format: :keyword,
+            strategy: unquote(strategy)
+          )
+
+        assert result == [[tag: "a", id: "1", tag: "b"]]
+      end
+    end
+
+    test "explicit keys" do
+      result = CSV.parse_string("x,y\n1,2\n", headers: [:a, :b], row_format: :keyword)
+      assert result == [[a: "1", b: "2"]]
+    end
+
+    test "missing columns are nil, extra columns ignored" do
+      result = CSV.parse_string("a,b\n1\n1,2,3\n", headers: true, row_format: :keyword)
+      assert result == [[a: "1", b: nil], [a: "1", b: "2"]]
+    end
+
+    test "works with Keyword functions" do
+      [row] = CSV.parse_string("a,b,a\n1,2,3\n", headers: true, row_format: :keyword)
+      assert Keyword.get_values(row, :a) == ["1", "3"]
+    end
+
+    test "headers that are not existing atoms raise instead of creating atoms" do
+      header = "rusty_csv_no_such_atom_#{System.unique_integer([:positive])}"
+
+      assert_raise RustyCSV.ParseError, ~r/is not an existing atom/, fn ->
+        CSV.parse_string("#{header}\n1\n", headers: true, row_format: :keyword)
+      end
+
+      assert_raise ArgumentError, fn -> String.to_existing_atom(header) end
+    end
+
+    test ":duplicate_headers is rejected" do
+      assert_raise ArgumentError, ~r/does not apply to row_format: :keyword/, fn ->
+        CSV.parse_string("a,a\n1,2\n",
+          headers: true,
+          row_format: :keyword,
+          duplicate_headers: :suffix
+        )
+      end
+    end
+  end
+
+  # ============================================================================
+  # into: StructModule
+  # ============================================================================
+
+  describe "into:" do
+    for strategy <- @strategies do
+      @tag strategy: strategy
+      test "builds structs from header names (#{strategy})" do
+        result =
+          CSV.parse_string("name,age,email\njohn,27,j@x\n",
+            headers: true,
+            into: Person,
+            strategy: unquote(strategy)
+          )
+
+        assert result == [%Person{name: "john", age: "27", country: "NZ"}]
+      end
+    end
+
+    test "explicit atom keys" do
+      result = CSV.parse_string("x,y\njane,NL\n", headers: [:name, :country], into: Person)
+      assert result == [%Person{name: "jane", age: nil, country: "NL"}]
+    end
+
+    test "short rows fill nil" do
+      result = CSV.parse_string("name,age\njohn\n", headers: true, into: Person)
+      assert result == [%Person{name: "john", age: nil, country: "NZ"}]
+    end
+
+    test "extra_columns: {:collect, key} is rejected" do
+      assert_raise ArgumentError, ~r/can't be used with :into/, fn ->
+        CSV.parse_string("name\njohn,x\n",
+          headers: true,
+          into: Person,
+          extra_columns: {:collect, :rest}
+        )
+      end
+    end
+
+    test "duplicate_headers policy applies before projection" do
+      result =
+        CSV.parse_string("name,name\na,b\n",
+          headers: true,
+          into: Person,
+          duplicate_headers: :first
+        )
+
+      assert result == [%Person{name: "a", age: nil, country: "NZ"}]
+    end
+
+    test "required fields present" do
+      input = "name,age\njohn,27\n"
+      result = CSV.parse_string(input, headers: true, into: Person, required: [:age])
+      assert [%Person{age: "27"}] = result
+    end
+
+    test "missing required field raises ParseError" do
+      assert_raise RustyCSV.ParseError, ~r/Missing required header: "age"/, fn ->
+        CSV.parse_string("name\njohn\n", headers: true, into: Person, required: [:age])
+      end
+    end
+
+    test "required must be struct fields" do
+      assert_raise ArgumentError, ~r/invalid :required option/, fn ->
+        CSV.parse_string("name\njohn\n", headers: true, into: Person, required: [:nope])
+      end
+    end
+
+    test "non-struct module raises ArgumentError" do
+      assert_raise ArgumentError, ~r/invalid :into option/, fn ->
+        CSV.parse_string("name\njohn\n", headers: true, into: String)
+      end
+    end
+
+    test "requires headers" do
+      assert_raise ArgumentError, ~r/:into requires the :headers option/, fn ->
+        CSV.parse_string("name\njohn\n", into: Person)
+      end
+    end
+  end
+
+  # ============================================================================
+  # Invalid combinations
+  # ============================================================================
+
+  describe "invalid row_format" do
+    test ":keyword without headers" do
+      assert_raise ArgumentError, ~r/invalid :row_format option/, fn ->
+        CSV.parse_string("a\n1\n", row_format: :keyword)
+      end
+    end
+
+    test ":tuple with headers" do
+      assert_raise ArgumentError, ~r/invalid :row_format option/, fn ->
+        CSV.parse_string("a\n1\n", headers: true, row_format: :tuple)
+      end
+    end
+
+    test "parse_stream rejects :tuple" do
+      assert_raise ArgumentError, ~r/requires parse_string/, fn ->
+        ["a\n1\n"] |> CSV.parse_stream(row_format: :tuple) |> Enum.to_list()
+      end
+    end
+  end
+end
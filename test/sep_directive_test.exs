@@ -0,0 +1,206 @@
+defmodule SepDirectiveTest do
+  @moduledoc """
+  Tests for the `sep_directive` option: a `sep=` first line, as Excel writes it,
+  picks the separator for the rest of the input and is dropped.
+  """
+  use ExUnit.Case
+
+  alias RustyCSV.Native
+
+  RustyCSV.define(DirectiveCSV,
+    separator: ",",
+    escape: "\"",
+    sep_directive: true
+  )
+
+  RustyCSV.define(DirectiveUTF16,
+    separator: ",",
+    escape: "\"",
+    encoding: {:utf16, :little},
+    trim_bom: true,
+    sep_directive: true
+  )
+
+  @strategies [:basic, :simd, :indexed, :parallel, :zero_copy]
+
+  @input "sep=;\nname;note\nalice;\"a,b\"\nbob;c\n"
+  @rows [["alice", "a,b"], ["bob", "c"]]
+
+  describe "parse_string/2" do
+    test "the directive sets the separator for every strategy" do
+      for strategy <- @strategies do
+        assert DirectiveCSV.parse_string(@input, strategy: strategy) == @rows,
+               "failed for strategy #{strategy}"
+      end
+    end
+
+    test "input without a directive uses the configured separator" do
+      for strategy <- @strategies do
+        assert DirectiveCSV.parse_string("a,b\n1,2\n", strategy: strategy) == [["1", "2"]]
+      end
+    end
+
+    test "CRLF, a lone directive line and multi-byte separators" do
+      assert DirectiveCSV.parse_string("sep=|\r\nh|i\r\n1|2\r\n") == [["1", "2"]]
+      assert DirectiveCSV.parse_string("sep=;", skip_headers: false) == []
+      assert DirectiveCSV.parse_string("sep=¦\nh¦i\n1¦2\n") == [["1", "2"]]
+    end
+
+    test "the directive line must be exactly sep= and one character" do
+      assert DirectiveCSV.parse_string("h\nsep=;\n", skip_headers: false) ==
+               [["h"], ["sep=;"]]
+
+      assert DirectiveCSV.parse_string("sep=;;\na,b\n", skip_headers: false) ==
+               [["sep=;;"], ["a", "b"]]
+
+      assert DirectiveCSV.parse_string("SEP=;\na,b\n", skip_headers: false) ==
+               [["SEP=;"], ["a", "b"]]
+    end
+
+    test "headers and row formats" do
+      assert DirectiveCSV.parse_string(@input, headers: true) == [
+               %{"name" => "alice", "note" => "a,b"},
+               %{"name" => "bob", "note" => "c"}
+             ]
+
+      assert DirectiveCSV.parse_string(@input, row_format: :tuple) ==
+               [{"alice", "a,b"}, {"bob", "c"}]
+
+      assert DirectiveCSV.parse_string(@input, strategy: :parallel, headers: true) ==
+               DirectiveCSV.parse_string(@input, headers: true)
+    end
+
+    test "without the option the directive is an ordinary row" do
+      assert RustyCSV.RFC4180.parse_string("sep=;\na;b\n", skip_headers: false) ==
+               [["sep=;"], ["a;b"]]
+    end
+
+    test "UTF-16 input" do
+      input = :unicode.characters_to_binary(@input, :utf8, {:utf16, :little})
+      assert DirectiveUTF16.parse_string(<<0xFF, 0xFE>> <> input) == @rows
+      assert DirectiveUTF16.parse_string(input, headers: true) |> length() == 2
+    end
+
+    test "reads back an excel: :utf8 dump, BOM and all" do
+      rows = [["name", "amount"], ["alice", "1,5"]]
+      dump = rows |> RustyCSV.RFC4180.dump_to_iodata(excel: :utf8) |> IO.iodata_to_binary()
+      assert <<0xEF, 0xBB, 0xBF, "sep=", _::binary>> = dump
+
+      for strategy <- @strategies do
+        assert DirectiveCSV.parse_string(dump, skip_headers: false, strategy: strategy) == rows
+      end
+
+      # A BOM without a directive is left to :trim_bom
+      assert DirectiveCSV.parse_string("\uFEFFa,b\n", skip_headers: false) == [["\uFEFFa", "b"]]
+    end
+
+    test "options/0 records the option" do
+      assert Keyword.get(DirectiveCSV.options(), :sep_directive) == true
+      assert Keyword.get(RustyCSV.RFC4180.options(), :sep_directive) == false
+    end
+  end
+
+  describe "Native batch functions" do
+    test "{:sep_directive, separator} accepts every separator form" do
+      for separator <- [?,, ",", [",", ";"]] do
+        assert Native.parse_string_fast_with_config(
+                 "sep=\t\na\tb\n",
+                 {:sep_directive, separator},
+                 "\"",
+                 :default
+               ) == [["a", "b"]]
+
+        assert Native.parse_string_fast_with_config(
+                 "a,b\n",
+                 {:sep_directive, separator},
+                 "\"",
+                 :default
+               ) == [["a", "b"]]
+      end
+    end
+
+    test "zero-copy rows after the directive are sub-binaries of the input" do
+      assert Native.parse_string_zero_copy_with_config(
+               "sep=;\na;b\n",
+               {:sep_directive, ","},
+               "\"",
+               :default
+             ) == [["a", "b"]]
+    end
+  end
+
+  describe "streaming" do
+    test "the directive may span chunks" do
+      chunks = ["se", "p=;", "\r", "\na;b\n1;", "2\n"]
+
+      assert RustyCSV.Streaming.parse_chunks(chunks, sep_directive: true) ==
+               [["a", "b"], ["1", "2"]]
+
+      assert RustyCSV.Streaming.parse_chunks(["a,b\n", "1,2\n"], sep_directive: true) ==
+               [["a", "b"], ["1", "2"]]
+
+      assert RustyCSV.Streaming.parse_chunks(["sep=|"], sep_directive: true) == []
+
+      chunks = [<<0xEF>>, <<0xBB, 0xBF, "sep">>, "=;\na;b\n"]
+      assert RustyCSV.Streaming.parse_chunks(chunks, sep_directive: true) == [["a", "b"]]
+    end
+
+    test "parse_stream/2 with one byte per chunk" do
+      chunks = for <<byte <- @input>>, do: <<byte>>
+      assert chunks |> DirectiveCSV.parse_stream() |> Enum.to_list() == @rows
+    end
+
+    test "a reset parser looks for the directive again" do
+      parser = Native.streaming_new_with_config({:sep_directive, ","}, "\"", :default)
+      Native.streaming_feed(parser, "sep=;\na;b\n")
+      assert Native.streaming_finalize(parser) == [["a", "b"]]
+
+      :ok = Native.streaming_reset(parser)
+      Native.streaming_feed(parser, "a,b\n")
+      assert Native.streaming_finalize(parser) == [["a", "b"]]
+
+      :ok = Native.streaming_reset(parser)
+      Native.streaming_feed(parser, "sep=|\na|b\n")
+      assert Native.streaming_finalize(parser) == [["a", "b"]]
+    end
+
+    test "checkpoint offsets count the directive line" do
+      parser = Native.streaming_new_with_config({:sep_directive, ","}, "\"", :default)
+      Native.streaming_feed(parser, "sep=;\na;b\n")
+      assert Native.streaming_next_rows(parser, 10) == [["a", "b"]]
+      assert Native.streaming_checkpoint(parser) == {10, 1}
+    end
+
+    @tag :tmp_dir
+    test "stream_file/2 and resuming past the directive", %{tmp_dir: tmp_dir} do
+      path = Path.join(tmp_dir, "directive.csv")
+      File.write!(path, "sep=;\n" <> Enum.map_join(1..50, &"#{&1};\"x;#{&1}\"\n"))
+
+      all = path |> RustyCSV.Streaming.stream_file(sep_directive: true, chunk_size: 3)
+      all = Enum.to_list(all)
+      assert length(all) == 50
+      assert hd(all) == ["1", "x;1"]
+
+      checkpoints = :ets.new(:checkpoints, [:public])
+
+      path
+      |> RustyCSV.Streaming.stream_file(
+        sep_directive: true,
+        chunk_size: 64,
+        batch_size: 7,
+        on_checkpoint: &:ets.insert(checkpoints, {:last, &1})
+      )
+      |> Enum.take(20)
+
+      [{:last, {offset, seen} = checkpoint}] = :ets.lookup(checkpoints, :last)
+      assert offset > 0
+
+      resumed =
+        path
+        |> RustyCSV.Streaming.stream_file(sep_directive: true, resume: checkpoint)
+        |> Enum.to_list()
+
+      assert resumed == Enum.drop(all, seen)
+    end
+  end
+end
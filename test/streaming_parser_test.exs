@@ -0,0 +1,589 @@
+defmodule RustyCSV.StreamingParserTest do
+  @moduledoc """
+  Tests for streaming parser lifecycle NIFs:
+  - Reusing parsers via streaming_reset/1 and streaming_reconfigure/2
+  - Checkpoints and resuming (streaming_checkpoint/1, streaming_new_at/2)
+  - Zero-copy rows (sub-binaries of the fed chunk)
+  - Cumulative counters (streaming_stats/1)
+  - Feeding and taking in one call (streaming_feed_take/3)
+  - Header-aware mode (streaming_set_width_policy/2, streaming_headers/1)
+  - Parsers reading files themselves (streaming_open_file/2, streaming_read_rows/2)
+  """
+  use ExUnit.Case, async: true
+
+  alias RustyCSV.Native
+
+  defp parse_all(parser, chunk) do
+    Native.streaming_feed(parser, chunk)
+    Native.streaming_next_rows(parser, 1000) ++ Native.streaming_finalize(parser)
+  end
+
+  # ==========================================================================
+  # Reset
+  # ==========================================================================
+
+  describe "streaming_reset/1" do
+    test "discards buffered data and pending rows" do
+      parser = Native.streaming_new()
+      Native.streaming_feed(parser, "a,b\n1,\"unterminated")
+
+      assert :ok = Native.streaming_reset(parser)
+      assert Native.streaming_status(parser) == {0, 0, false}
+      assert parse_all(parser, "x,y\n") == [["x", "y"]]
+    end
+
+    test "keeps separator, escape and max buffer size" do
+      parser = Native.streaming_new_with_config(<<9>>, <<?'>>, :default)
+      Native.streaming_set_max_buffer(parser, 16)
+      Native.streaming_feed(parser, "a\tb\n")
+      Native.streaming_reset(parser)
+
+      assert parse_all(parser, "'x\ty'\tz\n") == [["x\ty", "z"]]
+
+      assert_raise ErlangError, ~r/buffer_overflow/, fn ->
+        Native.streaming_feed(parser, String.duplicate("x", 17))
+      end
+    end
+
+    test "works for multi-byte and custom newline parsers" do
+      for parser <- [
+            Native.streaming_new_with_config("::", <<?">>, :default),
+            Native.streaming_new_with_config(<<?,>>, <<?">>, ["|"])
+          ] do
+        Native.streaming_feed(parser, "partial")
+        Native.streaming_reset(parser)
+        refute elem(Native.streaming_status(parser), 2)
+      end
+    end
+
+    test "map mode reads the next row as headers again" do
+      parser = Native.streaming_new()
+      :ok = Native.streaming_set_headers(parser, true, true, :last, {:ignore, nil})
+
+      assert parse_all(parser, "a,b\n1,2\n") == [%{"a" => "1", "b" => "2"}]
+
+      Native.streaming_reset(parser)
+      assert parse_all(parser, "x\n3\n") == [%{"x" => "3"}]
+    end
+  end
+
+  # ==========================================================================
+  # Reconfigure
+  # ==========================================================================
+
+  describe "streaming_reconfigure/2" do
+    test "switches separator and escape" do
+      parser = Native.streaming_new()
+      Native.streaming_feed(parser, "a,b\n")
+
+      assert :ok = Native.streaming_reconfigure(parser, {<<?;>>, <<?'>>, :default})
+      assert Native.streaming_status(parser) == {0, 0, false}
+      assert parse_all(parser, "'a;b';c\n") == [["a;b", "c"]]
+    end
+
+    test "switches between single-byte, multi-byte and custom newline parsers" do
+      parser = Native.streaming_new()
+
+      Native.streaming_reconfigure(parser, {"::", <<?">>, :default})
+      assert parse_all(parser, "a::b\n") == [["a", "b"]]
+
+      Native.streaming_reconfigure(parser, {<<?,>>, <<?">>, ["|"]})
+      assert parse_all(parser, "a,b|c,d|") == [["a", "b"], ["c", "d"]]
+
+      Native.streaming_reconfigure(parser, {[<<?,>>, <<?;>>], <<?">>, :default})
+      assert parse_all(parser, "a;b,c\n") == [["a", "b", "c"]]
+    end
+
+    test "keeps max buffer size" do
+      parser = Native.streaming_new()
+      Native.streaming_set_max_buffer(parser, 16)
+      Native.streaming_reconfigure(parser, {"::", <<?">>, :default})
+
+      assert_raise ErlangError, ~r/buffer_overflow/, fn ->
+        Native.streaming_feed(parser, String.duplicate("x", 17))
+      end
+    end
+
+    test "invalid config raises ArgumentError" do
+      parser = Native.streaming_new()
+
+      assert_raise ArgumentError, fn ->
+        Native.streaming_reconfigure(parser, {"", <<?">>, :default})
+      end
+
+      assert_raise ArgumentError, fn ->
+        Native.streaming_reconfigure(parser, :tsv)
+      end
+    end
+  end
+
+  # ==========================================================================
+  # Checkpoint and resume
+  # ==========================================================================
+
+  describe "streaming_checkpoint/1" do
+    test "tracks rows handed out, not rows parsed" do
+      parser = Native.streaming_new()
+      Native.streaming_feed(parser, "a,b\r\n1,2\n\n3,")
+      assert Native.streaming_checkpoint(parser) == {0, 0}
+
+      Native.streaming_next_rows(parser, 1)
+      assert Native.streaming_checkpoint(parser) == {5, 1}
+
+      Native.streaming_next_rows(parser, 10)
+      assert Native.streaming_checkpoint(parser) == {9, 2}
+
+      Native.streaming_feed(parser, "4\n")
+      Native.streaming_finalize(parser)
+      assert Native.streaming_checkpoint(parser) == {14, 3}
+    end
+
+    test "offsets stay absolute across many feeds" do
+      parser = Native.streaming_new()
+      row = "1234567890,abcdefghij\n"
+
+      for _ <- 1..500, do: Native.streaming_feed(parser, row)
+      Native.streaming_next_rows(parser, 1000)
+
+      assert Native.streaming_checkpoint(parser) == {500 * byte_size(row), 500}
+    end
+
+    test "streaming_new_at continues offsets and counts" do
+      input = "a,b\n1,2\n3,4\n"
+      parser = Native.streaming_new_at({<<?,>>, <<?">>, :default}, {4, 1})
+      Native.streaming_feed(parser, binary_part(input, 4, byte_size(input) - 4))
+
+      assert Native.streaming_next_rows(parser, 10) == [["1", "2"], ["3", "4"]]
+      assert Native.streaming_checkpoint(parser) == {byte_size(input), 3}
+    end
+
+    test "a parser decoding another encoding has no checkpoint" do
+      parser = Native.streaming_new()
+      :ok = Native.streaming_set_encoding(parser, :latin1)
+      Native.streaming_feed(parser, "caf\xE9\nb\n")
+      assert Native.streaming_next_rows(parser, 1) == [["café"]]
+
+      assert_raise ErlangError, ~r/:encoded_checkpoint, :latin1/, fn ->
+        Native.streaming_checkpoint(parser)
+      end
+
+      # Setting an encoding on a parser resumed from a checkpoint
+      parser = Native.streaming_new_at({<<?,>>, <<?">>, :default}, {5, 1})
+
+      assert_raise ErlangError, ~r/:encoded_checkpoint, \{:utf16, :little\}/, fn ->
+        Native.streaming_set_encoding(parser, {:utf16, :little})
+      end
+
+      assert Native.streaming_set_encoding(parser, :utf8) == :ok
+    end
+
+    test "streaming_reset clears the checkpoint" do
+      parser = Native.streaming_new()
+      Native.streaming_feed(parser, "a\n")
+      Native.streaming_next_rows(parser, 1)
+      Native.streaming_reset(parser)
+      assert Native.streaming_checkpoint(parser) == {0, 0}
+    end
+  end
+
+  describe "stream_file/2 :resume" do
+    @tag :tmp_dir
+    test "resumes lists from a checkpoint", %{tmp_dir: tmp_dir} do
+      path = Path.join(tmp_dir, "resume.csv")
+      File.write!(path, Enum.map_join(1..100, &"#{&1},\"row\n#{&1}\"\n"))
+
+      checkpoints = :ets.new(:checkpoints, [:public])
+
+      rows =
+        path
+        |> RustyCSV.Streaming.stream_file(
+          chunk_size: 64,
+          batch_size: 7,
+          on_checkpoint: &:ets.insert(checkpoints, {:last, &1})
+        )
+        |> Enum.take(30)
+
+      assert length(rows) == 30
+      [{:last, {_offset, seen} = checkpoint}] = :ets.lookup(checkpoints, :last)
+      assert seen <= 30
+
+      resumed = path |> RustyCSV.Streaming.stream_file(resume: checkpoint) |> Enum.to_list()
+      all = path |> RustyCSV.Streaming.stream_file() |> Enum.to_list()
+
+      assert resumed == Enum.drop(all, seen)
+    end
+
+    @tag :tmp_dir
+    test "headers: true reads keys from the file start", %{tmp_dir: tmp_dir} do
+      path = Path.join(tmp_dir, "resume_headers.csv")
+      File.write!(path, "id,name\n1,a\n2,b\n3,c\n")
+
+      result =
+        path
+        |> RustyCSV.Streaming.stream_file(headers: true, resume: {12, 2})
+        |> Enum.to_list()
+
+      assert result == [%{"id" => "2", "name" => "b"}, %{"id" => "3", "name" => "c"}]
+    end
+
+    @tag :tmp_dir
+    test "explicit headers do not skip the first resumed row", %{tmp_dir: tmp_dir} do
+      path = Path.join(tmp_dir, "resume_keys.csv")
+      File.write!(path, "id,name\n1,a\n2,b\n")
+
+      result =
+        path
+        |> RustyCSV.Streaming.stream_file(headers: [:id, :name], resume: {12, 2})
+        |> Enum.to_list()
+
+      assert result == [%{id: "2", name: "b"}]
+    end
+
+    @tag :tmp_dir
+    test "on_checkpoint reports the end of the file", %{tmp_dir: tmp_dir} do
+      path = Path.join(tmp_dir, "checkpoint.csv")
+      File.write!(path, "a,b\n1,2\n3,4")
+      parent = self()
+
+      path
+      |> RustyCSV.Streaming.stream_file(on_checkpoint: &send(parent, {:checkpoint, &1}))
+      |> Stream.run()
+
+      assert_received {:checkpoint, {0, 0}}
+      assert_received {:checkpoint, {11, 3}}
+    end
+
+    test "invalid checkpoint raises ArgumentError" do
+      assert_raise ArgumentError, ~r/invalid :resume option/, fn ->
+        "mix.exs" |> RustyCSV.Streaming.stream_file(resume: 10) |> Enum.to_list()
+      end
+    end
+  end
+
+  # ==========================================================================
+  # Zero-copy rows
+  # ==========================================================================
+
+  describe "zero-copy rows" do
+    # Long enough that the chunk is a refc binary, not a heap binary
+    @padding String.duplicate("x", 100)
+
+    test "rows inside one chunk are sub-binaries of it" do
+      chunk = "#{@padding},\"quoted\",\"esc\"\"aped\"\n1,2\n"
+      parser = Native.streaming_new()
+      Native.streaming_feed(parser, chunk)
+
+      [[padding, quoted, escaped], ["1", "2"] = last] = Native.streaming_next_rows(parser, 10)
+
+      assert padding == @padding
+      assert quoted == "quoted"
+      assert escaped == "esc\"aped"
+      assert :binary.referenced_byte_size(padding) == byte_size(chunk)
+      assert :binary.referenced_byte_size(quoted) == byte_size(chunk)
+      assert :binary.referenced_byte_size(escaped) == byte_size(escaped)
+      assert Enum.all?(last, &(:binary.referenced_byte_size(&1) == byte_size(chunk)))
+    end
+
+    test "rows spanning chunks are copied" do
+      parser = Native.streaming_new()
+      Native.streaming_feed(parser, "#{@padding},a")
+      Native.streaming_feed(parser, "b\n#{@padding}")
+
+      assert [[padding, "ab"]] = Native.streaming_next_rows(parser, 10)
+      assert :binary.referenced_byte_size(padding) == byte_size(@padding)
+      assert Native.streaming_finalize(parser) == [[@padding]]
+    end
+
+    test "rows survive the chunk being released" do
+      parser = Native.streaming_new()
+
+      rows =
+        for i <- 1..50 do
+          Native.streaming_feed(parser, "#{@padding},#{i}\n")
+          Native.streaming_next_rows(parser, 10)
+        end
+
+      :erlang.garbage_collect()
+      assert List.flatten(rows) == Enum.flat_map(1..50, &[@padding, "#{&1}"])
+    end
+
+    test "header rows from spans become map keys" do
+      parser = Native.streaming_new()
+      :ok = Native.streaming_set_headers(parser, true, true, :last, {:ignore, nil})
+      Native.streaming_feed(parser, "id,#{@padding}\n1,2\n")
+
+      assert Native.streaming_next_rows(parser, 10) == [%{"id" => "1", @padding => "2"}]
+    end
+  end
+
+  # ==========================================================================
+  # Stats
+  # ==========================================================================
+
+  describe "streaming_stats/1" do
+    test "starts at zero" do
+      assert Native.streaming_stats(Native.streaming_new()) == %{
+               bytes_fed: 0,
+               bytes_consumed: 0,
+               rows_emitted: 0,
+               physical_lines: 0,
+               largest_row: 0,
+               peak_buffer: 0,
+               compactions: 0
+             }
+    end
+
+    test "counts fed, consumed and emitted data" do
+      parser = Native.streaming_new()
+      Native.streaming_feed(parser, "id,name\n1,\"a\nb\"\n")
+      Native.streaming_feed(parser, "2,c")
+      assert [["id", "name"]] = Native.streaming_next_rows(parser, 1)
+
+      assert Native.streaming_stats(parser) == %{
+               bytes_fed: 19,
+               bytes_consumed: 16,
+               rows_emitted: 1,
+               physical_lines: 3,
+               largest_row: 7,
+               peak_buffer: 16,
+               compactions: 1
+             }
+
+      Native.streaming_next_rows(parser, 10)
+      Native.streaming_finalize(parser)
+      assert %{bytes_consumed: 19, rows_emitted: 3} = Native.streaming_stats(parser)
+    end
+
+    test "counts lines and rows for multi-byte separators" do
+      parser = Native.streaming_new_with_config("::", "\"", :default)
+      Native.streaming_feed(parser, "a::b\r\nc::d\r\n")
+      Native.streaming_next_rows(parser, 10)
+
+      assert %{bytes_fed: 12, bytes_consumed: 12, rows_emitted: 2, physical_lines: 2} =
+               Native.streaming_stats(parser)
+    end
+
+    test "reset clears the counters" do
+      parser = Native.streaming_new()
+      Native.streaming_feed(parser, "a,b\n")
+      Native.streaming_reset(parser)
+
+      assert %{bytes_fed: 0, rows_emitted: 0} = Native.streaming_stats(parser)
+    end
+  end
+
+  # ==========================================================================
+  # Feed and take
+  # ==========================================================================
+
+  describe "streaming_feed_take/3" do
+    test "feeds a chunk and takes rows" do
+      parser = Native.streaming_new()
+
+      assert {[["a", "b"]], {1, 3}} =
+               Native.streaming_feed_take(parser, "a,b\n1,2\n3,4", 1)
+
+      assert {[["1", "2"]], {0, 3}} = Native.streaming_feed_take(parser, "", 5)
+      assert Native.streaming_finalize(parser) == [["3", "4"]]
+    end
+
+    test "feeds a list of chunks in order" do
+      parser = Native.streaming_new()
+
+      assert {[["a", "b"], ["1", "2"]], {0, 0}} =
+               Native.streaming_feed_take(parser, ["a,", "b\n1", ",2\n"], 10)
+    end
+
+    test "matches streaming_feed/2 plus streaming_next_rows/2" do
+      chunks = ["x,\"y\n", "z\"\n1", ",2\n"]
+      separate = Native.streaming_new()
+      combined = Native.streaming_new()
+
+      for chunk <- chunks do
+        Native.streaming_feed(separate, chunk)
+
+        assert {rows, _status} = Native.streaming_feed_take(combined, chunk, 10)
+        assert rows == Native.streaming_next_rows(separate, 10)
+      end
+    end
+
+    test "returns maps in map mode without counting the header row" do
+      parser = Native.streaming_new()
+      :ok = Native.streaming_set_headers(parser, true, true, :last, {:ignore, nil})
+
+      assert {[%{"id" => "1"}], {1, _}} =
+               Native.streaming_feed_take(parser, ["id\n1\n", "2\n"], 1)
+    end
+
+    test "raises on buffer overflow" do
+      parser = Native.streaming_new()
+      Native.streaming_set_max_buffer(parser, 4)
+
+      assert_raise ErlangError, ~r/buffer_overflow/, fn ->
+        Native.streaming_feed_take(parser, ["a,", "bcdef"], 10)
+      end
+    end
+  end
+
+  # ==========================================================================
+  # Header-aware mode
+  # ==========================================================================
+
+  describe "streaming_set_width_policy/2" do
+    test "keeps the header row" do
+      parser = Native.streaming_new()
+      :ok = Native.streaming_set_width_policy(parser, :error)
+      assert Native.streaming_headers(parser) == nil
+
+      Native.streaming_feed(parser, "id,\"full name\"\n1,a\n")
+      assert Native.streaming_headers(parser) == ["id", "full name"]
+      assert parse_all(parser, "") == [["id", "full name"], ["1", "a"]]
+    end
+
+    test ":error raises with the row number and offset after earlier rows" do
+      parser = Native.streaming_new()
+      :ok = Native.streaming_set_width_policy(parser, :error)
+      Native.streaming_feed(parser, "a,b\n1,2\n3\n4,5\n")
+
+      assert Native.streaming_next_rows(parser, 10) == [["a", "b"], ["1", "2"]]
+
+      assert_raise ErlangError, ~r/row_width, 3, 8, 2, 1/, fn ->
+        Native.streaming_next_rows(parser, 10)
+      end
+
+      assert_raise ErlangError, ~r/row_width/, fn -> Native.streaming_finalize(parser) end
+    end
+
+    test ":pad and :truncate fix the widths they name" do
+      # Rows stop at the first mismatch the policy can't fix
+      for {policy, expected} <- [
+            {:pad, [["1", ""]]},
+            {:truncate, []},
+            {[:pad, :truncate], [["1", ""], ["2", "x"]]}
+          ] do
+        parser = Native.streaming_new_with_config("::", "\"", :default)
+        :ok = Native.streaming_set_width_policy(parser, policy)
+        Native.streaming_feed(parser, "a::b\n1\n2::x::y\n")
+
+        assert Native.streaming_next_rows(parser, 10) == [["a", "b"] | expected]
+      end
+    end
+
+    test "reset reads the header again" do
+      parser = Native.streaming_new()
+      :ok = Native.streaming_set_width_policy(parser, :pad)
+      Native.streaming_feed(parser, "a,b\n")
+      Native.streaming_reset(parser)
+
+      assert Native.streaming_headers(parser) == nil
+      assert parse_all(parser, "x,y,z\n1\n") == [["x", "y", "z"], ["1", "", ""]]
+    end
+
+    test "width_policy option for streams" do
+      chunks = ["a,b\n1,2\n", "3,4,5\n"]
+
+      assert_raise RustyCSV.ParseError, "Row 3 at byte 8 has 3 fields, expected 2", fn ->
+        chunks |> RustyCSV.Streaming.stream_enumerable(width_policy: :error) |> Enum.to_list()
+      end
+
+      assert RustyCSV.Streaming.parse_chunks(chunks, width_policy: :truncate) ==
+               [["a", "b"], ["1", "2"], ["3", "4"]]
+    end
+
+    test "the error is raised after more than 100_000 queued rows" do
+      csv = "a,b\n" <> String.duplicate("1,2\n", 150_000) <> "3,4,5\n"
+      message = "Row 150002 at byte 600004 has 3 fields, expected 2"
+
+      assert_raise RustyCSV.ParseError, message, fn ->
+        [csv] |> RustyCSV.Streaming.stream_enumerable(width_policy: :error) |> Enum.to_list()
+      end
+
+      assert_raise RustyCSV.ParseError, message, fn ->
+        RustyCSV.Streaming.parse_chunks([csv], width_policy: :error)
+      end
+
+      {:ok, device} = StringIO.open(csv)
+
+      assert_raise RustyCSV.ParseError, message, fn ->
+        device |> RustyCSV.Streaming.stream_device(width_policy: :error) |> Enum.to_list()
+      end
+    end
+  end
+
+  # ==========================================================================
+  # File-reading parsers
+  # ==========================================================================
+
+  describe "streaming_open_file/2" do
+    @tag :tmp_dir
+    test "reads rows straight from the file", %{tmp_dir: tmp_dir} do
+      path = Path.join(tmp_dir, "open.csv")
+      File.write!(path, "a;\"b\nc\"\n1;2\n3;4")
+
+      {:ok, parser} = Native.streaming_open_file(path, separator: ";", chunk_size: 3)
+
+      assert Native.streaming_read_rows(parser, 1) == [["a", "b\nc"]]
+      assert Native.streaming_read_rows(parser, 10) == [["1", "2"], ["3", "4"]]
+      assert Native.streaming_read_rows(parser, 10) == []
+      assert %{bytes_fed: 15, rows_emitted: 3} = Native.streaming_stats(parser)
+    end
+
+    @tag :tmp_dir
+    test "resumes from a checkpoint in map mode", %{tmp_dir: tmp_dir} do
+      path = Path.join(tmp_dir, "resume_open.csv")
+      File.write!(path, "id,name\n1,a\n2,b\n")
+
+      {:ok, parser} = Native.streaming_open_file(path, resume: {12, 2})
+      Native.streaming_set_headers(parser, [:id, :name], false, :last, {:ignore, nil})
+
+      assert Native.streaming_read_rows(parser, 10) == [%{id: "2", name: "b"}]
+      assert Native.streaming_checkpoint(parser) == {16, 3}
+    end
+
+    @tag :tmp_dir
+    test "won't resume non-UTF-8 input", %{tmp_dir: tmp_dir} do
+      path = Path.join(tmp_dir, "resume_latin1.csv")
+      File.write!(path, "caf\xE9,1\nna\xEFve,2\n")
+
+      {:ok, parser} = Native.streaming_open_file(path, resume: {8, 1})
+
+      assert_raise ErlangError, ~r/:encoded_checkpoint, :latin1/, fn ->
+        Native.streaming_set_encoding(parser, :latin1)
+      end
+
+      {:ok, parser} = Native.streaming_open_file(path, [])
+      :ok = Native.streaming_set_encoding(parser, :latin1)
+      assert Native.streaming_read_rows(parser, 1) == [["café", "1"]]
+
+      assert_raise ErlangError, ~r/:encoded_checkpoint, :latin1/, fn ->
+        Native.streaming_checkpoint(parser)
+      end
+    end
+
+    @tag :tmp_dir
+    test "closing keeps rows already read", %{tmp_dir: tmp_dir} do
+      path = Path.join(tmp_dir, "close.csv")
+      File.write!(path, "a\nb\nc\n")
+
+      {:ok, parser} = Native.streaming_open_file(path, [])
+      assert Native.streaming_read_rows(parser, 1) == [["a"]]
+      :ok = Native.streaming_close_file(parser)
+      assert Native.streaming_read_rows(parser, 10) == [["b"], ["c"]]
+      assert Native.streaming_read_rows(parser, 10) == []
+    end
+
+    test "reports open errors and rejects other parsers" do
+      assert Native.streaming_open_file("does/not/exist.csv", []) == {:error, :enoent}
+
+      assert_raise ArgumentError, fn ->
+        Native.streaming_read_rows(Native.streaming_new(), 10)
+      end
+    end
+
+    test "stream_file/2 raises File.Error for a missing file" do
+      assert_raise File.Error, ~r/could not open/, fn ->
+        "does/not/exist.csv" |> RustyCSV.Streaming.stream_file() |> Enum.to_list()
+      end
+    end
+  end
+end
@@ -3,6 +3,7 @@ defmodule RustyCSV.StreamingSafetyTest do
   Tests for streaming parser safety features:
   - Buffer overflow detection and :buffer_overflow exception
   - Configurable max_buffer_size via NIF and high-level APIs
+  - Pending row limit (max_pending_rows) holding back unscanned bytes
   - Mutex poisoning recovery (:mutex_poisoned exception)
   """
   use ExUnit.Case, async: true
@@ -122,6 +123,80 @@ defmodule RustyCSV.StreamingSafetyTest do
     end
   end
 
+  # ==========================================================================
+  # Pending row limit
+  # ==========================================================================
+
+  describe "max_pending_rows" do
+    test "feed stops scanning at the limit until rows are taken" do
+      parser = Native.streaming_new()
+      :ok = Native.streaming_set_max_pending_rows(parser, 2)
+
+      assert {:drain, 2, 4} = Native.streaming_feed(parser, "a\nb\nc\nd\n")
+      assert Native.streaming_next_rows(parser, 1) == [["a"]]
+      assert {2, _buf, true} = Native.streaming_status(parser)
+      assert Native.streaming_next_rows(parser, 10) == [["b"], ["c"]]
+      assert Native.streaming_next_rows(parser, 10) == [["d"]]
+      assert Native.streaming_finalize(parser) == []
+    end
+
+    test "later chunks are kept and finalize parses everything" do
+      for parser <- [
+            Native.streaming_new(),
+            Native.streaming_new_with_config("::", "\"", :default)
+          ] do
+        :ok = Native.streaming_set_max_pending_rows(parser, 1)
+
+        assert {:drain, 1, _buf} = Native.streaming_feed(parser, "a\n\"b\nc\"\n")
+        assert {:drain, 1, _buf} = Native.streaming_feed(parser, "d\ne")
+        assert Native.streaming_finalize(parser) == [["a"], ["b\nc"], ["d"], ["e"]]
+      end
+    end
+
+    test "streaming_feed_take/3 reports the drain status" do
+      parser = Native.streaming_new()
+      :ok = Native.streaming_set_max_pending_rows(parser, 2)
+
+      assert {[["a"]], {:drain, 2, _buf}} =
+               Native.streaming_feed_take(parser, "a\nb\nc\nd\n", 1)
+
+      # Taking resumes the scan, which queues the last row
+      assert {[["b"], ["c"]], {1, _buf}} = Native.streaming_feed_take(parser, [], 10)
+      assert Native.streaming_finalize(parser) == [["d"]]
+    end
+
+    test "high-level streams return the same rows with a limit" do
+      csv = Enum.map_join(1..500, &"#{&1},\"x\n#{&1}\"\n")
+      chunks = csv |> String.codepoints() |> Enum.chunk_every(700) |> Enum.map(&Enum.join/1)
+      expected = RustyCSV.Streaming.parse_chunks(chunks)
+
+      assert length(expected) == 500
+      assert RustyCSV.Streaming.parse_chunks(chunks, max_pending_rows: 7) == expected
+
+      assert chunks
+             |> RustyCSV.Streaming.stream_enumerable(max_pending_rows: 7)
+             |> Enum.to_list() == expected
+
+      assert chunks
+             |> RustyCSV.RFC4180.parse_stream(skip_headers: false, max_pending_rows: 7)
+             |> Enum.to_list() == expected
+    end
+
+    test "stream_enumerable drains held-back rows batch by batch" do
+      # One chunk past the 64 KB feed threshold, so rows are fed and drained
+      # while the stream is consumed
+      chunk = Enum.map_join(1..20_000, &"#{&1},x\n")
+      opts = [max_pending_rows: 10, batch_size: 4]
+
+      rows = [chunk] |> RustyCSV.Streaming.stream_enumerable(opts) |> Enum.to_list()
+      assert length(rows) == 20_000
+      assert List.last(rows) == ["20000", "x"]
+
+      assert [chunk] |> RustyCSV.Streaming.stream_enumerable(opts) |> Enum.take(25) ==
+               Enum.take(rows, 25)
+    end
+  end
+
   # ==========================================================================
   # Mutex poisoning
   # ==========================================================================